@@ -0,0 +1,331 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell;
+use std::io;
+
+use itertools::Itertools as _;
+use jj_lib::backend::ObjectId;
+use jj_lib::git;
+use jj_lib::op_store::RefTarget;
+
+use crate::cli_util::short_commit_hash;
+use crate::formatter::Formatter;
+use crate::template_builder::{
+    self, BuildContext, CoreTemplatePropertyKind, IntoTemplateProperty, TemplateLanguage,
+};
+use crate::template_parser::{
+    self, FunctionCallNode, TemplateAliasesMap, TemplateParseError, TemplateParseResult,
+};
+use crate::templater::{
+    self, IntoTemplate, PlainTextFormattedProperty, Template, TemplateFunction, TemplateProperty,
+    TemplatePropertyFn,
+};
+
+/// A branch as listed by `jj branch list`.
+#[derive(Clone, Debug)]
+pub struct BranchListItem {
+    pub name: String,
+    pub local_target: RefTarget,
+    pub remotes: Vec<RemoteBranch>,
+}
+
+/// The target of a branch on a remote, compared to the local target.
+#[derive(Clone, Debug)]
+pub struct RemoteBranch {
+    pub name: String,
+    pub remote: String,
+    pub target: RefTarget,
+    /// Whether the remote target is the same as the local target.
+    pub synced: bool,
+    /// Number of commits in the remote target that aren't in the local target.
+    /// Only computed if the template uses it.
+    pub ahead_count: usize,
+    /// Number of commits in the local target that aren't in the remote target.
+    /// Only computed if the template uses it.
+    pub behind_count: usize,
+}
+
+struct BranchTemplateLanguage {
+    uses_tracking_counts: Cell<bool>,
+}
+
+impl TemplateLanguage<'static> for BranchTemplateLanguage {
+    type Context = BranchListItem;
+    type Property = BranchTemplatePropertyKind;
+
+    template_builder::impl_core_wrap_property_fns!('static, BranchTemplatePropertyKind::Core);
+
+    fn build_keyword(&self, name: &str, span: pest::Span) -> TemplateParseResult<Self::Property> {
+        build_branch_keyword(self, name, span)
+    }
+
+    fn build_method(
+        &self,
+        build_ctx: &BuildContext<Self::Property>,
+        property: Self::Property,
+        function: &FunctionCallNode,
+    ) -> TemplateParseResult<Self::Property> {
+        match property {
+            BranchTemplatePropertyKind::Core(property) => {
+                template_builder::build_core_method(self, build_ctx, property, function)
+            }
+            BranchTemplatePropertyKind::RefTarget(property) => {
+                build_ref_target_method(self, build_ctx, property, function)
+            }
+            BranchTemplatePropertyKind::RemoteBranch(property) => {
+                build_remote_branch_method(self, build_ctx, property, function)
+            }
+            BranchTemplatePropertyKind::RemoteBranchList(property) => {
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    |item| self.wrap_remote_branch(item),
+                    |property| self.wrap_remote_branch_list(property),
+                )
+            }
+        }
+    }
+}
+
+impl BranchTemplateLanguage {
+    fn wrap_ref_target(
+        &self,
+        property: impl TemplateProperty<BranchListItem, Output = RefTarget> + 'static,
+    ) -> BranchTemplatePropertyKind {
+        BranchTemplatePropertyKind::RefTarget(Box::new(property))
+    }
+
+    fn wrap_remote_branch(
+        &self,
+        property: impl TemplateProperty<BranchListItem, Output = RemoteBranch> + 'static,
+    ) -> BranchTemplatePropertyKind {
+        BranchTemplatePropertyKind::RemoteBranch(Box::new(property))
+    }
+
+    fn wrap_remote_branch_list(
+        &self,
+        property: impl TemplateProperty<BranchListItem, Output = Vec<RemoteBranch>> + 'static,
+    ) -> BranchTemplatePropertyKind {
+        BranchTemplatePropertyKind::RemoteBranchList(Box::new(property))
+    }
+}
+
+enum BranchTemplatePropertyKind {
+    Core(CoreTemplatePropertyKind<'static, BranchListItem>),
+    RefTarget(Box<dyn TemplateProperty<BranchListItem, Output = RefTarget>>),
+    RemoteBranch(Box<dyn TemplateProperty<BranchListItem, Output = RemoteBranch>>),
+    RemoteBranchList(Box<dyn TemplateProperty<BranchListItem, Output = Vec<RemoteBranch>>>),
+}
+
+impl IntoTemplateProperty<'static, BranchListItem> for BranchTemplatePropertyKind {
+    fn try_into_boolean(self) -> Option<Box<dyn TemplateProperty<BranchListItem, Output = bool>>> {
+        match self {
+            BranchTemplatePropertyKind::Core(property) => property.try_into_boolean(),
+            BranchTemplatePropertyKind::RefTarget(property) => {
+                Some(Box::new(TemplateFunction::new(property, |target| {
+                    target.is_present()
+                })))
+            }
+            BranchTemplatePropertyKind::RemoteBranchList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |remotes| {
+                    !remotes.is_empty()
+                })))
+            }
+            _ => None,
+        }
+    }
+
+    fn try_into_integer(self) -> Option<Box<dyn TemplateProperty<BranchListItem, Output = i64>>> {
+        match self {
+            BranchTemplatePropertyKind::Core(property) => property.try_into_integer(),
+            _ => None,
+        }
+    }
+
+    fn try_into_plain_text(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<BranchListItem, Output = String>>> {
+        match self {
+            BranchTemplatePropertyKind::Core(property) => property.try_into_plain_text(),
+            _ => {
+                let template = self.try_into_template()?;
+                Some(Box::new(PlainTextFormattedProperty::new(template)))
+            }
+        }
+    }
+
+    fn try_into_template(self) -> Option<Box<dyn Template<BranchListItem>>> {
+        match self {
+            BranchTemplatePropertyKind::Core(property) => property.try_into_template(),
+            BranchTemplatePropertyKind::RefTarget(property) => Some(property.into_template()),
+            BranchTemplatePropertyKind::RemoteBranch(property) => Some(property.into_template()),
+            BranchTemplatePropertyKind::RemoteBranchList(property) => {
+                Some(property.into_template())
+            }
+        }
+    }
+}
+
+fn build_branch_keyword(
+    language: &BranchTemplateLanguage,
+    name: &str,
+    span: pest::Span,
+) -> TemplateParseResult<BranchTemplatePropertyKind> {
+    fn wrap_fn<O, F: Fn(&BranchListItem) -> O>(f: F) -> TemplatePropertyFn<F> {
+        TemplatePropertyFn(f)
+    }
+
+    let property = match name {
+        "conflict" => language.wrap_boolean(wrap_fn(|branch| {
+            branch.local_target.has_conflict()
+                || branch
+                    .remotes
+                    .iter()
+                    .any(|remote| remote.target.has_conflict())
+        })),
+        "local_target" => language.wrap_ref_target(wrap_fn(|branch| branch.local_target.clone())),
+        "name" => language.wrap_string(wrap_fn(|branch| branch.name.clone())),
+        "present" => language.wrap_boolean(wrap_fn(|branch| branch.local_target.is_present())),
+        "remotes" => language.wrap_remote_branch_list(wrap_fn(|branch| branch.remotes.clone())),
+        "tracked" => language.wrap_boolean(wrap_fn(|branch| {
+            branch
+                .remotes
+                .iter()
+                .any(|remote| remote.remote != git::REMOTE_NAME_FOR_LOCAL_GIT_REPO)
+        })),
+        _ => return Err(TemplateParseError::no_such_keyword(name, span)),
+    };
+    Ok(property)
+}
+
+/// Prints the short IDs of the target commits, separated by spaces if the
+/// target is conflicted.
+impl Template<()> for RefTarget {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        let ids = self.added_ids().map(short_commit_hash).join(" ");
+        formatter.write_str(&ids)
+    }
+}
+
+fn build_ref_target_method(
+    language: &BranchTemplateLanguage,
+    _build_ctx: &BuildContext<BranchTemplatePropertyKind>,
+    self_property: impl TemplateProperty<BranchListItem, Output = RefTarget> + 'static,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<BranchTemplatePropertyKind> {
+    let property = match function.name {
+        "present" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_boolean(TemplateFunction::new(self_property, |target| {
+                target.is_present()
+            }))
+        }
+        "conflict" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_boolean(TemplateFunction::new(self_property, |target| {
+                target.has_conflict()
+            }))
+        }
+        "added_ids" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string_list(TemplateFunction::new(self_property, |target| {
+                target.added_ids().map(|id| id.hex()).collect()
+            }))
+        }
+        "removed_ids" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string_list(TemplateFunction::new(self_property, |target| {
+                target.removed_ids().map(|id| id.hex()).collect()
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("RefTarget", function)),
+    };
+    Ok(property)
+}
+
+impl Template<()> for RemoteBranch {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}@{}", self.name, self.remote)
+    }
+}
+
+impl Template<()> for Vec<RemoteBranch> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, " ")
+    }
+}
+
+fn build_remote_branch_method(
+    language: &BranchTemplateLanguage,
+    _build_ctx: &BuildContext<BranchTemplatePropertyKind>,
+    self_property: impl TemplateProperty<BranchListItem, Output = RemoteBranch> + 'static,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<BranchTemplatePropertyKind> {
+    let property = match function.name {
+        "remote" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |remote| remote.remote))
+        }
+        "target" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_ref_target(TemplateFunction::new(self_property, |remote| remote.target))
+        }
+        "synced" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_boolean(TemplateFunction::new(self_property, |remote| remote.synced))
+        }
+        "ahead_count" => {
+            template_parser::expect_no_arguments(function)?;
+            language.uses_tracking_counts.set(true);
+            language.wrap_integer(TemplateFunction::new(self_property, |remote| {
+                i64::try_from(remote.ahead_count).unwrap()
+            }))
+        }
+        "behind_count" => {
+            template_parser::expect_no_arguments(function)?;
+            language.uses_tracking_counts.set(true);
+            language.wrap_integer(TemplateFunction::new(self_property, |remote| {
+                i64::try_from(remote.behind_count).unwrap()
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("RemoteBranch", function)),
+    };
+    Ok(property)
+}
+
+/// Template for `jj branch list`.
+pub struct BranchTemplate {
+    pub template: Box<dyn Template<BranchListItem>>,
+    /// Whether the template uses `ahead_count()` or `behind_count()`, which
+    /// have to walk the history to compute.
+    pub uses_tracking_counts: bool,
+}
+
+pub fn parse(
+    template_text: &str,
+    aliases_map: &TemplateAliasesMap,
+) -> TemplateParseResult<BranchTemplate> {
+    let language = BranchTemplateLanguage {
+        uses_tracking_counts: Cell::new(false),
+    };
+    let node = template_parser::parse(template_text, aliases_map)?;
+    let template = template_builder::build(&language, &node)?;
+    Ok(BranchTemplate {
+        template,
+        uses_tracking_counts: language.uses_tracking_counts.get(),
+    })
+}
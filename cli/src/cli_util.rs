@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use std::env::{self, ArgsOs, VarError};
 use std::ffi::{OsStr, OsString};
 use std::fmt::Debug;
@@ -44,25 +44,30 @@ use jj_lib::matchers::{EverythingMatcher, Matcher, PrefixMatcher, Visit};
 use jj_lib::merged_tree::{MergedTree, MergedTreeBuilder};
 use jj_lib::op_heads_store::{self, OpHeadResolutionError, OpHeadsStore};
 use jj_lib::op_store::{OpStore, OpStoreError, OperationId, RefTarget, WorkspaceId};
+use jj_lib::op_tag_store::{self, OpTagStore, OpTagStoreError};
 use jj_lib::operation::Operation;
 use jj_lib::repo::{
     CheckOutCommitError, EditCommitError, MutableRepo, ReadonlyRepo, Repo, RepoLoader,
     RepoLoaderError, RewriteRootCommit, StoreFactories, StoreLoadError,
 };
 use jj_lib::repo_path::{FsPathParseError, RepoPath};
+use jj_lib::resolution_store::{ResolutionStore, ResolutionStoreError};
 use jj_lib::revset::{
     DefaultSymbolResolver, Revset, RevsetAliasesMap, RevsetCommitRef, RevsetEvaluationError,
     RevsetExpression, RevsetIteratorExt, RevsetParseContext, RevsetParseError,
     RevsetParseErrorKind, RevsetResolutionError, RevsetWorkspaceContext,
 };
 use jj_lib::settings::{ConfigResultExt as _, UserSettings};
+use jj_lib::signing::SshVerifier;
 use jj_lib::transaction::Transaction;
 use jj_lib::tree::TreeMergeError;
 use jj_lib::working_copy::{
-    CheckoutStats, LockedWorkingCopy, ResetError, SnapshotError, SnapshotOptions, TreeStateError,
-    WorkingCopy,
+    CheckoutError, CheckoutOptions, CheckoutStats, LockedWorkingCopy, ResetError, SnapshotError,
+    SnapshotOptions, TreeStateError, WorkingCopy,
+};
+use jj_lib::workspace::{
+    get_workspace_root, Workspace, WorkspaceInitError, WorkspaceLoadError, WorkspaceLoader,
 };
-use jj_lib::workspace::{Workspace, WorkspaceInitError, WorkspaceLoadError, WorkspaceLoader};
 use jj_lib::{dag_walk, file_util, git, revset};
 use once_cell::unsync::OnceCell;
 use thiserror::Error;
@@ -71,15 +76,19 @@ use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
 
+use crate::commit_templater::{self, DescriptionLabels, HyperlinkPatterns};
 use crate::config::{
-    new_config_path, AnnotatedValue, CommandNameAndArgs, ConfigSource, LayeredConfigs,
+    is_repo_config_trusted, new_config_path, repo_config_requires_trust, AnnotatedValue,
+    CommandNameAndArgs, ConfigIssue, ConfigSource, LayeredConfigs,
 };
 use crate::formatter::{FormatRecorder, Formatter, PlainTextFormatter};
 use crate::merge_tools::{ConflictResolveError, DiffEditError, DiffGenerateError};
-use crate::template_parser::{TemplateAliasesMap, TemplateParseError};
+use crate::template_builder::AvailableWidth;
+use crate::template_parser::{self, ExpressionNode, TemplateAliasesMap, TemplateParseError};
 use crate::templater::Template;
-use crate::ui::{ColorChoice, Ui};
-use crate::{commit_templater, text_util};
+use crate::text_util;
+use crate::timing::Timing;
+use crate::ui::{output_width, ColorChoice, OutputFormat, Ui};
 
 #[derive(Clone, Debug)]
 pub enum CommandError {
@@ -88,6 +97,11 @@ pub enum CommandError {
         hint: Option<String>,
     },
     ConfigError(String),
+    /// The command can't proceed because of conflicted commits or branches
+    ConflictError {
+        message: String,
+        hint: Option<String>,
+    },
     /// Invalid command line
     CliError(String),
     /// Invalid command line detected by clap
@@ -109,6 +123,48 @@ pub fn user_error_with_hint(message: impl Into<String>, hint: impl Into<String>)
     }
 }
 
+pub fn conflict_error(message: impl Into<String>) -> CommandError {
+    CommandError::ConflictError {
+        message: message.into(),
+        hint: None,
+    }
+}
+
+pub fn conflict_error_with_hint(
+    message: impl Into<String>,
+    hint: impl Into<String>,
+) -> CommandError {
+    CommandError::ConflictError {
+        message: message.into(),
+        hint: Some(hint.into()),
+    }
+}
+
+impl CommandError {
+    /// Exit code of the process if the command failed with this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CommandError::UserError { .. } | CommandError::ConfigError(_) => 1,
+            CommandError::CliError(_) | CommandError::ClapCliError(_) => 2,
+            CommandError::BrokenPipe => BROKEN_PIPE_EXIT_CODE,
+            CommandError::ConflictError { .. } => 4,
+            CommandError::InternalError(_) => 255,
+        }
+    }
+
+    /// Name of the error kind in machine-readable output.
+    fn kind_name(&self) -> &'static str {
+        match self {
+            CommandError::UserError { .. } => "user",
+            CommandError::ConfigError(_) => "config",
+            CommandError::ConflictError { .. } => "conflict",
+            CommandError::CliError(_) | CommandError::ClapCliError(_) => "cli",
+            CommandError::BrokenPipe => "broken_pipe",
+            CommandError::InternalError(_) => "internal",
+        }
+    }
+}
+
 fn format_similarity_hint<S: AsRef<str>>(candidates: &[S]) -> Option<String> {
     match candidates {
         [] => None,
@@ -227,6 +283,18 @@ impl From<TreeMergeError> for CommandError {
     }
 }
 
+impl From<ResolutionStoreError> for CommandError {
+    fn from(err: ResolutionStoreError) -> Self {
+        CommandError::InternalError(format!("Failed to record conflict resolutions: {err}"))
+    }
+}
+
+impl From<OpTagStoreError> for CommandError {
+    fn from(err: OpTagStoreError) -> Self {
+        CommandError::InternalError(format!("Failed to access operation tags: {err}"))
+    }
+}
+
 impl From<OpStoreError> for CommandError {
     fn from(err: OpStoreError) -> Self {
         CommandError::InternalError(format!("Failed to load an operation: {err}"))
@@ -352,6 +420,7 @@ impl From<RevsetResolutionError> for CommandError {
             | RevsetResolutionError::WorkspaceMissingWorkingCopy { .. }
             | RevsetResolutionError::AmbiguousCommitIdPrefix(_)
             | RevsetResolutionError::AmbiguousChangeIdPrefix(_)
+            | RevsetResolutionError::InvalidOperation { .. }
             | RevsetResolutionError::StoreError(_) => None,
         };
 
@@ -420,6 +489,7 @@ pub struct TracingSubscription {
         tracing_subscriber::Registry,
     >,
     _chrome_tracing_flush_guard: ChromeTracingFlushGuard,
+    timing: Timing,
 }
 
 impl TracingSubscription {
@@ -459,6 +529,7 @@ impl TracingSubscription {
             Err(_) => (None, ChromeTracingFlushGuard { _inner: None }),
         };
 
+        let timing = Timing::new();
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::fmt::Layer::default()
@@ -466,10 +537,12 @@ impl TracingSubscription {
                     .with_filter(filter),
             )
             .with(chrome_tracing_layer)
+            .with(timing.layer())
             .init();
         TracingSubscription {
             reload_log_filter,
             _chrome_tracing_flush_guard: chrome_tracing_flush_guard,
+            timing,
         }
     }
 
@@ -486,6 +559,17 @@ impl TracingSubscription {
         tracing::info!("verbose logging enabled");
         Ok(())
     }
+
+    /// Starts collecting the time spent in snapshotting, revset evaluation,
+    /// etc.
+    pub fn enable_timing(&self) {
+        self.timing.enable();
+    }
+
+    /// Writes the time collected since `enable_timing()` was called.
+    pub fn write_timing_report(&self, ui: &mut Ui) -> std::io::Result<()> {
+        self.timing.write_report(ui)
+    }
 }
 
 pub struct CommandHelper {
@@ -554,6 +638,11 @@ impl CommandHelper {
         &self.settings
     }
 
+    /// Checks the user and repo configs for unknown keys and invalid values.
+    pub fn validate_config(&self) -> Vec<ConfigIssue> {
+        self.layered_configs.validate(true)
+    }
+
     pub fn resolved_config_values(
         &self,
         prefix: &[&str],
@@ -595,9 +684,49 @@ impl CommandHelper {
     #[instrument(skip_all)]
     pub fn load_workspace(&self) -> Result<Workspace, CommandError> {
         let loader = self.workspace_loader()?;
-        loader
+        let workspace = loader
             .load(&self.settings, &self.store_factories)
-            .map_err(|err| map_workspace_load_error(err, &self.global_args))
+            .map_err(|err| map_workspace_load_error(err, &self.global_args))?;
+        Ok(workspace)
+    }
+
+    /// Snapshots the working copy of another workspace of the current repo.
+    /// Returns false if the workspace's location isn't known or it can't be
+    /// loaded, in which case its last snapshot will have to do.
+    #[instrument(skip(self, ui))]
+    pub fn snapshot_other_workspace(
+        &self,
+        ui: &mut Ui,
+        workspace_id: &WorkspaceId,
+    ) -> Result<bool, CommandError> {
+        let loader = self.workspace_loader()?;
+        let Some(workspace_root) = get_workspace_root(loader.repo_path(), workspace_id) else {
+            return Ok(false);
+        };
+        let Ok(other_loader) = WorkspaceLoader::init(&workspace_root) else {
+            return Ok(false);
+        };
+        let same_repo = match (
+            loader.repo_path().canonicalize(),
+            other_loader.repo_path().canonicalize(),
+        ) {
+            (Ok(path), Ok(other_path)) => path == other_path,
+            _ => false,
+        };
+        if !same_repo {
+            return Ok(false);
+        }
+        let Ok(workspace) = other_loader.load(&self.settings, &self.store_factories) else {
+            return Ok(false);
+        };
+        if workspace.workspace_id() != workspace_id {
+            return Ok(false);
+        }
+        let op_head = self.resolve_operation(ui, workspace.repo_loader())?;
+        let repo = workspace.repo_loader().load_at(&op_head)?;
+        let mut workspace_command = self.for_loaded_repo(ui, workspace, repo)?;
+        workspace_command.snapshot(ui)?;
+        Ok(true)
     }
 
     #[instrument(skip_all)]
@@ -638,11 +767,7 @@ impl CommandHelper {
                 },
             )
         } else {
-            resolve_op_for_load(
-                repo_loader.op_store(),
-                repo_loader.op_heads_store(),
-                &self.global_args.at_operation,
-            )
+            resolve_op_for_load(repo_loader, &self.global_args.at_operation)
         }
     }
 
@@ -706,6 +831,7 @@ pub struct WorkspaceCommandHelper {
     template_aliases_map: TemplateAliasesMap,
     may_update_working_copy: bool,
     working_copy_shared_with_git: bool,
+    template_width: AvailableWidth,
 }
 
 impl WorkspaceCommandHelper {
@@ -718,20 +844,11 @@ impl WorkspaceCommandHelper {
     ) -> Result<Self, CommandError> {
         let revset_aliases_map = load_revset_aliases(ui, &command.layered_configs)?;
         let template_aliases_map = load_template_aliases(ui, &command.layered_configs)?;
-        // Parse commit_summary template early to report error before starting mutable
-        // operation.
-        // TODO: Parsed template can be cached if it doesn't capture repo
-        let id_prefix_context = IdPrefixContext::default();
-        parse_commit_summary_template(
-            repo.as_ref(),
-            workspace.workspace_id(),
-            &id_prefix_context,
-            &template_aliases_map,
-            &command.settings,
-        )?;
         let loaded_at_head = command.global_args.at_operation == "@";
         let may_update_working_copy = loaded_at_head && !command.global_args.ignore_working_copy;
         let working_copy_shared_with_git = is_colocated_git_workspace(&workspace, &repo);
+        let template_width =
+            AvailableWidth::new(output_width(command.settings.config())?.map(usize::from));
         let helper = Self {
             cwd: command.cwd.clone(),
             string_args: command.string_args.clone(),
@@ -743,7 +860,21 @@ impl WorkspaceCommandHelper {
             template_aliases_map,
             may_update_working_copy,
             working_copy_shared_with_git,
+            template_width,
         };
+        // Parse commit_summary template early to report error before starting mutable
+        // operation.
+        // TODO: Parsed template can be cached if it doesn't capture repo
+        let id_prefix_context = IdPrefixContext::default();
+        parse_commit_summary_template(
+            helper.repo().as_ref(),
+            helper.repo().operation(),
+            helper.workspace_id(),
+            helper.revset_parse_context(),
+            &id_prefix_context,
+            &helper.template_aliases_map,
+            &helper.settings,
+        )?;
         // Parse short-prefixes revset early to report error before starting mutable
         // operation.
         helper.id_prefix_context()?;
@@ -795,7 +926,7 @@ impl WorkspaceCommandHelper {
         git::import_some_refs(
             tx.mut_repo(),
             git_repo,
-            &self.settings.git_settings(),
+            &self.settings.git_settings()?,
             |ref_name| !git::is_reserved_git_remote_ref(ref_name),
         )?;
         if tx.mut_repo().has_changes() {
@@ -903,7 +1034,9 @@ impl WorkspaceCommandHelper {
     ) -> Result<(LockedWorkingCopy, Commit), CommandError> {
         let (locked_working_copy, wc_commit) = self.unchecked_start_working_copy_mutation()?;
         if wc_commit.tree_id() != locked_working_copy.old_tree_id() {
-            return Err(user_error("Concurrent working copy operation. Try again."));
+            return Err(conflict_error(
+                "Concurrent working copy operation. Try again.",
+            ));
         }
         Ok((locked_working_copy, wc_commit))
     }
@@ -996,6 +1129,7 @@ impl WorkspaceCommandHelper {
         resolve_single_op(
             self.repo().op_store(),
             self.repo().op_heads_store(),
+            &OpTagStore::load(self.repo().repo_path()),
             || Ok(self.repo().operation().clone()),
             op_str,
         )
@@ -1025,33 +1159,34 @@ impl WorkspaceCommandHelper {
                     .map(|c| self.format_commit_summary(c))
                     .join("\n")
                     + elided.then_some("\n...").unwrap_or_default();
-                let hint = if commits[0].change_id() == commits[1].change_id() {
+                let message =
+                    format!(r#"Revset "{revision_str}" resolved to more than one revision"#);
+                if commits[0].change_id() == commits[1].change_id() {
                     // Separate hint if there's commits with same change id
-                    format!(
+                    let hint = format!(
                         r#"The revset "{revision_str}" resolved to these revisions:
 {commits_summary}
 Some of these commits have the same change id. Abandon one of them with `jj abandon -r <REVISION>`."#,
-                    )
+                    );
+                    Err(conflict_error_with_hint(message, hint))
                 } else if let RevsetExpression::CommitRef(RevsetCommitRef::Symbol(branch_name)) =
                     revset_expression.as_ref()
                 {
                     // Separate hint if there's a conflicted branch
-                    format!(
+                    let hint = format!(
                         r#"Branch {branch_name} resolved to multiple revisions because it's conflicted.
 It resolved to these revisions:
 {commits_summary}
 Set which revision the branch points to with `jj branch set {branch_name} -r <REVISION>`."#,
-                    )
+                    );
+                    Err(conflict_error_with_hint(message, hint))
                 } else {
-                    format!(
+                    let hint = format!(
                         r#"The revset "{revision_str}" resolved to these revisions:
 {commits_summary}"#,
-                    )
-                };
-                Err(user_error_with_hint(
-                    format!(r#"Revset "{revision_str}" resolved to more than one revision"#),
-                    hint,
-                ))
+                    );
+                    Err(user_error_with_hint(message, hint))
+                }
             }
         }
     }
@@ -1131,6 +1266,8 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
                     } => *is_legacy || has_legacy_rule(roots) || has_legacy_rule(heads),
                     RevsetExpression::Heads(expression) => has_legacy_rule(expression),
                     RevsetExpression::Roots(expression) => has_legacy_rule(expression),
+                    RevsetExpression::Predecessors(expression) => has_legacy_rule(expression),
+                    RevsetExpression::ForkPoint(expression) => has_legacy_rule(expression),
                     RevsetExpression::Latest {
                         candidates,
                         count: _,
@@ -1161,13 +1298,29 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
         Ok(revset::optimize(expression))
     }
 
+    #[instrument(skip_all)]
     pub fn evaluate_revset<'repo>(
         &'repo self,
         revset_expression: Rc<RevsetExpression>,
     ) -> Result<Box<dyn Revset<'repo> + 'repo>, CommandError> {
         let symbol_resolver = self.revset_symbol_resolver()?;
-        let revset_expression =
-            revset_expression.resolve_user_expression(self.repo().as_ref(), &symbol_resolver)?;
+        let revset_expression = revset_expression
+            .resolve_user_expression(self.repo().as_ref(), &symbol_resolver)
+            .map_err(|err| match err {
+                // The workspace may have been added after the loaded operation
+                RevsetResolutionError::WorkspaceMissingWorkingCopy { name }
+                    if self.global_args.at_operation != "@" =>
+                {
+                    user_error_with_hint(
+                        format!(
+                            r#"Workspace "{name}" doesn't have a working-copy commit at operation {}"#,
+                            short_operation_hash(self.repo().op_id())
+                        ),
+                        "Specify revisions other than `@`, e.g. with `-r`.",
+                    )
+                }
+                err => err.into(),
+            })?;
         Ok(revset_expression.evaluate(self.repo().as_ref())?)
     }
 
@@ -1190,9 +1343,27 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
             Box::new(|repo, prefix| id_prefix_context.resolve_commit_prefix(repo, prefix));
         let change_id_resolver: revset::PrefixResolver<Vec<CommitId>> =
             Box::new(|repo, prefix| id_prefix_context.resolve_change_prefix(repo, prefix));
+        let operation_resolver: revset::OperationResolver = Box::new(|op_str| {
+            let operation = self
+                .resolve_single_op(op_str)
+                .and_then(|operation| commits_added_by_operation(self.repo().as_ref(), &operation));
+            operation.map_err(|err| RevsetResolutionError::InvalidOperation {
+                name: op_str.to_owned(),
+                message: match err {
+                    CommandError::UserError { message, .. }
+                    | CommandError::ConfigError(message)
+                    | CommandError::ConflictError { message, .. }
+                    | CommandError::CliError(message)
+                    | CommandError::InternalError(message) => message,
+                    CommandError::ClapCliError(err) => err.to_string(),
+                    CommandError::BrokenPipe => "Broken pipe".to_owned(),
+                },
+            })
+        });
         let symbol_resolver = DefaultSymbolResolver::new(self.repo().as_ref())
             .with_commit_id_resolver(commit_id_resolver)
-            .with_change_id_resolver(change_id_resolver);
+            .with_change_id_resolver(change_id_resolver)
+            .with_operation_resolver(operation_resolver);
         Ok(symbol_resolver)
     }
 
@@ -1221,21 +1392,61 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
         &self.template_aliases_map
     }
 
+    /// Width available to the commit templates, which `wrap(0, ..)` fills
+    /// lines at.
+    pub fn template_width(&self) -> &AvailableWidth {
+        &self.template_width
+    }
+
     pub fn parse_commit_template(
         &self,
         template_text: &str,
     ) -> Result<Box<dyn Template<Commit> + '_>, CommandError> {
         let id_prefix_context = self.id_prefix_context()?;
+        self.parse_commit_template_with_id_prefix_context(template_text, id_prefix_context)
+    }
+
+    /// Parses commit template which disambiguates id prefixes within the given
+    /// context instead of the one configured by `revsets.short-prefixes`.
+    pub fn parse_commit_template_with_id_prefix_context<'a>(
+        &'a self,
+        template_text: &str,
+        id_prefix_context: &'a IdPrefixContext,
+    ) -> Result<Box<dyn Template<Commit> + 'a>, CommandError> {
         let template = commit_templater::parse(
             self.repo().as_ref(),
+            self.repo().operation(),
             self.workspace_id(),
+            self.revset_parse_context(),
             id_prefix_context,
+            HyperlinkPatterns::from_config(self.settings.config())?,
+            DescriptionLabels::from_config(self.settings.config())?,
+            SshVerifier::from_config(self.settings.config())?,
+            self.template_width.clone(),
+            self.settings.config(),
             template_text,
             &self.template_aliases_map,
         )?;
         Ok(template)
     }
 
+    /// Returns the types of the commit keywords referenced by the parsed
+    /// template.
+    pub fn commit_template_keyword_types<'i>(
+        &self,
+        node: &ExpressionNode<'i>,
+    ) -> Result<Vec<(&'i str, &'static str)>, CommandError> {
+        let id_prefix_context = self.id_prefix_context()?;
+        Ok(commit_templater::keyword_types(
+            self.repo().as_ref(),
+            self.repo().operation(),
+            self.workspace_id(),
+            self.revset_parse_context(),
+            id_prefix_context,
+            node,
+        ))
+    }
+
     /// Returns one-line summary of the given `commit`.
     pub fn format_commit_summary(&self, commit: &Commit) -> String {
         let mut output = Vec::new();
@@ -1256,7 +1467,9 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
             .expect("parse error should be confined by WorkspaceCommandHelper::new()");
         let template = parse_commit_summary_template(
             self.repo().as_ref(),
+            self.repo().operation(),
             self.workspace_id(),
+            self.revset_parse_context(),
             id_prefix_context,
             &self.template_aliases_map,
             &self.settings,
@@ -1266,16 +1479,9 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
         Ok(())
     }
 
-    pub fn check_rewritable<'a>(
-        &self,
-        commits: impl IntoIterator<Item = &'a Commit>,
-    ) -> Result<(), CommandError> {
-        let to_rewrite_revset = RevsetExpression::commits(
-            commits
-                .into_iter()
-                .map(|commit| commit.id().clone())
-                .collect(),
-        );
+    /// Parses the set of immutable commits, which are the ancestors of
+    /// `immutable_heads()` and the root commit.
+    pub fn parse_immutable_revset(&self) -> Result<Rc<RevsetExpression>, CommandError> {
         let (params, immutable_heads_str) = self
             .revset_aliases_map
             .get_function("immutable_heads")
@@ -1286,11 +1492,24 @@ Set which revision the branch points to with `jj branch set {branch_name} -r <RE
             ));
         }
         let immutable_heads_revset = self.parse_revset(immutable_heads_str, None)?;
-        let immutable_revset = immutable_heads_revset
+        Ok(immutable_heads_revset
             .ancestors()
             .union(&RevsetExpression::commit(
                 self.repo().store().root_commit_id().clone(),
-            ));
+            )))
+    }
+
+    pub fn check_rewritable<'a>(
+        &self,
+        commits: impl IntoIterator<Item = &'a Commit>,
+    ) -> Result<(), CommandError> {
+        let to_rewrite_revset = RevsetExpression::commits(
+            commits
+                .into_iter()
+                .map(|commit| commit.id().clone())
+                .collect(),
+        );
+        let immutable_revset = self.parse_immutable_revset()?;
         let revset = self.evaluate_revset(to_rewrite_revset.intersection(&immutable_revset))?;
         if let Some(commit) = revset.iter().commits(self.repo().store()).next() {
             let commit = commit?;
@@ -1391,6 +1610,12 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
                 .set_tree_id(new_tree_id)
                 .write()?;
             mut_repo.set_wc_commit(workspace_id, commit.id().clone())?;
+            record_conflict_resolutions(
+                &self.settings,
+                mut_repo.base_repo(),
+                &wc_commit.tree()?,
+                &commit.tree()?,
+            )?;
 
             // Rebase descendants
             let num_rebased = mut_repo.rebase_descendants(&self.settings)?;
@@ -1426,11 +1651,13 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
                 return Ok(());
             }
         };
+        let checkout_options = checkout_options(&self.settings)?;
         let stats = update_working_copy(
             &self.user_repo.repo,
             self.workspace.working_copy_mut(),
             maybe_old_commit,
             &new_commit,
+            &checkout_options,
         )?;
         if Some(&new_commit) != maybe_old_commit {
             ui.write("Working copy now at: ")?;
@@ -1481,6 +1708,11 @@ See https://github.com/martinvonz/jj/blob/main/docs/working-copy.md#stale-workin
             .map(|commit_id| store.get_commit(commit_id))
             .transpose()?;
         self.user_repo = ReadonlyUserRepo::new(tx.commit());
+        // Workspaces created by older versions haven't recorded their location.
+        // It's only needed by commands run in other workspaces, so it's recorded
+        // by the commands that modify the repo anyway, and failing to record it
+        // isn't an error.
+        let _ = self.workspace.record_workspace_root();
         if self.may_update_working_copy {
             self.update_working_copy(ui, maybe_old_commit.as_ref())?;
         }
@@ -1608,7 +1840,9 @@ impl WorkspaceCommandTransaction<'_> {
         let id_prefix_context = IdPrefixContext::default();
         let template = parse_commit_summary_template(
             self.tx.repo(),
+            self.tx.base_repo().operation(),
             self.helper.workspace_id(),
+            self.helper.revset_parse_context(),
             &id_prefix_context,
             &self.helper.template_aliases_map,
             &self.helper.settings,
@@ -1618,9 +1852,112 @@ impl WorkspaceCommandTransaction<'_> {
     }
 
     pub fn finish(self, ui: &mut Ui) -> Result<(), CommandError> {
+        if self.helper.global_args.dry_run {
+            return self.show_dry_run(ui);
+        }
         self.helper.finish_transaction(ui, self.tx)
     }
 
+    /// Prints the changes the transaction would make instead of committing it.
+    fn show_dry_run(mut self, ui: &mut Ui) -> Result<(), CommandError> {
+        ui.set_discard_stdout(false);
+        let mut_repo = self.tx.mut_repo();
+        if !mut_repo.has_changes() {
+            writeln!(ui, "Nothing would change.")?;
+            return Ok(());
+        }
+        // The rebased descendants are listed as rewritten commits below.
+        mut_repo.rebase_descendants(&self.helper.settings)?;
+
+        let old_repo = self.tx.base_repo().clone();
+        let repo = self.tx.repo();
+        let old_heads =
+            RevsetExpression::commits(old_repo.view().heads().iter().cloned().collect());
+        let new_heads = RevsetExpression::commits(repo.view().heads().iter().cloned().collect());
+        let evaluate = |expression: Rc<RevsetExpression>| -> Result<Vec<Commit>, CommandError> {
+            let revset = expression.resolve(repo)?.evaluate(repo)?;
+            Ok(revset.iter().commits(repo.store()).try_collect()?)
+        };
+        let added_commits = evaluate(old_heads.range(&new_heads))?;
+        let removed_commits = evaluate(new_heads.range(&old_heads))?;
+
+        let mut formatter = ui.stdout_formatter();
+        let formatter = formatter.as_mut();
+        let removed_ids: HashSet<&CommitId> = removed_commits.iter().map(|c| c.id()).collect();
+        let mut rewritten_ids = HashSet::new();
+        for commit in added_commits.iter().rev() {
+            let predecessor_ids = commit
+                .predecessor_ids()
+                .iter()
+                .filter(|id| removed_ids.contains(id))
+                .collect_vec();
+            if predecessor_ids.is_empty() {
+                write!(formatter, "Would create commit ")?;
+            } else {
+                write!(
+                    formatter,
+                    "Would rewrite commit {} as ",
+                    predecessor_ids
+                        .iter()
+                        .map(|id| short_commit_hash(id))
+                        .join(", ")
+                )?;
+                rewritten_ids.extend(predecessor_ids);
+            }
+            self.write_commit_summary(formatter, commit)?;
+            writeln!(formatter)?;
+        }
+        for commit in removed_commits.iter().rev() {
+            if !rewritten_ids.contains(commit.id()) {
+                write!(formatter, "Would abandon commit ")?;
+                self.write_commit_summary(formatter, commit)?;
+                writeln!(formatter)?;
+            }
+        }
+
+        let format_target =
+            |target: &RefTarget| target.added_ids().map(short_commit_hash).join(", ");
+        let old_view = old_repo.view();
+        let new_view = repo.view();
+        let branch_names: BTreeSet<&str> = old_view
+            .local_branches()
+            .chain(new_view.local_branches())
+            .map(|(name, _)| name)
+            .collect();
+        for name in branch_names {
+            let old_target = old_view.get_local_branch(name);
+            let new_target = new_view.get_local_branch(name);
+            if old_target == new_target {
+                continue;
+            }
+            if old_target.is_absent() {
+                writeln!(
+                    formatter,
+                    "Would create branch {name} pointing to {}",
+                    format_target(new_target)
+                )?;
+            } else if new_target.is_absent() {
+                writeln!(
+                    formatter,
+                    "Would delete branch {name} (was {})",
+                    format_target(old_target)
+                )?;
+            } else {
+                writeln!(
+                    formatter,
+                    "Would move branch {name} from {} to {}",
+                    format_target(old_target),
+                    format_target(new_target)
+                )?;
+            }
+        }
+        writeln!(
+            formatter,
+            "Dry-run requested, not committing the operation."
+        )?;
+        Ok(())
+    }
+
     pub fn into_inner(self) -> Transaction {
         self.tx
     }
@@ -1787,6 +2124,16 @@ pub fn check_stale_working_copy(
     }
 }
 
+pub fn print_config_issues(ui: &mut Ui, issues: &[ConfigIssue]) -> std::io::Result<()> {
+    for issue in issues {
+        writeln!(ui.warning(), "{issue}")?;
+        if let Some(hint) = &issue.hint {
+            writeln!(ui.hint(), "Hint: {hint}")?;
+        }
+    }
+    Ok(())
+}
+
 pub fn print_checkout_stats(ui: &mut Ui, stats: CheckoutStats) -> Result<(), std::io::Error> {
     if stats.added_files > 0 || stats.updated_files > 0 || stats.removed_files > 0 {
         writeln!(
@@ -1837,10 +2184,12 @@ fn expand_git_path(path_str: String) -> PathBuf {
 }
 
 pub fn resolve_op_for_load(
-    op_store: &Arc<dyn OpStore>,
-    op_heads_store: &Arc<dyn OpHeadsStore>,
+    repo_loader: &RepoLoader,
     op_str: &str,
 ) -> Result<Operation, OpHeadResolutionError<CommandError>> {
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store();
+    let op_tag_store = OpTagStore::load(repo_loader.repo_path());
     let get_current_op = || {
         op_heads_store::resolve_op_heads(op_heads_store.as_ref(), op_store, |_| {
             Err(user_error(format!(
@@ -1848,14 +2197,21 @@ pub fn resolve_op_for_load(
             )))
         })
     };
-    let operation = resolve_single_op(op_store, op_heads_store, get_current_op, op_str)
-        .map_err(OpHeadResolutionError::Err)?;
+    let operation = resolve_single_op(
+        op_store,
+        op_heads_store,
+        &op_tag_store,
+        get_current_op,
+        op_str,
+    )
+    .map_err(OpHeadResolutionError::Err)?;
     Ok(operation)
 }
 
 fn resolve_single_op(
     op_store: &Arc<dyn OpStore>,
     op_heads_store: &Arc<dyn OpHeadsStore>,
+    op_tag_store: &OpTagStore,
     get_current_op: impl FnOnce() -> Result<Operation, OpHeadResolutionError<CommandError>>,
     op_str: &str,
 ) -> Result<Operation, CommandError> {
@@ -1863,8 +2219,17 @@ fn resolve_single_op(
     let op_postfix = &op_str[op_symbol.len()..];
     let mut operation = match op_symbol {
         "@" => get_current_op(),
-        s => resolve_single_op_from_store(op_store, op_heads_store, s)
-            .map_err(OpHeadResolutionError::Err),
+        s => match op_tag_store.get(s)? {
+            Some(op_id) => {
+                let store_operation = op_store.read_operation(&op_id)?;
+                Ok(Operation::new(op_store.clone(), op_id, store_operation))
+            }
+            None if op_tag_store::is_valid_tag_name(s) => Err(OpHeadResolutionError::Err(
+                user_error(format!(r#"No operation tag named "{s}""#)),
+            )),
+            None => resolve_single_op_from_store(op_store, op_heads_store, s)
+                .map_err(OpHeadResolutionError::Err),
+        },
     }?;
     for _ in op_postfix.chars() {
         operation = match operation.parents().as_slice() {
@@ -2003,11 +2368,47 @@ pub fn resolve_multiple_nonempty_revsets_default_single(
     Ok(all_commits)
 }
 
+/// Records resolutions of the conflicts in `old_tree` that are resolved in
+/// `new_tree` if `conflicts.remember-resolutions` is enabled.
+pub fn record_conflict_resolutions(
+    settings: &UserSettings,
+    repo: &ReadonlyRepo,
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+) -> Result<(), CommandError> {
+    if settings.remember_conflict_resolutions() && old_tree.has_conflict() {
+        ResolutionStore::load(repo.repo_path()).record_tree_resolutions(old_tree, new_tree)?;
+    }
+    Ok(())
+}
+
+pub fn checkout_options(settings: &UserSettings) -> Result<CheckoutOptions, config::ConfigError> {
+    Ok(CheckoutOptions {
+        conflict_materialization: settings.conflict_materialization()?,
+        symlink_materialization: settings.symlink_materialization()?,
+    })
+}
+
+/// Converts a checkout error to a command error. Errors caused by the contents
+/// of the tree are reported as user errors with a hint.
+pub fn checkout_error(message: impl Into<String>, err: CheckoutError) -> CommandError {
+    let message = message.into();
+    match err {
+        CheckoutError::CaseCollision { .. } => user_error_with_hint(
+            format!("{message}: {err}"),
+            "Use `jj sparse set --remove <path>` to leave one of the paths out of the working \
+             copy, or rename one of them on a case-sensitive file system.",
+        ),
+        _ => CommandError::InternalError(format!("{message}: {err}")),
+    }
+}
+
 pub fn update_working_copy(
     repo: &Arc<ReadonlyRepo>,
     wc: &mut WorkingCopy,
     old_commit: Option<&Commit>,
     new_commit: &Commit,
+    options: &CheckoutOptions,
 ) -> Result<Option<CheckoutStats>, CommandError> {
     let old_tree_id = old_commit.map(|commit| commit.tree_id().clone());
     let stats = if Some(new_commit.tree_id()) != old_tree_id.as_ref() {
@@ -2015,13 +2416,17 @@ pub fn update_working_copy(
         // warning for most commands (but be an error for the checkout command)
         let new_tree = new_commit.tree()?;
         let stats = wc
-            .check_out(repo.op_id().clone(), old_tree_id.as_ref(), &new_tree)
+            .check_out(
+                repo.op_id().clone(),
+                old_tree_id.as_ref(),
+                &new_tree,
+                options,
+            )
             .map_err(|err| {
-                CommandError::InternalError(format!(
-                    "Failed to check out commit {}: {}",
-                    new_commit.id().hex(),
-                    err
-                ))
+                checkout_error(
+                    format!("Failed to check out commit {}", new_commit.id().hex()),
+                    err,
+                )
             })?;
         Some(stats)
     } else {
@@ -2060,10 +2465,44 @@ fn load_template_aliases(
     Ok(aliases_map)
 }
 
+/// Returns the template given by the `-T`/`--template` or `--template-file`
+/// argument, if any. A `-T` template starting with `@` is read from the file at
+/// the rest of the argument. The path `-` reads the template from stdin.
+pub fn read_template_arg(
+    cwd: &Path,
+    template: Option<&str>,
+    template_file: Option<&Path>,
+) -> Result<Option<String>, CommandError> {
+    let path = match (template_file, template) {
+        (Some(path), _) => path,
+        (None, Some(text)) => match text.strip_prefix('@') {
+            Some(path) => Path::new(path),
+            None => return Ok(Some(text.to_owned())),
+        },
+        (None, None) => return Ok(None),
+    };
+    if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+            .map_err(|err| user_error(format!("Failed to read template from stdin: {err}")))?;
+        Ok(Some(text))
+    } else {
+        let text = std::fs::read_to_string(cwd.join(path)).map_err(|err| {
+            user_error(format!(
+                "Failed to read template file {}: {err}",
+                path.display()
+            ))
+        })?;
+        Ok(Some(text))
+    }
+}
+
 #[instrument(skip_all)]
 fn parse_commit_summary_template<'a>(
     repo: &'a dyn Repo,
+    operation: &'a Operation,
     workspace_id: &WorkspaceId,
+    revset_parse_context: RevsetParseContext<'_>,
     id_prefix_context: &'a IdPrefixContext,
     aliases_map: &TemplateAliasesMap,
     settings: &UserSettings,
@@ -2071,8 +2510,15 @@ fn parse_commit_summary_template<'a>(
     let template_text = settings.config().get_string("templates.commit_summary")?;
     Ok(commit_templater::parse(
         repo,
+        operation,
         workspace_id,
+        revset_parse_context,
         id_prefix_context,
+        HyperlinkPatterns::from_config(settings.config())?,
+        DescriptionLabels::from_config(settings.config())?,
+        SshVerifier::from_config(settings.config())?,
+        AvailableWidth::new(output_width(settings.config())?.map(usize::from)),
+        settings.config(),
         &template_text,
         aliases_map,
     )?)
@@ -2123,6 +2569,29 @@ impl LogContentFormat {
             }
         }
     }
+
+    /// Warns if the template uses `table()`, whose cells can only be aligned
+    /// into columns if the rows are written as is, without graph or wrapping.
+    pub fn warn_unaligned_table(
+        &self,
+        ui: &Ui,
+        template_text: &str,
+        aliases_map: &TemplateAliasesMap,
+        graph: bool,
+    ) -> Result<(), CommandError> {
+        if !graph && matches!(self, LogContentFormat::NoWrap) {
+            return Ok(());
+        }
+        let node = template_parser::parse(template_text, aliases_map)?;
+        if template_parser::calls_function(&node, "table") {
+            writeln!(
+                ui.warning(),
+                "warning: The cells of table() are only aligned into columns with --no-graph and \
+                 ui.log-word-wrap disabled."
+            )?;
+        }
+        Ok(())
+    }
 }
 
 // TODO: Use a proper TOML library to serialize instead.
@@ -2262,6 +2731,34 @@ pub fn short_change_hash(change_id: &ChangeId) -> String {
     to_reverse_hex(&change_id.hex()[0..12]).unwrap()
 }
 
+/// Returns the commits that became visible in the operation, i.e. the commits
+/// reachable from the heads of its view but not from the heads of its parents'
+/// views.
+pub fn commits_added_by_operation(
+    repo: &dyn Repo,
+    operation: &Operation,
+) -> Result<Vec<CommitId>, CommandError> {
+    // The index may not know about commits of an operation that isn't an
+    // ancestor of the current one.
+    let known_heads = |operation: &Operation| -> Result<Vec<CommitId>, CommandError> {
+        let view = operation.view()?;
+        Ok(view
+            .heads()
+            .iter()
+            .filter(|id| repo.index().has_id(id))
+            .cloned()
+            .collect())
+    };
+    let heads = known_heads(operation)?;
+    let mut parent_heads = vec![];
+    for parent in operation.parents() {
+        parent_heads.extend(known_heads(&parent)?);
+    }
+    let revset = revset::walk_revs(repo, &heads, &parent_heads)?;
+    let commit_ids = revset.iter().collect();
+    Ok(commit_ids)
+}
+
 pub fn short_operation_hash(operation_id: &OperationId) -> String {
     operation_id.hex()[0..12].to_string()
 }
@@ -2333,6 +2830,16 @@ pub struct GlobalArgs {
     /// Enable verbose logging
     #[arg(long, short = 'v', global = true, help_heading = "Global Options")]
     pub verbose: bool,
+    /// Only show what the command would change, without changing anything
+    ///
+    /// This is supported by `jj rebase`, `jj abandon`, `jj squash`, `jj branch
+    /// delete`, `jj branch move`, and `jj git push`.
+    #[arg(long, global = true, help_heading = "Global Options")]
+    pub dry_run: bool,
+    /// Report the time spent in snapshotting, revset evaluation, template
+    /// rendering, index access, and backend I/O
+    #[arg(long, global = true, help_heading = "Global Options")]
+    pub debug_timing: bool,
 
     #[command(flatten)]
     pub early_args: EarlyArgs,
@@ -2359,6 +2866,23 @@ pub struct EarlyArgs {
     // Parsing with ignore_errors will crash if this is bool, so use
     // Option<bool>.
     pub no_pager: Option<bool>,
+    /// Width of the output, instead of the terminal width
+    #[arg(
+        long,
+        value_name = "COLUMNS",
+        value_parser = clap::value_parser!(u16).range(1..),
+        global = true,
+        help_heading = "Global Options"
+    )]
+    pub width: Option<u16>,
+    /// Format of error messages and of `jj log` output (text, json)
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        global = true,
+        help_heading = "Global Options"
+    )]
+    pub output: Option<OutputFormat>,
     /// Additional configuration options (can be repeated)
     //  TODO: Introduce a `--config` option with simpler syntax for simple
     //  cases, designed so that `--config ui.color=auto` works
@@ -2443,21 +2967,31 @@ fn resolve_default_command(
 
     if let Some(matches) = matches {
         if matches.subcommand_name().is_none() {
-            if config.get_string("ui.default-command").is_err() {
-                writeln!(
-                    ui.hint(),
-                    "Hint: Use `jj -h` for a list of available commands."
-                )?;
-                writeln!(
-                    ui.hint(),
-                    "Set the config `ui.default-command = \"log\"` to disable this message."
-                )?;
-            }
-            let default_command = config
-                .get_string("ui.default-command")
-                .unwrap_or_else(|_| "log".to_string());
+            // The default command can be a command name or a list of the
+            // command name and its arguments.
+            let default_command = match config.get_string("ui.default-command").optional() {
+                Ok(name) => name.map(|name| vec![name]),
+                Err(_) => Some(config.get::<Vec<String>>("ui.default-command")?),
+            };
+            let default_command = match default_command {
+                Some(args) if args.is_empty() => {
+                    return Err(user_error("ui.default-command must not be an empty list"));
+                }
+                Some(args) => args,
+                None => {
+                    writeln!(
+                        ui.hint(),
+                        "Hint: Use `jj -h` for a list of available commands."
+                    )?;
+                    writeln!(
+                        ui.hint(),
+                        "Set the config `ui.default-command = \"log\"` to disable this message."
+                    )?;
+                    vec!["log".to_string()]
+                }
+            };
             // Insert the default command directly after the path to the binary.
-            string_args.insert(1, default_command);
+            string_args.splice(1..1, default_command);
         }
     }
     Ok(string_args)
@@ -2551,6 +3085,12 @@ fn handle_early_args(
     if args.no_pager.unwrap_or_default() {
         args.config_toml.push(r#"ui.paginate="never""#.to_owned());
     }
+    if let Some(width) = args.width {
+        args.config_toml.push(format!("ui.width={width}"));
+    }
+    if let Some(format) = args.output {
+        args.config_toml.push(format!(r#"ui.output="{format}""#));
+    }
     if !args.config_toml.is_empty() {
         layered_configs.parse_config_args(&args.config_toml)?;
         ui.reset(&layered_configs.merge())?;
@@ -2592,38 +3132,51 @@ pub fn parse_args(
         // TODO: set up verbose logging as early as possible
         tracing_subscription.enable_verbose_logging()?;
     }
+    if args.global_args.debug_timing {
+        tracing_subscription.enable_timing();
+    }
 
     Ok((matches, args))
 }
 
 const BROKEN_PIPE_EXIT_CODE: u8 = 3;
 
+const CONFIG_ERROR_HINT: &str =
+    "For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.";
+
 pub fn handle_command_result(
     ui: &mut Ui,
     result: Result<(), CommandError>,
 ) -> std::io::Result<ExitCode> {
-    match result {
-        Ok(()) => Ok(ExitCode::SUCCESS),
-        Err(CommandError::UserError { message, hint }) => {
+    let err = match result {
+        Ok(()) => return Ok(ExitCode::SUCCESS),
+        Err(err) => err,
+    };
+    if ui.output_format() == OutputFormat::Json {
+        if let Some(exit_code) = write_json_error(ui, &err)? {
+            return Ok(exit_code);
+        }
+    }
+    let exit_code = ExitCode::from(err.exit_code());
+    match err {
+        CommandError::UserError { message, hint }
+        | CommandError::ConflictError { message, hint } => {
             writeln!(ui.error(), "Error: {message}")?;
             if let Some(hint) = hint {
                 writeln!(ui.hint(), "Hint: {hint}")?;
             }
-            Ok(ExitCode::from(1))
+            Ok(exit_code)
         }
-        Err(CommandError::ConfigError(message)) => {
+        CommandError::ConfigError(message) => {
             writeln!(ui.error(), "Config error: {message}")?;
-            writeln!(
-                ui.hint(),
-                "For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md."
-            )?;
-            Ok(ExitCode::from(1))
+            writeln!(ui.hint(), "{CONFIG_ERROR_HINT}")?;
+            Ok(exit_code)
         }
-        Err(CommandError::CliError(message)) => {
+        CommandError::CliError(message) => {
             writeln!(ui.error(), "Error: {message}")?;
-            Ok(ExitCode::from(2))
+            Ok(exit_code)
         }
-        Err(CommandError::ClapCliError(inner)) => {
+        CommandError::ClapCliError(inner) => {
             let clap_str = if ui.color() {
                 inner.render().ansi().to_string()
             } else {
@@ -2646,21 +3199,49 @@ pub fn handle_command_result(
                 }
                 _ => {
                     ui.write_stderr(&clap_str)?;
-                    Ok(ExitCode::from(2))
+                    Ok(exit_code)
                 }
             }
         }
-        Err(CommandError::BrokenPipe) => {
+        CommandError::BrokenPipe => {
             // A broken pipe is not an error, but a signal to exit gracefully.
-            Ok(ExitCode::from(BROKEN_PIPE_EXIT_CODE))
+            Ok(exit_code)
         }
-        Err(CommandError::InternalError(message)) => {
+        CommandError::InternalError(message) => {
             writeln!(ui.error(), "Internal error: {message}")?;
-            Ok(ExitCode::from(255))
+            Ok(exit_code)
         }
     }
 }
 
+/// Prints the error as a JSON object to stderr. Returns `None` if the error
+/// isn't reported as such, e.g. a request to display help.
+fn write_json_error(ui: &mut Ui, err: &CommandError) -> std::io::Result<Option<ExitCode>> {
+    let (message, hint) = match err {
+        CommandError::UserError { message, hint }
+        | CommandError::ConflictError { message, hint } => (message.clone(), hint.clone()),
+        CommandError::ConfigError(message) => (message.clone(), Some(CONFIG_ERROR_HINT.to_owned())),
+        CommandError::CliError(message) | CommandError::InternalError(message) => {
+            (message.clone(), None)
+        }
+        CommandError::ClapCliError(inner) => match inner.kind() {
+            clap::error::ErrorKind::DisplayHelp
+            | clap::error::ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+            | clap::error::ErrorKind::DisplayVersion => return Ok(None),
+            _ => (inner.render().to_string().trim_end().to_owned(), None),
+        },
+        CommandError::BrokenPipe => return Ok(None),
+    };
+    let json = serde_json::json!({
+        "kind": err.kind_name(),
+        "code": err.exit_code(),
+        "message": message,
+        "hint": hint,
+    });
+    ui.write_stderr(&format!("{json}\n"))?;
+    Ok(Some(ExitCode::from(err.exit_code())))
+}
+
 /// CLI command builder and runner.
 #[must_use]
 pub struct CliRunner {
@@ -2774,11 +3355,33 @@ impl CliRunner {
 
         let maybe_workspace_loader = init_workspace_loader(&cwd, &args.global_args);
         if let Ok(loader) = &maybe_workspace_loader {
-            // TODO: maybe show error/warning if repo config contained command alias
-            layered_configs.read_repo_config(loader.repo_path())?;
+            let repo_config_path = loader.repo_path().join("config.toml");
+            if !repo_config_requires_trust(&layered_configs.merge())?
+                || is_repo_config_trusted(&repo_config_path)?
+            {
+                // TODO: maybe show error/warning if repo config contained command alias
+                layered_configs.read_repo_config(loader.repo_path())?;
+            } else {
+                writeln!(
+                    ui.warning(),
+                    "Ignoring untrusted repo config {}",
+                    repo_config_path.display()
+                )?;
+                writeln!(
+                    ui.hint(),
+                    "Hint: Review the file and run `jj config trust` to use it."
+                )?;
+            }
         }
         let config = layered_configs.merge();
         ui.reset(&config)?;
+        // `jj config validate` reports the same issues itself
+        let is_config_validate = matches
+            .subcommand_matches("config")
+            .is_some_and(|matches| matches.subcommand_name() == Some("validate"));
+        if !is_config_validate {
+            print_config_issues(ui, &layered_configs.validate(false))?;
+        }
         let settings = UserSettings::from_config(config);
         let command_helper = CommandHelper::new(
             self.app,
@@ -2808,9 +3411,11 @@ impl CliRunner {
         let layered_configs = LayeredConfigs::from_environment(default_config);
         let mut ui = Ui::with_config(&layered_configs.merge())
             .expect("default config should be valid, env vars are stringly typed");
+        let tracing_subscription = self.tracing_subscription.clone();
         let result = self.run_internal(&mut ui, layered_configs);
         let exit_code = handle_command_result(&mut ui, result)
             .unwrap_or_else(|_| ExitCode::from(BROKEN_PIPE_EXIT_CODE));
+        tracing_subscription.write_timing_report(&mut ui).ok();
         ui.finalize_pager();
         exit_code
     }
@@ -1,17 +1,23 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use clap::builder::NonEmptyStringValueParser;
 use itertools::Itertools;
 use jj_lib::backend::{CommitId, ObjectId};
+use jj_lib::commit::Commit;
 use jj_lib::git;
 use jj_lib::op_store::{BranchTarget, RefTarget};
 use jj_lib::repo::Repo;
-use jj_lib::revset::{self, RevsetExpression};
+use jj_lib::revset::{self, RevsetExpression, RevsetIteratorExt};
 use jj_lib::view::View;
 
-use crate::cli_util::{user_error, user_error_with_hint, CommandError, CommandHelper, RevisionArg};
+use crate::branch_templater::{self, BranchListItem, BranchTemplate, RemoteBranch};
+use crate::cli_util::{
+    short_commit_hash, user_error, user_error_with_hint, CommandError, CommandHelper, RevisionArg,
+    WorkspaceCommandHelper,
+};
+use crate::commands::git::{get_git_repo, remove_branch_upstream, set_branch_upstream};
 use crate::commands::make_branch_term;
-use crate::formatter::Formatter;
+use crate::formatter::{Formatter, TableFormatter};
 use crate::ui::Ui;
 
 /// Manage branches.
@@ -28,6 +34,8 @@ pub enum BranchSubcommand {
     Forget(BranchForgetArgs),
     #[command(visible_alias("l"))]
     List(BranchListArgs),
+    #[command(visible_alias("m"))]
+    Move(BranchMoveArgs),
     #[command(visible_alias("s"))]
     Set(BranchSetArgs),
 }
@@ -42,6 +50,14 @@ pub struct BranchCreateArgs {
     /// The branches to create.
     #[arg(required = true, value_parser=NonEmptyStringValueParser::new())]
     names: Vec<String>,
+
+    /// Track the branches on the given Git remote.
+    ///
+    /// This sets the upstream of the branches in the backing Git repo, like
+    /// `git branch --set-upstream-to` does. `jj git push --branch` then pushes
+    /// the branches to this remote by default.
+    #[arg(long, value_name = "REMOTE")]
+    track: Option<String>,
 }
 
 /// Delete an existing branch and propagate the deletion to remotes on the
@@ -72,6 +88,26 @@ pub struct BranchListArgs {
     /// wouldn't have a local target.
     #[arg(long, short)]
     revisions: Vec<RevisionArg>,
+
+    /// Show only conflicted branches, locally or on a remote.
+    #[arg(long)]
+    conflicted: bool,
+
+    /// Show only branches that exist on a remote.
+    ///
+    /// The Git repository of a colocated repo isn't considered a remote.
+    #[arg(long, alias = "tracked-only", conflicts_with = "untracked")]
+    tracked: bool,
+
+    /// Show only branches that don't exist on any remote.
+    #[arg(long)]
+    untracked: bool,
+
+    /// Render each branch using the given template
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
 }
 
 /// Forget everything about a branch, including its local and remote
@@ -90,6 +126,39 @@ pub struct BranchForgetArgs {
     pub glob: Vec<String>,
 }
 
+/// Move all branches pointing to some revisions to another revision.
+///
+/// For example, after history was rewritten upstream, `jj branch move --from
+/// 'old_commits' --to new_commit` moves all branches pointing to the old
+/// commits in one operation. With `--to-successors`, each branch is instead
+/// moved to the commit its target was rewritten into. Use `--dry-run` to
+/// preview the changes.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BranchMoveArgs {
+    /// Move branches whose local targets are in the given revisions.
+    #[arg(long, required = true)]
+    pub from: Vec<RevisionArg>,
+
+    /// The revision to move the branches to.
+    #[arg(long, default_value = "@")]
+    pub to: RevisionArg,
+
+    /// Move each branch to the visible successor of its target.
+    ///
+    /// The successor is the visible commit that has the branch's target
+    /// among its predecessors, possibly through hidden intermediate
+    /// rewrites. Only rewrites made by jj record predecessors. Branches
+    /// whose targets have no visible successor are left alone. It's an error
+    /// if a target has more than one visible successor. The branches may move
+    /// sideways without `--allow-backwards`.
+    #[arg(long, conflicts_with = "to")]
+    pub to_successors: bool,
+
+    /// Allow moving the branches backwards or sideways.
+    #[arg(long, short = 'B')]
+    pub allow_backwards: bool,
+}
+
 /// Update a given branch to point to a certain commit.
 #[derive(clap::Args, Clone, Debug)]
 pub struct BranchSetArgs {
@@ -117,6 +186,7 @@ pub fn cmd_branch(
         BranchSubcommand::Delete(sub_args) => cmd_branch_delete(ui, command, sub_args),
         BranchSubcommand::Forget(sub_args) => cmd_branch_forget(ui, command, sub_args),
         BranchSubcommand::List(sub_args) => cmd_branch_list(ui, command, sub_args),
+        BranchSubcommand::Move(sub_args) => cmd_branch_move(ui, command, sub_args),
     }
 }
 
@@ -150,6 +220,16 @@ fn cmd_branch_create(
         )?;
     }
 
+    let git_repo = if let Some(remote_name) = &args.track {
+        let git_repo = get_git_repo(workspace_command.repo().store())?;
+        if git_repo.find_remote(remote_name).is_err() {
+            return Err(user_error(format!("No git remote named '{remote_name}'")));
+        }
+        Some(git_repo)
+    } else {
+        None
+    };
+
     let target_commit =
         workspace_command.resolve_single_rev(args.revision.as_deref().unwrap_or("@"), ui)?;
     let mut tx = workspace_command.start_transaction(&format!(
@@ -157,10 +237,17 @@ fn cmd_branch_create(
         make_branch_term(&branch_names),
         target_commit.id().hex()
     ));
-    for branch_name in branch_names {
+    for branch_name in &branch_names {
         tx.mut_repo()
             .set_local_branch_target(branch_name, RefTarget::normal(target_commit.id().clone()));
     }
+    // Set the upstream before committing the operation so that a failure
+    // doesn't leave the branches created without it.
+    if let (Some(git_repo), Some(remote_name)) = (&git_repo, &args.track) {
+        for branch_name in &branch_names {
+            set_branch_upstream(git_repo, branch_name, remote_name)?;
+        }
+    }
     tx.finish(ui)?;
     Ok(())
 }
@@ -209,6 +296,149 @@ fn cmd_branch_set(
     Ok(())
 }
 
+fn cmd_branch_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BranchMoveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = if args.to_successors {
+        None
+    } else {
+        Some(workspace_command.resolve_single_rev(&args.to, ui)?)
+    };
+    let from_expressions: Vec<_> = args
+        .from
+        .iter()
+        .map(|revision_str| workspace_command.parse_revset(revision_str, Some(ui)))
+        .try_collect()?;
+    let repo = workspace_command.repo().clone();
+    // Intersects with the set of all branch targets to minimize the lookup space.
+    let all_targets = repo
+        .view()
+        .local_branches()
+        .flat_map(|(_, target)| target.added_ids())
+        .cloned()
+        .collect();
+    let revset_expression = RevsetExpression::commits(all_targets)
+        .intersection(&RevsetExpression::union_all(&from_expressions));
+    let revset = workspace_command.evaluate_revset(revset::optimize(revset_expression))?;
+    let from_ids: HashSet<CommitId> = revset.iter().collect();
+    drop(revset);
+
+    let new_targets: HashMap<CommitId, CommitId> = match &target_commit {
+        Some(target_commit) => from_ids
+            .iter()
+            .map(|id| (id.clone(), target_commit.id().clone()))
+            .collect(),
+        None => {
+            let successors = find_successors(&workspace_command, &from_ids)?;
+            let mut new_targets = HashMap::new();
+            for (old_id, new_ids) in successors {
+                if new_ids.len() > 1 {
+                    return Err(user_error(format!(
+                        "Commit {} has {} visible successors: {}",
+                        short_commit_hash(&old_id),
+                        new_ids.len(),
+                        new_ids.iter().map(short_commit_hash).join(", ")
+                    )));
+                }
+                new_targets.insert(old_id, new_ids.into_iter().next().unwrap());
+            }
+            new_targets
+        }
+    };
+    let branch_moves = repo
+        .view()
+        .local_branches()
+        .filter_map(|(name, target)| {
+            let new_id = if args.to_successors {
+                // A conflicted branch has no single commit to follow.
+                new_targets.get(target.as_normal()?)?
+            } else {
+                target.added_ids().find_map(|id| new_targets.get(id))?
+            };
+            (target.as_normal() != Some(new_id)).then(|| (name.to_owned(), new_id.clone()))
+        })
+        .collect_vec();
+    if branch_moves.is_empty() {
+        writeln!(ui, "No branches to move.")?;
+        return Ok(());
+    }
+    // Successors are usually sideways from the rewritten commits, which is the
+    // point of moving the branches to them.
+    if !args.allow_backwards
+        && !args.to_successors
+        && !branch_moves
+            .iter()
+            .all(|(branch_name, new_id)| is_fast_forward(repo.as_ref(), branch_name, new_id))
+    {
+        return Err(user_error_with_hint(
+            "Refusing to move branch backwards or sideways.",
+            "Use --allow-backwards to allow it.",
+        ));
+    }
+    let branch_names = branch_moves
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect_vec();
+    let mut tx = match &target_commit {
+        Some(target_commit) => workspace_command.start_transaction(&format!(
+            "point {} to commit {}",
+            make_branch_term(&branch_names),
+            target_commit.id().hex()
+        )),
+        None => workspace_command.start_transaction(&format!(
+            "point {} to successors",
+            make_branch_term(&branch_names)
+        )),
+    };
+    for (branch_name, new_id) in branch_moves {
+        tx.mut_repo()
+            .set_local_branch_target(&branch_name, RefTarget::normal(new_id));
+    }
+    tx.finish(ui)?;
+    Ok(())
+}
+
+/// Finds the visible successors of the given commits by walking the
+/// predecessors of the visible commits that aren't their ancestors. Hidden
+/// predecessors are followed so that a commit rewritten several times still
+/// maps to its latest visible version.
+fn find_successors(
+    workspace_command: &WorkspaceCommandHelper,
+    old_ids: &HashSet<CommitId>,
+) -> Result<HashMap<CommitId, Vec<CommitId>>, CommandError> {
+    let repo = workspace_command.repo();
+    let old_expression = RevsetExpression::commits(old_ids.iter().cloned().collect());
+    let candidates_expression = RevsetExpression::all().minus(&old_expression.ancestors());
+    let revset = workspace_command.evaluate_revset(candidates_expression)?;
+    let candidates: Vec<Commit> = revset.iter().commits(repo.store()).try_collect()?;
+    let candidate_ids: HashSet<&CommitId> = candidates.iter().map(|commit| commit.id()).collect();
+    let mut successors: HashMap<CommitId, Vec<CommitId>> = HashMap::new();
+    for candidate in &candidates {
+        let mut visited = HashSet::new();
+        let mut to_visit = candidate.predecessor_ids().to_vec();
+        while let Some(id) = to_visit.pop() {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            if old_ids.contains(&id) {
+                successors
+                    .entry(id)
+                    .or_default()
+                    .push(candidate.id().clone());
+            } else if !candidate_ids.contains(&id) {
+                // Visible predecessors are candidates themselves, so only
+                // hidden ones are walked through.
+                let predecessor = repo.store().get_commit(&id)?;
+                to_visit.extend(predecessor.predecessor_ids().iter().cloned());
+            }
+        }
+    }
+    Ok(successors)
+}
+
 /// This function may return the same branch more than once
 fn find_globs(
     view: &View,
@@ -275,12 +505,14 @@ fn cmd_branch_delete(
     let globbed_names = find_globs(view, &args.glob, false)?;
     let names: BTreeSet<String> = args.names.iter().cloned().chain(globbed_names).collect();
     let branch_term = make_branch_term(names.iter().collect_vec().as_slice());
+    let git_repo = get_git_repo(workspace_command.repo().store()).ok();
     let mut tx = workspace_command.start_transaction(&format!("delete {branch_term}"));
     for branch_name in names.iter() {
         tx.mut_repo()
             .set_local_branch_target(branch_name, RefTarget::absent());
     }
     tx.finish(ui)?;
+    remove_branch_upstreams(ui, git_repo.as_ref(), &names)?;
     if names.len() > 1 {
         writeln!(ui, "Deleted {} branches.", names.len())?;
     }
@@ -302,17 +534,41 @@ fn cmd_branch_forget(
     let globbed_names = find_globs(view, &args.glob, true)?;
     let names: BTreeSet<String> = args.names.iter().cloned().chain(globbed_names).collect();
     let branch_term = make_branch_term(names.iter().collect_vec().as_slice());
+    let git_repo = get_git_repo(workspace_command.repo().store()).ok();
     let mut tx = workspace_command.start_transaction(&format!("forget {branch_term}"));
     for branch_name in names.iter() {
         tx.mut_repo().remove_branch(branch_name);
     }
     tx.finish(ui)?;
+    remove_branch_upstreams(ui, git_repo.as_ref(), &names)?;
     if names.len() > 1 {
         writeln!(ui, "Forgot {} branches.", names.len())?;
     }
     Ok(())
 }
 
+/// Removes the upstreams set by `jj branch create --track` for branches that
+/// no longer exist. Failures are only reported as warnings since the branches
+/// have already been removed.
+fn remove_branch_upstreams(
+    ui: &Ui,
+    git_repo: Option<&git2::Repository>,
+    names: &BTreeSet<String>,
+) -> Result<(), CommandError> {
+    let Some(git_repo) = git_repo else {
+        return Ok(());
+    };
+    for branch_name in names {
+        if let Err(err) = remove_branch_upstream(git_repo, branch_name) {
+            writeln!(
+                ui.warning(),
+                "Failed to remove the upstream of branch {branch_name}: {err}"
+            )?;
+        }
+    }
+    Ok(())
+}
+
 fn cmd_branch_list(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -353,6 +609,63 @@ fn cmd_branch_list(
             local_targets(branch_target).any(|id| filtered_targets.contains(id))
         });
     }
+    if args.conflicted {
+        all_branches.retain(|_, branch_target| {
+            branch_target.local_target.has_conflict()
+                || branch_target
+                    .remote_targets
+                    .values()
+                    .any(|target| target.has_conflict())
+        });
+    }
+    if args.tracked || args.untracked {
+        all_branches.retain(|_, branch_target| {
+            let has_remote = branch_target
+                .remote_targets
+                .keys()
+                .any(|remote| remote != git::REMOTE_NAME_FOR_LOCAL_GIT_REPO);
+            has_remote == args.tracked
+        });
+    }
+
+    if let Some(template_text) = &args.template {
+        let BranchTemplate {
+            template,
+            uses_tracking_counts,
+        } = branch_templater::parse(template_text, workspace_command.template_aliases_map())?;
+        ui.request_pager();
+        let mut formatter = ui.stdout_formatter();
+        let mut table_formatter = TableFormatter::new(formatter.as_mut());
+        for (name, branch_target) in all_branches {
+            let mut remotes = vec![];
+            for (remote, remote_target) in branch_target.remote_targets {
+                let (ahead_count, behind_count) = if uses_tracking_counts {
+                    tracking_counts(repo.as_ref(), &branch_target.local_target, &remote_target)?
+                } else {
+                    (0, 0)
+                };
+                remotes.push(RemoteBranch {
+                    name: name.clone(),
+                    remote,
+                    synced: remote_target == branch_target.local_target,
+                    target: remote_target,
+                    ahead_count,
+                    behind_count,
+                });
+            }
+            let branch = BranchListItem {
+                name,
+                local_target: branch_target.local_target,
+                remotes,
+            };
+            let formatter: &mut dyn Formatter = &mut table_formatter;
+            formatter.with_label("branch_list", |formatter| {
+                template.format(&branch, formatter)
+            })?;
+        }
+        table_formatter.finish()?;
+        return Ok(());
+    }
 
     let no_branches_template = workspace_command.parse_commit_template(
         &command
@@ -417,12 +730,8 @@ fn cmd_branch_list(
             write!(formatter.labeled("branch"), "@{remote}")?;
             let local_target = &branch_target.local_target;
             if local_target.is_present() {
-                let remote_added_ids = remote_target.added_ids().cloned().collect_vec();
-                let local_added_ids = local_target.added_ids().cloned().collect_vec();
-                let remote_ahead_count =
-                    revset::walk_revs(repo.as_ref(), &remote_added_ids, &local_added_ids)?.count();
-                let local_ahead_count =
-                    revset::walk_revs(repo.as_ref(), &local_added_ids, &remote_added_ids)?.count();
+                let (remote_ahead_count, local_ahead_count) =
+                    tracking_counts(repo.as_ref(), local_target, remote_target)?;
                 if remote_ahead_count != 0 && local_ahead_count == 0 {
                     write!(formatter, " (ahead by {remote_ahead_count} commits)")?;
                 } else if remote_ahead_count == 0 && local_ahead_count != 0 {
@@ -458,6 +767,23 @@ fn cmd_branch_list(
     Ok(())
 }
 
+/// Counts the commits the remote target is ahead of and behind the local
+/// target. Both counts are 0 if the branch doesn't exist locally.
+fn tracking_counts(
+    repo: &dyn Repo,
+    local_target: &RefTarget,
+    remote_target: &RefTarget,
+) -> Result<(usize, usize), CommandError> {
+    if local_target.is_absent() {
+        return Ok((0, 0));
+    }
+    let remote_added_ids = remote_target.added_ids().cloned().collect_vec();
+    let local_added_ids = local_target.added_ids().cloned().collect_vec();
+    let ahead_count = revset::walk_revs(repo, &remote_added_ids, &local_added_ids)?.count();
+    let behind_count = revset::walk_revs(repo, &local_added_ids, &remote_added_ids)?.count();
+    Ok((ahead_count, behind_count))
+}
+
 fn is_fast_forward(repo: &dyn Repo, branch_name: &str, new_target_id: &CommitId) -> bool {
     let current_target = repo.view().get_local_branch(branch_name);
     if current_target.is_present() {
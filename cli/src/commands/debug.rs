@@ -13,11 +13,16 @@
 // limitations under the License.
 
 use std::fmt::Debug;
+use std::io::{Read as _, Write as _};
 
 use clap::Subcommand;
-use jj_lib::backend::ObjectId;
+use itertools::Itertools as _;
+use jj_lib::backend::{CommitId, ConflictId, FileId, ObjectId, SymlinkId, TreeId};
 use jj_lib::default_index_store::{DefaultIndexStore, ReadonlyIndexWrapper};
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::revset;
+use jj_lib::working_copy::FileType;
 
 use crate::cli_util::{resolve_op_for_load, user_error, CommandError, CommandHelper};
 use crate::template_parser;
@@ -28,6 +33,7 @@ use crate::ui::Ui;
 #[command(hide = true)]
 pub enum DebugCommands {
     Revset(DebugRevsetArgs),
+    CommonAncestors(DebugCommonAncestorsArgs),
     #[command(name = "workingcopy")]
     WorkingCopy(DebugWorkingCopyArgs),
     Template(DebugTemplateArgs),
@@ -36,6 +42,8 @@ pub enum DebugCommands {
     ReIndex(DebugReIndexArgs),
     #[command(visible_alias = "view")]
     Operation(DebugOperationArgs),
+    Tree(DebugTreeArgs),
+    Object(DebugObjectArgs),
     #[command(subcommand)]
     Watchman(DebugWatchmanSubcommand),
 }
@@ -46,11 +54,32 @@ pub struct DebugRevsetArgs {
     revision: String,
 }
 
+/// Print the heads of the common ancestors of two sets of revisions
+///
+/// This is the merge base of the revisions, like `git merge-base --all`. The
+/// `fork_point()` revset function can be used for the same purpose.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugCommonAncestorsArgs {
+    revision1: String,
+    revision2: String,
+}
+
 /// Show information about the working copy state
+///
+/// Prints the operation and tree the working copy was last updated to, the
+/// sparse patterns, the conflicted paths in that tree, and the recorded state
+/// of each tracked file.
 #[derive(clap::Args, Clone, Debug)]
-pub struct DebugWorkingCopyArgs {}
+pub struct DebugWorkingCopyArgs {
+    /// Print the state as a JSON object
+    #[arg(long)]
+    json: bool,
+}
 
-/// Parse a template
+/// Parse a template and show the keyword types
+///
+/// Prints the parsed template, the template after alias expansion, and the
+/// types of the commit keywords it references.
 #[derive(clap::Args, Clone, Debug)]
 pub struct DebugTemplateArgs {
     template: String,
@@ -85,6 +114,39 @@ pub enum DebugOperationDisplay {
     All,
 }
 
+/// List the entries of a revision's tree, including conflicted ones
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugTreeArgs {
+    #[arg(long, short, default_value = "@")]
+    revision: String,
+    /// Only list entries matching these paths
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+/// Show a raw object from the backend
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugObjectArgs {
+    #[arg(value_enum)]
+    kind: DebugObjectKind,
+    /// The hash of the object
+    id: String,
+    /// The repo-relative directory or file the object belongs to
+    ///
+    /// Some backends need this to find trees and files.
+    #[arg(long, default_value = "")]
+    path: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DebugObjectKind {
+    Commit,
+    Tree,
+    File,
+    Symlink,
+    Conflict,
+}
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum DebugWatchmanSubcommand {
     QueryClock,
@@ -99,23 +161,9 @@ pub fn cmd_debug(
 ) -> Result<(), CommandError> {
     match subcommand {
         DebugCommands::Revset(args) => cmd_debug_revset(ui, command, args)?,
-        DebugCommands::WorkingCopy(_wc_matches) => {
-            let workspace_command = command.workspace_helper(ui)?;
-            let wc = workspace_command.working_copy();
-            writeln!(ui, "Current operation: {:?}", wc.operation_id())?;
-            writeln!(ui, "Current tree: {:?}", wc.current_tree_id()?)?;
-            for (file, state) in wc.file_states()? {
-                writeln!(
-                    ui,
-                    "{:?} {:13?} {:10?} {:?}",
-                    state.file_type, state.size, state.mtime.0, file
-                )?;
-            }
-        }
-        DebugCommands::Template(template_matches) => {
-            let node = template_parser::parse_template(&template_matches.template)?;
-            writeln!(ui, "{node:#?}")?;
-        }
+        DebugCommands::CommonAncestors(args) => cmd_debug_common_ancestors(ui, command, args)?,
+        DebugCommands::WorkingCopy(args) => cmd_debug_working_copy(ui, command, args)?,
+        DebugCommands::Template(args) => cmd_debug_template(ui, command, args)?,
         DebugCommands::Index(_index_matches) => {
             let workspace_command = command.workspace_helper(ui)?;
             let repo = workspace_command.repo();
@@ -171,11 +219,7 @@ pub fn cmd_debug(
             // even if e.g. the view object is broken.
             let workspace = command.load_workspace()?;
             let repo_loader = workspace.repo_loader();
-            let op = resolve_op_for_load(
-                repo_loader.op_store(),
-                repo_loader.op_heads_store(),
-                &operation_args.operation,
-            )?;
+            let op = resolve_op_for_load(repo_loader, &operation_args.operation)?;
             if operation_args.display == DebugOperationDisplay::Id {
                 writeln!(ui, "{}", op.id().hex())?;
                 return Ok(());
@@ -187,6 +231,8 @@ pub fn cmd_debug(
                 writeln!(ui, "{:#?}", op.view()?.store_view())?;
             }
         }
+        DebugCommands::Tree(args) => cmd_debug_tree(ui, command, args)?,
+        DebugCommands::Object(args) => cmd_debug_object(ui, command, args)?,
         DebugCommands::Watchman(watchman_subcommand) => {
             cmd_debug_watchman(ui, command, watchman_subcommand)?;
         }
@@ -194,6 +240,105 @@ pub fn cmd_debug(
     Ok(())
 }
 
+fn cmd_debug_working_copy(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugWorkingCopyArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let wc = workspace_command.working_copy();
+    let tree_id = wc.current_tree_id()?;
+    let tree = workspace_command.repo().store().get_root_tree(tree_id)?;
+    let conflicted_paths = tree.conflicts().map(|(path, _)| path).collect_vec();
+    if args.json {
+        let files = wc
+            .file_states()?
+            .iter()
+            .map(|(path, state)| {
+                serde_json::json!({
+                    "path": path.to_internal_file_string(),
+                    "type": file_type_name(&state.file_type),
+                    "size": state.size,
+                    "mtime": state.mtime.0,
+                    "inode": state.inode,
+                })
+            })
+            .collect_vec();
+        let json = serde_json::json!({
+            "operation": wc.operation_id().hex(),
+            "tree": tree_id.to_merge().iter().map(|id| id.hex()).collect_vec(),
+            "sparse_patterns": wc
+                .sparse_patterns()?
+                .iter()
+                .map(|path| path.to_internal_file_string())
+                .collect_vec(),
+            "conflicts": conflicted_paths
+                .iter()
+                .map(|path| path.to_internal_file_string())
+                .collect_vec(),
+            "files": files,
+        });
+        writeln!(ui, "{json:#}")?;
+        return Ok(());
+    }
+    writeln!(ui, "Current operation: {:?}", wc.operation_id())?;
+    writeln!(ui, "Current tree: {tree_id:?}")?;
+    writeln!(ui, "Sparse patterns:")?;
+    for path in wc.sparse_patterns()? {
+        writeln!(ui, "  {:?}", path.to_internal_file_string())?;
+    }
+    writeln!(ui, "Conflicts:")?;
+    for path in &conflicted_paths {
+        writeln!(ui, "  {:?}", path.to_internal_file_string())?;
+    }
+    writeln!(ui, "Files:")?;
+    for (file, state) in wc.file_states()? {
+        writeln!(
+            ui,
+            "{:?} {:13?} {:10?} {:?}",
+            state.file_type, state.size, state.mtime.0, file
+        )?;
+    }
+    Ok(())
+}
+
+fn file_type_name(file_type: &FileType) -> &'static str {
+    match file_type {
+        #[cfg(unix)]
+        FileType::Normal { executable: true } => "executable",
+        FileType::Normal { .. } => "normal",
+        FileType::Symlink => "symlink",
+        FileType::GitSubmodule => "git-submodule",
+    }
+}
+
+fn cmd_debug_template(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugTemplateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+
+    let node = template_parser::parse_template(&args.template)?;
+    writeln!(ui, "-- Parsed:")?;
+    writeln!(ui, "{node:#?}")?;
+    writeln!(ui)?;
+
+    let node = template_parser::expand_aliases(node, workspace_command.template_aliases_map())?;
+    writeln!(ui, "-- Expanded:")?;
+    writeln!(ui, "{node:#?}")?;
+    writeln!(ui)?;
+
+    writeln!(ui, "-- Keywords:")?;
+    for (name, type_name) in workspace_command.commit_template_keyword_types(&node)? {
+        writeln!(ui, "{name}: {type_name}")?;
+    }
+
+    // Report type errors, if any.
+    workspace_command.parse_commit_template(&args.template)?;
+    Ok(())
+}
+
 fn cmd_debug_revset(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -231,6 +376,83 @@ fn cmd_debug_revset(
     Ok(())
 }
 
+fn cmd_debug_common_ancestors(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugCommonAncestorsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let expression1 = workspace_command.parse_revset(&args.revision1, Some(ui))?;
+    let ids1 = workspace_command
+        .evaluate_revset(expression1)?
+        .iter()
+        .collect_vec();
+    let expression2 = workspace_command.parse_revset(&args.revision2, Some(ui))?;
+    let ids2 = workspace_command
+        .evaluate_revset(expression2)?
+        .iter()
+        .collect_vec();
+    let index = workspace_command.repo().index();
+    for commit_id in index.common_ancestors(&ids1, &ids2) {
+        writeln!(ui, "{}", commit_id.hex())?;
+    }
+    Ok(())
+}
+
+fn cmd_debug_tree(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugTreeArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(&args.revision, ui)?;
+    let tree = commit.tree()?;
+    let matcher = workspace_command.matcher_from_values(&args.paths)?;
+    writeln!(ui, "Tree: {:?}", commit.tree_id())?;
+    for (path, value) in tree.entries_matching(matcher.as_ref()) {
+        writeln!(ui, "{:?}: {:?}", path.to_internal_file_string(), value)?;
+    }
+    Ok(())
+}
+
+fn cmd_debug_object(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugObjectArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let store = workspace_command.repo().store();
+    let id =
+        hex::decode(&args.id).map_err(|_| user_error(format!("Invalid object ID: {}", args.id)))?;
+    let path = RepoPath::from_internal_string(&args.path);
+    match args.kind {
+        DebugObjectKind::Commit => {
+            let commit = store.get_commit(&CommitId::new(id))?;
+            writeln!(ui, "{:#?}", commit.store_commit())?;
+        }
+        DebugObjectKind::Tree => {
+            let tree = store.get_tree(&path, &TreeId::new(id))?;
+            writeln!(ui, "{:#?}", tree.data())?;
+        }
+        DebugObjectKind::File => {
+            let mut contents = vec![];
+            store
+                .read_file(&path, &FileId::new(id))?
+                .read_to_end(&mut contents)?;
+            ui.stdout_formatter().write_all(&contents)?;
+        }
+        DebugObjectKind::Symlink => {
+            let target = store.read_symlink(&path, &SymlinkId::new(id))?;
+            writeln!(ui, "{target}")?;
+        }
+        DebugObjectKind::Conflict => {
+            let conflict = store.read_conflict(&path, &ConflictId::new(id))?;
+            writeln!(ui, "{conflict:#?}")?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(feature = "watchman")]
 fn cmd_debug_watchman(
     ui: &mut Ui,
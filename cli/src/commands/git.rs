@@ -4,27 +4,33 @@ use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{fs, io};
 
 use clap::{ArgGroup, Subcommand};
 use itertools::Itertools;
 use jj_lib::backend::{CommitId, ObjectId, TreeValue};
+use jj_lib::commit::Commit;
 use jj_lib::git::{self, parse_gitmodules, GitFetchError, GitPushError, GitRefUpdate};
 use jj_lib::git_backend::GitBackend;
+use jj_lib::matchers::EverythingMatcher;
 use jj_lib::op_store::{BranchTarget, RefTarget};
 use jj_lib::refs::{classify_branch_push_action, BranchPushAction, BranchPushUpdate};
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
-use jj_lib::revset::{self, RevsetExpression, RevsetIteratorExt as _, StringPattern};
-use jj_lib::settings::{ConfigResultExt as _, UserSettings};
+use jj_lib::revset::{
+    self, RevsetExpression, RevsetIteratorExt as _, StringPattern, StringPatternParseError,
+};
+use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::settings::{ConfigResultExt as _, HumanByteSize, UserSettings};
 use jj_lib::store::Store;
 use jj_lib::view::View;
 use jj_lib::workspace::Workspace;
 use maplit::hashset;
+use regex::Regex;
 
 use crate::cli_util::{
-    print_failed_git_export, resolve_multiple_nonempty_revsets, short_change_hash,
+    conflict_error, print_failed_git_export, resolve_multiple_nonempty_revsets, short_change_hash,
     short_commit_hash, user_error, user_error_with_hint, CommandError, CommandHelper, RevisionArg,
     WorkspaceCommandHelper,
 };
@@ -94,10 +100,10 @@ pub struct GitRemoteListArgs {}
 pub struct GitFetchArgs {
     /// Fetch only some of the branches
     ///
-    /// Any `*` in the argument is expanded as a glob. So, one `--branch` can
-    /// match several branches.
-    #[arg(long)]
-    branch: Vec<String>,
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// expand `*` as a glob. The other wildcard characters aren't supported.
+    #[arg(long, value_parser = parse_string_pattern)]
+    branch: Vec<StringPattern>,
     /// The remote to fetch from (only named remotes are supported, can be
     /// repeated)
     #[arg(long = "remote", value_name = "remote")]
@@ -105,6 +111,9 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+    /// Fetch all branches, ignoring `git.remotes.<remote>.fetch-branches`
+    #[arg(long, conflicts_with = "branch")]
+    all_branches: bool,
 }
 
 /// Create a new repo backed by a clone of a Git repo
@@ -134,6 +143,10 @@ pub struct GitCloneArgs {
 #[command(group(ArgGroup::new("what").args(&["all", "deleted"]).conflicts_with("specific")))]
 pub struct GitPushArgs {
     /// The remote to push to (only named remotes are supported)
+    ///
+    /// Defaults to `git.push`. If that isn't set, only `--branch` is given, and
+    /// the branches were created with `jj branch create --track`, defaults to
+    /// the remote they track.
     #[arg(long)]
     remote: Option<String>,
     /// Push only this branch (can be repeated)
@@ -150,11 +163,15 @@ pub struct GitPushArgs {
     revisions: Vec<RevisionArg>,
     /// Push this commit by creating a branch based on its change ID (can be
     /// repeated)
+    ///
+    /// Prefix the revset with `all:` to push a branch for each commit in it,
+    /// e.g. a whole stack of changes.
     #[arg(long, short)]
     change: Vec<RevisionArg>,
-    /// Only display what will change on the remote
+    /// Push even if the commits fail the checks configured in
+    /// `git.push-checks`
     #[arg(long)]
-    dry_run: bool,
+    ignore_checks: bool,
 }
 
 /// Update repo with changes made in the underlying Git repo
@@ -182,13 +199,51 @@ pub struct GitSubmodulePrintGitmodulesArgs {
     revisions: RevisionArg,
 }
 
-fn get_git_repo(store: &Store) -> Result<git2::Repository, CommandError> {
+pub fn get_git_repo(store: &Store) -> Result<git2::Repository, CommandError> {
     match store.backend_impl().downcast_ref::<GitBackend>() {
         None => Err(user_error("The repo is not backed by a git repo")),
         Some(git_backend) => Ok(git_backend.git_repo_clone()),
     }
 }
 
+/// Sets the remote the branch is pushed to by default, the same way as
+/// `git branch --set-upstream-to` does.
+pub fn set_branch_upstream(
+    git_repo: &git2::Repository,
+    branch_name: &str,
+    remote_name: &str,
+) -> Result<(), CommandError> {
+    let mut config = git_repo.config()?;
+    config.set_str(&format!("branch.{branch_name}.remote"), remote_name)?;
+    config.set_str(
+        &format!("branch.{branch_name}.merge"),
+        &format!("refs/heads/{branch_name}"),
+    )?;
+    Ok(())
+}
+
+/// Removes the upstream of the branch set by `set_branch_upstream()`, if any.
+pub fn remove_branch_upstream(
+    git_repo: &git2::Repository,
+    branch_name: &str,
+) -> Result<(), git2::Error> {
+    let mut config = git_repo.config()?;
+    for key in ["remote", "merge"] {
+        match config.remove(&format!("branch.{branch_name}.{key}")) {
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+fn get_branch_upstream(git_repo: &git2::Repository, branch_name: &str) -> Option<String> {
+    let config = git_repo.config().ok()?;
+    config
+        .get_string(&format!("branch.{branch_name}.remote"))
+        .ok()
+}
+
 fn map_git_error(err: git2::Error) -> CommandError {
     if err.class() == git2::ErrorClass::Ssh {
         let hint =
@@ -324,21 +379,47 @@ fn cmd_git_fetch(
     } else {
         args.remotes.clone()
     };
+    fetch_remotes(
+        ui,
+        command,
+        &mut workspace_command,
+        &git_repo,
+        &remotes,
+        &args.branch,
+        args.all_branches,
+    )
+}
+
+fn parse_string_pattern(src: &str) -> Result<StringPattern, StringPatternParseError> {
+    StringPattern::parse(src)
+}
+
+fn fetch_remotes(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    workspace_command: &mut WorkspaceCommandHelper,
+    git_repo: &git2::Repository,
+    remotes: &[String],
+    branches: &[StringPattern],
+    all_branches: bool,
+) -> Result<(), CommandError> {
     let mut tx = workspace_command.start_transaction(&format!(
         "fetch from git remote(s) {}",
         remotes.iter().join(",")
     ));
-    let branches = args.branch.iter().map(|b| b.as_str()).collect_vec();
+    let git_settings = command.settings().git_settings()?;
     for remote in remotes {
+        let configured_branches;
+        let branches = if !branches.is_empty() {
+            Some(branches)
+        } else if all_branches {
+            None
+        } else {
+            configured_branches = get_fetch_branch_patterns(command.settings(), remote)?;
+            configured_branches.as_deref()
+        };
         with_remote_callbacks(ui, |cb| {
-            git::fetch(
-                tx.mut_repo(),
-                &git_repo,
-                &remote,
-                (!branches.is_empty()).then_some(&*branches),
-                cb,
-                &command.settings().git_settings(),
-            )
+            git::fetch(tx.mut_repo(), git_repo, remote, branches, cb, &git_settings)
         })
         .map_err(|err| match err {
             GitFetchError::GitImportError(err) => err.into(),
@@ -347,6 +428,70 @@ fn cmd_git_fetch(
         })?;
     }
     tx.finish(ui)?;
+    // Recorded even if nothing was fetched so that `git.auto-fetch-interval`
+    // counts from the last attempt.
+    fs::write(last_fetch_path(workspace_command.repo().repo_path()), "")?;
+    Ok(())
+}
+
+fn last_fetch_path(repo_path: &Path) -> PathBuf {
+    repo_path.join("git_last_fetch")
+}
+
+/// Fetches from the default remotes if `git.auto-fetch-interval` is set and
+/// the last fetch happened longer ago than that. Errors are reported as
+/// warnings so that they don't prevent the actual command from running.
+pub fn maybe_auto_fetch(ui: &mut Ui, command: &CommandHelper) -> Result<(), CommandError> {
+    let Some(interval_minutes) = command
+        .settings()
+        .config()
+        .get_int("git.auto-fetch-interval")
+        .optional()?
+    else {
+        return Ok(());
+    };
+    let Ok(loader) = command.workspace_loader() else {
+        return Ok(());
+    };
+    let interval = Duration::from_secs(60 * interval_minutes.max(0) as u64);
+    let last_fetch = fs::metadata(last_fetch_path(loader.repo_path()))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if last_fetch.is_some_and(|time| time.elapsed().map_or(true, |elapsed| elapsed < interval)) {
+        return Ok(());
+    }
+    // If the workspace can't be loaded, let the actual command report why.
+    let Ok(mut workspace_command) = command.workspace_helper(ui) else {
+        return Ok(());
+    };
+    let Ok(git_repo) = get_git_repo(workspace_command.repo().store()) else {
+        return Ok(());
+    };
+    if git_repo.remotes()?.is_empty() {
+        return Ok(());
+    }
+    let result = get_default_fetch_remotes(ui, command.settings(), &git_repo).and_then(|remotes| {
+        fetch_remotes(
+            ui,
+            command,
+            &mut workspace_command,
+            &git_repo,
+            &remotes,
+            &[],
+            false,
+        )
+    });
+    if let Err(err) = result {
+        let message = match err {
+            CommandError::UserError { message, .. }
+            | CommandError::ConfigError(message)
+            | CommandError::InternalError(message) => message,
+            err => return Err(err),
+        };
+        // Don't retry on every command while the remote is unreachable.
+        fs::write(last_fetch_path(workspace_command.repo().repo_path()), "")?;
+        writeln!(ui.warning(), "Automatic fetch failed: {message}")?;
+    }
     Ok(())
 }
 
@@ -385,6 +530,37 @@ fn get_default_fetch_remotes(
     }
 }
 
+/// Reads the branches to fetch from `git.remotes.<remote>.fetch-branches`.
+/// Returns `None` if all branches should be fetched.
+fn get_fetch_branch_patterns(
+    settings: &UserSettings,
+    remote: &str,
+) -> Result<Option<Vec<StringPattern>>, CommandError> {
+    // Look up the remote in the table instead of building a dotted key so
+    // that remote names containing "." work.
+    let Some(remotes) = settings.config().get_table("git.remotes").optional()? else {
+        return Ok(None);
+    };
+    let Some(remote_config) = remotes.get(remote) else {
+        return Ok(None);
+    };
+    let Some(patterns) = remote_config.clone().into_table()?.remove("fetch-branches") else {
+        return Ok(None);
+    };
+    let patterns: Vec<String> = patterns.try_deserialize()?;
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            StringPattern::glob(pattern).map_err(|err| {
+                user_error(format!(
+                    "Invalid pattern in git.remotes.{remote}.fetch-branches: {pattern}: {err}"
+                ))
+            })
+        })
+        .try_collect()?;
+    Ok(Some(patterns))
+}
+
 fn get_all_remotes(git_repo: &git2::Repository) -> Result<Vec<String>, CommandError> {
     let git_remotes = git_repo.remotes()?;
     Ok(git_remotes
@@ -524,6 +700,7 @@ fn do_git_clone(
     git_repo.remote(remote_name, source).unwrap();
     let mut fetch_tx = workspace_command.start_transaction("fetch from git remote into empty repo");
 
+    let git_settings = command.settings().git_settings()?;
     let maybe_default_branch = with_remote_callbacks(ui, |cb| {
         git::fetch(
             fetch_tx.mut_repo(),
@@ -531,7 +708,7 @@ fn do_git_clone(
             remote_name,
             None,
             cb,
-            &command.settings().git_settings(),
+            &git_settings,
         )
     })
     .map_err(|err| match err {
@@ -540,8 +717,8 @@ fn do_git_clone(
         }
         GitFetchError::GitImportError(err) => CommandError::from(err),
         GitFetchError::InternalGitError(err) => map_git_error(err),
-        GitFetchError::InvalidGlob => {
-            unreachable!("we didn't provide any globs")
+        GitFetchError::InvalidBranchPattern => {
+            unreachable!("we didn't provide any branch patterns")
         }
     })?;
     fetch_tx.finish(ui)?;
@@ -672,16 +849,25 @@ fn cmd_git_push(
     let remote = if let Some(name) = &args.remote {
         name.clone()
     } else {
-        get_default_push_remote(ui, command.settings(), &git_repo)?
+        let branches_upstream = get_branches_upstream(&git_repo, args);
+        get_default_push_remote(ui, command.settings(), &git_repo, branches_upstream)?
     };
 
     let repo = workspace_command.repo().clone();
     let wc_commit_id = workspace_command.get_wc_commit_id().cloned();
-    let change_commits: Vec<_> = args
-        .change
-        .iter()
-        .map(|change_str| workspace_command.resolve_single_rev(change_str, ui))
-        .try_collect()?;
+    let mut change_commits = vec![];
+    for change_str in &args.change {
+        let commits = workspace_command.resolve_revset_default_single(change_str, ui)?;
+        if commits.len() == 1 {
+            change_commits.push((change_str.deref().to_owned(), commits[0].clone()));
+        } else {
+            // Create branches for a stack of changes in topological order, and
+            // identify each revision by its change id
+            for commit in commits.into_iter().rev() {
+                change_commits.push((short_change_hash(commit.change_id()), commit));
+            }
+        }
+    }
 
     fn find_branches_targeting<'a>(
         view: &'a View,
@@ -733,11 +919,11 @@ fn cmd_git_push(
                     ui,
                     "Branch {branch_name}@{remote} already matches {branch_name}",
                 )?,
-                Err(message) => return Err(user_error(message)),
+                Err(message) => return Err(conflict_error(message)),
             }
         }
 
-        for (change_str, commit) in std::iter::zip(args.change.iter(), change_commits) {
+        for (change_str, commit) in change_commits {
             let mut branch_name = format!(
                 "{}{}",
                 command.settings().push_branch_prefix(),
@@ -767,9 +953,7 @@ fn cmd_git_push(
             if view.get_local_branch(&branch_name).is_absent() {
                 writeln!(
                     ui,
-                    "Creating branch {} for revision {}",
-                    branch_name,
-                    change_str.deref()
+                    "Creating branch {branch_name} for revision {change_str}",
                 )?;
             }
             tx.mut_repo()
@@ -781,7 +965,7 @@ fn cmd_git_push(
                     ui,
                     "Branch {branch_name}@{remote} already matches {branch_name}",
                 )?,
-                Err(message) => return Err(user_error(message)),
+                Err(message) => return Err(conflict_error(message)),
             }
         }
 
@@ -844,6 +1028,24 @@ fn cmd_git_push(
         return Ok(());
     }
 
+    // Updating the checked-out branch of a non-bare repository would leave its
+    // working tree out of sync with HEAD.
+    if let Some(checked_out_branch) = get_remote_checked_out_branch(&git_repo, &remote) {
+        if branch_updates
+            .iter()
+            .any(|(branch_name, _)| *branch_name == checked_out_branch)
+        {
+            return Err(user_error_with_hint(
+                format!(
+                    "Refusing to push branch {checked_out_branch} since it is checked out in \
+                     the working tree of remote {remote}"
+                ),
+                "Check out another branch in the remote repository, or push to a bare \
+                 repository instead.",
+            ));
+        }
+    }
+
     tx.set_description(&tx_description);
 
     let mut ref_updates = vec![];
@@ -885,29 +1087,42 @@ fn cmd_git_push(
     if old_heads.is_empty() {
         old_heads.push(repo.store().root_commit_id().clone());
     }
+    let private_commits: HashSet<CommitId> = if let Some(private_revset_str) = command
+        .settings()
+        .config()
+        .get_string("git.private-commits")
+        .optional()?
+    {
+        let workspace_command = tx.base_workspace_helper();
+        let private_revset = workspace_command.parse_revset(&private_revset_str, None)?;
+        let to_push_revset = RevsetExpression::commits(old_heads.clone())
+            .range(&RevsetExpression::commits(new_heads.clone()));
+        workspace_command
+            .evaluate_revset(to_push_revset.intersection(&private_revset))?
+            .iter()
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let push_checks = PushChecks::from_settings(command.settings())?;
     for commit in revset::walk_revs(repo.as_ref(), &new_heads, &old_heads)?
         .iter()
         .commits(repo.store())
     {
         let commit = commit?;
-        let mut reasons = vec![];
-        if commit.description().is_empty() {
-            reasons.push("it has no description");
+        if private_commits.contains(commit.id()) {
+            return Err(user_error_with_hint(
+                format!(
+                    "Won't push commit {} since it is private",
+                    short_commit_hash(commit.id())
+                ),
+                "Configure the set of private commits via `git.private-commits`.",
+            ));
         }
-        if commit.author().name.is_empty()
-            || commit.author().name == UserSettings::USER_NAME_PLACEHOLDER
-            || commit.author().email.is_empty()
-            || commit.author().email == UserSettings::USER_EMAIL_PLACEHOLDER
-            || commit.committer().name.is_empty()
-            || commit.committer().name == UserSettings::USER_NAME_PLACEHOLDER
-            || commit.committer().email.is_empty()
-            || commit.committer().email == UserSettings::USER_EMAIL_PLACEHOLDER
-        {
-            reasons.push("it has no author and/or committer set");
-        }
-        if commit.has_conflict()? {
-            reasons.push("it has conflicts");
+        if args.ignore_checks {
+            continue;
         }
+        let reasons = push_checks.check_commit(repo.as_ref(), &commit)?;
         if !reasons.is_empty() {
             return Err(user_error(format!(
                 "Won't push commit {} since {}",
@@ -957,7 +1172,7 @@ fn cmd_git_push(
         }
     }
 
-    if args.dry_run {
+    if command.global_args().dry_run {
         writeln!(ui, "Dry-run requested, not pushing.")?;
         return Ok(());
     }
@@ -966,6 +1181,12 @@ fn cmd_git_push(
         git::push_updates(&git_repo, &remote, &ref_updates, cb)
     })
     .map_err(|err| match err {
+        GitPushError::InternalGitError(err) if err.code() == git2::ErrorCode::BareRepo => {
+            user_error_with_hint(
+                format!("Cannot push to remote {remote} since it is not a bare repository"),
+                "Push to a bare repository instead, e.g. one created by `git clone --bare`.",
+            )
+        }
         GitPushError::InternalGitError(err) => map_git_error(err),
         GitPushError::NotFastForward => user_error_with_hint(
             "The push conflicts with changes made on the remote (it is not fast-forwardable).",
@@ -974,18 +1195,137 @@ fn cmd_git_push(
         ),
         _ => user_error(err.to_string()),
     })?;
-    git::import_refs(tx.mut_repo(), &git_repo, &command.settings().git_settings())?;
+    git::import_refs(
+        tx.mut_repo(),
+        &git_repo,
+        &command.settings().git_settings()?,
+    )?;
     tx.finish(ui)?;
     Ok(())
 }
 
+/// Checks run over the commits to push, configured by `git.push-checks`.
+struct PushChecks {
+    no_empty_description: bool,
+    no_missing_author: bool,
+    no_conflicts: bool,
+    description_regex: Option<Regex>,
+    max_commit_size: Option<u64>,
+}
+
+impl PushChecks {
+    fn from_settings(settings: &UserSettings) -> Result<Self, CommandError> {
+        let config = settings.config();
+        let get_bool = |name: &str| -> Result<bool, CommandError> {
+            let key = format!("git.push-checks.{name}");
+            Ok(config.get_bool(&key).optional()?.unwrap_or(true))
+        };
+        let description_regex = config
+            .get_string("git.push-checks.description-regex")
+            .optional()?
+            .map(|pattern| {
+                Regex::new(&pattern).map_err(|err| {
+                    user_error(format!(
+                        "Invalid regex in `git.push-checks.description-regex`: {err}"
+                    ))
+                })
+            })
+            .transpose()?;
+        let max_commit_size = config
+            .get::<HumanByteSize>("git.push-checks.max-commit-size")
+            .optional()?
+            .map(|size| size.0);
+        Ok(PushChecks {
+            no_empty_description: get_bool("no-empty-description")?,
+            no_missing_author: get_bool("no-missing-author")?,
+            no_conflicts: get_bool("no-conflicts")?,
+            description_regex,
+            max_commit_size,
+        })
+    }
+
+    /// Returns the reasons why the commit shouldn't be pushed.
+    fn check_commit(&self, repo: &dyn Repo, commit: &Commit) -> Result<Vec<String>, CommandError> {
+        let mut reasons = vec![];
+        if self.no_empty_description && commit.description().is_empty() {
+            reasons.push("it has no description".to_owned());
+        }
+        if self.no_missing_author
+            && (commit.author().name.is_empty()
+                || commit.author().name == UserSettings::USER_NAME_PLACEHOLDER
+                || commit.author().email.is_empty()
+                || commit.author().email == UserSettings::USER_EMAIL_PLACEHOLDER
+                || commit.committer().name.is_empty()
+                || commit.committer().name == UserSettings::USER_NAME_PLACEHOLDER
+                || commit.committer().email.is_empty()
+                || commit.committer().email == UserSettings::USER_EMAIL_PLACEHOLDER)
+        {
+            reasons.push("it has no author and/or committer set".to_owned());
+        }
+        if self.no_conflicts && commit.has_conflict()? {
+            reasons.push("it has conflicts".to_owned());
+        }
+        if let Some(regex) = &self.description_regex {
+            if !regex.is_match(commit.description()) {
+                reasons.push(format!(
+                    "its description doesn't match `{}`",
+                    regex.as_str()
+                ));
+            }
+        }
+        if let Some(max_size) = self.max_commit_size {
+            let size = commit_size(repo, commit)?;
+            if size > max_size {
+                reasons.push(format!(
+                    "its changes are larger than {} ({} bytes)",
+                    HumanByteSize(max_size),
+                    size
+                ));
+            }
+        }
+        Ok(reasons)
+    }
+}
+
+/// Total size of the files added or modified by the commit.
+fn commit_size(repo: &dyn Repo, commit: &Commit) -> Result<u64, CommandError> {
+    let parent_tree = merge_commit_trees(repo, &commit.parents())?;
+    let tree = commit.tree()?;
+    let mut size = 0;
+    for (path, _before, after) in parent_tree.diff(&tree, &EverythingMatcher) {
+        if let Some(Some(TreeValue::File { id, .. })) = after.as_resolved() {
+            let mut reader = repo.store().read_file(&path, id)?;
+            size += io::copy(&mut reader, &mut io::sink())?;
+        }
+    }
+    Ok(size)
+}
+
+/// Returns the upstream remote of the branches to push if only named branches
+/// are pushed, and they all have the same upstream.
+fn get_branches_upstream(git_repo: &git2::Repository, args: &GitPushArgs) -> Option<String> {
+    if args.all || args.deleted || !args.revisions.is_empty() || !args.change.is_empty() {
+        return None;
+    }
+    let (first, rest) = args.branch.split_first()?;
+    let remote = get_branch_upstream(git_repo, first)?;
+    rest.iter()
+        .all(|name| get_branch_upstream(git_repo, name).as_ref() == Some(&remote))
+        .then_some(remote)
+}
+
+/// Returns the remote to push to if `--remote` isn't given. `git.push` takes
+/// precedence over the upstream of the pushed branches in the Git config.
 fn get_default_push_remote(
     ui: &Ui,
     settings: &UserSettings,
     git_repo: &git2::Repository,
+    branches_upstream: Option<String>,
 ) -> Result<String, CommandError> {
     if let Some(remote) = settings.config().get_string("git.push").optional()? {
         Ok(remote)
+    } else if let Some(remote) = branches_upstream {
+        Ok(remote)
     } else if let Some(remote) = get_single_remote(git_repo)? {
         // similar to get_default_fetch_remotes
         if remote != DEFAULT_REMOTE {
@@ -997,6 +1337,29 @@ fn get_default_push_remote(
     }
 }
 
+/// Returns the branch checked out in the working tree of the remote if the
+/// remote is a non-bare repository on the local filesystem.
+fn get_remote_checked_out_branch(git_repo: &git2::Repository, remote_name: &str) -> Option<String> {
+    let remote = git_repo.find_remote(remote_name).ok()?;
+    let url = remote.url()?;
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    if path.contains("://") {
+        return None;
+    }
+    // Like git, resolve relative paths from the working tree, or from the git
+    // directory of a bare repo, rather than from the current directory.
+    let base_dir = git_repo.workdir().unwrap_or_else(|| git_repo.path());
+    let remote_repo = git2::Repository::open(base_dir.join(path)).ok()?;
+    if remote_repo.is_bare() {
+        return None;
+    }
+    // libgit2 can't push to non-bare repos at all, so `receive.denyCurrentBranch`
+    // doesn't matter.
+    let head = remote_repo.find_reference("HEAD").ok()?;
+    let branch_name = head.symbolic_target()?.strip_prefix("refs/heads/")?;
+    Some(branch_name.to_owned())
+}
+
 fn classify_branch_update(
     branch_name: &str,
     branch_target: &BranchTarget,
@@ -1022,7 +1385,11 @@ fn cmd_git_import(
     let repo = workspace_command.repo();
     let git_repo = get_git_repo(repo.store())?;
     let mut tx = workspace_command.start_transaction("import git refs");
-    git::import_refs(tx.mut_repo(), &git_repo, &command.settings().git_settings())?;
+    git::import_refs(
+        tx.mut_repo(),
+        &git_repo,
+        &command.settings().git_settings()?,
+    )?;
     tx.finish(ui)?;
     Ok(())
 }
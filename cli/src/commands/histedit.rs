@@ -0,0 +1,331 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use jj_lib::backend::{CommitId, ObjectId};
+use jj_lib::commit::Commit;
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::revset::{RevsetExpression, RevsetIteratorExt};
+use jj_lib::rewrite::{merge_commit_trees, rebase_commit};
+use jj_lib::settings::UserSettings;
+use tracing::instrument;
+
+use super::{check_description, edit_description, edit_temp_file};
+use crate::cli_util::{short_commit_hash, user_error, CommandError, CommandHelper, RevisionArg};
+use crate::ui::Ui;
+
+/// Reorder, squash, drop, and reword a stack of commits
+///
+/// Opens an editor with a list of the commits in the given revisions, oldest
+/// first, each prefixed by an action. The lines can be reordered and the
+/// actions changed. When the editor is closed, the commits are rewritten
+/// according to the edited list in a single operation, and any descendants
+/// are rebased onto the results.
+///
+/// The available actions are:
+///
+///  * `pick`: keep the commit
+///
+///  * `reword`: keep the commit, but edit its description
+///
+///  * `squash`: move the changes into the commit above it in the list
+///
+///  * `drop`: abandon the commit
+///
+/// Removing a line also abandons the commit. This is similar to `git rebase
+/// --interactive`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct HisteditArgs {
+    /// The revisions to edit, e.g. `main..@`
+    ///
+    /// The revisions must form a linear stack.
+    revisions: RevisionArg,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    Pick,
+    Reword,
+    Squash,
+    Drop,
+}
+
+impl Action {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pick" | "p" => Some(Action::Pick),
+            "reword" | "r" => Some(Action::Reword),
+            "squash" | "s" => Some(Action::Squash),
+            "drop" | "d" => Some(Action::Drop),
+            _ => None,
+        }
+    }
+}
+
+/// A commit in the rewritten stack, along with the commits squashed into it.
+struct PlanEntry {
+    commit: Commit,
+    reword: bool,
+    squashed: Vec<Commit>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_histedit(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &HisteditArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let expression = workspace_command.parse_revset(&args.revisions, Some(ui))?;
+    let revset = workspace_command.evaluate_revset(expression)?;
+    let mut commits: Vec<Commit> = revset
+        .iter()
+        .commits(workspace_command.repo().store())
+        .try_collect()?;
+    drop(revset);
+    if commits.is_empty() {
+        return Err(user_error("Empty revision set"));
+    }
+    // The revset is in reverse topological order, but the plan lists the oldest
+    // commit first
+    commits.reverse();
+    for (parent, child) in commits.iter().tuple_windows() {
+        if child.parent_ids() != [parent.id().clone()] {
+            return Err(user_error("The revisions must form a linear stack"));
+        }
+    }
+    workspace_command.check_rewritable(&commits)?;
+
+    let plan_text = edit_plan(
+        workspace_command.repo(),
+        &format_plan(&commits),
+        command.settings(),
+    )?;
+    let plan = parse_plan(&plan_text, &commits)?;
+
+    // Children of the tip that aren't part of the stack (e.g. the working-copy
+    // commit) are rebased onto the new top of the stack, wherever the old tip
+    // ends up.
+    let old_tip = commits.last().unwrap();
+    let external_children: Vec<Commit> = RevsetExpression::commit(old_tip.id().clone())
+        .children()
+        .resolve(workspace_command.repo().as_ref())
+        .unwrap()
+        .evaluate(workspace_command.repo().as_ref())
+        .unwrap()
+        .iter()
+        .commits(workspace_command.repo().store())
+        .try_collect()?;
+    let tip_moves = plan
+        .last()
+        .is_some_and(|entry| entry.commit.id() != old_tip.id());
+
+    let mut tx = workspace_command.start_transaction(&format!(
+        "histedit {} commits starting at {}",
+        commits.len(),
+        commits[0].id().hex()
+    ));
+    let planned_ids: HashSet<&CommitId> = plan
+        .iter()
+        .flat_map(|entry| [&entry.commit].into_iter().chain(&entry.squashed))
+        .map(|commit| commit.id())
+        .collect();
+    for commit in &commits {
+        if !planned_ids.contains(commit.id()) {
+            tx.mut_repo().record_abandoned_commit(commit.id().clone());
+        }
+    }
+    let mut new_parents = commits[0].parents();
+    for entry in plan {
+        if !entry.reword && entry.squashed.is_empty() && entry.commit.parents() == new_parents {
+            new_parents = vec![entry.commit];
+            continue;
+        }
+        let mut_repo = tx.mut_repo();
+        let parent_tree = merge_commit_trees(mut_repo, &new_parents)?;
+        let mut new_tree = parent_tree.merge(
+            &merge_commit_trees(mut_repo, &entry.commit.parents())?,
+            &entry.commit.tree()?,
+        )?;
+        for squashed in &entry.squashed {
+            new_tree = new_tree.merge(
+                &merge_commit_trees(mut_repo, &squashed.parents())?,
+                &squashed.tree()?,
+            )?;
+            mut_repo.record_abandoned_commit(squashed.id().clone());
+        }
+        let description = combine_descriptions(tx.base_repo(), &entry, command.settings())?;
+        let description = if entry.reword {
+            let description = edit_description(tx.base_repo(), &description, command.settings())?;
+            check_description(ui, command.settings(), &description)?;
+            description
+        } else {
+            description
+        };
+        let predecessors = [&entry.commit]
+            .into_iter()
+            .chain(&entry.squashed)
+            .map(|commit| commit.id().clone())
+            .collect();
+        let new_commit = tx
+            .mut_repo()
+            .rewrite_commit(command.settings(), &entry.commit)
+            .set_parents(new_parents.iter().map(|c| c.id().clone()).collect())
+            .set_tree_id(new_tree.id())
+            .set_predecessors(predecessors)
+            .set_description(description)
+            .write()?;
+        new_parents = vec![new_commit];
+    }
+    if tip_moves {
+        // Rebasing the children explicitly records them as rewritten, so the
+        // descendant rebaser won't move them onto the rewritten old tip.
+        for child in &external_children {
+            let child_parents = child
+                .parents()
+                .into_iter()
+                .flat_map(|parent| {
+                    if parent.id() == old_tip.id() {
+                        new_parents.clone()
+                    } else {
+                        vec![parent]
+                    }
+                })
+                .collect_vec();
+            rebase_commit(command.settings(), tx.mut_repo(), child, &child_parents)?;
+        }
+    }
+    tx.finish(ui)?;
+    Ok(())
+}
+
+fn format_plan(commits: &[Commit]) -> String {
+    let mut plan = String::new();
+    for commit in commits {
+        let summary = commit.description().lines().next().unwrap_or("");
+        plan.push_str(&format!(
+            "pick {} {}\n",
+            short_commit_hash(commit.id()),
+            summary
+        ));
+    }
+    plan.push_str(
+        "\
+JJ: Edit the list of commits above, oldest first. The lines can be reordered.
+JJ: Actions:
+JJ:   pick = keep the commit
+JJ:   reword = keep the commit, but edit its description
+JJ:   squash = move the changes into the commit on the previous line
+JJ:   drop = abandon the commit
+JJ: Removing a line also abandons the commit. An empty list aborts.
+",
+    );
+    plan
+}
+
+fn parse_plan(text: &str, commits: &[Commit]) -> Result<Vec<PlanEntry>, CommandError> {
+    let mut plan: Vec<PlanEntry> = vec![];
+    let mut seen_ids = HashSet::new();
+    let mut num_lines = 0;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ: ") {
+            continue;
+        }
+        num_lines += 1;
+        let mut words = line.split_whitespace();
+        let (Some(action_str), Some(id_prefix)) = (words.next(), words.next()) else {
+            return Err(user_error(format!("Invalid line in plan: {line:?}")));
+        };
+        let action = Action::parse(action_str)
+            .ok_or_else(|| user_error(format!("Unknown action {action_str:?} in plan")))?;
+        let commit = match commits
+            .iter()
+            .filter(|commit| commit.id().hex().starts_with(id_prefix))
+            .exactly_one()
+        {
+            Ok(commit) => commit,
+            Err(_) => {
+                return Err(user_error(format!(
+                    "Commit {id_prefix:?} in plan is not one of the edited commits"
+                )))
+            }
+        };
+        if !seen_ids.insert(commit.id().clone()) {
+            return Err(user_error(format!(
+                "Commit {id_prefix:?} appears more than once in plan"
+            )));
+        }
+        match action {
+            Action::Pick | Action::Reword => plan.push(PlanEntry {
+                commit: commit.clone(),
+                reword: action == Action::Reword,
+                squashed: vec![],
+            }),
+            Action::Squash => match plan.last_mut() {
+                Some(entry) => entry.squashed.push(commit.clone()),
+                None => {
+                    return Err(user_error(format!(
+                        "Cannot squash commit {id_prefix:?} without a previous commit"
+                    )))
+                }
+            },
+            Action::Drop => {}
+        }
+    }
+    if num_lines == 0 {
+        return Err(user_error("Aborted because the plan is empty"));
+    }
+    Ok(plan)
+}
+
+/// Returns the description of the commit, combined with the descriptions of
+/// the commits squashed into it. If more than one of them is non-empty, the
+/// user is asked to combine them.
+fn combine_descriptions(
+    repo: &ReadonlyRepo,
+    entry: &PlanEntry,
+    settings: &UserSettings,
+) -> Result<String, CommandError> {
+    let commits = [&entry.commit]
+        .into_iter()
+        .chain(&entry.squashed)
+        .filter(|commit| !commit.description().is_empty())
+        .collect_vec();
+    match commits.as_slice() {
+        [] => Ok(String::new()),
+        [commit] => Ok(commit.description().to_owned()),
+        _ => {
+            let mut combined = "JJ: Enter a description for the combined commit.\n".to_string();
+            for commit in commits {
+                combined.push_str(&format!(
+                    "JJ: Description from commit {}:\n",
+                    short_commit_hash(commit.id())
+                ));
+                combined.push_str(commit.description());
+            }
+            edit_description(repo, &combined, settings)
+        }
+    }
+}
+
+fn edit_plan(
+    repo: &ReadonlyRepo,
+    plan: &str,
+    settings: &UserSettings,
+) -> Result<String, CommandError> {
+    edit_temp_file("plan", ".jjhistedit", repo.repo_path(), plan, settings)
+}
@@ -17,14 +17,20 @@ mod bench;
 mod branch;
 mod debug;
 mod git;
+mod histedit;
+mod note;
 mod operation;
+mod resolution;
+mod shortlog;
+mod stats;
 
 use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
 use std::io::{BufRead, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::Arc;
-use std::{fs, io};
+use std::{fs, io, iter};
 
 use clap::builder::NonEmptyStringValueParser;
 use clap::parser::ValueSource;
@@ -35,10 +41,11 @@ use jj_lib::backend::{CommitId, ObjectId, TreeValue};
 use jj_lib::commit::Commit;
 use jj_lib::dag_walk::topo_order_reverse;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::merge::Merge;
 use jj_lib::merged_tree::{MergedTree, MergedTreeBuilder};
-use jj_lib::op_store::WorkspaceId;
+use jj_lib::op_store::{RefTarget, WorkspaceId};
 use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::repo_path::RepoPath;
 use jj_lib::revset::{RevsetExpression, RevsetFilterPredicate, RevsetIteratorExt};
@@ -46,26 +53,32 @@ use jj_lib::revset_graph::{
     ReverseRevsetGraphIterator, RevsetGraphEdgeType, TopoGroupedRevsetGraphIterator,
 };
 use jj_lib::rewrite::{back_out_commit, merge_commit_trees, rebase_commit, DescendantRebaser};
-use jj_lib::settings::UserSettings;
+use jj_lib::settings::{ConfigResultExt as _, UserSettings};
 use jj_lib::working_copy::SnapshotOptions;
-use jj_lib::workspace::Workspace;
+use jj_lib::workspace::{forget_workspace_root, Workspace};
 use jj_lib::{conflicts, file_util, revset};
 use maplit::{hashmap, hashset};
+use regex::Regex;
 use tracing::instrument;
 
 use crate::cli_util::{
-    self, check_stale_working_copy, get_new_config_file_path, print_checkout_stats,
-    resolve_multiple_nonempty_revsets, resolve_multiple_nonempty_revsets_default_single,
-    run_ui_editor, serialize_config_value, short_commit_hash, user_error, user_error_with_hint,
-    write_config_value_to_file, Args, CommandError, CommandHelper, LogContentFormat, RevisionArg,
-    WorkspaceCommandHelper,
+    self, check_stale_working_copy, checkout_error, checkout_options, conflict_error,
+    get_new_config_file_path, print_checkout_stats, print_config_issues, read_template_arg,
+    record_conflict_resolutions, resolve_multiple_nonempty_revsets,
+    resolve_multiple_nonempty_revsets_default_single, run_ui_editor, serialize_config_value,
+    short_commit_hash, user_error, user_error_with_hint, write_config_value_to_file, Args,
+    CommandError, CommandHelper, LogContentFormat, RevisionArg, WorkspaceCommandHelper,
+};
+use crate::config::{
+    repo_config_requires_trust, trust_repo_config, untrust_repo_config, AnnotatedValue,
+    CommandNameAndArgs, ConfigSource,
 };
-use crate::config::{AnnotatedValue, ConfigSource};
 use crate::diff_util::{self, DiffFormat, DiffFormatArgs};
-use crate::formatter::{Formatter, PlainTextFormatter};
-use crate::graphlog::{get_graphlog, Edge};
+use crate::formatter::{Formatter, JsonFormatter, PlainTextFormatter, TableFormatter};
+use crate::graphlog::{get_graphlog, Edge, NodeSymbols};
+use crate::template_parser;
 use crate::text_util;
-use crate::ui::Ui;
+use crate::ui::{OutputFormat, Ui};
 
 #[derive(clap::Parser, Clone, Debug)]
 enum Commands {
@@ -93,6 +106,7 @@ enum Commands {
     Files(FilesArgs),
     #[command(subcommand)]
     Git(git::GitCommands),
+    Histedit(histedit::HisteditArgs),
     Init(InitArgs),
     Interdiff(InterdiffArgs),
     Log(LogArgs),
@@ -109,22 +123,28 @@ enum Commands {
     Move(MoveArgs),
     New(NewArgs),
     Next(NextArgs),
+    #[command(subcommand)]
+    Note(note::NoteCommands),
     Obslog(ObslogArgs),
     #[command(subcommand)]
     #[command(visible_alias = "op")]
     Operation(operation::OperationCommands),
     Prev(PrevArgs),
     Rebase(RebaseArgs),
+    #[command(subcommand)]
+    Resolution(resolution::ResolutionCommands),
     Resolve(ResolveArgs),
     Restore(RestoreArgs),
     #[command(hide = true)]
     // TODO: Flesh out.
     Run(RunArgs),
+    Shortlog(shortlog::ShortlogArgs),
     Show(ShowArgs),
     #[command(subcommand)]
     Sparse(SparseArgs),
     Split(SplitArgs),
     Squash(SquashArgs),
+    Stats(stats::StatsArgs),
     Status(StatusArgs),
     #[command(subcommand)]
     Util(UtilCommands),
@@ -201,6 +221,8 @@ enum ConfigSubcommand {
     Set(ConfigSetArgs),
     #[command(visible_alias("e"))]
     Edit(ConfigEditArgs),
+    Trust(ConfigTrustArgs),
+    Validate(ConfigValidateArgs),
 }
 
 /// List variables set in config file, along with their values.
@@ -250,6 +272,27 @@ struct ConfigEditArgs {
     pub config_args: ConfigArgs,
 }
 
+/// Trust the repo config file of the current repo.
+///
+/// If `repo-config.require-trust` is enabled, the repo config file
+/// `.jj/repo/config.toml` is ignored unless its current content has been
+/// trusted. Files written by `jj config set --repo` and `jj config edit
+/// --repo` are trusted automatically.
+#[derive(clap::Args, Clone, Debug)]
+struct ConfigTrustArgs {
+    /// Stop trusting the repo config file instead
+    #[arg(long)]
+    revoke: bool,
+}
+
+/// Check the config files for unknown keys and invalid values.
+///
+/// The user config, the repo config, and `--config-toml` arguments are checked
+/// against the config schema. The same problems are reported as warnings when
+/// running other commands; this command fails if there are any.
+#[derive(clap::Args, Clone, Debug)]
+struct ConfigValidateArgs {}
+
 /// Create a new, empty change and edit it in the working copy
 ///
 /// For more information, see
@@ -314,9 +357,14 @@ struct DiffArgs {
     #[arg(long, short)]
     revision: Option<RevisionArg>,
     /// Show changes from this revision
+    ///
+    /// Prefix the expression with `all:` to compare against the merged trees
+    /// of several revisions, e.g. `--from all:@-` for the parents of a merge.
     #[arg(long, conflicts_with = "revision")]
     from: Option<RevisionArg>,
     /// Show changes to this revision
+    ///
+    /// Like `--from`, this accepts the `all:` prefix.
     #[arg(long, conflicts_with = "revision")]
     to: Option<RevisionArg>,
     /// Restrict the diff to these paths
@@ -352,6 +400,12 @@ struct ShowArgs {
 struct StatusArgs {}
 
 /// Show commit history
+///
+/// With `--output json`, each revision is printed as a JSON object on its own
+/// line, mapping the keywords in the template to their output. For example,
+/// `-T 'commit_id ++ author.name()'` produces objects with `commit_id` and
+/// `author` fields. This requires `--no-graph`, and can't be combined with
+/// patches.
 #[derive(clap::Args, Clone, Debug)]
 struct LogArgs {
     /// Which revisions to show. Defaults to the `revsets.log` setting, or
@@ -370,14 +424,28 @@ struct LogArgs {
     /// Applied after revisions are filtered and reordered.
     #[arg(long, short)]
     limit: Option<usize>,
+    /// Show the revisions after the one with this full commit id
+    ///
+    /// Together with `--limit`, this can be used to show the revisions page by
+    /// page: pass the commit id of the last revision shown on the previous
+    /// page. The revisions aren't evaluated past the end of the page.
+    #[arg(long, value_name = "COMMIT_ID")]
+    cursor: Option<String>,
     /// Don't show the graph, show a flat list of revisions
     #[arg(long)]
     no_graph: bool,
     /// Render each revision using the given template
     ///
     /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    ///
+    /// If the template starts with `@`, it's read from the file at the rest of
+    /// the argument, or from stdin with `@-`.
     #[arg(long, short = 'T')]
     template: Option<String>,
+    /// Read the template to render each revision with from the file
+    #[arg(long, value_name = "PATH", conflicts_with = "template")]
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    template_file: Option<PathBuf>,
     /// Show patch
     #[arg(long, short = 'p')]
     patch: bool,
@@ -395,14 +463,27 @@ struct ObslogArgs {
     /// Limit number of revisions to show
     #[arg(long, short)]
     limit: Option<usize>,
+    /// Show the revisions after the one with this full commit id
+    ///
+    /// Together with `--limit`, this can be used to show the revisions page by
+    /// page.
+    #[arg(long, value_name = "COMMIT_ID")]
+    cursor: Option<String>,
     /// Don't show the graph, show a flat list of revisions
     #[arg(long)]
     no_graph: bool,
     /// Render each revision using the given template
     ///
     /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    ///
+    /// If the template starts with `@`, it's read from the file at the rest of
+    /// the argument, or from stdin with `@-`.
     #[arg(long, short = 'T')]
     template: Option<String>,
+    /// Read the template to render each revision with from the file
+    #[arg(long, value_name = "PATH", conflicts_with = "template")]
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    template_file: Option<PathBuf>,
     /// Show patch compared to the previous version of this change
     ///
     /// If the previous version has different parents, it will be temporarily
@@ -502,9 +583,13 @@ struct AbandonArgs {
     /// The revision(s) to abandon
     #[arg(default_value = "@")]
     revisions: Vec<RevisionArg>,
-    /// Do not print every abandoned commit on a separate line
+    /// Do not print every abandoned and rebased commit on a separate line
     #[arg(long, short)]
     summary: bool,
+    /// Delete branches pointing to the abandoned commits instead of moving
+    /// them to the parents
+    #[arg(long)]
+    delete_branches: bool,
     /// Ignored (but lets you pass `-r` for consistency with other commands)
     #[arg(short = 'r', hide = true)]
     unused_revision: bool,
@@ -642,6 +727,10 @@ struct PrevArgs {
 /// If the source became empty and both the source and destination had a
 /// non-empty description, you will be asked for the combined description. If
 /// either was empty, then the other one will be used.
+///
+/// The source and destination can be anywhere in the graph. The commits
+/// between them are rebased, and any of the rewritten commits that end up with
+/// new conflicts are listed.
 #[derive(clap::Args, Clone, Debug)]
 #[command(group(ArgGroup::new("to_move").args(&["from", "to"]).multiple(true).required(true)))]
 struct MoveArgs {
@@ -1026,6 +1115,7 @@ struct BackoutArgs {
 #[derive(Subcommand, Clone, Debug)]
 enum WorkspaceCommands {
     Add(WorkspaceAddArgs),
+    CopyChanges(WorkspaceCopyChangesArgs),
     Forget(WorkspaceForgetArgs),
     List(WorkspaceListArgs),
     Root(WorkspaceRootArgs),
@@ -1045,6 +1135,27 @@ struct WorkspaceAddArgs {
     name: Option<String>,
 }
 
+/// Copy the changes in the working copy to another workspace
+///
+/// Applies the changes in the current working-copy commit (compared to its
+/// parents) on top of the working-copy commit of another workspace, like
+/// `jj move` but leaving the changes in place here. Any conflicts with the
+/// other workspace's changes are recorded in its working-copy commit.
+///
+/// The other workspace becomes stale and needs to be updated with `jj
+/// workspace update-stale` before it can be used again.
+#[derive(clap::Args, Clone, Debug)]
+struct WorkspaceCopyChangesArgs {
+    /// Name of the workspace to copy the changes to
+    workspace: String,
+    /// Interactively choose which parts to copy
+    #[arg(long, short)]
+    interactive: bool,
+    /// Copy only changes to these paths (instead of all paths)
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
 /// Stop tracking a workspace's working-copy commit in the repo
 ///
 /// The workspace will not be touched on disk. It can be deleted from disk
@@ -1216,7 +1327,7 @@ fn cmd_init(ui: &mut Ui, command: &CommandHelper, args: &InitArgs) -> Result<(),
             jj_lib::git::import_some_refs(
                 tx.mut_repo(),
                 &git_repo,
-                &command.settings().git_settings(),
+                &command.settings().git_settings()?,
                 |ref_name| !jj_lib::git::is_reserved_git_remote_ref(ref_name),
             )?;
             if let Some(git_head_id) = tx.mut_repo().view().git_head().as_normal().cloned() {
@@ -1265,6 +1376,8 @@ fn cmd_config(
         ConfigSubcommand::Get(sub_args) => cmd_config_get(ui, command, sub_args),
         ConfigSubcommand::Set(sub_args) => cmd_config_set(ui, command, sub_args),
         ConfigSubcommand::Edit(sub_args) => cmd_config_edit(ui, command, sub_args),
+        ConfigSubcommand::Trust(sub_args) => cmd_config_trust(ui, command, sub_args),
+        ConfigSubcommand::Validate(sub_args) => cmd_config_validate(ui, command, sub_args),
     }
 }
 
@@ -1362,7 +1475,11 @@ fn cmd_config_set(
             path = config_path.display()
         )));
     }
-    write_config_value_to_file(&args.name, &args.value, &config_path)
+    write_config_value_to_file(&args.name, &args.value, &config_path)?;
+    if args.config_args.repo && repo_config_requires_trust(command.settings().config())? {
+        trust_repo_config(&config_path)?;
+    }
+    Ok(())
 }
 
 #[instrument(skip_all)]
@@ -1372,7 +1489,45 @@ fn cmd_config_edit(
     args: &ConfigEditArgs,
 ) -> Result<(), CommandError> {
     let config_path = get_new_config_file_path(&args.config_args.get_source_kind(), command)?;
-    run_ui_editor(command.settings(), &config_path)
+    run_ui_editor(command.settings(), &config_path)?;
+    if args.config_args.repo && repo_config_requires_trust(command.settings().config())? {
+        trust_repo_config(&config_path)?;
+    }
+    Ok(())
+}
+
+#[instrument(skip_all)]
+fn cmd_config_trust(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ConfigTrustArgs,
+) -> Result<(), CommandError> {
+    let config_path = command.workspace_loader()?.repo_path().join("config.toml");
+    if args.revoke {
+        untrust_repo_config(&config_path)?;
+        writeln!(ui, "No longer trusting {}", config_path.display())?;
+    } else {
+        trust_repo_config(&config_path)?;
+        writeln!(ui, "Trusting {}", config_path.display())?;
+    }
+    Ok(())
+}
+
+fn cmd_config_validate(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &ConfigValidateArgs,
+) -> Result<(), CommandError> {
+    let issues = command.validate_config();
+    if issues.is_empty() {
+        writeln!(ui, "No problems found in the config")?;
+        return Ok(());
+    }
+    print_config_issues(ui, &issues)?;
+    Err(user_error(format!(
+        "Found {} problems in the config",
+        issues.len()
+    )))
 }
 
 #[instrument(skip_all)]
@@ -1516,14 +1671,46 @@ fn cmd_cat(ui: &mut Ui, command: &CommandHelper, args: &CatArgs) -> Result<(), C
 
 #[instrument(skip_all)]
 fn cmd_diff(ui: &mut Ui, command: &CommandHelper, args: &DiffArgs) -> Result<(), CommandError> {
+    // Include the uncommitted changes of other workspaces whose working-copy
+    // commits are compared. This has to happen before loading the current
+    // workspace so the repo includes the new snapshots.
+    let workspace_names = [&args.from, &args.to]
+        .into_iter()
+        .flatten()
+        .filter_map(|revision_str| revision_str.strip_suffix('@'))
+        .filter(|name| !name.is_empty())
+        .collect_vec();
+    if !workspace_names.is_empty() && !command.global_args().ignore_working_copy {
+        let current_workspace_id = command.load_workspace()?.workspace_id().clone();
+        for name in workspace_names {
+            let workspace_id = WorkspaceId::new(name.to_owned());
+            if workspace_id != current_workspace_id
+                && !command.snapshot_other_workspace(ui, &workspace_id)?
+            {
+                writeln!(
+                    ui.warning(),
+                    "Could not snapshot the working copy of workspace {name}; using its last \
+                     snapshot instead"
+                )?;
+            }
+        }
+    }
     let workspace_command = command.workspace_helper(ui)?;
     let from_tree;
     let to_tree;
     if args.from.is_some() || args.to.is_some() {
-        let from = workspace_command.resolve_single_rev(args.from.as_deref().unwrap_or("@"), ui)?;
-        from_tree = from.tree()?;
-        let to = workspace_command.resolve_single_rev(args.to.as_deref().unwrap_or("@"), ui)?;
-        to_tree = to.tree()?;
+        // With the `all:` prefix, the trees of multiple revisions are merged,
+        // e.g. to compare against the parents of a merge commit.
+        let mut resolve_tree = |revision_str: &str| -> Result<MergedTree, CommandError> {
+            let commits = workspace_command.resolve_revset_default_single(revision_str, ui)?;
+            workspace_command.check_non_empty(&commits)?;
+            Ok(merge_commit_trees(
+                workspace_command.repo().as_ref(),
+                &commits,
+            )?)
+        };
+        from_tree = resolve_tree(args.from.as_deref().unwrap_or("@"))?;
+        to_tree = resolve_tree(args.to.as_deref().unwrap_or("@"))?;
     } else {
         let commit =
             workspace_command.resolve_single_rev(args.revision.as_deref().unwrap_or("@"), ui)?;
@@ -1532,7 +1719,23 @@ fn cmd_diff(ui: &mut Ui, command: &CommandHelper, args: &DiffArgs) -> Result<(),
         to_tree = commit.tree()?
     }
     let matcher = workspace_command.matcher_from_values(&args.paths)?;
-    let diff_formats = diff_util::diff_formats_for(command.settings(), &args.format)?;
+    let mut diff_formats = diff_util::diff_formats_for(command.settings(), &args.format)?;
+    if let Some(threshold) = diff_util::summary_threshold(command.settings())? {
+        if !args.format.has_format() {
+            let num_paths = from_tree.diff(&to_tree, matcher.as_ref()).count();
+            if num_paths > threshold {
+                writeln!(
+                    ui.hint(),
+                    "Hint: Showing only a summary because {num_paths} paths changed (more than \
+                     ui.diff.summary-threshold). Pass a format like --git, or restrict the diff \
+                     to some paths, to see the changes."
+                )?;
+                diff_formats = vec![DiffFormat::Summary {
+                    null_terminated: args.format.null_terminated,
+                }];
+            }
+        }
+    }
     ui.request_pager();
     diff_util::show_diff(
         ui,
@@ -1595,6 +1798,7 @@ fn cmd_status(
                 formatter,
                 &workspace_command,
                 parent_tree.diff(&tree, &EverythingMatcher),
+                false,
             )?;
         }
 
@@ -1604,7 +1808,10 @@ fn cmd_status(
                 formatter.labeled("conflict"),
                 "There are unresolved conflicts at these paths:"
             )?;
-            print_conflicted_paths(&conflicts, formatter, &workspace_command)?
+            print_conflicted_paths(&conflicts, formatter, &workspace_command)?;
+            if wc_commit.parent_ids().len() > 1 {
+                print_conflict_origins(&conflicts, wc_commit, formatter, &workspace_command)?;
+            }
         }
 
         formatter.write_str("Working copy : ")?;
@@ -1696,36 +1903,101 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
         revset::optimize(expression)
     };
     let repo = workspace_command.repo();
-    let wc_commit_id = workspace_command.get_wc_commit_id();
     let matcher = workspace_command.matcher_from_values(&args.paths)?;
-    let revset = workspace_command.evaluate_revset(revset_expression)?;
+    let revset = workspace_command.evaluate_revset(revset_expression.clone())?;
 
     let store = repo.store();
     let diff_formats =
         diff_util::diff_formats_for_log(command.settings(), &args.diff_format, args.patch)?;
+    if ui.output_format() == OutputFormat::Json {
+        if !diff_formats.is_empty() {
+            return Err(user_error("Patches can't be shown with JSON output"));
+        }
+        if !args.no_graph {
+            return Err(user_error_with_hint(
+                "The graph can't be shown with JSON output",
+                "Use --no-graph to print one JSON object per revision.",
+            ));
+        }
+    }
 
-    let template_string = match &args.template {
-        Some(value) => value.to_string(),
+    let template_string = match read_template_arg(
+        command.cwd(),
+        args.template.as_deref(),
+        args.template_file.as_deref(),
+    )? {
+        Some(value) => value,
         None => command.settings().config().get_string("templates.log")?,
     };
-    let template = workspace_command.parse_commit_template(&template_string)?;
+    let scoped_id_prefix_context;
+    let template = if command
+        .settings()
+        .config()
+        .get_bool("ui.log-scoped-prefixes")?
+    {
+        // The displayed revisions are only evaluated again once the template
+        // asks for a prefix, so templates without ids still stream.
+        scoped_id_prefix_context =
+            IdPrefixContext::default().disambiguate_within(revset_expression.clone());
+        workspace_command.parse_commit_template_with_id_prefix_context(
+            &template_string,
+            &scoped_id_prefix_context,
+        )?
+    } else {
+        workspace_command.parse_commit_template(&template_string)?
+    };
     let with_content_format = LogContentFormat::new(ui, command.settings())?;
+    if ui.output_format() != OutputFormat::Json {
+        with_content_format.warn_unaligned_table(
+            ui,
+            &template_string,
+            workspace_command.template_aliases_map(),
+            !args.no_graph,
+        )?;
+    }
 
     {
         ui.request_pager();
         let mut formatter = ui.stdout_formatter();
         let formatter = formatter.as_mut();
 
-        if !args.no_graph {
+        if ui.output_format() == OutputFormat::Json {
+            let node =
+                template_parser::parse(&template_string, workspace_command.template_aliases_map())?;
+            let keywords = template_parser::collect_keywords(&node);
+            let iter: Box<dyn Iterator<Item = CommitId>> = if args.reversed {
+                Box::new(revset.iter().reversed())
+            } else {
+                Box::new(revset.iter())
+            };
+            let page = paginate(iter, |id| id, args.cursor.as_deref(), args.limit)?;
+            for commit_or_error in page.commits(store) {
+                let commit = commit_or_error?;
+                let mut json_formatter = JsonFormatter::new(keywords.iter().map(|(name, _)| *name));
+                template.format(&commit, &mut json_formatter)?;
+                writeln!(formatter, "{}", json_formatter.to_json())?;
+            }
+        } else if !args.no_graph {
             let mut graph = get_graphlog(command.settings(), formatter.raw());
-            let default_node_symbol = graph.default_node_symbol().to_owned();
+            let node_symbols =
+                NodeSymbols::from_settings(command.settings(), graph.default_node_symbol())?;
+            let immutable_commit_ids: HashSet<CommitId> = if node_symbols.needs_immutable() {
+                let immutable_revset = workspace_command.parse_immutable_revset()?;
+                workspace_command
+                    .evaluate_revset(revset_expression.intersection(&immutable_revset))?
+                    .iter()
+                    .collect()
+            } else {
+                HashSet::new()
+            };
             let forward_iter = TopoGroupedRevsetGraphIterator::new(revset.iter_graph());
             let iter: Box<dyn Iterator<Item = _>> = if args.reversed {
                 Box::new(ReverseRevsetGraphIterator::new(forward_iter))
             } else {
                 Box::new(forward_iter)
             };
-            for (commit_id, edges) in iter.take(args.limit.unwrap_or(usize::MAX)) {
+            let page = paginate(iter, |(id, _)| id, args.cursor.as_deref(), args.limit)?;
+            for (commit_id, edges) in page {
                 let mut graphlog_edges = vec![];
                 // TODO: Should we update RevsetGraphIterator to yield this flag instead of all
                 // the missing edges since we don't care about where they point here
@@ -1751,10 +2023,12 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
                 }
                 let mut buffer = vec![];
                 let commit = store.get_commit(&commit_id)?;
+                let graph_width = graph.width(&commit_id, &graphlog_edges);
+                workspace_command.template_width().set_reserved(graph_width);
                 with_content_format.write_graph_text(
                     ui.new_formatter(&mut buffer).as_mut(),
                     |formatter| template.format(&commit, formatter),
-                    || graph.width(&commit_id, &graphlog_edges),
+                    || graph_width,
                 )?;
                 if !buffer.ends_with(b"\n") {
                     buffer.push(b'\n');
@@ -1770,11 +2044,12 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
                         &diff_formats,
                     )?;
                 }
-                let node_symbol = if Some(&commit_id) == wc_commit_id {
-                    "@"
-                } else {
-                    &default_node_symbol
-                };
+                let node_symbol = node_symbols.for_commit(
+                    repo.as_ref(),
+                    workspace_command.workspace_id(),
+                    &commit,
+                    immutable_commit_ids.contains(&commit_id),
+                )?;
 
                 graph.add_node(
                     &commit_id,
@@ -1789,14 +2064,17 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
             } else {
                 Box::new(revset.iter())
             };
-            for commit_or_error in iter.commits(store).take(args.limit.unwrap_or(usize::MAX)) {
+            let page = paginate(iter, |id| id, args.cursor.as_deref(), args.limit)?;
+            let mut formatter = TableFormatter::new(formatter);
+            for commit_or_error in page.commits(store) {
                 let commit = commit_or_error?;
-                with_content_format
-                    .write(formatter, |formatter| template.format(&commit, formatter))?;
+                with_content_format.write(&mut formatter, |formatter| {
+                    template.format(&commit, formatter)
+                })?;
                 if !diff_formats.is_empty() {
                     diff_util::show_patch(
                         ui,
-                        formatter,
+                        &mut formatter,
                         &workspace_command,
                         &commit,
                         matcher.as_ref(),
@@ -1804,6 +2082,7 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
                     )?;
                 }
             }
+            formatter.finish()?;
         }
     }
 
@@ -1832,18 +2111,45 @@ fn cmd_log(ui: &mut Ui, command: &CommandHelper, args: &LogArgs) -> Result<(), C
     Ok(())
 }
 
+/// Skips the revisions up to and including the one the cursor points to, and
+/// takes up to `limit` revisions after it.
+///
+/// The iterator is consumed only as far as needed, so showing a page doesn't
+/// evaluate the revisions after it.
+fn paginate<I: Iterator>(
+    mut iter: I,
+    id_fn: fn(&I::Item) -> &CommitId,
+    cursor: Option<&str>,
+    limit: Option<usize>,
+) -> Result<iter::Take<I>, CommandError> {
+    if let Some(cursor) = cursor {
+        let cursor_id = hex::decode(cursor)
+            .map(CommitId::new)
+            .map_err(|_| user_error(format!("Invalid cursor: {cursor}")))?;
+        if !iter.by_ref().any(|item| *id_fn(&item) == cursor_id) {
+            return Err(user_error(format!(
+                "Cursor {cursor} doesn't point to any of the revisions"
+            )));
+        }
+    }
+    Ok(iter.take(limit.unwrap_or(usize::MAX)))
+}
+
 #[instrument(skip_all)]
 fn cmd_obslog(ui: &mut Ui, command: &CommandHelper, args: &ObslogArgs) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
 
     let start_commit = workspace_command.resolve_single_rev(&args.revision, ui)?;
-    let wc_commit_id = workspace_command.get_wc_commit_id();
 
     let diff_formats =
         diff_util::diff_formats_for_log(command.settings(), &args.diff_format, args.patch)?;
 
-    let template_string = match &args.template {
-        Some(value) => value.to_string(),
+    let template_string = match read_template_arg(
+        command.cwd(),
+        args.template.as_deref(),
+        args.template_file.as_deref(),
+    )? {
+        Some(value) => value,
         None => command.settings().config().get_string("templates.log")?,
     };
     let template = workspace_command.parse_commit_template(&template_string)?;
@@ -1854,17 +2160,33 @@ fn cmd_obslog(ui: &mut Ui, command: &CommandHelper, args: &ObslogArgs) -> Result
     let formatter = formatter.as_mut();
     formatter.push_label("log")?;
 
-    let mut commits = topo_order_reverse(
+    let all_commits = topo_order_reverse(
         vec![start_commit],
         |commit: &Commit| commit.id().clone(),
         |commit: &Commit| commit.predecessors(),
     );
-    if let Some(n) = args.limit {
-        commits.truncate(n);
-    }
+    let commits = paginate(
+        all_commits.into_iter(),
+        |commit| commit.id(),
+        args.cursor.as_deref(),
+        args.limit,
+    )?
+    .collect_vec();
     if !args.no_graph {
         let mut graph = get_graphlog(command.settings(), formatter.raw());
-        let default_node_symbol = graph.default_node_symbol().to_owned();
+        let node_symbols =
+            NodeSymbols::from_settings(command.settings(), graph.default_node_symbol())?;
+        let immutable_commit_ids: HashSet<CommitId> = if node_symbols.needs_immutable() {
+            let immutable_revset = workspace_command.parse_immutable_revset()?;
+            let commits_revset =
+                RevsetExpression::commits(commits.iter().map(|c| c.id().clone()).collect());
+            workspace_command
+                .evaluate_revset(commits_revset.intersection(&immutable_revset))?
+                .iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
         for commit in commits {
             let mut edges = vec![];
             for predecessor in &commit.predecessors() {
@@ -1889,11 +2211,12 @@ fn cmd_obslog(ui: &mut Ui, command: &CommandHelper, args: &ObslogArgs) -> Result
                     &diff_formats,
                 )?;
             }
-            let node_symbol = if Some(commit.id()) == wc_commit_id {
-                "@"
-            } else {
-                &default_node_symbol
-            };
+            let node_symbol = node_symbols.for_commit(
+                workspace_command.repo().as_ref(),
+                workspace_command.workspace_id(),
+                &commit,
+                immutable_commit_ids.contains(commit.id()),
+            )?;
             graph.add_node(
                 commit.id(),
                 &edges,
@@ -1988,40 +2311,168 @@ fn edit_description(
     description: &str,
     settings: &UserSettings,
 ) -> Result<String, CommandError> {
-    let description_file_path = (|| -> Result<_, io::Error> {
+    let content = format!(
+        "{description}\nJJ: Lines starting with \"JJ: \" (like this one) will be removed.\n"
+    );
+    let description = edit_temp_file(
+        "description",
+        ".jjdescription",
+        repo.repo_path(),
+        &content,
+        settings,
+    )?;
+    // Normalize line ending, remove leading and trailing blank lines.
+    let description = description
+        .lines()
+        .filter(|line| !line.starts_with("JJ: "))
+        .join("\n");
+    Ok(text_util::complete_newline(description.trim_matches('\n')))
+}
+
+/// Writes `content` to a temporary file in `dir`, opens it in the user's
+/// editor, and returns the edited content. `name` describes the file in error
+/// messages.
+fn edit_temp_file(
+    name: &str,
+    suffix: &str,
+    dir: &Path,
+    content: &str,
+    settings: &UserSettings,
+) -> Result<String, CommandError> {
+    let path = (|| -> Result<_, io::Error> {
         let mut file = tempfile::Builder::new()
             .prefix("editor-")
-            .suffix(".jjdescription")
-            .tempfile_in(repo.repo_path())?;
-        file.write_all(description.as_bytes())?;
-        file.write_all(b"\nJJ: Lines starting with \"JJ: \" (like this one) will be removed.\n")?;
+            .suffix(suffix)
+            .tempfile_in(dir)?;
+        file.write_all(content.as_bytes())?;
         let (_, path) = file.keep().map_err(|e| e.error)?;
         Ok(path)
     })()
     .map_err(|e| {
         user_error(format!(
-            r#"Failed to create description file in "{path}": {e}"#,
-            path = repo.repo_path().display()
+            r#"Failed to create {name} file in "{path}": {e}"#,
+            path = dir.display()
         ))
     })?;
 
-    run_ui_editor(settings, &description_file_path)?;
+    run_ui_editor(settings, &path)?;
 
-    let description = fs::read_to_string(&description_file_path).map_err(|e| {
+    let edited = fs::read_to_string(&path).map_err(|e| {
         user_error(format!(
-            r#"Failed to read description file "{path}": {e}"#,
-            path = description_file_path.display()
+            r#"Failed to read {name} file "{path}": {e}"#,
+            path = path.display()
         ))
     })?;
     // Delete the file only if everything went well.
     // TODO: Tell the user the name of the file we left behind.
-    std::fs::remove_file(description_file_path).ok();
-    // Normalize line ending, remove leading and trailing blank lines.
-    let description = description
-        .lines()
-        .filter(|line| !line.starts_with("JJ: "))
-        .join("\n");
-    Ok(text_util::complete_newline(description.trim_matches('\n')))
+    std::fs::remove_file(path).ok();
+    Ok(edited)
+}
+
+/// Checks the description against the rules in `[description-checks]`. The
+/// problems are reported as an error, or as warnings if `warn-only` is set.
+fn check_description(
+    ui: &Ui,
+    settings: &UserSettings,
+    description: &str,
+) -> Result<(), CommandError> {
+    // An empty description means that the description hasn't been written yet
+    if description.is_empty() {
+        return Ok(());
+    }
+    let config = settings.config();
+    let mut problems = vec![];
+    let subject = description.lines().next().unwrap_or_default();
+    if let Some(max_len) = config
+        .get_int("description-checks.subject-max-length")
+        .optional()?
+    {
+        let len = subject.chars().count();
+        if len as i64 > max_len {
+            problems.push(format!(
+                "The first line is {len} characters long (maximum is {max_len})"
+            ));
+        }
+    }
+    if let Some(max_len) = config
+        .get_int("description-checks.body-max-line-length")
+        .optional()?
+    {
+        for (i, line) in description.lines().enumerate().skip(1) {
+            let len = line.chars().count();
+            if len as i64 > max_len {
+                problems.push(format!(
+                    "Line {} is {len} characters long (maximum is {max_len})",
+                    i + 1
+                ));
+            }
+        }
+    }
+    if let Some(pattern) = config
+        .get_string("description-checks.subject-pattern")
+        .optional()?
+    {
+        let regex = Regex::new(&pattern).map_err(|err| {
+            CommandError::ConfigError(format!("description-checks.subject-pattern: {err}"))
+        })?;
+        if !regex.is_match(subject) {
+            problems.push(format!(
+                "The first line doesn't match the pattern `{pattern}`"
+            ));
+        }
+    }
+    if let Some(checker) = config
+        .get::<CommandNameAndArgs>("description-checks.command")
+        .optional()?
+    {
+        let mut child = checker
+            .to_command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| user_error(format!("Failed to run '{checker}': {err}")))?;
+        // The command may exit without reading the description
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(description.as_bytes())
+            .ok();
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let message = [output.stderr, output.stdout]
+                .iter()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim_end().to_owned())
+                .filter(|message| !message.is_empty())
+                .join("\n");
+            if message.is_empty() {
+                problems.push(format!("'{checker}' rejected the description"));
+            } else {
+                problems.push(format!("'{checker}' rejected the description:\n{message}"));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let warn_only = config
+        .get_bool("description-checks.warn-only")
+        .optional()?
+        .unwrap_or(false);
+    if warn_only {
+        for problem in &problems {
+            writeln!(ui.warning(), "{problem}")?;
+        }
+        Ok(())
+    } else {
+        Err(user_error_with_hint(
+            format!("Invalid description:\n{}", problems.join("\n")),
+            "Fix the description, or set `description-checks.warn-only = true` to only warn \
+             about these problems.",
+        ))
+    }
 }
 
 fn edit_sparse(
@@ -2116,6 +2567,7 @@ fn cmd_describe(
     if description == *commit.description() && !args.reset_author {
         ui.write("Nothing changed.\n")?;
     } else {
+        check_description(ui, command.settings(), &description)?;
         let mut tx =
             workspace_command.start_transaction(&format!("describe commit {}", commit.id().hex()));
         let mut commit_builder = tx
@@ -2177,6 +2629,7 @@ new working-copy commit.
     } else {
         edit_description(tx.base_repo(), &template, command.settings())?
     };
+    check_description(ui, command.settings(), &description)?;
 
     let new_commit = tx
         .mut_repo()
@@ -2288,7 +2741,24 @@ fn cmd_abandon(
     for commit in &to_abandon {
         tx.mut_repo().record_abandoned_commit(commit.id().clone());
     }
-    let num_rebased = tx.mut_repo().rebase_descendants(command.settings())?;
+    let abandoned_ids: HashSet<_> = to_abandon.iter().map(|commit| commit.id()).collect();
+    let old_branches = tx
+        .repo()
+        .view()
+        .local_branches()
+        .map(|(name, target)| (name.to_owned(), target.clone()))
+        .collect_vec();
+    if args.delete_branches {
+        for (name, target) in &old_branches {
+            if target.added_ids().all(|id| abandoned_ids.contains(id)) {
+                tx.mut_repo()
+                    .set_local_branch_target(name, RefTarget::absent());
+            }
+        }
+    }
+    let mut rebaser = tx.mut_repo().create_descendant_rebaser(command.settings());
+    rebaser.rebase_all()?;
+    let rebased_ids = rebaser.rebased().values().cloned().collect_vec();
 
     if to_abandon.len() == 1 {
         ui.write("Abandoned commit ")?;
@@ -2297,20 +2767,55 @@ fn cmd_abandon(
         ui.write("\n")?;
     } else if !args.summary {
         ui.write("Abandoned the following commits:\n")?;
-        for commit in to_abandon {
+        for commit in &to_abandon {
             ui.write("  ")?;
             tx.base_workspace_helper()
-                .write_commit_summary(ui.stdout_formatter().as_mut(), &commit)?;
+                .write_commit_summary(ui.stdout_formatter().as_mut(), commit)?;
             ui.write("\n")?;
         }
     } else {
         writeln!(ui, "Abandoned {} commits.", &to_abandon.len())?;
     }
-    if num_rebased > 0 {
-        writeln!(
-            ui,
-            "Rebased {num_rebased} descendant commits onto parents of abandoned commits"
-        )?;
+    if !rebased_ids.is_empty() {
+        let num_rebased = rebased_ids.len();
+        if args.summary {
+            writeln!(
+                ui,
+                "Rebased {num_rebased} descendant commits onto parents of abandoned commits"
+            )?;
+        } else {
+            writeln!(
+                ui,
+                "Rebased {num_rebased} descendant commits onto parents of abandoned commits:"
+            )?;
+            let rebased_commits: Vec<_> = RevsetExpression::commits(rebased_ids)
+                .resolve(tx.repo())?
+                .evaluate(tx.repo())?
+                .iter()
+                .commits(tx.repo().store())
+                .try_collect()?;
+            for commit in &rebased_commits {
+                ui.write("  ")?;
+                tx.write_commit_summary(ui.stdout_formatter().as_mut(), commit)?;
+                ui.write("\n")?;
+            }
+        }
+    }
+    for (name, old_target) in &old_branches {
+        if !old_target.added_ids().any(|id| abandoned_ids.contains(id)) {
+            continue;
+        }
+        let new_target = tx.repo().get_local_branch(name);
+        if new_target.is_absent() {
+            writeln!(ui, "Deleted branch {name}")?;
+        } else if let Some(id) = new_target.as_normal() {
+            let commit = tx.repo().store().get_commit(id)?;
+            write!(ui, "Moved branch {name} to ")?;
+            tx.write_commit_summary(ui.stdout_formatter().as_mut(), &commit)?;
+            ui.write("\n")?;
+        } else {
+            writeln!(ui, "Moved branch {name} (now conflicted)")?;
+        }
     }
     tx.finish(ui)?;
     Ok(())
@@ -2707,7 +3212,45 @@ from the source will be moved into the destination.
         .set_tree_id(new_destination_tree.id().clone())
         .set_description(description)
         .write()?;
+    let old_repo = tx.base_repo().clone();
     tx.finish(ui)?;
+    print_new_conflicts(ui, &workspace_command, &old_repo)?;
+    Ok(())
+}
+
+/// Prints the commits added since `old_repo` that have conflicts none of their
+/// predecessors had.
+fn print_new_conflicts(
+    ui: &mut Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    old_repo: &ReadonlyRepo,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo().as_ref();
+    let old_heads = RevsetExpression::commits(old_repo.view().heads().iter().cloned().collect());
+    let new_heads = RevsetExpression::commits(repo.view().heads().iter().cloned().collect());
+    let added_commits = old_heads.range(&new_heads).resolve(repo)?.evaluate(repo)?;
+    let mut conflicted_commits = vec![];
+    for commit in added_commits.iter().commits(repo.store()) {
+        let commit = commit?;
+        if !commit.has_conflict()? {
+            continue;
+        }
+        let mut had_conflict = false;
+        for predecessor in commit.predecessors() {
+            had_conflict |= predecessor.has_conflict()?;
+        }
+        if !had_conflict {
+            conflicted_commits.push(commit);
+        }
+    }
+    if !conflicted_commits.is_empty() {
+        ui.write("New conflicts appeared in these commits:\n")?;
+        for commit in &conflicted_commits {
+            ui.write("  ")?;
+            workspace_command.write_commit_summary(ui.stdout_formatter().as_mut(), commit)?;
+            ui.write("\n")?;
+        }
+    }
     Ok(())
 }
 
@@ -3002,6 +3545,12 @@ fn cmd_resolve(
         .rewrite_commit(command.settings(), &commit)
         .set_tree_id(new_tree_id)
         .write()?;
+    record_conflict_resolutions(
+        command.settings(),
+        tx.base_repo(),
+        &tree,
+        &new_commit.tree()?,
+    )?;
     tx.finish(ui)?;
 
     if !args.quiet {
@@ -3019,6 +3568,43 @@ fn cmd_resolve(
     Ok(())
 }
 
+/// Tells which parents of the merge `commit` the `conflicts` were inherited
+/// from, and which were introduced by merging the parents.
+fn print_conflict_origins(
+    conflicts: &[(RepoPath, Merge<Option<TreeValue>>)],
+    commit: &Commit,
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<(), CommandError> {
+    let mut merged_paths = conflicts.iter().map(|(path, _)| path).collect_vec();
+    for parent in commit.parents() {
+        let parent_conflicts: HashSet<_> =
+            parent.tree()?.conflicts().map(|(path, _)| path).collect();
+        let inherited_paths = conflicts
+            .iter()
+            .map(|(path, _)| path)
+            .filter(|path| parent_conflicts.contains(path))
+            .collect_vec();
+        if inherited_paths.is_empty() {
+            continue;
+        }
+        merged_paths.retain(|path| !parent_conflicts.contains(path));
+        formatter.write_str("Conflicts inherited from parent ")?;
+        workspace_command.write_commit_summary(formatter, &parent)?;
+        formatter.write_str(":\n")?;
+        for path in inherited_paths {
+            writeln!(formatter, "  {}", workspace_command.format_file_path(path))?;
+        }
+    }
+    if !merged_paths.is_empty() {
+        formatter.write_str("Conflicts introduced by merging the parents:\n")?;
+        for path in merged_paths {
+            writeln!(formatter, "  {}", workspace_command.format_file_path(path))?;
+        }
+    }
+    Ok(())
+}
+
 #[instrument(skip_all)]
 fn print_conflicted_paths(
     conflicts: &[(RepoPath, Merge<Option<TreeValue>>)],
@@ -3236,7 +3822,9 @@ fn description_template_for_commit(
         workspace_command,
         commit,
         &EverythingMatcher,
-        &[DiffFormat::Summary],
+        &[DiffFormat::Summary {
+            null_terminated: false,
+        }],
     )?;
     let description = if commit.description().is_empty() {
         settings.default_description()
@@ -3267,7 +3855,9 @@ fn description_template_for_cmd_split(
         from_tree,
         to_tree,
         &EverythingMatcher,
-        &[DiffFormat::Summary],
+        &[DiffFormat::Summary {
+            null_terminated: false,
+        }],
     )?;
     let description = if overall_commit_description.is_empty() {
         settings.default_description()
@@ -3340,6 +3930,7 @@ don't make any changes, then the operation will be aborted.
         &middle_tree,
     )?;
     let first_description = edit_description(tx.base_repo(), &first_template, command.settings())?;
+    check_description(ui, command.settings(), &first_description)?;
     let first_commit = tx
         .mut_repo()
         .rewrite_commit(command.settings(), &commit)
@@ -3361,6 +3952,7 @@ don't make any changes, then the operation will be aborted.
         )?;
         edit_description(tx.base_repo(), &second_template, command.settings())?
     };
+    check_description(ui, command.settings(), &second_description)?;
     let second_commit = tx
         .mut_repo()
         .rewrite_commit(command.settings(), &commit)
@@ -3687,6 +4279,9 @@ fn cmd_workspace(
 ) -> Result<(), CommandError> {
     match subcommand {
         WorkspaceCommands::Add(command_matches) => cmd_workspace_add(ui, command, command_matches),
+        WorkspaceCommands::CopyChanges(command_matches) => {
+            cmd_workspace_copy_changes(ui, command, command_matches)
+        }
         WorkspaceCommands::Forget(command_matches) => {
             cmd_workspace_forget(ui, command, command_matches)
         }
@@ -3766,6 +4361,101 @@ fn cmd_workspace_add(
     Ok(())
 }
 
+#[instrument(skip_all)]
+fn cmd_workspace_copy_changes(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceCopyChangesArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let workspace_id = WorkspaceId::new(args.workspace.clone());
+    if &workspace_id == workspace_command.workspace_id() {
+        return Err(user_error("Cannot copy changes to the current workspace"));
+    }
+    let source = workspace_command.repo().store().get_commit(
+        workspace_command
+            .get_wc_commit_id()
+            .ok_or_else(|| user_error("This command requires a working copy"))?,
+    )?;
+    let destination_id = workspace_command
+        .repo()
+        .view()
+        .get_wc_commit_id(&workspace_id)
+        .ok_or_else(|| user_error("No such workspace"))?
+        .clone();
+    let destination = workspace_command
+        .repo()
+        .store()
+        .get_commit(&destination_id)?;
+    workspace_command.check_rewritable([&destination])?;
+    let matcher = workspace_command.matcher_from_values(&args.paths)?;
+    let mut tx = workspace_command.start_transaction(&format!(
+        "copy changes from workspace {} to workspace {}",
+        workspace_command.workspace_id().as_str(),
+        workspace_id.as_str()
+    ));
+    let parent_tree = merge_commit_trees(tx.repo(), &source.parents())?;
+    let source_tree = source.tree()?;
+    let instructions = format!(
+        "\
+You are copying changes from: {}
+into the working-copy commit of workspace {}: {}
+
+The left side of the diff shows the contents of the parent commit. The
+right side initially shows the contents of the working-copy commit.
+
+Adjust the right side until the diff shows the changes you want to copy
+to the other workspace. If you don't make any changes, then all the
+changes will be copied.
+",
+        tx.format_commit_summary(&source),
+        workspace_id.as_str(),
+        tx.format_commit_summary(&destination)
+    );
+    let selected_tree_id = tx.select_diff(
+        ui,
+        &parent_tree,
+        &source_tree,
+        matcher.as_ref(),
+        &instructions,
+        args.interactive,
+    )?;
+    if selected_tree_id == parent_tree.id() {
+        return Err(user_error("No changes to copy"));
+    }
+    let selected_tree = tx.repo().store().get_root_tree(&selected_tree_id)?;
+    let destination_tree = destination.tree()?;
+    let new_destination_tree = destination_tree.merge(&parent_tree, &selected_tree)?;
+    tx.mut_repo()
+        .rewrite_commit(command.settings(), &destination)
+        .set_tree_id(new_destination_tree.id().clone())
+        .write()?;
+    tx.finish(ui)?;
+
+    let new_conflicts = new_destination_tree
+        .conflicts()
+        .filter(|(path, _)| destination_tree.path_value(path).is_resolved())
+        .collect_vec();
+    if !new_conflicts.is_empty() {
+        writeln!(
+            ui,
+            "The changes conflict with the working-copy commit of workspace {} at these paths:",
+            workspace_id.as_str()
+        )?;
+        print_conflicted_paths(
+            &new_conflicts,
+            ui.stdout_formatter().as_mut(),
+            &workspace_command,
+        )?;
+    }
+    writeln!(
+        ui.hint(),
+        "Hint: Run `jj workspace update-stale` in workspace {} to update its working copy.",
+        workspace_id.as_str()
+    )?;
+    Ok(())
+}
+
 #[instrument(skip_all)]
 fn cmd_workspace_forget(
     ui: &mut Ui,
@@ -3792,6 +4482,9 @@ fn cmd_workspace_forget(
         workspace_command.start_transaction(&format!("forget workspace {}", workspace_id.as_str()));
     tx.mut_repo().remove_wc_commit(&workspace_id);
     tx.finish(ui)?;
+    // A leftover record is harmless since the workspace at the recorded path
+    // is checked before it's used.
+    let _ = forget_workspace_root(workspace_command.repo().repo_path(), &workspace_id);
     Ok(())
 }
 
@@ -3856,16 +4549,23 @@ fn cmd_workspace_update_stale(
             // The same check as start_working_copy_mutation(), but with the stale
             // working-copy commit.
             if known_wc_commit.tree_id() != locked_wc.old_tree_id() {
-                return Err(user_error("Concurrent working copy operation. Try again."));
+                return Err(conflict_error(
+                    "Concurrent working copy operation. Try again.",
+                ));
             }
             let desired_tree = desired_wc_commit.tree()?;
-            let stats = locked_wc.check_out(&desired_tree).map_err(|err| {
-                CommandError::InternalError(format!(
-                    "Failed to check out commit {}: {}",
-                    desired_wc_commit.id().hex(),
-                    err
-                ))
-            })?;
+            let checkout_options = checkout_options(command.settings())?;
+            let stats = locked_wc
+                .check_out(&desired_tree, &checkout_options)
+                .map_err(|err| {
+                    checkout_error(
+                        format!(
+                            "Failed to check out commit {}",
+                            desired_wc_commit.id().hex()
+                        ),
+                        err,
+                    )
+                })?;
             locked_wc.finish(repo.op_id().clone())?;
             ui.write("Working copy now at: ")?;
             ui.stdout_formatter().with_label("working_copy", |fmt| {
@@ -3951,9 +4651,10 @@ fn cmd_sparse_set(
         )?;
         new_patterns.sort();
     }
-    let stats = locked_wc.set_sparse_patterns(new_patterns).map_err(|err| {
-        CommandError::InternalError(format!("Failed to update working copy paths: {err}"))
-    })?;
+    let checkout_options = checkout_options(command.settings())?;
+    let stats = locked_wc
+        .set_sparse_patterns(new_patterns, &checkout_options)
+        .map_err(|err| checkout_error("Failed to update working copy paths", err))?;
     let operation_id = locked_wc.old_operation_id().clone();
     locked_wc.finish(operation_id)?;
     print_checkout_stats(ui, stats)?;
@@ -3965,10 +4666,60 @@ pub fn default_app() -> Command {
     Commands::augment_subcommands(Args::command())
 }
 
+fn supports_dry_run(subcommand: &Commands) -> bool {
+    matches!(
+        subcommand,
+        Commands::Abandon(_)
+            | Commands::Rebase(_)
+            | Commands::Squash(_)
+            | Commands::Branch(
+                branch::BranchSubcommand::Delete(_) | branch::BranchSubcommand::Move(_)
+            )
+            | Commands::Git(git::GitCommands::Push(_))
+    )
+}
+
+/// Commands that show or build on remote branches, and therefore benefit from
+/// `git.auto-fetch-interval`. Commands that operate on the operation log (such
+/// as `undo`) must not be listed since the fetch would create a new operation.
+fn supports_auto_fetch(subcommand: &Commands) -> bool {
+    matches!(
+        subcommand,
+        Commands::Log(_)
+            | Commands::Status(_)
+            | Commands::Show(_)
+            | Commands::New(_)
+            | Commands::Rebase(_)
+            | Commands::Branch(branch::BranchSubcommand::List(_))
+    )
+}
+
 #[instrument(skip_all)]
 pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), CommandError> {
     let derived_subcommands: Commands =
         Commands::from_arg_matches(command_helper.matches()).unwrap();
+    if command_helper.global_args().dry_run && !supports_dry_run(&derived_subcommands) {
+        return Err(user_error("--dry-run is not supported by this command"));
+    }
+    if command_helper.global_args().dry_run
+        && !matches!(
+            derived_subcommands,
+            Commands::Git(git::GitCommands::Push(_))
+        )
+    {
+        // The command's own messages describe the changes as if they were made.
+        // Only the summary printed when finishing the transaction is shown.
+        // `jj git push` prints its own dry-run summary.
+        ui.set_discard_stdout(true);
+    }
+    let global_args = command_helper.global_args();
+    if !global_args.dry_run
+        && !global_args.ignore_working_copy
+        && global_args.at_operation == "@"
+        && supports_auto_fetch(&derived_subcommands)
+    {
+        git::maybe_auto_fetch(ui, command_helper)?;
+    }
     match &derived_subcommands {
         Commands::Version(sub_args) => cmd_version(ui, command_helper, sub_args),
         Commands::Init(sub_args) => cmd_init(ui, command_helper, sub_args),
@@ -3980,6 +4731,9 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Commands::Diff(sub_args) => cmd_diff(ui, command_helper, sub_args),
         Commands::Show(sub_args) => cmd_show(ui, command_helper, sub_args),
         Commands::Status(sub_args) => cmd_status(ui, command_helper, sub_args),
+        Commands::Histedit(sub_args) => histedit::cmd_histedit(ui, command_helper, sub_args),
+        Commands::Shortlog(sub_args) => shortlog::cmd_shortlog(ui, command_helper, sub_args),
+        Commands::Stats(sub_args) => stats::cmd_stats(ui, command_helper, sub_args),
         Commands::Log(sub_args) => cmd_log(ui, command_helper, sub_args),
         Commands::Interdiff(sub_args) => cmd_interdiff(ui, command_helper, sub_args),
         Commands::Obslog(sub_args) => cmd_obslog(ui, command_helper, sub_args),
@@ -4002,7 +4756,9 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Commands::Rebase(sub_args) => cmd_rebase(ui, command_helper, sub_args),
         Commands::Backout(sub_args) => cmd_backout(ui, command_helper, sub_args),
         Commands::Resolve(sub_args) => cmd_resolve(ui, command_helper, sub_args),
+        Commands::Resolution(sub_args) => resolution::cmd_resolution(ui, command_helper, sub_args),
         Commands::Branch(sub_args) => branch::cmd_branch(ui, command_helper, sub_args),
+        Commands::Note(sub_args) => note::cmd_note(ui, command_helper, sub_args),
         Commands::Undo(sub_args) => operation::cmd_op_undo(ui, command_helper, sub_args),
         Commands::Operation(sub_args) => operation::cmd_operation(ui, command_helper, sub_args),
         Commands::Workspace(sub_args) => cmd_workspace(ui, command_helper, sub_args),
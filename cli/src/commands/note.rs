@@ -0,0 +1,120 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Subcommand;
+use jj_lib::backend::ObjectId;
+
+use crate::cli_util::{join_message_paragraphs, CommandError, CommandHelper, RevisionArg};
+use crate::ui::Ui;
+
+/// Manage notes attached to changes
+///
+/// Notes are stored in the operation log instead of in the commits, so they
+/// can be edited without rewriting any commits. A note belongs to the change,
+/// so it stays attached when the change is rewritten. Notes are not pushed to
+/// Git remotes.
+///
+/// The `note` template keyword prints the note of a commit.
+#[derive(Subcommand, Clone, Debug)]
+pub enum NoteCommands {
+    Add(NoteAddArgs),
+    Show(NoteShowArgs),
+    Remove(NoteRemoveArgs),
+}
+
+/// Add lines to the note of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct NoteAddArgs {
+    /// The revision whose note to add to
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// The text to add (can be repeated)
+    #[arg(long = "message", short, value_name = "MESSAGE", required = true)]
+    message_paragraphs: Vec<String>,
+}
+
+/// Print the note of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct NoteShowArgs {
+    /// The revision whose note to print
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+}
+
+/// Remove the note of a change
+#[derive(clap::Args, Clone, Debug)]
+pub struct NoteRemoveArgs {
+    /// The revision whose note to remove
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+}
+
+pub fn cmd_note(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &NoteCommands,
+) -> Result<(), CommandError> {
+    match subcommand {
+        NoteCommands::Add(sub_args) => cmd_note_add(ui, command, sub_args),
+        NoteCommands::Show(sub_args) => cmd_note_show(ui, command, sub_args),
+        NoteCommands::Remove(sub_args) => cmd_note_remove(ui, command, sub_args),
+    }
+}
+
+fn cmd_note_add(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteAddArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(&args.revision, ui)?;
+    let mut tx =
+        workspace_command.start_transaction(&format!("add note to commit {}", commit.id().hex()));
+    let added = join_message_paragraphs(&args.message_paragraphs);
+    let note = match tx.repo().get_note(commit.change_id()) {
+        Some(old_note) => format!("{}\n{added}", old_note.trim_end()),
+        None => added,
+    };
+    tx.mut_repo().set_note(commit.change_id(), note);
+    tx.finish(ui)?;
+    Ok(())
+}
+
+fn cmd_note_show(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteShowArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(&args.revision, ui)?;
+    if let Some(note) = workspace_command.repo().view().get_note(commit.change_id()) {
+        ui.request_pager();
+        ui.write(note)?;
+    }
+    Ok(())
+}
+
+fn cmd_note_remove(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &NoteRemoveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(&args.revision, ui)?;
+    let mut tx = workspace_command
+        .start_transaction(&format!("remove note from commit {}", commit.id().hex()));
+    tx.mut_repo().set_note(commit.change_id(), String::new());
+    tx.finish(ui)?;
+    Ok(())
+}
@@ -1,12 +1,21 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 
 use clap::Subcommand;
+use itertools::Itertools as _;
 use jj_lib::backend::ObjectId;
+use jj_lib::commit::Commit;
 use jj_lib::op_store::{BranchTarget, RefTarget};
+use jj_lib::op_tag_store::{self, OpTagStore};
 use jj_lib::operation;
 use jj_lib::repo::Repo;
+use jj_lib::revset::{DefaultSymbolResolver, RevsetResolutionError, SymbolResolver as _};
 
-use crate::cli_util::{user_error, CommandError, CommandHelper, LogContentFormat};
+use crate::cli_util::{
+    read_template_arg, short_operation_hash, user_error, user_error_with_hint, CommandError,
+    CommandHelper, LogContentFormat,
+};
+use crate::formatter::TableFormatter;
 use crate::graphlog::{get_graphlog, Edge};
 use crate::operation_templater;
 use crate::templater::Template as _;
@@ -21,6 +30,8 @@ pub enum OperationCommands {
     Log(OperationLogArgs),
     Undo(OperationUndoArgs),
     Restore(OperationRestoreArgs),
+    Tag(OperationTagArgs),
+    Recover(OperationRecoverArgs),
 }
 
 /// Show the operation log
@@ -35,8 +46,15 @@ pub struct OperationLogArgs {
     /// Render each operation using the given template
     ///
     /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    ///
+    /// If the template starts with `@`, it's read from the file at the rest of
+    /// the argument, or from stdin with `@-`.
     #[arg(long, short = 'T')]
     template: Option<String>,
+    /// Read the template to render each operation with from the file
+    #[arg(long, value_name = "PATH", conflicts_with = "template")]
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    template_file: Option<PathBuf>,
 }
 
 /// Create a new operation that restores the repo to an earlier state
@@ -62,6 +80,22 @@ pub struct OperationRestoreArgs {
     /// This option is EXPERIMENTAL.
     #[arg(long)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Restore only the branches (local and remote-tracking), keeping the rest
+    /// of the current state
+    #[arg(long, conflicts_with_all = ["what", "branch"])]
+    branches: bool,
+
+    /// Restore only the given branches (can be repeated)
+    #[arg(long, value_name = "BRANCH", conflicts_with = "what")]
+    branch: Vec<String>,
+
+    /// Restore only the working-copy commits of the current workspaces,
+    /// keeping the rest of the current state
+    ///
+    /// Can be combined with `--branches` or `--branch`.
+    #[arg(long, conflicts_with = "what")]
+    working_copies: bool,
 }
 
 /// Create a new operation that undoes an earlier operation
@@ -88,6 +122,46 @@ pub struct OperationUndoArgs {
     what: Vec<UndoWhatToRestore>,
 }
 
+/// Name an operation so it can be referred to later
+///
+/// The name can be used wherever an operation ID is expected, e.g. `jj op
+/// restore <NAME>` or `jj --at-op=<NAME> log`. This is useful for creating a
+/// restore point before rewriting history.
+///
+/// Lists the existing tags if no name is given.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationTagArgs {
+    /// The name of the tag
+    ///
+    /// Names may contain letters, digits, `-`, `_`, and `.`. They must not end
+    /// with `-` or consist only of hexadecimal digits, so they can't be confused
+    /// with operation IDs.
+    name: Option<String>,
+
+    /// The operation to tag
+    #[arg(long, short, default_value = "@", requires = "name")]
+    operation: String,
+
+    /// Delete the tag instead of creating it
+    #[arg(long, short, requires = "name", conflicts_with = "operation")]
+    delete: bool,
+}
+
+/// Make a hidden commit visible again
+///
+/// Finds the latest operation in which the given revision existed, and makes
+/// its commit visible in a new operation. Unlike `jj op restore`, the rest of
+/// the repo is left as it is, so this can bring back an abandoned commit
+/// without undoing the work done since.
+///
+/// Hidden ancestors of the commit become visible too. If the commit was
+/// rewritten rather than abandoned, its change becomes divergent.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationRecoverArgs {
+    /// The change ID or commit ID of the hidden commit
+    revision: String,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
 enum UndoWhatToRestore {
     /// The jj repo state and local branches
@@ -109,8 +183,12 @@ fn cmd_op_log(
     let head_op = repo.operation().clone();
     let head_op_id = head_op.id().clone();
 
-    let template_string = match &args.template {
-        Some(value) => value.to_owned(),
+    let template_string = match read_template_arg(
+        command.cwd(),
+        args.template.as_deref(),
+        args.template_file.as_deref(),
+    )? {
+        Some(value) => value,
         None => command.settings().config().get_string("templates.op_log")?,
     };
     let template = operation_templater::parse(
@@ -119,6 +197,12 @@ fn cmd_op_log(
         workspace_command.template_aliases_map(),
     )?;
     let with_content_format = LogContentFormat::new(ui, command.settings())?;
+    with_content_format.warn_unaligned_table(
+        ui,
+        &template_string,
+        workspace_command.template_aliases_map(),
+        !args.no_graph,
+    )?;
 
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
@@ -157,11 +241,13 @@ fn cmd_op_log(
             )?;
         }
     } else {
+        let mut formatter = TableFormatter::new(formatter);
         for op in iter {
-            with_content_format.write(formatter, |formatter| {
+            with_content_format.write(&mut formatter, |formatter| {
                 formatter.with_label("op_log", |formatter| template.format(&op, formatter))
             })?;
         }
+        formatter.finish()?;
     }
 
     Ok(())
@@ -226,6 +312,47 @@ fn view_with_desired_portions_restored(
     new_view
 }
 
+/// Restore only the given branches and (optionally) the working-copy commits,
+/// keeping the rest of the current view
+fn view_with_selected_refs_restored(
+    view_being_restored: &jj_lib::op_store::View,
+    current_view: &jj_lib::op_store::View,
+    branch_names: &[&str],
+    restore_working_copies: bool,
+) -> jj_lib::op_store::View {
+    let mut new_view = current_view.clone();
+    for &branch_name in branch_names {
+        match view_being_restored.branches.get(branch_name) {
+            Some(branch_target) => {
+                let target_ids = itertools::chain(
+                    branch_target.local_target.added_ids(),
+                    branch_target
+                        .remote_targets
+                        .values()
+                        .flat_map(|target| target.added_ids()),
+                );
+                // Restored targets may have been abandoned since
+                new_view.head_ids.extend(target_ids.cloned());
+                new_view
+                    .branches
+                    .insert(branch_name.to_owned(), branch_target.clone());
+            }
+            None => {
+                new_view.branches.remove(branch_name);
+            }
+        }
+    }
+    if restore_working_copies {
+        for (workspace_id, commit_id) in &mut new_view.wc_commit_ids {
+            if let Some(old_commit_id) = view_being_restored.wc_commit_ids.get(workspace_id) {
+                commit_id.clone_from(old_commit_id);
+                new_view.head_ids.insert(old_commit_id.clone());
+            }
+        }
+    }
+    new_view
+}
+
 fn process_what_arg(what_arg: &[UndoWhatToRestore], colocated: bool) -> Vec<UndoWhatToRestore> {
     if !what_arg.is_empty() {
         what_arg.to_vec()
@@ -300,19 +427,158 @@ fn cmd_op_restore(
     let mut workspace_command = command.workspace_helper(ui)?;
     let target_op = workspace_command.resolve_single_op(&args.operation)?;
     let repo_is_colocated = workspace_command.working_copy_shared_with_git();
+    let target_view = target_op.view()?;
+    let target_view = target_view.store_view();
+    let current_view = workspace_command.repo().view().store_view();
+    let branch_names = if args.branches {
+        target_view
+            .branches
+            .keys()
+            .chain(current_view.branches.keys())
+            .map(|name| name.as_str())
+            .sorted()
+            .dedup()
+            .collect_vec()
+    } else {
+        for name in &args.branch {
+            if !target_view.branches.contains_key(name) && !current_view.branches.contains_key(name)
+            {
+                return Err(user_error(format!("No such branch: {name}")));
+            }
+        }
+        args.branch.iter().map(|name| name.as_str()).collect_vec()
+    };
+    let new_view = if args.branches || !args.branch.is_empty() || args.working_copies {
+        view_with_selected_refs_restored(
+            target_view,
+            current_view,
+            &branch_names,
+            args.working_copies,
+        )
+    } else {
+        view_with_desired_portions_restored(
+            target_view,
+            current_view,
+            &process_what_arg(&args.what, repo_is_colocated),
+        )
+    };
     let mut tx = workspace_command
         .start_transaction(&format!("restore to operation {}", target_op.id().hex()));
-    let new_view = view_with_desired_portions_restored(
-        target_op.view()?.store_view(),
-        tx.base_repo().view().store_view(),
-        &process_what_arg(&args.what, repo_is_colocated),
-    );
     tx.mut_repo().set_view(new_view);
     tx.finish(ui)?;
 
     Ok(())
 }
 
+fn cmd_op_tag(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationTagArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let op_tag_store = OpTagStore::load(repo.repo_path());
+    let Some(name) = &args.name else {
+        for (name, op_id) in op_tag_store.tags()? {
+            let op = repo.op_store().read_operation(&op_id)?;
+            writeln!(
+                ui,
+                "{name}: {} {}",
+                short_operation_hash(&op_id),
+                op.metadata.description
+            )?;
+        }
+        return Ok(());
+    };
+    if args.delete {
+        if !op_tag_store.remove(name)? {
+            return Err(user_error(format!(r#"No operation tag named "{name}""#)));
+        }
+        return Ok(());
+    }
+    if !op_tag_store::is_valid_tag_name(name) {
+        return Err(user_error_with_hint(
+            format!(r#"Invalid operation tag name "{name}""#),
+            "Names may contain letters, digits, `-`, `_`, and `.`. They must not end with `-` \
+             or consist only of hexadecimal digits.",
+        ));
+    }
+    if let Some(op_id) = op_tag_store.get(name)? {
+        return Err(user_error_with_hint(
+            format!(
+                r#"Operation tag "{name}" already exists and points to operation {}"#,
+                short_operation_hash(&op_id)
+            ),
+            format!("Use `jj op tag --delete {name}` to delete it first."),
+        ));
+    }
+    let op = workspace_command.resolve_single_op(&args.operation)?;
+    op_tag_store.set(name, op.id())?;
+    writeln!(
+        ui,
+        "Tagged operation {} as {name}",
+        short_operation_hash(op.id())
+    )?;
+    Ok(())
+}
+
+fn cmd_op_recover(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationRecoverArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo().clone();
+    let repo_loader = repo.loader();
+    let mut found_ids = None;
+    for op in operation::walk_ancestors(repo.operation()) {
+        let op_repo = repo_loader.load_at(&op)?;
+        let symbol_resolver = DefaultSymbolResolver::new(op_repo.as_ref());
+        match symbol_resolver.resolve_symbol(&args.revision) {
+            Ok(ids) => {
+                found_ids = Some(ids);
+                break;
+            }
+            Err(RevsetResolutionError::NoSuchRevision { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let Some(found_ids) = found_ids else {
+        return Err(user_error(format!(
+            r#"Revision "{}" doesn't exist in any operation"#,
+            args.revision
+        )));
+    };
+    let is_visible = |commit: &Commit| {
+        repo.resolve_change_id(commit.change_id())
+            .is_some_and(|ids| ids.contains(commit.id()))
+    };
+    let to_recover: Vec<_> = found_ids
+        .iter()
+        .map(|id| repo.store().get_commit(id))
+        .filter_ok(|commit| !is_visible(commit))
+        .try_collect()?;
+    if to_recover.is_empty() {
+        return Err(user_error(format!(
+            r#"Revision "{}" is already visible"#,
+            args.revision
+        )));
+    }
+
+    let mut tx = workspace_command.start_transaction(&format!(
+        "recover commit {}",
+        to_recover.iter().map(|commit| commit.id().hex()).join(", ")
+    ));
+    tx.mut_repo().add_heads(&to_recover);
+    for commit in &to_recover {
+        ui.write("Recovered commit ")?;
+        tx.write_commit_summary(ui.stdout_formatter().as_mut(), commit)?;
+        ui.write("\n")?;
+    }
+    tx.finish(ui)?;
+    Ok(())
+}
+
 pub fn cmd_operation(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -322,5 +588,7 @@ pub fn cmd_operation(
         OperationCommands::Log(command_matches) => cmd_op_log(ui, command, command_matches),
         OperationCommands::Restore(command_matches) => cmd_op_restore(ui, command, command_matches),
         OperationCommands::Undo(command_matches) => cmd_op_undo(ui, command, command_matches),
+        OperationCommands::Tag(command_matches) => cmd_op_tag(ui, command, command_matches),
+        OperationCommands::Recover(command_matches) => cmd_op_recover(ui, command, command_matches),
     }
 }
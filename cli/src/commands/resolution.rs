@@ -0,0 +1,114 @@
+use clap::Subcommand;
+use itertools::Itertools;
+use jj_lib::resolution_store::ResolutionStore;
+
+use crate::cli_util::{user_error, CommandError, CommandHelper};
+use crate::ui::Ui;
+
+/// Manage recorded conflict resolutions
+///
+/// If `conflicts.remember-resolutions` is enabled, resolutions of conflicts
+/// made by `jj resolve` or in the working copy are recorded. When an
+/// identical conflict reappears during a rebase, the recorded resolution is
+/// applied automatically.
+#[derive(Subcommand, Clone, Debug)]
+pub enum ResolutionCommands {
+    List(ResolutionListArgs),
+    Forget(ResolutionForgetArgs),
+}
+
+/// List recorded conflict resolutions
+#[derive(clap::Args, Clone, Debug)]
+pub struct ResolutionListArgs {}
+
+/// Forget recorded conflict resolutions
+#[derive(clap::Args, Clone, Debug)]
+pub struct ResolutionForgetArgs {
+    /// The resolutions to forget (a unique prefix of the key is sufficient)
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    keys: Vec<String>,
+    /// Forget all recorded resolutions
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn cmd_resolution(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &ResolutionCommands,
+) -> Result<(), CommandError> {
+    match subcommand {
+        ResolutionCommands::List(sub_args) => cmd_resolution_list(ui, command, sub_args),
+        ResolutionCommands::Forget(sub_args) => cmd_resolution_forget(ui, command, sub_args),
+    }
+}
+
+fn load_resolution_store(
+    ui: &mut Ui,
+    command: &CommandHelper,
+) -> Result<ResolutionStore, CommandError> {
+    // Snapshot the working copy so that pending resolutions are recorded
+    let workspace_command = command.workspace_helper(ui)?;
+    Ok(ResolutionStore::load(workspace_command.repo().repo_path()))
+}
+
+fn cmd_resolution_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &ResolutionListArgs,
+) -> Result<(), CommandError> {
+    let store = load_resolution_store(ui, command)?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    for (key, entry) in store.entries()? {
+        formatter.with_label("resolution", |formatter| {
+            formatter.with_label("key", |formatter| {
+                write!(formatter, "{}", key.get(..12).unwrap_or(&key))
+            })?;
+            writeln!(formatter, " {}", entry.path)
+        })?;
+    }
+    Ok(())
+}
+
+fn cmd_resolution_forget(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ResolutionForgetArgs,
+) -> Result<(), CommandError> {
+    let store = load_resolution_store(ui, command)?;
+    let keys = store
+        .entries()?
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect_vec();
+    let to_forget = if args.all {
+        keys
+    } else {
+        let mut to_forget = vec![];
+        for prefix in &args.keys {
+            match keys
+                .iter()
+                .filter(|key| key.starts_with(prefix.as_str()))
+                .at_most_one()
+            {
+                Ok(Some(key)) => to_forget.push(key.clone()),
+                Ok(None) => {
+                    return Err(user_error(format!("No such recorded resolution: {prefix}")))
+                }
+                Err(_) => {
+                    return Err(user_error(format!(
+                        "Recorded resolution key prefix \"{prefix}\" is ambiguous"
+                    )))
+                }
+            }
+        }
+        to_forget
+    };
+    for key in &to_forget {
+        store.remove(key)?;
+    }
+    writeln!(ui, "Forgot {} recorded resolutions", to_forget.len())?;
+    Ok(())
+}
@@ -0,0 +1,178 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+
+use itertools::Itertools;
+use jj_lib::backend::ObjectId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetIteratorExt;
+use serde::Serialize;
+
+use crate::cli_util::{user_error, CommandError, CommandHelper, RevisionArg};
+use crate::formatter::Formatter;
+use crate::templater::Template as _;
+use crate::ui::Ui;
+
+/// Summarize commits by author
+///
+/// Groups the commits in the given revisions by author and lists the number of
+/// commits and the first line of the description of each commit, oldest
+/// first. This is useful e.g. for writing release notes.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ShortlogArgs {
+    /// The revisions to summarize
+    #[arg(long, short, default_value = "::@ ~ root()")]
+    revisions: RevisionArg,
+    /// Only show the number of commits of each author
+    #[arg(long, short)]
+    summary: bool,
+    /// Group by email address as well as name, and show the email address
+    #[arg(long, short)]
+    email: bool,
+    /// Render each commit using the given template
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T', conflicts_with = "json")]
+    template: Option<String>,
+    /// Print the summary as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Serialize)]
+struct AuthorEntry {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    count: usize,
+    commits: Vec<CommitEntry>,
+}
+
+#[derive(Serialize)]
+struct CommitEntry {
+    commit_id: String,
+    change_id: String,
+    summary: String,
+}
+
+pub(crate) fn cmd_shortlog(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ShortlogArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let expression = workspace_command.parse_revset(&args.revisions, Some(ui))?;
+    let revset = workspace_command.evaluate_revset(expression)?;
+
+    let mut groups: HashMap<(String, Option<String>), Vec<Commit>> = HashMap::new();
+    for commit in revset.iter().commits(repo.store()) {
+        let commit = commit?;
+        let author = commit.author();
+        let email = args.email.then(|| author.email.clone());
+        groups
+            .entry((author.name.clone(), email))
+            .or_default()
+            .push(commit);
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(key, mut commits)| {
+            // The revset is iterated in reverse topological order
+            commits.reverse();
+            (key, commits)
+        })
+        .sorted_by(
+            |((a_name, a_email), a_commits), ((b_name, b_email), b_commits)| {
+                b_commits
+                    .len()
+                    .cmp(&a_commits.len())
+                    .then_with(|| a_name.cmp(b_name))
+                    .then_with(|| a_email.cmp(b_email))
+            },
+        )
+        .collect_vec();
+
+    if args.json {
+        for ((name, email), commits) in groups {
+            let entry = AuthorEntry {
+                name,
+                email,
+                count: commits.len(),
+                commits: commits
+                    .iter()
+                    .map(|commit| CommitEntry {
+                        commit_id: commit.id().hex(),
+                        change_id: commit.change_id().hex(),
+                        summary: commit.description().lines().next().unwrap_or("").to_owned(),
+                    })
+                    .collect(),
+            };
+            let json = serde_json::to_string(&entry)
+                .map_err(|err| user_error(format!("Failed to serialize summary: {err}")))?;
+            writeln!(ui, "{json}")?;
+        }
+        return Ok(());
+    }
+
+    let template_string = match &args.template {
+        Some(value) => value.to_owned(),
+        None => command
+            .settings()
+            .config()
+            .get_string("templates.shortlog")?,
+    };
+    let template = workspace_command.parse_commit_template(&template_string)?;
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    let count_width = groups
+        .iter()
+        .map(|(_, commits)| commits.len().to_string().len())
+        .max()
+        .unwrap_or(0);
+    for (i, ((name, email), commits)) in groups.iter().enumerate() {
+        if args.summary {
+            write!(formatter, "{:>count_width$}  ", commits.len())?;
+            write_author(formatter, name, email.as_deref())?;
+            writeln!(formatter)?;
+            continue;
+        }
+        if i > 0 {
+            writeln!(formatter)?;
+        }
+        write_author(formatter, name, email.as_deref())?;
+        writeln!(formatter, " ({}):", commits.len())?;
+        for commit in commits {
+            write!(formatter, "      ")?;
+            template.format(commit, formatter)?;
+            writeln!(formatter)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_author(formatter: &mut dyn Formatter, name: &str, email: Option<&str>) -> io::Result<()> {
+    formatter.with_label("author", |formatter| write!(formatter, "{name}"))?;
+    if let Some(email) = email {
+        write!(formatter, " <")?;
+        formatter.with_label("email", |formatter| write!(formatter, "{email}"))?;
+        write!(formatter, ">")?;
+    }
+    Ok(())
+}
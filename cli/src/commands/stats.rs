@@ -0,0 +1,177 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::{fs, io};
+
+use itertools::Itertools;
+use jj_lib::default_index_store::ReadonlyIndexWrapper;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetIteratorExt;
+use jj_lib::settings::HumanByteSize;
+
+use crate::cli_util::{user_error, CommandError, CommandHelper, RevisionArg};
+use crate::stats_templater::{self, AuthorStats, IndexStats, RepoStats};
+use crate::ui::Ui;
+
+/// Show statistics about the repository
+///
+/// Reports the number of commits and changes in the given revisions along
+/// with a breakdown by author, the on-disk size of the repository, and the
+/// state of the commit index.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct StatsArgs {
+    /// The revisions to count commits and authors in
+    #[arg(long, short, default_value = "all() ~ root()")]
+    revisions: RevisionArg,
+    /// Print the statistics as JSON
+    #[arg(long, conflicts_with = "template")]
+    json: bool,
+    /// Render the statistics using the given template
+    ///
+    /// For the syntax, see https://github.com/martinvonz/jj/blob/main/docs/templates.md
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+}
+
+pub(crate) fn cmd_stats(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &StatsArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let expression = workspace_command.parse_revset(&args.revisions, Some(ui))?;
+    let revset = workspace_command.evaluate_revset(expression)?;
+
+    let mut num_commits = 0;
+    let mut num_merges = 0;
+    let mut change_ids = HashSet::new();
+    let mut author_counts: HashMap<(String, String), usize> = HashMap::new();
+    for commit in revset.iter().commits(repo.store()) {
+        let commit = commit?;
+        num_commits += 1;
+        if commit.parent_ids().len() > 1 {
+            num_merges += 1;
+        }
+        change_ids.insert(commit.change_id().clone());
+        let author = commit.author();
+        *author_counts
+            .entry((author.name.clone(), author.email.clone()))
+            .or_default() += 1;
+    }
+    let authors = author_counts
+        .into_iter()
+        .map(|((name, email), commits)| AuthorStats {
+            name,
+            email,
+            commits,
+        })
+        .sorted_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.email.cmp(&b.email))
+        })
+        .collect_vec();
+
+    // The sizes are disjoint: the repo size excludes the store directory, which
+    // contains the backing Git repo unless the repo is colocated or was
+    // initialized with an external Git repo.
+    let store_path = repo.repo_path().join("store");
+    let mut store_size = dir_size(&store_path)?;
+    let repo_size = dir_size(repo.repo_path())? - store_size;
+    if let Some(git_backend) = repo.store().backend_impl().downcast_ref::<GitBackend>() {
+        let git_repo_path = git_backend.git_repo().path().canonicalize()?;
+        if !git_repo_path.starts_with(store_path.canonicalize()?) {
+            store_size += dir_size(&git_repo_path)?;
+        }
+    }
+
+    let index_impl: Option<&ReadonlyIndexWrapper> = repo.readonly_index().as_any().downcast_ref();
+    let index = index_impl.map(|index_impl| {
+        let stats = index_impl.as_composite().stats();
+        IndexStats {
+            commits: stats.num_commits,
+            levels: stats.levels.len(),
+        }
+    });
+
+    let stats = RepoStats {
+        commits: num_commits,
+        changes: change_ids.len(),
+        merges: num_merges,
+        authors,
+        repo_size,
+        store_size,
+        index,
+    };
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&stats)
+            .map_err(|err| user_error(format!("Failed to serialize statistics: {err}")))?;
+        writeln!(ui, "{json}")?;
+        return Ok(());
+    }
+
+    if let Some(template_text) = &args.template {
+        let template =
+            stats_templater::parse(template_text, workspace_command.template_aliases_map())?;
+        ui.request_pager();
+        let mut formatter = ui.stdout_formatter();
+        formatter.with_label("stats", |formatter| template.format(&stats, formatter))?;
+        return Ok(());
+    }
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let formatter = formatter.as_mut();
+    writeln!(formatter, "Commits: {}", stats.commits)?;
+    writeln!(formatter, "Changes: {}", stats.changes)?;
+    writeln!(formatter, "Merges: {}", stats.merges)?;
+    writeln!(formatter, "Authors:")?;
+    for author in &stats.authors {
+        writeln!(
+            formatter,
+            "{:>6}  {} <{}>",
+            author.commits, author.name, author.email
+        )?;
+    }
+    writeln!(formatter, "Repo size: {}", HumanByteSize(stats.repo_size))?;
+    writeln!(formatter, "Store size: {}", HumanByteSize(stats.store_size))?;
+    if let Some(index) = &stats.index {
+        writeln!(
+            formatter,
+            "Index: {} commits in {} levels",
+            index.commits, index.levels
+        )?;
+    }
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
@@ -12,39 +12,63 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::RefCell;
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io;
+use std::ops::Deref;
 use std::rc::Rc;
 
 use itertools::Itertools as _;
-use jj_lib::backend::{ChangeId, CommitId, ObjectId as _};
+use jj_lib::backend::{BackendResult, ChangeId, CommitId, ObjectId as _, TreeValue};
 use jj_lib::commit::Commit;
+use jj_lib::git_backend::GitBackend;
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::op_store::{RefTarget, WorkspaceId};
+use jj_lib::operation::{self, Operation};
 use jj_lib::repo::Repo;
+use jj_lib::revset::{
+    self, DefaultSymbolResolver, PrefixResolver, Revset, RevsetEvaluationError, RevsetExpression,
+    RevsetParseContext,
+};
+use jj_lib::signing::{SecureSig, SigStatus, SshVerifier, Verification};
+use jj_lib::trailer::{self, Trailer};
 use jj_lib::{git, rewrite};
 use once_cell::unsync::OnceCell;
+use regex::Regex;
 
+use crate::cli_util::{commits_added_by_operation, short_operation_hash};
+use crate::diff_util::DiffStats;
 use crate::formatter::Formatter;
 use crate::template_builder::{
-    self, BuildContext, CoreTemplatePropertyKind, IntoTemplateProperty, TemplateLanguage,
+    self, AvailableWidth, BuildContext, CoreTemplatePropertyKind, IntoTemplateProperty,
+    TemplateLanguage,
 };
 use crate::template_parser::{
-    self, FunctionCallNode, TemplateAliasesMap, TemplateParseError, TemplateParseResult,
+    self, ExpressionNode, FunctionCallNode, TemplateAliasesMap, TemplateParseError,
+    TemplateParseResult,
 };
 use crate::templater::{
-    IntoTemplate, PlainTextFormattedProperty, Template, TemplateFunction, TemplateProperty,
-    TemplatePropertyFn,
+    self, IntoTemplate, Literal, PlainTextFormattedProperty, Template, TemplateFunction,
+    TemplateProperty, TemplatePropertyFn,
 };
 use crate::text_util;
 
 struct CommitTemplateLanguage<'repo, 'b> {
     repo: &'repo dyn Repo,
+    operation: &'repo Operation,
     workspace_id: &'b WorkspaceId,
+    revset_parse_context: RevsetParseContext<'b>,
     id_prefix_context: &'repo IdPrefixContext,
-    keyword_cache: CommitKeywordCache,
+    hyperlink_patterns: Rc<HyperlinkPatterns>,
+    description_labels: Rc<DescriptionLabels>,
+    signature_verifier: Rc<SignatureVerifier>,
+    available_width: AvailableWidth,
+    config: &'b config::Config,
+    keyword_cache: CommitKeywordCache<'repo>,
 }
 
 impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo, '_> {
@@ -53,10 +77,30 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo, '_> {
 
     template_builder::impl_core_wrap_property_fns!('repo, CommitTemplatePropertyKind::Core);
 
+    fn available_width(&self) -> Option<&AvailableWidth> {
+        Some(&self.available_width)
+    }
+
+    fn config(&self) -> Option<&config::Config> {
+        Some(self.config)
+    }
+
     fn build_keyword(&self, name: &str, span: pest::Span) -> TemplateParseResult<Self::Property> {
         build_commit_keyword(self, name, span)
     }
 
+    fn build_keyword_labels(&self, name: &str) -> Option<Self::Property> {
+        match name {
+            "description" if !self.description_labels.is_empty() => {
+                let description_labels = self.description_labels.clone();
+                Some(self.wrap_string(TemplatePropertyFn(move |commit: &Commit| {
+                    description_labels.matching(commit.description()).join(" ")
+                })))
+            }
+            _ => None,
+        }
+    }
+
     fn build_method(
         &self,
         build_ctx: &BuildContext<Self::Property>,
@@ -77,6 +121,7 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo, '_> {
                     property,
                     function,
                     |item| self.wrap_commit(item),
+                    |property| self.wrap_commit_list(property),
                 )
             }
             CommitTemplatePropertyKind::CommitOrChangeId(property) => {
@@ -85,6 +130,115 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo, '_> {
             CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
                 build_shortest_id_prefix_method(self, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::Tree(property) => {
+                build_tree_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TreeInteger(_) => {
+                Err(TemplateParseError::no_such_method("Integer", function))
+            }
+            CommitTemplatePropertyKind::TreeDiff(property) => {
+                build_tree_diff_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::DiffStats(property) => {
+                build_diff_stats_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::ChangedFile(property) => {
+                build_changed_file_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::RefName(property) => {
+                build_ref_name_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => match function.name {
+                "with_distance" => {
+                    template_parser::expect_no_arguments(function)?;
+                    let index = self
+                        .keyword_cache
+                        .branch_distances_index(self.repo)
+                        .map_err(|err| {
+                            TemplateParseError::unexpected_expression(
+                                format!("Failed to evaluate revset: {err}"),
+                                function.name_span,
+                            )
+                        })?
+                        .clone();
+                    Ok(
+                        self.wrap_ref_name_list(TemplateFunction::new(property, move |names| {
+                            names
+                                .into_iter()
+                                .map(|name| with_branch_distance(name, &index))
+                                .collect()
+                        })),
+                    )
+                }
+                "len" | "join" | "first" | "filter" | "map" => {
+                    template_builder::build_formattable_list_method(
+                        self,
+                        build_ctx,
+                        property,
+                        function,
+                        |item| self.wrap_ref_name(item),
+                        |property| self.wrap_ref_name_list(property),
+                    )
+                }
+                _ => {
+                    // Other methods are inherited from the String type for
+                    // compatibility with the space-separated ref names.
+                    let property = CoreTemplatePropertyKind::String(Box::new(
+                        TemplateFunction::new(property, |names| format_ref_names(&names)),
+                    ));
+                    template_builder::build_core_method(self, build_ctx, property, function)
+                }
+            },
+            CommitTemplatePropertyKind::Trailer(property) => {
+                build_trailer_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TrailerList(property) => {
+                build_trailer_list_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::CryptographicSignature(property) => {
+                build_cryptographic_signature_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::SignatureStatus(property) => {
+                // Methods are inherited from the String type.
+                let property = CoreTemplatePropertyKind::String(Box::new(TemplateFunction::new(
+                    property,
+                    |status| status.as_str().to_owned(),
+                )));
+                template_builder::build_core_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::ChangedFileList(property) => {
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    |item| self.wrap_changed_file(item),
+                    |property| self.wrap_changed_file_list(property),
+                )
+            }
+            CommitTemplatePropertyKind::WorkspaceRef(property) => {
+                build_workspace_ref_method(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::WorkspaceRefList(property) => match function.name {
+                "len" | "join" | "first" | "filter" | "map" => {
+                    template_builder::build_formattable_list_method(
+                        self,
+                        build_ctx,
+                        property,
+                        function,
+                        |item| self.wrap_workspace_ref(item),
+                        |property| self.wrap_workspace_ref_list(property),
+                    )
+                }
+                _ => {
+                    // Other methods are inherited from the String type for
+                    // compatibility with the space-separated workspace names.
+                    let property = CoreTemplatePropertyKind::String(Box::new(
+                        TemplateFunction::new(property, |refs| format_workspace_refs(&refs)),
+                    ));
+                    template_builder::build_core_method(self, build_ctx, property, function)
+                }
+            },
         }
     }
 }
@@ -119,6 +273,104 @@ impl<'repo> CommitTemplateLanguage<'repo, '_> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::ShortestIdPrefix(Box::new(property))
     }
+
+    fn wrap_ref_name(
+        &self,
+        property: impl TemplateProperty<Commit, Output = RefName> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RefName(Box::new(property))
+    }
+
+    fn wrap_ref_name_list(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Vec<RefName>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::RefNameList(Box::new(property))
+    }
+
+    fn wrap_tree(
+        &self,
+        property: impl TemplateProperty<Commit, Output = BackendResult<MergedTree>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Tree(Box::new(property))
+    }
+
+    fn wrap_tree_integer(
+        &self,
+        property: impl TemplateProperty<Commit, Output = BackendResult<i64>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeInteger(Box::new(property))
+    }
+
+    fn wrap_tree_diff(
+        &self,
+        property: impl TemplateProperty<Commit, Output = TreeDiff> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeDiff(Box::new(property))
+    }
+
+    fn wrap_diff_stats(
+        &self,
+        property: impl TemplateProperty<Commit, Output = DiffStats> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::DiffStats(Box::new(property))
+    }
+
+    fn wrap_changed_file(
+        &self,
+        property: impl TemplateProperty<Commit, Output = ChangedFile> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::ChangedFile(Box::new(property))
+    }
+
+    fn wrap_changed_file_list(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Vec<ChangedFile>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::ChangedFileList(Box::new(property))
+    }
+
+    fn wrap_trailer(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Trailer> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Trailer(Box::new(property))
+    }
+
+    fn wrap_trailer_list(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Vec<Trailer>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TrailerList(Box::new(property))
+    }
+
+    fn wrap_cryptographic_signature(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Option<CryptographicSignature>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::CryptographicSignature(Box::new(property))
+    }
+
+    fn wrap_signature_status(
+        &self,
+        property: impl TemplateProperty<Commit, Output = SignatureStatus> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::SignatureStatus(Box::new(property))
+    }
+
+    fn wrap_workspace_ref(
+        &self,
+        property: impl TemplateProperty<Commit, Output = WorkspaceRef> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::WorkspaceRef(Box::new(property))
+    }
+
+    fn wrap_workspace_ref_list(
+        &self,
+        property: impl TemplateProperty<Commit, Output = Vec<WorkspaceRef>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::WorkspaceRefList(Box::new(property))
+    }
 }
 
 enum CommitTemplatePropertyKind<'repo> {
@@ -127,12 +379,71 @@ enum CommitTemplatePropertyKind<'repo> {
     CommitList(Box<dyn TemplateProperty<Commit, Output = Vec<Commit>> + 'repo>),
     CommitOrChangeId(Box<dyn TemplateProperty<Commit, Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Commit, Output = ShortestIdPrefix> + 'repo>),
+    RefName(Box<dyn TemplateProperty<Commit, Output = RefName> + 'repo>),
+    RefNameList(Box<dyn TemplateProperty<Commit, Output = Vec<RefName>> + 'repo>),
+    Tree(Box<dyn TemplateProperty<Commit, Output = BackendResult<MergedTree>> + 'repo>),
+    /// Integer computed from a tree, which fails if the tree can't be read.
+    TreeInteger(Box<dyn TemplateProperty<Commit, Output = BackendResult<i64>> + 'repo>),
+    TreeDiff(Box<dyn TemplateProperty<Commit, Output = TreeDiff> + 'repo>),
+    DiffStats(Box<dyn TemplateProperty<Commit, Output = DiffStats> + 'repo>),
+    ChangedFile(Box<dyn TemplateProperty<Commit, Output = ChangedFile> + 'repo>),
+    ChangedFileList(Box<dyn TemplateProperty<Commit, Output = Vec<ChangedFile>> + 'repo>),
+    Trailer(Box<dyn TemplateProperty<Commit, Output = Trailer> + 'repo>),
+    TrailerList(Box<dyn TemplateProperty<Commit, Output = Vec<Trailer>> + 'repo>),
+    CryptographicSignature(
+        Box<dyn TemplateProperty<Commit, Output = Option<CryptographicSignature>> + 'repo>,
+    ),
+    SignatureStatus(Box<dyn TemplateProperty<Commit, Output = SignatureStatus> + 'repo>),
+    WorkspaceRef(Box<dyn TemplateProperty<Commit, Output = WorkspaceRef> + 'repo>),
+    WorkspaceRefList(Box<dyn TemplateProperty<Commit, Output = Vec<WorkspaceRef>> + 'repo>),
+}
+
+impl CommitTemplatePropertyKind<'_> {
+    /// Name of the type as it appears in the documentation.
+    fn type_name(&self) -> &'static str {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.type_name(),
+            CommitTemplatePropertyKind::Commit(_) => "Commit",
+            CommitTemplatePropertyKind::CommitList(_) => "List<Commit>",
+            CommitTemplatePropertyKind::CommitOrChangeId(_) => "CommitOrChangeId",
+            CommitTemplatePropertyKind::ShortestIdPrefix(_) => "ShortestIdPrefix",
+            CommitTemplatePropertyKind::RefName(_) => "RefName",
+            CommitTemplatePropertyKind::RefNameList(_) => "List<RefName>",
+            CommitTemplatePropertyKind::Tree(_) => "Tree",
+            CommitTemplatePropertyKind::TreeInteger(_) => "Integer",
+            CommitTemplatePropertyKind::TreeDiff(_) => "TreeDiff",
+            CommitTemplatePropertyKind::DiffStats(_) => "DiffStats",
+            CommitTemplatePropertyKind::ChangedFile(_) => "ChangedFile",
+            CommitTemplatePropertyKind::ChangedFileList(_) => "List<ChangedFile>",
+            CommitTemplatePropertyKind::Trailer(_) => "Trailer",
+            CommitTemplatePropertyKind::TrailerList(_) => "List<Trailer>",
+            CommitTemplatePropertyKind::CryptographicSignature(_) => "CryptographicSignature",
+            CommitTemplatePropertyKind::SignatureStatus(_) => "SignatureStatus",
+            CommitTemplatePropertyKind::WorkspaceRef(_) => "WorkspaceRef",
+            CommitTemplatePropertyKind::WorkspaceRefList(_) => "List<WorkspaceRef>",
+        }
+    }
 }
 
 impl<'repo> IntoTemplateProperty<'repo, Commit> for CommitTemplatePropertyKind<'repo> {
     fn try_into_boolean(self) -> Option<Box<dyn TemplateProperty<Commit, Output = bool> + 'repo>> {
         match self {
             CommitTemplatePropertyKind::Core(property) => property.try_into_boolean(),
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |names| {
+                    !names.is_empty()
+                })))
+            }
+            CommitTemplatePropertyKind::CryptographicSignature(property) => {
+                Some(Box::new(TemplateFunction::new(property, |sig| {
+                    sig.is_some()
+                })))
+            }
+            CommitTemplatePropertyKind::WorkspaceRefList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |refs| {
+                    !refs.is_empty()
+                })))
+            }
             // TODO: should we allow implicit cast of List type?
             _ => None,
         }
@@ -150,6 +461,26 @@ impl<'repo> IntoTemplateProperty<'repo, Commit> for CommitTemplatePropertyKind<'
     ) -> Option<Box<dyn TemplateProperty<Commit, Output = String> + 'repo>> {
         match self {
             CommitTemplatePropertyKind::Core(property) => property.try_into_plain_text(),
+            CommitTemplatePropertyKind::RefName(property) => {
+                Some(Box::new(TemplateFunction::new(property, |name| {
+                    name.decorated_name
+                })))
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |names| {
+                    format_ref_names(&names)
+                })))
+            }
+            CommitTemplatePropertyKind::SignatureStatus(property) => {
+                Some(Box::new(TemplateFunction::new(property, |status| {
+                    status.as_str().to_owned()
+                })))
+            }
+            CommitTemplatePropertyKind::WorkspaceRefList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |refs| {
+                    format_workspace_refs(&refs)
+                })))
+            }
             _ => {
                 let template = self.try_into_template()?;
                 Some(Box::new(PlainTextFormattedProperty::new(template)))
@@ -168,32 +499,110 @@ impl<'repo> IntoTemplateProperty<'repo, Commit> for CommitTemplatePropertyKind<'
             CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
                 Some(property.into_template())
             }
+            CommitTemplatePropertyKind::RefName(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::RefNameList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::Tree(_) => None,
+            CommitTemplatePropertyKind::TreeInteger(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::ChangedFile(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::ChangedFileList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::CryptographicSignature(_) => None,
+            CommitTemplatePropertyKind::SignatureStatus(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::WorkspaceRef(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::WorkspaceRefList(property) => {
+                Some(property.into_template())
+            }
         }
     }
 }
 
-#[derive(Debug, Default)]
-struct CommitKeywordCache {
-    // Build index lazily, and Rc to get away from &self lifetime.
-    branches_index: OnceCell<Rc<RefNamesIndex>>,
-    tags_index: OnceCell<Rc<RefNamesIndex>>,
-    git_refs_index: OnceCell<Rc<RefNamesIndex>>,
+/// Index which is built when a property using it is first evaluated, so
+/// templates that reference a keyword but never evaluate it (e.g. in an
+/// untaken `if()` branch, or for an empty revset) don't pay for it.
+struct LazyIndex<'repo, T> {
+    cell: OnceCell<T>,
+    build: Box<dyn Fn() -> T + 'repo>,
+}
+
+impl<'repo, T> LazyIndex<'repo, T> {
+    fn new(build: impl Fn() -> T + 'repo) -> Rc<Self> {
+        Rc::new(LazyIndex {
+            cell: OnceCell::new(),
+            build: Box::new(build),
+        })
+    }
+}
+
+impl<T> Deref for LazyIndex<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.cell.get_or_init(&self.build)
+    }
+}
+
+#[derive(Default)]
+struct CommitKeywordCache<'repo> {
+    // Shared by the keywords using the same index, and Rc to get away from
+    // &self lifetime.
+    branches_index: OnceCell<Rc<LazyIndex<'repo, RefNamesIndex>>>,
+    tags_index: OnceCell<Rc<LazyIndex<'repo, RefNamesIndex>>>,
+    git_refs_index: OnceCell<Rc<LazyIndex<'repo, RefNamesIndex>>>,
+    branch_distances_index: OnceCell<Rc<LazyIndex<'repo, BranchDistancesIndex>>>,
+    operations_index: OnceCell<Rc<LazyIndex<'repo, OperationsIndex>>>,
+    children_index: OnceCell<Rc<LazyIndex<'repo, ChildrenIndex>>>,
+    publish_state_index: OnceCell<Rc<LazyIndex<'repo, PublishStateIndex>>>,
 }
 
-impl CommitKeywordCache {
-    fn branches_index(&self, repo: &dyn Repo) -> &Rc<RefNamesIndex> {
+impl<'repo> CommitKeywordCache<'repo> {
+    fn branches_index(&self, repo: &'repo dyn Repo) -> &Rc<LazyIndex<'repo, RefNamesIndex>> {
         self.branches_index
-            .get_or_init(|| Rc::new(build_branches_index(repo)))
+            .get_or_init(|| LazyIndex::new(move || build_branches_index(repo)))
     }
 
-    fn tags_index(&self, repo: &dyn Repo) -> &Rc<RefNamesIndex> {
+    fn tags_index(&self, repo: &'repo dyn Repo) -> &Rc<LazyIndex<'repo, RefNamesIndex>> {
         self.tags_index
-            .get_or_init(|| Rc::new(build_ref_names_index(repo.view().tags())))
+            .get_or_init(|| LazyIndex::new(move || build_ref_names_index(repo.view().tags())))
     }
 
-    fn git_refs_index(&self, repo: &dyn Repo) -> &Rc<RefNamesIndex> {
+    fn git_refs_index(&self, repo: &'repo dyn Repo) -> &Rc<LazyIndex<'repo, RefNamesIndex>> {
         self.git_refs_index
-            .get_or_init(|| Rc::new(build_ref_names_index(repo.view().git_refs())))
+            .get_or_init(|| LazyIndex::new(move || build_ref_names_index(repo.view().git_refs())))
+    }
+
+    fn branch_distances_index(
+        &self,
+        repo: &'repo dyn Repo,
+    ) -> Result<&Rc<LazyIndex<'repo, BranchDistancesIndex>>, RevsetEvaluationError> {
+        self.branch_distances_index
+            .get_or_try_init(|| build_branch_distances_index(repo))
+    }
+
+    fn operations_index(
+        &self,
+        repo: &'repo dyn Repo,
+        operation: &'repo Operation,
+    ) -> &Rc<LazyIndex<'repo, OperationsIndex>> {
+        self.operations_index
+            .get_or_init(|| LazyIndex::new(move || build_operations_index(repo, operation)))
+    }
+
+    fn children_index(&self, repo: &'repo dyn Repo) -> &Rc<LazyIndex<'repo, ChildrenIndex>> {
+        self.children_index
+            .get_or_init(|| LazyIndex::new(move || build_children_index(repo)))
+    }
+
+    fn publish_state_index(
+        &self,
+        repo: &'repo dyn Repo,
+        revset_parse_context: &RevsetParseContext,
+        span: pest::Span<'_>,
+    ) -> TemplateParseResult<&Rc<LazyIndex<'repo, PublishStateIndex>>> {
+        self.publish_state_index
+            .get_or_try_init(|| build_publish_state_index(repo, revset_parse_context, span))
     }
 }
 
@@ -208,16 +617,70 @@ fn build_commit_keyword<'repo>(
     // maybe we can add an abstraction that takes "Fn(&Commit) -> O" and returns
     // "TemplateProperty<Commit, Output = O>".
     let property = TemplatePropertyFn(|commit: &Commit| commit.clone());
+    if name == "self" {
+        return Ok(language.wrap_commit(property));
+    }
+    if name == "publish_state" {
+        // Evaluating the revsets may fail, which is reported at the keyword.
+        let index = language
+            .keyword_cache
+            .publish_state_index(language.repo, &language.revset_parse_context, span)?
+            .clone();
+        let property =
+            TemplateFunction::new(property, move |commit| index.get(commit.id()).to_owned());
+        return Ok(language.wrap_string(property));
+    }
+    if let Some(property) = build_repo_keyword_opt(language, name) {
+        return Ok(property);
+    }
     build_commit_keyword_opt(language, property, name)
         .ok_or_else(|| TemplateParseError::no_such_keyword(name, span))
 }
 
+/// Builds a keyword that doesn't depend on the commit, but on the state of the
+/// repo and workspace the template is evaluated in.
+fn build_repo_keyword_opt<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    name: &str,
+) -> Option<CommitTemplatePropertyKind<'repo>> {
+    let property = match name {
+        "current_operation_id" => {
+            let id = short_operation_hash(language.operation.id());
+            language.wrap_string(Literal(id))
+        }
+        "current_workspace" => {
+            let workspace_id = language.workspace_id.as_str().to_owned();
+            language.wrap_string(Literal(workspace_id))
+        }
+        "workspace_root" => {
+            let path = match &language.revset_parse_context.workspace {
+                Some(workspace) => workspace.workspace_root.display().to_string(),
+                None => String::new(),
+            };
+            language.wrap_string(Literal(path))
+        }
+        _ => return None,
+    };
+    Some(property)
+}
+
 fn build_commit_method<'repo>(
     language: &CommitTemplateLanguage<'repo, '_>,
     _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
     self_property: impl TemplateProperty<Commit, Output = Commit> + 'repo,
     function: &FunctionCallNode,
 ) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    if function.name == "contained_in" {
+        let [revset_node] = template_parser::expect_exact_arguments(function)?;
+        let commit_ids = template_parser::expect_string_literal_with(revset_node, |text, span| {
+            evaluate_user_revset(language, text, span)
+        })?;
+        return Ok(
+            language.wrap_boolean(TemplateFunction::new(self_property, move |commit| {
+                commit_ids.contains(commit.id())
+            })),
+        );
+    }
     if let Some(property) = build_commit_keyword_opt(language, self_property, function.name) {
         template_parser::expect_no_arguments(function)?;
         Ok(property)
@@ -226,6 +689,41 @@ fn build_commit_method<'repo>(
     }
 }
 
+/// Evaluates the user revset once at template build time, and returns the set
+/// of the matching commit ids.
+fn evaluate_user_revset(
+    language: &CommitTemplateLanguage<'_, '_>,
+    revset_text: &str,
+    span: pest::Span<'_>,
+) -> TemplateParseResult<HashSet<CommitId>> {
+    let repo = language.repo;
+    let id_prefix_context = language.id_prefix_context;
+    let expression = revset::parse(revset_text, &language.revset_parse_context).map_err(|err| {
+        TemplateParseError::unexpected_expression(format!("Invalid revset: {err}"), span)
+    })?;
+    let commit_id_resolver: PrefixResolver<CommitId> =
+        Box::new(|repo, prefix| id_prefix_context.resolve_commit_prefix(repo, prefix));
+    let change_id_resolver: PrefixResolver<Vec<CommitId>> =
+        Box::new(|repo, prefix| id_prefix_context.resolve_change_prefix(repo, prefix));
+    let symbol_resolver = DefaultSymbolResolver::new(repo)
+        .with_commit_id_resolver(commit_id_resolver)
+        .with_change_id_resolver(change_id_resolver);
+    let revset = revset::optimize(expression)
+        .resolve_user_expression(repo, &symbol_resolver)
+        .map_err(|err| {
+            TemplateParseError::unexpected_expression(format!("Invalid revset: {err}"), span)
+        })?
+        .evaluate(repo)
+        .map_err(|err| {
+            TemplateParseError::unexpected_expression(
+                format!("Failed to evaluate revset: {err}"),
+                span,
+            )
+        })?;
+    let commit_ids = revset.iter().collect();
+    Ok(commit_ids)
+}
+
 fn build_commit_keyword_opt<'repo>(
     language: &CommitTemplateLanguage<'repo, '_>,
     property: impl TemplateProperty<Commit, Output = Commit> + 'repo,
@@ -258,12 +756,35 @@ fn build_commit_keyword_opt<'repo>(
             CommitOrChangeId::Commit(commit.id().to_owned())
         })),
         "parents" => language.wrap_commit_list(wrap_fn(property, |commit| commit.parents())),
+        "children" => {
+            let index = cache.children_index(repo).clone();
+            language.wrap_commit_list(wrap_fn(property, move |commit| {
+                index
+                    .get(commit.id())
+                    .map(|ids| {
+                        ids.iter()
+                            .map(|id| repo.store().get_commit(id).unwrap())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }))
+        }
+        "predecessors" => {
+            language.wrap_commit_list(wrap_fn(property, |commit| commit.predecessors()))
+        }
         "author" => language.wrap_signature(wrap_fn(property, |commit| commit.author().clone())),
         "committer" => {
             language.wrap_signature(wrap_fn(property, |commit| commit.committer().clone()))
         }
+        "is_committer_same_as_author" => language.wrap_boolean(wrap_fn(property, |commit| {
+            let (author, committer) = (commit.author(), commit.committer());
+            author.name == committer.name && author.email == committer.email
+        })),
         "working_copies" => {
-            language.wrap_string(wrap_repo_fn(repo, property, extract_working_copies))
+            let workspace_id = language.workspace_id.clone();
+            language.wrap_workspace_ref_list(wrap_fn(property, move |commit| {
+                extract_working_copies(repo, commit, &workspace_id)
+            }))
         }
         "current_working_copy" => {
             let workspace_id = language.workspace_id.clone();
@@ -273,23 +794,53 @@ fn build_commit_keyword_opt<'repo>(
         }
         "branches" => {
             let index = cache.branches_index(repo).clone();
+            let hyperlink_patterns = language.hyperlink_patterns.clone();
+            language.wrap_ref_name_list(wrap_fn(property, move |commit| {
+                index
+                    .get(commit.id())
+                    .iter()
+                    .map(|name| RefName {
+                        url: hyperlink_patterns.branch_url(&name.name),
+                        ..name.clone()
+                    })
+                    .collect()
+            }))
+        }
+        "operation_id" => {
+            let index = cache.operations_index(repo, language.operation).clone();
+            language.wrap_string(wrap_fn(property, move |commit| {
+                index
+                    .get(commit.id())
+                    .map(|op| short_operation_hash(op.id()))
+                    .unwrap_or_default()
+            }))
+        }
+        "operation_description" => {
+            let index = cache.operations_index(repo, language.operation).clone();
             language.wrap_string(wrap_fn(property, move |commit| {
-                index.get(commit.id()).join(" ") // TODO: return Vec<Branch>?
+                index
+                    .get(commit.id())
+                    .map(|op| op.store_operation().metadata.description.clone())
+                    .unwrap_or_default()
             }))
         }
         "tags" => {
             let index = cache.tags_index(repo).clone();
-            language.wrap_string(wrap_fn(property, move |commit| {
-                index.get(commit.id()).join(" ") // TODO: return Vec<NameRef>?
+            language.wrap_ref_name_list(wrap_fn(property, move |commit| {
+                index.get(commit.id()).to_vec()
             }))
         }
         "git_refs" => {
             let index = cache.git_refs_index(repo).clone();
-            language.wrap_string(wrap_fn(property, move |commit| {
-                index.get(commit.id()).join(" ") // TODO: return Vec<NameRef>?
+            language.wrap_ref_name_list(wrap_fn(property, move |commit| {
+                index.get(commit.id()).to_vec()
             }))
         }
         "git_head" => language.wrap_string(wrap_repo_fn(repo, property, extract_git_head)),
+        "note" => language.wrap_string(wrap_repo_fn(repo, property, |repo, commit| {
+            let note = repo.view().get_note(commit.change_id()).unwrap_or_default();
+            text_util::complete_newline(note)
+        })),
         "divergent" => language.wrap_boolean(wrap_fn(property, |commit| {
             // The given commit could be hidden in e.g. obslog.
             let maybe_entries = repo.resolve_change_id(commit.change_id());
@@ -312,42 +863,178 @@ fn build_commit_keyword_opt<'repo>(
         "root" => language.wrap_boolean(wrap_fn(property, move |commit| {
             commit.id() == repo.store().root_commit_id()
         })),
+        "tree" => language.wrap_tree(wrap_fn(property, |commit| commit.tree())),
+        "diff" => language.wrap_tree_diff(wrap_fn(property, |commit| {
+            TreeDiff::from_commit(repo, commit)
+        })),
+        "files" => language.wrap_changed_file_list(wrap_fn(property, |commit| {
+            TreeDiff::from_commit(repo, commit).changed_files()
+        })),
+        "trailers" => language.wrap_trailer_list(wrap_fn(property, |commit| {
+            trailer::parse_description_trailers(commit.description())
+        })),
+        "signature" => {
+            let verifier = language.signature_verifier.clone();
+            language.wrap_cryptographic_signature(wrap_fn(property, move |commit| {
+                read_cryptographic_signature(repo, commit, &verifier)
+            }))
+        }
+        "signature_status" => {
+            let verifier = language.signature_verifier.clone();
+            language.wrap_signature_status(wrap_fn(property, move |commit| {
+                let sig = read_cryptographic_signature(repo, commit, &verifier);
+                SignatureStatus(sig.map(|sig| sig.verify().status))
+            }))
+        }
         _ => return None,
     };
     Some(property)
 }
 
-// TODO: return Vec<String>
-fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
+fn extract_working_copies(
+    repo: &dyn Repo,
+    commit: &Commit,
+    current_workspace_id: &WorkspaceId,
+) -> Vec<WorkspaceRef> {
     let wc_commit_ids = repo.view().wc_commit_ids();
     if wc_commit_ids.len() <= 1 {
-        return "".to_string();
+        return vec![];
+    }
+    wc_commit_ids
+        .iter()
+        .filter(|(_, wc_commit_id)| *wc_commit_id == commit.id())
+        .map(|(workspace_id, _)| WorkspaceRef {
+            name: workspace_id.as_str().to_owned(),
+            current: workspace_id == current_workspace_id,
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect()
+}
+
+/// Workspace whose working-copy commit is the commit.
+#[derive(Clone, Debug)]
+struct WorkspaceRef {
+    name: String,
+    /// Whether this is the workspace the command is run in.
+    current: bool,
+}
+
+impl Template<()> for WorkspaceRef {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}@", self.name)
     }
-    let mut names = vec![];
-    for (workspace_id, wc_commit_id) in wc_commit_ids.iter().sorted() {
-        if wc_commit_id == commit.id() {
-            names.push(format!("{}@", workspace_id.as_str()));
+}
+
+impl Template<()> for Vec<WorkspaceRef> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, " ")
+    }
+}
+
+fn format_workspace_refs(refs: &[WorkspaceRef]) -> String {
+    refs.iter().map(|r| format!("{}@", r.name)).join(" ")
+}
+
+fn build_workspace_ref_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = WorkspaceRef> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "name" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |r| r.name))
+        }
+        "current" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_boolean(TemplateFunction::new(self_property, |r| r.current))
+        }
+        _ => return Err(TemplateParseError::no_such_method("WorkspaceRef", function)),
+    };
+    Ok(property)
+}
+
+/// Branch, tag, or Git ref name pointing to a commit.
+#[derive(Clone, Debug)]
+struct RefName {
+    name: String,
+    /// The remote name if this is a remote branch.
+    remote: Option<String>,
+    /// The name with the remote name and conflict or sync markers, e.g.
+    /// `main@origin`.
+    decorated_name: String,
+    /// The URL the name is printed as a hyperlink to.
+    url: Option<String>,
+}
+
+impl Template<()> for RefName {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        match &self.url {
+            Some(url) => formatter.with_hyperlink(url, |fmt| fmt.write_str(&self.decorated_name)),
+            None => formatter.write_str(&self.decorated_name),
         }
     }
-    names.join(" ")
+}
+
+impl Template<()> for Vec<RefName> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, " ")
+    }
+}
+
+fn format_ref_names(names: &[RefName]) -> String {
+    names.iter().map(|name| &name.decorated_name).join(" ")
+}
+
+fn build_ref_name_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = RefName> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "name" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |name| name.name))
+        }
+        "remote" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |name| {
+                name.remote.unwrap_or_default()
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("RefName", function)),
+    };
+    Ok(property)
 }
 
 /// Cache for reverse lookup refs.
 #[derive(Clone, Debug, Default)]
 struct RefNamesIndex {
-    // TODO: store Branch/NameRef type in place of String?
-    index: HashMap<CommitId, Vec<String>>,
+    index: HashMap<CommitId, Vec<RefName>>,
 }
 
 impl RefNamesIndex {
-    fn insert<'a>(&mut self, ids: impl IntoIterator<Item = &'a CommitId>, name: String) {
+    fn insert<'a>(
+        &mut self,
+        ids: impl IntoIterator<Item = &'a CommitId>,
+        name: &str,
+        remote: Option<&str>,
+        decorated_name: String,
+    ) {
         for id in ids {
             let ref_names = self.index.entry(id.clone()).or_default();
-            ref_names.push(name.clone());
+            ref_names.push(RefName {
+                name: name.to_owned(),
+                remote: remote.map(|remote| remote.to_owned()),
+                decorated_name: decorated_name.clone(),
+                url: None,
+            });
         }
     }
 
-    fn get(&self, id: &CommitId) -> &[String] {
+    fn get(&self, id: &CommitId) -> &[RefName] {
         if let Some(names) = self.index.get(id) {
             names
         } else {
@@ -356,6 +1043,116 @@ impl RefNamesIndex {
     }
 }
 
+type OperationsIndex = HashMap<CommitId, Operation>;
+
+/// Maps each commit to the operation that added it, walking the operation log
+/// back from `operation`. If a commit was added more than once, e.g. by undoing
+/// and redoing an operation, the latest operation wins.
+///
+/// The walk stops once all visible commits are attributed, so hidden commits
+/// added by older operations aren't indexed.
+fn build_operations_index(repo: &dyn Repo, operation: &Operation) -> OperationsIndex {
+    let mut index = HashMap::new();
+    // Templates can't report errors, and evaluating "all()" shouldn't fail.
+    let mut unattributed: HashSet<CommitId> = RevsetExpression::all()
+        .resolve(repo)
+        .unwrap()
+        .evaluate(repo)
+        .unwrap()
+        .iter()
+        .collect();
+    for op in operation::walk_ancestors(operation) {
+        if unattributed.is_empty() {
+            break;
+        }
+        // Operations whose views can't be read are skipped since the template
+        // can't report errors.
+        let Ok(commit_ids) = commits_added_by_operation(repo, &op) else {
+            continue;
+        };
+        for commit_id in commit_ids {
+            unattributed.remove(&commit_id);
+            index.entry(commit_id).or_insert_with(|| op.clone());
+        }
+    }
+    index
+}
+
+type ChildrenIndex = HashMap<CommitId, Vec<CommitId>>;
+
+/// Maps each visible commit to its visible children, newest first.
+fn build_children_index(repo: &dyn Repo) -> ChildrenIndex {
+    let mut index: HashMap<CommitId, Vec<CommitId>> = HashMap::new();
+    // Templates can't report errors, and evaluating "all()" shouldn't fail.
+    let revset = RevsetExpression::all()
+        .resolve(repo)
+        .unwrap()
+        .evaluate(repo)
+        .unwrap();
+    for (commit_id, edges) in revset.iter_graph() {
+        for edge in edges {
+            index
+                .entry(edge.target)
+                .or_default()
+                .push(commit_id.clone());
+        }
+    }
+    index
+}
+
+/// Sets of the visible commits that aren't immutable, and of those that aren't
+/// on any remote either. Both are usually small, unlike their complements.
+#[derive(Debug, Default)]
+struct PublishStateIndex {
+    mutable: HashSet<CommitId>,
+    unpushed: HashSet<CommitId>,
+}
+
+impl PublishStateIndex {
+    fn get(&self, id: &CommitId) -> &'static str {
+        if !self.mutable.contains(id) {
+            "immutable"
+        } else if self.unpushed.contains(id) {
+            "unpushed"
+        } else {
+            "pushed"
+        }
+    }
+}
+
+/// Evaluates the revsets upfront so errors, e.g. in the `immutable_heads()`
+/// alias, can be reported, but collects the commits only when the index is
+/// first used.
+fn build_publish_state_index<'repo>(
+    repo: &'repo dyn Repo,
+    revset_parse_context: &RevsetParseContext,
+    span: pest::Span<'_>,
+) -> TemplateParseResult<Rc<LazyIndex<'repo, PublishStateIndex>>> {
+    let evaluate = |revset_text| -> TemplateParseResult<Box<dyn Revset<'repo> + 'repo>> {
+        let invalid_revset = |err: &dyn std::fmt::Display| {
+            TemplateParseError::unexpected_expression(format!("Invalid revset: {err}"), span)
+        };
+        let expression =
+            revset::parse(revset_text, revset_parse_context).map_err(|err| invalid_revset(&err))?;
+        revset::optimize(expression)
+            .resolve_user_expression(repo, &DefaultSymbolResolver::new(repo))
+            .map_err(|err| invalid_revset(&err))?
+            .evaluate(repo)
+            .map_err(|err| {
+                TemplateParseError::unexpected_expression(
+                    format!("Failed to evaluate revset: {err}"),
+                    span,
+                )
+            })
+    };
+    let mutable = evaluate("(immutable_heads() | root())..")?;
+    let unpushed = evaluate("(immutable_heads() | root() | remote_branches())..")?;
+    Ok(LazyIndex::new(move || PublishStateIndex {
+        mutable: mutable.iter().collect(),
+        unpushed: unpushed.iter().collect(),
+    }))
+}
+
 fn build_branches_index(repo: &dyn Repo) -> RefNamesIndex {
     let mut index = RefNamesIndex::default();
     let all_branches = git::build_unified_branches_map(repo.view());
@@ -374,7 +1171,7 @@ fn build_branches_index(repo: &dyn Repo) -> RefNamesIndex {
             } else {
                 branch_name.clone()
             };
-            index.insert(local_target.added_ids(), decorated_name);
+            index.insert(local_target.added_ids(), branch_name, None, decorated_name);
         }
         for (remote_name, target) in unsynced_remote_targets {
             let decorated_name = if target.has_conflict() {
@@ -382,12 +1179,68 @@ fn build_branches_index(repo: &dyn Repo) -> RefNamesIndex {
             } else {
                 format!("{branch_name}@{remote_name}")
             };
-            index.insert(target.added_ids(), decorated_name);
+            index.insert(
+                target.added_ids(),
+                branch_name,
+                Some(remote_name),
+                decorated_name,
+            );
         }
     }
     index
 }
 
+/// Numbers of commits each remote branch is ahead of and behind the local
+/// branch, keyed by `(branch_name, remote_name)`. Only non-conflicting branches
+/// that are out of sync are included.
+type BranchDistancesIndex = HashMap<(String, String), (usize, usize)>;
+
+/// Evaluates the ranges between the local and remote branches upfront so
+/// errors can be reported, but counts the commits only when the index is
+/// first used.
+fn build_branch_distances_index<'repo>(
+    repo: &'repo dyn Repo,
+) -> Result<Rc<LazyIndex<'repo, BranchDistancesIndex>>, RevsetEvaluationError> {
+    let mut ranges = vec![];
+    let all_branches = git::build_unified_branches_map(repo.view());
+    for (branch_name, branch_target) in &all_branches {
+        let local_target = &branch_target.local_target;
+        if local_target.as_normal().is_none() {
+            continue;
+        }
+        let local_ids = local_target.added_ids().cloned().collect_vec();
+        for (remote_name, target) in &branch_target.remote_targets {
+            if target.as_normal().is_none() || target == local_target {
+                continue;
+            }
+            let remote_ids = target.added_ids().cloned().collect_vec();
+            let ahead = revset::walk_revs(repo, &remote_ids, &local_ids)?;
+            let behind = revset::walk_revs(repo, &local_ids, &remote_ids)?;
+            let key = (branch_name.clone(), remote_name.clone());
+            ranges.push((key, ahead, behind));
+        }
+    }
+    Ok(LazyIndex::new(move || {
+        ranges
+            .iter()
+            .map(|(key, ahead, behind)| (key.clone(), (ahead.count(), behind.count())))
+            .collect()
+    }))
+}
+
+/// Appends the distance between the remote branch and the local branch, e.g.
+/// `main@origin+2-1` if the remote has 2 commits that aren't on the local
+/// branch, and the local branch has 1 commit that isn't on the remote.
+fn with_branch_distance(mut name: RefName, index: &BranchDistancesIndex) -> RefName {
+    let Some(remote) = &name.remote else {
+        return name;
+    };
+    if let Some((ahead, behind)) = index.get(&(name.name.clone(), remote.clone())) {
+        name.decorated_name = format!("{}+{ahead}-{behind}", name.decorated_name);
+    }
+    name
+}
+
 fn build_ref_names_index<'a>(
     ref_pairs: impl IntoIterator<Item = (&'a String, &'a RefTarget)>,
 ) -> RefNamesIndex {
@@ -398,11 +1251,85 @@ fn build_ref_names_index<'a>(
         } else {
             name.clone()
         };
-        index.insert(target.added_ids(), decorated_name);
+        index.insert(target.added_ids(), name, None, decorated_name);
     }
     index
 }
 
+/// URL patterns for linking commit ids and branch names, e.g. to a forge.
+#[derive(Clone, Debug, Default)]
+pub struct HyperlinkPatterns {
+    commit_id: Option<String>,
+    branch: Option<String>,
+}
+
+impl HyperlinkPatterns {
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        let get_pattern = |key| match config.get_string(key) {
+            Ok(pattern) => Ok(Some(pattern)),
+            Err(config::ConfigError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        };
+        Ok(HyperlinkPatterns {
+            commit_id: get_pattern("hyperlinks.commit-id")?,
+            branch: get_pattern("hyperlinks.branch")?,
+        })
+    }
+
+    fn commit_url(&self, id: &CommitId) -> Option<String> {
+        let pattern = self.commit_id.as_ref()?;
+        Some(pattern.replace("{commit_id}", &id.hex()))
+    }
+
+    fn branch_url(&self, name: &str) -> Option<String> {
+        let pattern = self.branch.as_ref()?;
+        Some(pattern.replace("{branch}", name))
+    }
+}
+
+/// Labels added to the `description` keyword if the description matches the
+/// associated regex, e.g. `wip` for descriptions starting with "WIP:".
+#[derive(Clone, Debug, Default)]
+pub struct DescriptionLabels {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl DescriptionLabels {
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        let table = match config.get_table("description-labels") {
+            Ok(table) => table,
+            Err(config::ConfigError::NotFound(_)) => return Ok(DescriptionLabels::default()),
+            Err(err) => return Err(err),
+        };
+        let patterns = table
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(label, value)| {
+                let pattern = value.into_string()?;
+                let regex = Regex::new(&pattern).map_err(|err| {
+                    config::ConfigError::Message(format!(
+                        "Invalid regex for description label \"{label}\": {err}"
+                    ))
+                })?;
+                Ok((label, regex))
+            })
+            .try_collect()?;
+        Ok(DescriptionLabels { patterns })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    fn matching(&self, description: &str) -> Vec<&str> {
+        self.patterns
+            .iter()
+            .filter(|(_, regex)| regex.is_match(description))
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+}
+
 // TODO: return NameRef?
 fn extract_git_head(repo: &dyn Repo, commit: &Commit) -> String {
     let target = repo.view().git_head();
@@ -486,6 +1413,16 @@ fn build_commit_or_change_id_method<'repo>(
                 |(id, len)| id.short(len.map_or(12, |l| l.try_into().unwrap_or(0))),
             ))
         }
+        "url" => {
+            template_parser::expect_no_arguments(function)?;
+            let hyperlink_patterns = language.hyperlink_patterns.clone();
+            language.wrap_string(TemplateFunction::new(self_property, move |id| match id {
+                CommitOrChangeId::Commit(id) => {
+                    hyperlink_patterns.commit_url(&id).unwrap_or_default()
+                }
+                CommitOrChangeId::Change(_) => String::new(),
+            }))
+        }
         "shortest" => {
             let id_prefix_context = &language.id_prefix_context;
             let len_property = parse_optional_integer(function)?;
@@ -572,19 +1509,502 @@ fn build_shortest_id_prefix_method<'repo>(
     Ok(property)
 }
 
+fn build_tree_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = BackendResult<MergedTree>> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "file_count" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_tree_integer(TemplateFunction::new(self_property, |tree| {
+                Ok(tree?.entries().count() as i64)
+            }))
+        }
+        "total_size" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_tree_integer(TemplateFunction::new(self_property, |tree| {
+                Ok(tree_total_size(&tree?)? as i64)
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("Tree", function)),
+    };
+    Ok(property)
+}
+
+/// Sums up the sizes of the resolved files in the tree. The sizes are looked up
+/// without reading the file contents if the backend supports it.
+fn tree_total_size(tree: &MergedTree) -> BackendResult<u64> {
+    let store = tree.store();
+    tree.entries()
+        .filter_map(|(path, value)| match value.into_resolved() {
+            Ok(Some(TreeValue::File { id, .. })) => Some((path, id)),
+            _ => None,
+        })
+        .map(|(path, id)| store.file_size(&path, &id))
+        .sum()
+}
+
+impl Template<()> for BackendResult<i64> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        match self {
+            Ok(value) => value.format(&(), formatter),
+            // Templates can't report errors other than by failing to render
+            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err.to_string())),
+        }
+    }
+}
+
+/// Changes made by a commit, compared to the auto-merged parents.
+struct TreeDiff {
+    from_tree: MergedTree,
+    to_tree: MergedTree,
+}
+
+impl TreeDiff {
+    fn from_commit(repo: &dyn Repo, commit: &Commit) -> Self {
+        let from_tree = rewrite::merge_commit_trees(repo, &commit.parents()).unwrap();
+        let to_tree = commit.tree().unwrap();
+        TreeDiff { from_tree, to_tree }
+    }
+
+    fn stats(&self, repo: &dyn Repo) -> DiffStats {
+        let tree_diff = self.from_tree.diff(&self.to_tree, &EverythingMatcher);
+        // Templates can't report errors, so unreadable content is counted as
+        // empty.
+        DiffStats::compute(repo, tree_diff).unwrap_or_default()
+    }
+
+    fn changed_files(&self) -> Vec<ChangedFile> {
+        self.from_tree
+            .diff(&self.to_tree, &EverythingMatcher)
+            .map(|(repo_path, before, after)| {
+                let status = if before.is_present() && after.is_present() {
+                    ChangedFileStatus::Modified
+                } else if before.is_absent() {
+                    ChangedFileStatus::Added
+                } else {
+                    ChangedFileStatus::Removed
+                };
+                ChangedFile {
+                    path: repo_path.to_internal_file_string(),
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = String::new();
+        for file in self.changed_files() {
+            summary.push_str(&format!("{} {}\n", file.status.to_char(), file.path));
+        }
+        summary
+    }
+}
+
+fn build_tree_diff_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = TreeDiff> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let repo = language.repo;
+    let property = match function.name {
+        "stat" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_diff_stats(TemplateFunction::new(self_property, |diff| {
+                diff.stats(repo)
+            }))
+        }
+        "summary" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |diff| diff.summary()))
+        }
+        _ => return Err(TemplateParseError::no_such_method("TreeDiff", function)),
+    };
+    Ok(property)
+}
+
+impl Template<()> for DiffStats {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter.labeled("stat-summary"), "{self}")
+    }
+}
+
+fn build_diff_stats_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = DiffStats> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "files" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_integer(TemplateFunction::new(self_property, |stats| {
+                stats.files_changed as i64
+            }))
+        }
+        "added" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_integer(TemplateFunction::new(self_property, |stats| {
+                stats.added as i64
+            }))
+        }
+        "removed" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_integer(TemplateFunction::new(self_property, |stats| {
+                stats.removed as i64
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("DiffStats", function)),
+    };
+    Ok(property)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChangedFileStatus {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl ChangedFileStatus {
+    fn to_char(self) -> char {
+        match self {
+            ChangedFileStatus::Added => 'A',
+            ChangedFileStatus::Removed => 'R',
+            ChangedFileStatus::Modified => 'M',
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ChangedFileStatus::Added => "added",
+            ChangedFileStatus::Removed => "removed",
+            ChangedFileStatus::Modified => "modified",
+        }
+    }
+}
+
+/// Path of a file changed by a commit, relative to the repository root.
+#[derive(Clone, Debug)]
+struct ChangedFile {
+    path: String,
+    status: ChangedFileStatus,
+}
+
+impl Template<()> for ChangedFile {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        formatter.with_label("diff", |formatter| {
+            formatter.with_label(self.status.label(), |formatter| {
+                formatter.write_str(&self.path)
+            })
+        })
+    }
+}
+
+impl Template<()> for Vec<ChangedFile> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, " ")
+    }
+}
+
+fn build_changed_file_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = ChangedFile> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "path" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |file| file.path))
+        }
+        "status" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |file| {
+                file.status.label().to_owned()
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("ChangedFile", function)),
+    };
+    Ok(property)
+}
+
+impl Template<()> for Trailer {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{}: {}", self.key, self.value)
+    }
+}
+
+impl Template<()> for Vec<Trailer> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, "\n")
+    }
+}
+
+fn build_trailer_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = Trailer> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "key" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |trailer| trailer.key))
+        }
+        "value" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |trailer| {
+                trailer.value
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("Trailer", function)),
+    };
+    Ok(property)
+}
+
+fn build_trailer_list_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = Vec<Trailer>> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "get" => {
+            let [key_node] = template_parser::expect_exact_arguments(function)?;
+            let key_property =
+                template_builder::expect_plain_text_expression(language, build_ctx, key_node)?;
+            language.wrap_string_list(TemplateFunction::new(
+                (self_property, key_property),
+                |(trailers, key)| {
+                    trailers
+                        .into_iter()
+                        .filter(|trailer| trailer.key.eq_ignore_ascii_case(&key))
+                        .map(|trailer| trailer.value)
+                        .collect()
+                },
+            ))
+        }
+        _ => template_builder::build_formattable_list_method(
+            language,
+            build_ctx,
+            self_property,
+            function,
+            |item| language.wrap_trailer(item),
+            |property| language.wrap_trailer_list(property),
+        )?,
+    };
+    Ok(property)
+}
+
+/// Verifies commit signatures on demand, remembering the results.
+#[derive(Debug)]
+struct SignatureVerifier {
+    ssh: SshVerifier,
+    verifications: RefCell<HashMap<CommitId, Verification>>,
+}
+
+impl SignatureVerifier {
+    fn new(ssh: SshVerifier) -> Self {
+        SignatureVerifier {
+            ssh,
+            verifications: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn verify(&self, commit_id: &CommitId, secure_sig: &SecureSig) -> Verification {
+        if let Some(verification) = self.verifications.borrow().get(commit_id) {
+            return verification.clone();
+        }
+        // The signature can't be checked if e.g. ssh-keygen isn't installed.
+        let verification = self.ssh.verify(secure_sig).unwrap_or(Verification {
+            status: SigStatus::Unknown,
+            key: None,
+            display: None,
+        });
+        self.verifications
+            .borrow_mut()
+            .insert(commit_id.clone(), verification.clone());
+        verification
+    }
+}
+
+/// Cryptographic signature of a commit, verified when queried.
+#[derive(Clone, Debug)]
+struct CryptographicSignature {
+    commit_id: CommitId,
+    secure_sig: SecureSig,
+    verifier: Rc<SignatureVerifier>,
+}
+
+impl CryptographicSignature {
+    fn verify(&self) -> Verification {
+        self.verifier.verify(&self.commit_id, &self.secure_sig)
+    }
+}
+
+fn read_cryptographic_signature(
+    repo: &dyn Repo,
+    commit: &Commit,
+    verifier: &Rc<SignatureVerifier>,
+) -> Option<CryptographicSignature> {
+    let git_backend = repo.store().backend_impl().downcast_ref::<GitBackend>()?;
+    let secure_sig = git_backend.read_commit_signature(commit.id()).ok()??;
+    Some(CryptographicSignature {
+        commit_id: commit.id().clone(),
+        secure_sig,
+        verifier: verifier.clone(),
+    })
+}
+
+/// Verification status of a commit signature, or `None` if the commit isn't
+/// signed.
+#[derive(Clone, Copy, Debug)]
+struct SignatureStatus(Option<SigStatus>);
+
+impl SignatureStatus {
+    fn as_str(&self) -> &'static str {
+        self.0.map_or("unsigned", |status| status.as_str())
+    }
+}
+
+impl Template<()> for SignatureStatus {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        let status = self.as_str();
+        formatter.with_label(status, |formatter| formatter.write_str(status))
+    }
+}
+
+fn build_cryptographic_signature_method<'repo>(
+    language: &CommitTemplateLanguage<'repo, '_>,
+    _build_ctx: &BuildContext<CommitTemplatePropertyKind<'repo>>,
+    self_property: impl TemplateProperty<Commit, Output = Option<CryptographicSignature>> + 'repo,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<CommitTemplatePropertyKind<'repo>> {
+    let property = match function.name {
+        "status" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |sig| {
+                sig.map(|sig| sig.verify().status.as_str().to_owned())
+                    .unwrap_or_default()
+            }))
+        }
+        "key" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |sig| {
+                sig.and_then(|sig| sig.verify().key).unwrap_or_default()
+            }))
+        }
+        "display" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |sig| {
+                sig.and_then(|sig| sig.verify().display).unwrap_or_default()
+            }))
+        }
+        _ => {
+            return Err(TemplateParseError::no_such_method(
+                "CryptographicSignature",
+                function,
+            ))
+        }
+    };
+    Ok(property)
+}
+
+impl<'repo, 'b> CommitTemplateLanguage<'repo, 'b> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        repo: &'repo dyn Repo,
+        operation: &'repo Operation,
+        workspace_id: &'b WorkspaceId,
+        revset_parse_context: RevsetParseContext<'b>,
+        id_prefix_context: &'repo IdPrefixContext,
+        hyperlink_patterns: HyperlinkPatterns,
+        description_labels: DescriptionLabels,
+        ssh_verifier: SshVerifier,
+        available_width: AvailableWidth,
+        config: &'b config::Config,
+    ) -> Self {
+        CommitTemplateLanguage {
+            repo,
+            operation,
+            workspace_id,
+            revset_parse_context,
+            id_prefix_context,
+            hyperlink_patterns: Rc::new(hyperlink_patterns),
+            description_labels: Rc::new(description_labels),
+            signature_verifier: Rc::new(SignatureVerifier::new(ssh_verifier)),
+            available_width,
+            config,
+            keyword_cache: CommitKeywordCache::default(),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn parse<'repo>(
     repo: &'repo dyn Repo,
+    operation: &'repo Operation,
     workspace_id: &WorkspaceId,
+    revset_parse_context: RevsetParseContext<'_>,
     id_prefix_context: &'repo IdPrefixContext,
+    hyperlink_patterns: HyperlinkPatterns,
+    description_labels: DescriptionLabels,
+    ssh_verifier: SshVerifier,
+    available_width: AvailableWidth,
+    config: &config::Config,
     template_text: &str,
     aliases_map: &TemplateAliasesMap,
 ) -> TemplateParseResult<Box<dyn Template<Commit> + 'repo>> {
-    let language = CommitTemplateLanguage {
+    let language = CommitTemplateLanguage::new(
         repo,
+        operation,
         workspace_id,
+        revset_parse_context,
         id_prefix_context,
-        keyword_cache: CommitKeywordCache::default(),
-    };
+        hyperlink_patterns,
+        description_labels,
+        ssh_verifier,
+        available_width,
+        config,
+    );
     let node = template_parser::parse(template_text, aliases_map)?;
     template_builder::build(&language, &node)
 }
+
+/// Returns the types of the commit keywords referenced by the template, in
+/// order of first appearance. Unknown keywords and lambda parameters are
+/// omitted.
+pub fn keyword_types<'i>(
+    repo: &dyn Repo,
+    operation: &Operation,
+    workspace_id: &WorkspaceId,
+    revset_parse_context: RevsetParseContext<'_>,
+    id_prefix_context: &IdPrefixContext,
+    node: &ExpressionNode<'i>,
+) -> Vec<(&'i str, &'static str)> {
+    let config = config::Config::default();
+    let language = CommitTemplateLanguage::new(
+        repo,
+        operation,
+        workspace_id,
+        revset_parse_context,
+        id_prefix_context,
+        HyperlinkPatterns::default(),
+        DescriptionLabels::default(),
+        SshVerifier::default(),
+        AvailableWidth::new(None),
+        &config,
+    );
+    template_parser::collect_keywords(node)
+        .into_iter()
+        .filter_map(|(name, span)| {
+            let property = language.build_keyword(name, span).ok()?;
+            Some((name, property.type_name()))
+        })
+        .collect()
+}
@@ -20,7 +20,9 @@ use std::{env, fmt};
 
 use config::Source;
 use itertools::Itertools;
+use jj_lib::content_hash::blake2b_hash;
 use jj_lib::settings::ConfigResultExt as _;
+use once_cell::sync::Lazy;
 use thiserror::Error;
 use tracing::instrument;
 
@@ -32,6 +34,8 @@ pub enum ConfigError {
     AmbiguousSource(PathBuf, PathBuf),
     #[error(transparent)]
     ConfigCreateError(#[from] std::io::Error),
+    #[error("Cannot determine where to record trusted repo configs")]
+    TrustStoreUnavailable,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -70,6 +74,8 @@ pub struct LayeredConfigs {
     repo: Option<config::Config>,
     env_overrides: config::Config,
     arg_overrides: Option<config::Config>,
+    user_config_path: Option<PathBuf>,
+    repo_config_path: Option<PathBuf>,
 }
 
 impl LayeredConfigs {
@@ -82,20 +88,27 @@ impl LayeredConfigs {
             repo: None,
             env_overrides: env_overrides(),
             arg_overrides: None,
+            user_config_path: None,
+            repo_config_path: None,
         }
     }
 
     #[instrument]
     pub fn read_user_config(&mut self) -> Result<(), ConfigError> {
-        self.user = existing_config_path()?
-            .map(|path| read_config_path(&path))
+        self.user_config_path = existing_config_path()?;
+        self.user = self
+            .user_config_path
+            .as_ref()
+            .map(|path| read_config_path(path))
             .transpose()?;
         Ok(())
     }
 
     #[instrument]
     pub fn read_repo_config(&mut self, repo_path: &Path) -> Result<(), ConfigError> {
-        self.repo = Some(read_config_file(&repo_path.join("config.toml"))?);
+        let config_path = repo_path.join("config.toml");
+        self.repo = Some(read_config_file(&config_path)?);
+        self.repo_config_path = Some(config_path);
         Ok(())
     }
 
@@ -315,6 +328,395 @@ pub fn new_config_path() -> Result<Option<PathBuf>, ConfigError> {
     ConfigEnv::new().new_config_path()
 }
 
+/// Returns true if the repo config should only be used once trusted.
+pub fn repo_config_requires_trust(config: &config::Config) -> Result<bool, ConfigError> {
+    Ok(config
+        .get_bool("repo-config.require-trust")
+        .optional()?
+        .unwrap_or(false))
+}
+
+/// Returns the path of the file recording the trusted content of the repo
+/// config file at `config_path`.
+fn trust_record_path(config_path: &Path) -> Result<PathBuf, ConfigError> {
+    let data_dir = dirs::data_local_dir().ok_or(ConfigError::TrustStoreUnavailable)?;
+    let config_path = match config_path.parent() {
+        Some(dir) => dir.canonicalize()?.join(config_path.file_name().unwrap()),
+        None => config_path.to_owned(),
+    };
+    let key = hex::encode(blake2b_hash(&config_path.display().to_string()));
+    Ok(data_dir.join("jj").join("trusted-repo-configs").join(key))
+}
+
+fn config_content_hash(config_path: &Path) -> Result<Option<String>, ConfigError> {
+    match std::fs::read_to_string(config_path) {
+        Ok(content) => Ok(Some(hex::encode(blake2b_hash(&content)))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns true if the repo config file at `config_path` doesn't exist or was
+/// marked as trusted with its current content.
+pub fn is_repo_config_trusted(config_path: &Path) -> Result<bool, ConfigError> {
+    let Some(content_hash) = config_content_hash(config_path)? else {
+        return Ok(true);
+    };
+    match std::fs::read_to_string(trust_record_path(config_path)?) {
+        Ok(trusted_hash) => Ok(trusted_hash == content_hash),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Marks the current content of the repo config file at `config_path` as
+/// trusted.
+pub fn trust_repo_config(config_path: &Path) -> Result<(), ConfigError> {
+    let record_path = trust_record_path(config_path)?;
+    match config_content_hash(config_path)? {
+        Some(content_hash) => {
+            create_dir_all(record_path.parent().unwrap())?;
+            std::fs::write(record_path, content_hash)?;
+        }
+        None => untrust_repo_config(config_path)?,
+    }
+    Ok(())
+}
+
+/// Forgets that the repo config file at `config_path` was trusted.
+pub fn untrust_repo_config(config_path: &Path) -> Result<(), ConfigError> {
+    match std::fs::remove_file(trust_record_path(config_path)?) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+static CONFIG_SCHEMA: Lazy<serde_json::Value> = Lazy::new(|| {
+    serde_json::from_str(include_str!("config-schema.json")).expect("config schema should be valid")
+});
+
+/// A problem found in the config by [`LayeredConfigs::validate()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Where the offending key is defined, e.g. `path/to/config.toml:3`.
+    pub location: String,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+impl LayeredConfigs {
+    /// Checks the user config, the repo config, and `--config-toml` arguments
+    /// against the config schema. Keys that are set in the default config are
+    /// accepted even if they aren't in the schema, so custom commands can add
+    /// their own settings.
+    ///
+    /// Unless `strict` is set, unknown top-level tables are only reported if
+    /// their names are similar to known ones, since other tools may keep their
+    /// settings in the same files.
+    pub fn validate(&self, strict: bool) -> Vec<ConfigIssue> {
+        let default_table = self.default.collect().unwrap_or_default();
+        let mut validator = ConfigValidator {
+            default_table: &default_table,
+            strict,
+            issues: vec![],
+        };
+        let config_files = self
+            .user_config_path
+            .iter()
+            .flat_map(|path| config_files_in(path))
+            .chain(self.repo_config_path.clone());
+        for path in config_files {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            // Syntax errors are reported when the config is loaded
+            let Ok(table) = read_config_file(&path).and_then(|config| config.collect()) else {
+                continue;
+            };
+            validator.check_table(&CONFIG_SCHEMA, &mut vec![], &table, &|key| {
+                // Keys in inline tables can't be found, so point at the table
+                let line = (1..=key.len())
+                    .rev()
+                    .find_map(|len| find_key_line(&content, &key[..len]));
+                match line {
+                    Some(line) => format!("{}:{line}", path.display()),
+                    None => path.display().to_string(),
+                }
+            });
+        }
+        if let Some(config) = &self.arg_overrides {
+            if let Ok(table) = config.collect() {
+                validator.check_table(&CONFIG_SCHEMA, &mut vec![], &table, &|_| {
+                    "--config-toml".to_owned()
+                });
+            }
+        }
+        validator.issues
+    }
+}
+
+struct ConfigValidator<'a> {
+    default_table: &'a config::Map<String, config::Value>,
+    strict: bool,
+    issues: Vec<ConfigIssue>,
+}
+
+impl ConfigValidator<'_> {
+    fn check_table(
+        &mut self,
+        schema: &serde_json::Value,
+        path: &mut Vec<String>,
+        table: &config::Map<String, config::Value>,
+        location: &dyn Fn(&[String]) -> String,
+    ) {
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        let additional = schema
+            .get("additionalProperties")
+            .filter(|additional| additional.is_object());
+        if properties.is_none() && additional.is_none() {
+            // Anything goes in tables without a list of keys
+            return;
+        }
+        for (name, value) in table.iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
+            path.push(name.clone());
+            let child_schema = properties
+                .and_then(|properties| {
+                    properties
+                        .iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                        .map(|(_, schema)| schema)
+                })
+                .or(additional);
+            if let Some(child_schema) = child_schema {
+                self.check_value(child_schema, path, value, location);
+            } else if !self.is_default_key(path) {
+                let candidates = properties
+                    .into_iter()
+                    .flat_map(|properties| properties.keys())
+                    .filter(|candidate| strsim::jaro(name, candidate) > 0.7)
+                    .map(|candidate| {
+                        let parent = &path[..path.len() - 1];
+                        format!(r#""{}""#, join_key(parent, candidate))
+                    })
+                    .collect_vec();
+                let is_foreign_table = path.len() == 1 && candidates.is_empty();
+                if self.strict || !is_foreign_table {
+                    self.issues.push(ConfigIssue {
+                        location: location(path),
+                        message: format!(r#"Unknown config key "{}""#, path.join(".")),
+                        hint: (!candidates.is_empty())
+                            .then(|| format!("Did you mean {}?", candidates.join(", "))),
+                    });
+                }
+            }
+            path.pop();
+        }
+    }
+
+    fn check_value(
+        &mut self,
+        schema: &serde_json::Value,
+        path: &mut Vec<String>,
+        value: &config::Value,
+        location: &dyn Fn(&[String]) -> String,
+    ) {
+        let schema = resolve_schema_ref(schema);
+        if let config::ValueKind::Table(table) = &value.kind {
+            if let Some(table_schema) = table_schema(schema) {
+                self.check_table(table_schema, path, table, location);
+                return;
+            }
+        }
+        if !schema_accepts(schema, &value.kind) {
+            let found = match &value.kind {
+                config::ValueKind::String(s) => format!("{s:?}"),
+                kind => value_kind_name(kind).to_owned(),
+            };
+            self.issues.push(ConfigIssue {
+                location: location(path),
+                message: format!(
+                    r#"Invalid value for config key "{}": expected {}, found {found}"#,
+                    path.join("."),
+                    join_alternatives(&describe_schema(schema)),
+                ),
+                hint: None,
+            });
+        }
+    }
+
+    fn is_default_key(&self, path: &[String]) -> bool {
+        let (first, rest) = path.split_first().unwrap();
+        let mut value = self.default_table.get(first);
+        for name in rest {
+            value = match value.map(|value| &value.kind) {
+                Some(config::ValueKind::Table(table)) => table.get(name),
+                _ => None,
+            };
+        }
+        value.is_some()
+    }
+}
+
+fn resolve_schema_ref(schema: &serde_json::Value) -> &serde_json::Value {
+    let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) else {
+        return schema;
+    };
+    reference
+        .trim_start_matches("#/")
+        .split('/')
+        .try_fold(&*CONFIG_SCHEMA, |node, name| node.get(name))
+        .expect("config schema references should be valid")
+}
+
+/// Returns the schema to check a table against, if tables are accepted.
+fn table_schema(schema: &serde_json::Value) -> Option<&serde_json::Value> {
+    if schema.get("type").and_then(|t| t.as_str()) == Some("object") {
+        return Some(schema);
+    }
+    schema
+        .get("oneOf")?
+        .as_array()?
+        .iter()
+        .map(resolve_schema_ref)
+        .find_map(table_schema)
+}
+
+fn schema_accepts(schema: &serde_json::Value, kind: &config::ValueKind) -> bool {
+    let schema = resolve_schema_ref(schema);
+    if let Some(alternatives) = schema.get("oneOf").and_then(|o| o.as_array()) {
+        return alternatives.iter().any(|alt| schema_accepts(alt, kind));
+    }
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        return match kind {
+            config::ValueKind::String(s) => values.iter().any(|v| v.as_str() == Some(s)),
+            _ => false,
+        };
+    }
+    let types = match schema.get("type") {
+        Some(serde_json::Value::String(t)) => vec![t.as_str()],
+        Some(serde_json::Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => return true,
+    };
+    types.into_iter().any(|t| {
+        matches!(
+            (t, kind),
+            ("string", config::ValueKind::String(_))
+                | ("boolean", config::ValueKind::Boolean(_))
+                | (
+                    "integer" | "number",
+                    config::ValueKind::I64(_)
+                        | config::ValueKind::I128(_)
+                        | config::ValueKind::U64(_)
+                        | config::ValueKind::U128(_)
+                )
+                | ("number", config::ValueKind::Float(_))
+                | ("array", config::ValueKind::Array(_))
+                | ("object", config::ValueKind::Table(_))
+        )
+    })
+}
+
+fn describe_schema(schema: &serde_json::Value) -> Vec<String> {
+    let schema = resolve_schema_ref(schema);
+    if let Some(alternatives) = schema.get("oneOf").and_then(|o| o.as_array()) {
+        return alternatives.iter().flat_map(describe_schema).collect();
+    }
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        return values.iter().map(|v| v.to_string()).collect();
+    }
+    let types = match schema.get("type") {
+        Some(serde_json::Value::String(t)) => vec![t.as_str()],
+        Some(serde_json::Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+        _ => vec![],
+    };
+    types
+        .into_iter()
+        .map(|t| match t {
+            "object" => "a table".to_owned(),
+            "array" => "an array".to_owned(),
+            "integer" => "an integer".to_owned(),
+            t => format!("a {t}"),
+        })
+        .collect()
+}
+
+fn value_kind_name(kind: &config::ValueKind) -> &'static str {
+    match kind {
+        config::ValueKind::Nil => "nothing",
+        config::ValueKind::Boolean(_) => "a boolean",
+        config::ValueKind::I64(_)
+        | config::ValueKind::I128(_)
+        | config::ValueKind::U64(_)
+        | config::ValueKind::U128(_) => "an integer",
+        config::ValueKind::Float(_) => "a float",
+        config::ValueKind::String(_) => "a string",
+        config::ValueKind::Table(_) => "a table",
+        config::ValueKind::Array(_) => "an array",
+    }
+}
+
+fn join_alternatives(alternatives: &[String]) -> String {
+    match alternatives {
+        [] => "something else".to_owned(),
+        [single] => single.clone(),
+        [first, second] => format!("{first} or {second}"),
+        [init @ .., last] => format!("{}, or {last}", init.join(", ")),
+    }
+}
+
+fn join_key(parent: &[String], name: &str) -> String {
+    parent.iter().map(String::as_str).chain([name]).join(".")
+}
+
+/// Splits a TOML key like `ui.'diff'."format"` into its parts.
+fn split_toml_key(key: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut quote = None;
+    for c in key.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '.') => parts.push(std::mem::take(&mut current)),
+            (None, c) if c.is_whitespace() => {}
+            (_, c) => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+/// Finds the 1-based line where `key` is defined in the TOML `content`. Keys
+/// in inline tables aren't found.
+fn find_key_line(content: &str, key: &[String]) -> Option<usize> {
+    let matches = |name: &[String]| {
+        name.len() == key.len() && name.iter().zip(key).all(|(a, b)| a.eq_ignore_ascii_case(b))
+    };
+    let mut table = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let name = if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            table = split_toml_key(header.split(']').next().unwrap_or(""));
+            table.clone()
+        } else if let Some((lhs, _)) = line.split_once('=') {
+            table.iter().cloned().chain(split_toml_key(lhs)).collect()
+        } else {
+            continue;
+        };
+        if matches(&name) {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
 /// Environment variables that should be overridden by config values
 fn env_base() -> config::Config {
     let mut builder = config::Config::builder();
@@ -404,7 +806,7 @@ fn read_config_file(path: &Path) -> Result<config::Config, config::ConfigError>
         .build()
 }
 
-fn read_config_path(config_path: &Path) -> Result<config::Config, config::ConfigError> {
+fn config_files_in(config_path: &Path) -> Vec<PathBuf> {
     let mut files = vec![];
     if config_path.is_dir() {
         if let Ok(read_dir) = config_path.read_dir() {
@@ -420,8 +822,11 @@ fn read_config_path(config_path: &Path) -> Result<config::Config, config::Config
     } else {
         files.push(config_path.to_owned());
     }
-
     files
+}
+
+fn read_config_path(config_path: &Path) -> Result<config::Config, config::ConfigError> {
+    config_files_in(config_path)
         .iter()
         .fold(config::Config::builder(), |builder, path| {
             // TODO: Accept other formats and/or accept only certain file extensions?
@@ -599,6 +1004,8 @@ mod tests {
             repo: None,
             env_overrides: empty_config,
             arg_overrides: None,
+            user_config_path: None,
+            repo_config_path: None,
         };
         assert_eq!(layered_configs.resolved_config_values(&[]).unwrap(), []);
     }
@@ -625,6 +1032,8 @@ mod tests {
             repo: Some(repo_config),
             env_overrides: empty_config,
             arg_overrides: None,
+            user_config_path: None,
+            repo_config_path: None,
         };
         // Note: "email" is alphabetized, before "name" from same layer.
         insta::assert_debug_snapshot!(
@@ -700,6 +1109,8 @@ mod tests {
             repo: Some(repo_config),
             env_overrides: empty_config,
             arg_overrides: None,
+            user_config_path: None,
+            repo_config_path: None,
         };
         insta::assert_debug_snapshot!(
             layered_configs
@@ -14,9 +14,9 @@
 
 use std::cmp::max;
 use std::collections::VecDeque;
-use std::io;
 use std::ops::Range;
 use std::sync::Arc;
+use std::{fmt, io};
 
 use itertools::Itertools;
 use jj_lib::backend::{ObjectId, TreeValue};
@@ -30,6 +30,7 @@ use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::repo_path::RepoPath;
 use jj_lib::settings::{ConfigResultExt as _, UserSettings};
 use jj_lib::{conflicts, diff, files, rewrite};
+use serde::Serialize;
 use tracing::instrument;
 use unicode_width::UnicodeWidthStr as _;
 
@@ -40,8 +41,10 @@ use crate::text_util;
 use crate::ui::Ui;
 
 #[derive(clap::Args, Clone, Debug)]
-#[command(group(clap::ArgGroup::new("short-format").args(&["summary", "stat", "types"])))]
-#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "tool"])))]
+#[command(group(
+    clap::ArgGroup::new("short-format").args(&["summary", "stat", "types", "name_only"])
+))]
+#[command(group(clap::ArgGroup::new("long-format").args(&["git", "color_words", "json", "tool"])))]
 pub struct DiffFormatArgs {
     /// For each path, show only whether it was modified, added, or removed
     #[arg(long, short)]
@@ -58,24 +61,57 @@ pub struct DiffFormatArgs {
     /// Git submodule.
     #[arg(long)]
     pub types: bool,
+    /// For each path, show only the path
+    #[arg(long)]
+    pub name_only: bool,
+    /// Terminate each path with NUL instead of newline
+    ///
+    /// This applies to the `--summary`, `--types`, and `--name-only` formats,
+    /// and makes their output safe to parse even if paths contain newlines.
+    #[arg(short = 'z')]
+    pub null_terminated: bool,
     /// Show a Git-format diff
     #[arg(long)]
     pub git: bool,
     /// Show a word-level diff with changes indicated only by color
     #[arg(long)]
     pub color_words: bool,
+    /// Show the diff as JSON, one object per line for each changed path
+    ///
+    /// Each object has the path, whether it was added, modified, or removed,
+    /// the types before and after, and the hunks with their line ranges. The
+    /// lines of the hunks are included unless
+    /// `ui.diff.json.include-content` is set to false.
+    #[arg(long)]
+    pub json: bool,
     /// Generate diff by external command
     #[arg(long)]
     pub tool: Option<String>,
 }
 
+impl DiffFormatArgs {
+    /// Returns true if any diff format is requested.
+    pub fn has_format(&self) -> bool {
+        self.summary
+            || self.stat
+            || self.types
+            || self.name_only
+            || self.git
+            || self.color_words
+            || self.json
+            || self.tool.is_some()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DiffFormat {
-    Summary,
+    Summary { null_terminated: bool },
     Stat,
-    Types,
+    Types { null_terminated: bool },
+    NameOnly { null_terminated: bool },
     Git,
     ColorWords,
+    Json { include_content: bool },
     Tool(Box<ExternalMergeTool>),
 }
 
@@ -84,12 +120,14 @@ pub fn diff_formats_for(
     settings: &UserSettings,
     args: &DiffFormatArgs,
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
-    let formats = diff_formats_from_args(settings, args)?;
+    let mut formats = diff_formats_from_args(settings, args)?;
     if formats.is_empty() {
-        Ok(vec![default_diff_format(settings)?])
-    } else {
-        Ok(formats)
+        formats.push(default_diff_format(settings)?);
+    }
+    if args.null_terminated {
+        set_null_terminated(&mut formats);
     }
+    Ok(formats)
 }
 
 /// Returns a list of requested diff formats for log-like commands, which may be
@@ -101,20 +139,50 @@ pub fn diff_formats_for_log(
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
     let mut formats = diff_formats_from_args(settings, args)?;
     // --patch implies default if no format other than --summary is specified
-    if patch && matches!(formats.as_slice(), [] | [DiffFormat::Summary]) {
+    if patch && matches!(formats.as_slice(), [] | [DiffFormat::Summary { .. }]) {
         formats.push(default_diff_format(settings)?);
         formats.dedup();
     }
+    if args.null_terminated {
+        set_null_terminated(&mut formats);
+    }
     Ok(formats)
 }
 
+fn set_null_terminated(formats: &mut [DiffFormat]) {
+    for format in formats {
+        match format {
+            DiffFormat::Summary { null_terminated }
+            | DiffFormat::Types { null_terminated }
+            | DiffFormat::NameOnly { null_terminated } => *null_terminated = true,
+            _ => {}
+        }
+    }
+}
+
 fn diff_formats_from_args(
     settings: &UserSettings,
     args: &DiffFormatArgs,
 ) -> Result<Vec<DiffFormat>, config::ConfigError> {
     let mut formats = [
-        (args.summary, DiffFormat::Summary),
-        (args.types, DiffFormat::Types),
+        (
+            args.summary,
+            DiffFormat::Summary {
+                null_terminated: false,
+            },
+        ),
+        (
+            args.types,
+            DiffFormat::Types {
+                null_terminated: false,
+            },
+        ),
+        (
+            args.name_only,
+            DiffFormat::NameOnly {
+                null_terminated: false,
+            },
+        ),
         (args.git, DiffFormat::Git),
         (args.color_words, DiffFormat::ColorWords),
         (args.stat, DiffFormat::Stat),
@@ -122,6 +190,9 @@ fn diff_formats_from_args(
     .into_iter()
     .filter_map(|(arg, format)| arg.then_some(format))
     .collect_vec();
+    if args.json {
+        formats.push(json_diff_format(settings)?);
+    }
     if let Some(name) = &args.tool {
         let tool = merge_tools::get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::External(ExternalMergeTool::with_program(name)));
@@ -135,6 +206,15 @@ fn diff_formats_from_args(
     Ok(formats)
 }
 
+/// Returns the number of changed paths above which only a summary is shown by
+/// default.
+pub fn summary_threshold(settings: &UserSettings) -> Result<Option<usize>, config::ConfigError> {
+    settings
+        .config()
+        .get::<usize>("ui.diff.summary-threshold")
+        .optional()
+}
+
 fn default_diff_format(settings: &UserSettings) -> Result<DiffFormat, config::ConfigError> {
     let config = settings.config();
     if let Some(args) = config.get("ui.diff.tool").optional()? {
@@ -156,17 +236,34 @@ fn default_diff_format(settings: &UserSettings) -> Result<DiffFormat, config::Co
         "color-words".to_owned()
     };
     match name.as_ref() {
-        "summary" => Ok(DiffFormat::Summary),
-        "types" => Ok(DiffFormat::Types),
+        "summary" => Ok(DiffFormat::Summary {
+            null_terminated: false,
+        }),
+        "types" => Ok(DiffFormat::Types {
+            null_terminated: false,
+        }),
+        "name-only" => Ok(DiffFormat::NameOnly {
+            null_terminated: false,
+        }),
         "git" => Ok(DiffFormat::Git),
         "color-words" => Ok(DiffFormat::ColorWords),
         "stat" => Ok(DiffFormat::Stat),
+        "json" => json_diff_format(settings),
         _ => Err(config::ConfigError::Message(format!(
             "invalid diff format: {name}"
         ))),
     }
 }
 
+fn json_diff_format(settings: &UserSettings) -> Result<DiffFormat, config::ConfigError> {
+    let include_content = settings
+        .config()
+        .get_bool("ui.diff.json.include-content")
+        .optional()?
+        .unwrap_or(true);
+    Ok(DiffFormat::Json { include_content })
+}
+
 pub fn show_diff(
     ui: &Ui,
     formatter: &mut dyn Formatter,
@@ -178,17 +275,21 @@ pub fn show_diff(
 ) -> Result<(), CommandError> {
     for format in formats {
         match format {
-            DiffFormat::Summary => {
+            DiffFormat::Summary { null_terminated } => {
                 let tree_diff = from_tree.diff(to_tree, matcher);
-                show_diff_summary(formatter, workspace_command, tree_diff)?;
+                show_diff_summary(formatter, workspace_command, tree_diff, *null_terminated)?;
             }
             DiffFormat::Stat => {
                 let tree_diff = from_tree.diff(to_tree, matcher);
                 show_diff_stat(ui, formatter, workspace_command, tree_diff)?;
             }
-            DiffFormat::Types => {
+            DiffFormat::Types { null_terminated } => {
+                let tree_diff = from_tree.diff(to_tree, matcher);
+                show_types(formatter, workspace_command, tree_diff, *null_terminated)?;
+            }
+            DiffFormat::NameOnly { null_terminated } => {
                 let tree_diff = from_tree.diff(to_tree, matcher);
-                show_types(formatter, workspace_command, tree_diff)?;
+                show_names(formatter, workspace_command, tree_diff, *null_terminated)?;
             }
             DiffFormat::Git => {
                 let tree_diff = from_tree.diff(to_tree, matcher);
@@ -198,6 +299,10 @@ pub fn show_diff(
                 let tree_diff = from_tree.diff(to_tree, matcher);
                 show_color_words_diff(formatter, workspace_command, tree_diff)?;
             }
+            DiffFormat::Json { include_content } => {
+                let tree_diff = from_tree.diff(to_tree, matcher);
+                show_json_diff(formatter, workspace_command, tree_diff, *include_content)?;
+            }
             DiffFormat::Tool(tool) => {
                 merge_tools::generate_diff(ui, formatter.raw(), from_tree, to_tree, matcher, tool)?;
             }
@@ -342,7 +447,7 @@ fn show_color_words_diff_line(
 }
 
 fn diff_content(
-    repo: &Arc<ReadonlyRepo>,
+    repo: &dyn Repo,
     path: &RepoPath,
     value: &Merge<Option<TreeValue>>,
 ) -> Result<Vec<u8>, CommandError> {
@@ -404,7 +509,7 @@ pub fn show_color_words_diff(
     for (path, left_value, right_value) in tree_diff {
         let ui_path = workspace_command.format_file_path(&path);
         if left_value.is_absent() {
-            let right_content = diff_content(repo, &path, &right_value)?;
+            let right_content = diff_content(repo.as_ref(), &path, &right_value)?;
             let description = basic_diff_file_type(&right_value);
             writeln!(
                 formatter.labeled("header"),
@@ -416,8 +521,8 @@ pub fn show_color_words_diff(
                 show_color_words_diff_hunks(&[], &right_content, formatter)?;
             }
         } else if right_value.is_present() {
-            let left_content = diff_content(repo, &path, &left_value)?;
-            let right_content = diff_content(repo, &path, &right_value)?;
+            let left_content = diff_content(repo.as_ref(), &path, &left_value)?;
+            let right_content = diff_content(repo.as_ref(), &path, &right_value)?;
             let description = match (left_value.into_resolved(), right_value.into_resolved()) {
                 (
                     Ok(Some(TreeValue::File {
@@ -460,7 +565,7 @@ pub fn show_color_words_diff(
             writeln!(formatter.labeled("header"), "{description} {ui_path}:")?;
             show_color_words_diff_hunks(&left_content, &right_content, formatter)?;
         } else {
-            let left_content = diff_content(repo, &path, &left_value)?;
+            let left_content = diff_content(repo.as_ref(), &path, &left_value)?;
             let description = basic_diff_file_type(&left_value);
             writeln!(
                 formatter.labeled("header"),
@@ -726,30 +831,134 @@ pub fn show_git_diff(
     Ok(())
 }
 
+#[derive(Serialize)]
+struct JsonFileDiff {
+    path: String,
+    status: &'static str,
+    old_type: Option<&'static str>,
+    new_type: Option<&'static str>,
+    hunks: Vec<JsonDiffHunk>,
+}
+
+#[derive(Serialize)]
+struct JsonDiffHunk {
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<Vec<JsonDiffLine>>,
+}
+
+#[derive(Serialize)]
+struct JsonDiffLine {
+    kind: &'static str,
+    content: String,
+}
+
+fn json_diff_file_type(value: &Merge<Option<TreeValue>>) -> Option<&'static str> {
+    match value.as_resolved() {
+        Some(None) => None,
+        Some(Some(TreeValue::File { executable, .. })) => {
+            Some(if *executable { "executable" } else { "file" })
+        }
+        Some(Some(TreeValue::Symlink(_))) => Some("symlink"),
+        Some(Some(TreeValue::GitSubmodule(_))) => Some("submodule"),
+        None => Some("conflict"),
+        Some(Some(TreeValue::Tree(_))) | Some(Some(TreeValue::Conflict(_))) => {
+            panic!("Unexpected {value:?} in diff");
+        }
+    }
+}
+
+pub fn show_json_diff(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    tree_diff: TreeDiffIterator,
+    include_content: bool,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    for (path, left_value, right_value) in tree_diff {
+        let status = if left_value.is_absent() {
+            "added"
+        } else if right_value.is_absent() {
+            "removed"
+        } else {
+            "modified"
+        };
+        let left_content = diff_content(repo.as_ref(), &path, &left_value)?;
+        let right_content = diff_content(repo.as_ref(), &path, &right_value)?;
+        let hunks = unified_diff_hunks(&left_content, &right_content, 3)
+            .into_iter()
+            .map(|hunk| JsonDiffHunk {
+                old_start: hunk.left_line_range.start,
+                old_lines: hunk.left_line_range.len(),
+                new_start: hunk.right_line_range.start,
+                new_lines: hunk.right_line_range.len(),
+                lines: include_content.then(|| {
+                    hunk.lines
+                        .iter()
+                        .map(|(line_type, content)| JsonDiffLine {
+                            kind: match line_type {
+                                DiffLineType::Context => "context",
+                                DiffLineType::Removed => "removed",
+                                DiffLineType::Added => "added",
+                            },
+                            content: String::from_utf8_lossy(content).into_owned(),
+                        })
+                        .collect()
+                }),
+            })
+            .collect();
+        let file_diff = JsonFileDiff {
+            path: path.to_internal_file_string(),
+            status,
+            old_type: json_diff_file_type(&left_value),
+            new_type: json_diff_file_type(&right_value),
+            hunks,
+        };
+        let json = serde_json::to_string(&file_diff).map_err(|err| {
+            CommandError::InternalError(format!("Failed to serialize diff: {err}"))
+        })?;
+        writeln!(formatter, "{json}")?;
+    }
+    Ok(())
+}
+
+fn path_terminator(null_terminated: bool) -> char {
+    if null_terminated {
+        '\0'
+    } else {
+        '\n'
+    }
+}
+
 #[instrument(skip_all)]
 pub fn show_diff_summary(
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
     tree_diff: TreeDiffIterator,
+    null_terminated: bool,
 ) -> io::Result<()> {
+    let end = path_terminator(null_terminated);
     formatter.with_label("diff", |formatter| {
         for (repo_path, before, after) in tree_diff {
             if before.is_present() && after.is_present() {
-                writeln!(
+                write!(
                     formatter.labeled("modified"),
-                    "M {}",
+                    "M {}{end}",
                     workspace_command.format_file_path(&repo_path)
                 )?;
             } else if before.is_absent() {
-                writeln!(
+                write!(
                     formatter.labeled("added"),
-                    "A {}",
+                    "A {}{end}",
                     workspace_command.format_file_path(&repo_path)
                 )?;
             } else {
-                writeln!(
+                write!(
                     formatter.labeled("removed"),
-                    "R {}",
+                    "R {}{end}",
                     workspace_command.format_file_path(&repo_path)
                 )?;
             }
@@ -784,6 +993,44 @@ fn get_diff_stat(path: String, left_content: &[u8], right_content: &[u8]) -> Dif
     }
 }
 
+/// Total numbers of changed files and added/removed lines in a diff.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl DiffStats {
+    pub fn compute(repo: &dyn Repo, tree_diff: TreeDiffIterator) -> Result<Self, CommandError> {
+        let mut stats = DiffStats::default();
+        for (repo_path, left, right) in tree_diff {
+            let left_content = diff_content(repo, &repo_path, &left)?;
+            let right_content = diff_content(repo, &repo_path, &right)?;
+            let stat = get_diff_stat(String::new(), &left_content, &right_content);
+            stats.files_changed += 1;
+            stats.added += stat.added;
+            stats.removed += stat.removed;
+        }
+        Ok(stats)
+    }
+}
+
+impl fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+            self.files_changed,
+            if self.files_changed == 1 { "" } else { "s" },
+            self.added,
+            if self.added == 1 { "" } else { "s" },
+            self.removed,
+            if self.removed == 1 { "" } else { "s" },
+        )
+    }
+}
+
 pub fn show_diff_stat(
     ui: &Ui,
     formatter: &mut dyn Formatter,
@@ -795,8 +1042,8 @@ pub fn show_diff_stat(
     let mut max_diffs = 0;
     for (repo_path, left, right) in tree_diff {
         let path = workspace_command.format_file_path(&repo_path);
-        let left_content = diff_content(workspace_command.repo(), &repo_path, &left)?;
-        let right_content = diff_content(workspace_command.repo(), &repo_path, &right)?;
+        let left_content = diff_content(workspace_command.repo().as_ref(), &repo_path, &left)?;
+        let right_content = diff_content(workspace_command.repo().as_ref(), &repo_path, &right)?;
         max_path_width = max(max_path_width, path.width());
         let stat = get_diff_stat(path, &left_content, &right_content);
         max_diffs = max(max_diffs, stat.added + stat.removed);
@@ -839,16 +1086,12 @@ pub fn show_diff_stat(
             write!(formatter.labeled("added"), "{}", "+".repeat(bar_added))?;
             writeln!(formatter.labeled("removed"), "{}", "-".repeat(bar_removed))?;
         }
-        writeln!(
-            formatter.labeled("stat-summary"),
-            "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
-            total_files,
-            if total_files == 1 { "" } else { "s" },
-            total_added,
-            if total_added == 1 { "" } else { "s" },
-            total_removed,
-            if total_removed == 1 { "" } else { "s" },
-        )?;
+        let totals = DiffStats {
+            files_changed: total_files,
+            added: total_added,
+            removed: total_removed,
+        };
+        writeln!(formatter.labeled("stat-summary"), "{totals}")?;
         Ok(())
     })?;
     Ok(())
@@ -858,12 +1101,14 @@ pub fn show_types(
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
     tree_diff: TreeDiffIterator,
+    null_terminated: bool,
 ) -> io::Result<()> {
+    let end = path_terminator(null_terminated);
     formatter.with_label("diff", |formatter| {
         for (repo_path, before, after) in tree_diff {
-            writeln!(
+            write!(
                 formatter.labeled("modified"),
-                "{}{} {}",
+                "{}{} {}{end}",
                 diff_summary_char(&before),
                 diff_summary_char(&after),
                 workspace_command.format_file_path(&repo_path)
@@ -873,6 +1118,25 @@ pub fn show_types(
     })
 }
 
+pub fn show_names(
+    formatter: &mut dyn Formatter,
+    workspace_command: &WorkspaceCommandHelper,
+    tree_diff: TreeDiffIterator,
+    null_terminated: bool,
+) -> io::Result<()> {
+    let end = path_terminator(null_terminated);
+    formatter.with_label("diff", |formatter| {
+        for (repo_path, _before, _after) in tree_diff {
+            write!(
+                formatter,
+                "{}{end}",
+                workspace_command.format_file_path(&repo_path)
+            )?;
+        }
+        Ok(())
+    })
+}
+
 fn diff_summary_char(value: &Merge<Option<TreeValue>>) -> char {
     match value.as_resolved() {
         Some(None) => '-',
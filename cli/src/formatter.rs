@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{Error, Write};
 use std::ops::Range;
 use std::sync::Arc;
@@ -21,7 +21,11 @@ use std::{fmt, io, mem};
 
 use crossterm::queue;
 use crossterm::style::{Attribute, Color, SetAttribute, SetBackgroundColor, SetForegroundColor};
+use indexmap::IndexMap;
 use itertools::Itertools;
+use serde::ser::SerializeMap as _;
+use serde::{Serialize, Serializer};
+use unicode_width::UnicodeWidthStr as _;
 
 // Lets the caller label strings and translates the labels to colors
 pub trait Formatter: Write {
@@ -36,9 +40,40 @@ pub trait Formatter: Write {
     fn push_label(&mut self, label: &str) -> io::Result<()>;
 
     fn pop_label(&mut self) -> io::Result<()>;
+
+    /// Starts a hyperlink to the `url`. Formatters that can't render hyperlinks
+    /// write the linked text as is.
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Takes a row of cells to be aligned into columns with the other rows.
+    /// Returns the cells back if the formatter doesn't align tables.
+    fn push_table_row(&mut self, cells: Vec<FormatRecorder>) -> Result<(), Vec<FormatRecorder>> {
+        Err(cells)
+    }
 }
 
 impl dyn Formatter + '_ {
+    /// Writes a row of cells, which are aligned into columns if the formatter
+    /// supports it, or separated by a space otherwise.
+    pub fn write_table_row(&mut self, cells: Vec<FormatRecorder>) -> io::Result<()> {
+        let Err(cells) = self.push_table_row(cells) else {
+            return Ok(());
+        };
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                self.write_str(" ")?;
+            }
+            cell.replay(self)?;
+        }
+        Ok(())
+    }
+
     pub fn labeled<S: AsRef<str>>(&mut self, label: S) -> LabeledWriter<&mut Self, S> {
         LabeledWriter {
             formatter: self,
@@ -56,6 +91,15 @@ impl dyn Formatter + '_ {
         // its error replace the one from `write_inner()`.
         write_inner(self).and(self.pop_label())
     }
+
+    pub fn with_hyperlink(
+        &mut self,
+        url: &str,
+        write_inner: impl FnOnce(&mut dyn Formatter) -> io::Result<()>,
+    ) -> io::Result<()> {
+        self.push_hyperlink(url)?;
+        write_inner(self).and(self.pop_hyperlink())
+    }
 }
 
 /// `Formatter` wrapper to write a labeled message with `write!()` or
@@ -458,6 +502,17 @@ impl<W: Write> Formatter for ColorFormatter<W> {
         }
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        // Uses the OSC 8 escape sequence. Control characters are removed from
+        // the URL so it can't terminate the sequence early.
+        let url: String = url.chars().filter(|c| !c.is_control()).collect();
+        write!(self.output, "\x1b]8;;{url}\x1b\\")
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        write!(self.output, "\x1b]8;;\x1b\\")
+    }
 }
 
 /// Like buffered formatter, but records `push`/`pop_label()` calls.
@@ -477,6 +532,8 @@ pub struct FormatRecorder {
 enum LabelOp {
     PushLabel(String),
     PopLabel,
+    PushHyperlink(String),
+    PopHyperlink,
 }
 
 impl FormatRecorder {
@@ -516,6 +573,8 @@ impl FormatRecorder {
             match op {
                 LabelOp::PushLabel(label) => formatter.push_label(label)?,
                 LabelOp::PopLabel => formatter.pop_label()?,
+                LabelOp::PushHyperlink(url) => formatter.push_hyperlink(url)?,
+                LabelOp::PopHyperlink => formatter.pop_hyperlink()?,
             }
         }
         flush_data(formatter, self.data.len())
@@ -547,6 +606,241 @@ impl Formatter for FormatRecorder {
         self.push_label_op(LabelOp::PopLabel);
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.push_label_op(LabelOp::PushHyperlink(url.to_owned()));
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.push_label_op(LabelOp::PopHyperlink);
+        Ok(())
+    }
+}
+
+/// Width of each column, which is the width of its widest cell.
+fn column_widths(rows: &[Vec<FormatRecorder>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = vec![];
+    for row in rows {
+        if widths.len() < row.len() {
+            widths.resize(row.len(), 0);
+        }
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell_width(cell));
+        }
+    }
+    widths
+}
+
+/// Writes the cells of a row padded to the given column `widths`, without
+/// terminating newline. Padding is unlabeled, and the last cell isn't padded.
+fn write_row(
+    formatter: &mut dyn Formatter,
+    row: &[FormatRecorder],
+    widths: &[usize],
+) -> io::Result<()> {
+    for (i, cell) in row.iter().enumerate() {
+        if i > 0 {
+            formatter.write_str(" ")?;
+        }
+        cell.replay(formatter)?;
+        if i + 1 < row.len() {
+            write!(formatter, "{:1$}", "", widths[i] - cell_width(cell))?;
+        }
+    }
+    Ok(())
+}
+
+fn cell_width(cell: &FormatRecorder) -> usize {
+    String::from_utf8_lossy(cell.data()).width()
+}
+
+/// Wraps a formatter to align the rows written by `write_table_row()` into
+/// columns.
+///
+/// Output is passed through until the first row is written. After that, it's
+/// buffered until `finish()` is called, since the width of the columns isn't
+/// known before all rows are written. Cells are expected to fit in a single
+/// line.
+pub struct TableFormatter<'a> {
+    inner: &'a mut dyn Formatter,
+    rows: Vec<Vec<FormatRecorder>>,
+    /// Buffered output, which is `None` until the first row is written.
+    segments: Option<Vec<TableSegment>>,
+}
+
+enum TableSegment {
+    Text(FormatRecorder),
+    Row(usize),
+}
+
+impl<'a> TableFormatter<'a> {
+    pub fn new(inner: &'a mut dyn Formatter) -> Self {
+        TableFormatter {
+            inner,
+            rows: vec![],
+            segments: None,
+        }
+    }
+
+    /// Writes the buffered output with the rows aligned.
+    pub fn finish(self) -> io::Result<()> {
+        let Some(segments) = self.segments else {
+            return Ok(());
+        };
+        let widths = column_widths(&self.rows);
+        for segment in &segments {
+            match segment {
+                TableSegment::Text(recorder) => recorder.replay(self.inner)?,
+                TableSegment::Row(index) => {
+                    write_row(self.inner, &self.rows[*index], &widths)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the recorder to buffer unaligned output to, or `None` if the
+    /// output isn't buffered.
+    fn buffer(&mut self) -> Option<&mut FormatRecorder> {
+        let segments = self.segments.as_mut()?;
+        if !matches!(segments.last(), Some(TableSegment::Text(_))) {
+            segments.push(TableSegment::Text(FormatRecorder::new()));
+        }
+        match segments.last_mut() {
+            Some(TableSegment::Text(recorder)) => Some(recorder),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the formatter to write unaligned output to.
+    fn current(&mut self) -> &mut dyn Formatter {
+        if self.segments.is_some() {
+            self.buffer().unwrap()
+        } else {
+            self.inner
+        }
+    }
+}
+
+impl Write for TableFormatter<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.current().write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Formatter for TableFormatter<'_> {
+    fn raw(&mut self) -> &mut dyn Write {
+        if self.segments.is_some() {
+            self.buffer().unwrap()
+        } else {
+            self.inner.raw()
+        }
+    }
+
+    fn push_label(&mut self, label: &str) -> io::Result<()> {
+        self.current().push_label(label)
+    }
+
+    fn pop_label(&mut self) -> io::Result<()> {
+        self.current().pop_label()
+    }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.current().push_hyperlink(url)
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.current().pop_hyperlink()
+    }
+
+    fn push_table_row(&mut self, cells: Vec<FormatRecorder>) -> Result<(), Vec<FormatRecorder>> {
+        let index = self.rows.len();
+        self.rows.push(cells);
+        self.segments
+            .get_or_insert_with(Vec::new)
+            .push(TableSegment::Row(index));
+        Ok(())
+    }
+}
+
+/// Collects the text written by a template into a JSON object instead of
+/// rendering it. The text is keyed by the innermost label it was written with
+/// that names one of the template's keywords, so labels added by functions and
+/// methods (e.g. `label()` or `.short()`) don't split or hide the fields. Text
+/// written with the same keyword is concatenated, and text outside of any
+/// keyword such as separators is dropped.
+#[derive(Debug, Default)]
+pub struct JsonFormatter {
+    keywords: HashSet<String>,
+    labels: Vec<String>,
+    fields: IndexMap<String, String>,
+}
+
+impl JsonFormatter {
+    pub fn new(keywords: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        JsonFormatter {
+            keywords: keywords.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the collected fields as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl Serialize for JsonFormatter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (label, text) in &self.fields {
+            map.serialize_entry(label, text)?;
+        }
+        map.end()
+    }
+}
+
+impl Write for JsonFormatter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let keyword = self
+            .labels
+            .iter()
+            .rev()
+            .find(|label| self.keywords.contains(*label));
+        if let Some(keyword) = keyword {
+            self.fields
+                .entry(keyword.clone())
+                .or_default()
+                .push_str(&String::from_utf8_lossy(data));
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Formatter for JsonFormatter {
+    fn raw(&mut self) -> &mut dyn Write {
+        // Raw output is collected into the current field like any other text
+        self
+    }
+
+    fn push_label(&mut self, label: &str) -> io::Result<()> {
+        self.labels.push(label.to_owned());
+        Ok(())
+    }
+
+    fn pop_label(&mut self) -> io::Result<()> {
+        self.labels.pop();
+        Ok(())
+    }
 }
 
 fn write_sanitized(output: &mut impl Write, buf: &[u8]) -> Result<(), Error> {
@@ -958,6 +1252,27 @@ mod tests {
         @"[38;5;1m a1 [38;5;2m b1 [38;5;3m c [38;5;2m b2 [38;5;1m a2 [39m");
     }
 
+    #[test]
+    fn test_color_formatter_hyperlink() {
+        // The URL is written with the OSC 8 escape sequence, without control
+        // characters.
+        let config = config_from_string(r#" colors.inner = "red" "#);
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config).unwrap();
+        formatter.write_str("before ").unwrap();
+        formatter
+            .push_hyperlink("https://example.com/\x1b\x07x")
+            .unwrap();
+        formatter.push_label("inner").unwrap();
+        formatter.write_str(" link ").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_hyperlink().unwrap();
+        formatter.write_str(" after").unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        before ]8;;https://example.com/x\[38;5;1m link [39m]8;;\ after
+        "###);
+    }
+
     #[test]
     fn test_format_recorder() {
         let mut recorder = FormatRecorder::new();
@@ -994,4 +1309,128 @@ mod tests {
             String::from_utf8(output).unwrap(),
             @"<< outer1 >>[38;5;1m<< inner1  inner2 >>[39m<< outer2 >>");
     }
+
+    #[test]
+    fn test_table_formatter_columns() {
+        let record = |text: &str, label: Option<&str>| {
+            let mut recorder = FormatRecorder::new();
+            if let Some(label) = label {
+                recorder.push_label(label).unwrap();
+            }
+            recorder.write_str(text).unwrap();
+            if label.is_some() {
+                recorder.pop_label().unwrap();
+            }
+            recorder
+        };
+        let rows = || {
+            vec![
+                vec![record("main", Some("branch")), record("abc", None)],
+                vec![
+                    record("feature-1", Some("branch")),
+                    record("d", None),
+                    record("extra", None),
+                ],
+                // Wide characters take 2 columns each
+                vec![record("日本", None), record("ef", None)],
+            ]
+        };
+        let write_rows = |formatter: &mut dyn Formatter| {
+            let mut table_formatter = TableFormatter::new(formatter);
+            for row in rows() {
+                (&mut table_formatter as &mut dyn Formatter)
+                    .write_table_row(row)
+                    .unwrap();
+                table_formatter.write_str("\n").unwrap();
+            }
+            table_formatter.finish().unwrap();
+        };
+        assert_eq!(column_widths(&rows()), vec![9, 3, 5]);
+
+        let mut output: Vec<u8> = vec![];
+        write_rows(&mut PlainTextFormatter::new(&mut output));
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        main      abc
+        feature-1 d   extra
+        日本      ef
+        "###);
+
+        // Padding shouldn't be labeled.
+        let config = config_from_string(r#" colors.branch = "red" "#);
+        let mut output: Vec<u8> = vec![];
+        write_rows(&mut ColorFormatter::for_config(&mut output, &config).unwrap());
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        [38;5;1mmain[39m      abc
+        [38;5;1mfeature-1[39m d   extra
+        日本      ef
+        "###);
+    }
+
+    #[test]
+    fn test_table_formatter() {
+        let record = |text: &str| {
+            let mut recorder = FormatRecorder::new();
+            recorder.write_str(text).unwrap();
+            recorder
+        };
+        let config = config_from_string(r#" colors.outer = "green" "#);
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config).unwrap();
+        let mut table_formatter = TableFormatter::new(&mut formatter);
+        // Output before the first row is passed through
+        table_formatter.write_str("header\n").unwrap();
+        table_formatter.push_label("outer").unwrap();
+        (&mut table_formatter as &mut dyn Formatter)
+            .write_table_row(vec![record("a"), record("bc")])
+            .unwrap();
+        table_formatter.pop_label().unwrap();
+        table_formatter.write_str("\n").unwrap();
+        (&mut table_formatter as &mut dyn Formatter)
+            .write_table_row(vec![record("defg"), record("h")])
+            .unwrap();
+        table_formatter.write_str(" trailer\n").unwrap();
+        table_formatter.finish().unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r###"
+        header
+        [38;5;2ma    bc[39m
+        defg h trailer
+        "###);
+
+        // Formatters that don't align tables separate the cells by a space
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = PlainTextFormatter::new(&mut output);
+        (&mut formatter as &mut dyn Formatter)
+            .write_table_row(vec![record("a"), record("bc")])
+            .unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @"a bc");
+    }
+
+    #[test]
+    fn test_json_formatter() {
+        let mut formatter = JsonFormatter::new(["outer", "inner", "other"]);
+        formatter.push_label("outer").unwrap();
+        formatter.write_str("outer1 ").unwrap();
+        formatter.push_label("inner").unwrap();
+        formatter.write_str("inner").unwrap();
+        // Labels that aren't keywords don't start a new field
+        formatter.push_label("method").unwrap();
+        formatter.write_str(" method").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_label().unwrap();
+        formatter.write_str(" outer2").unwrap();
+        formatter.pop_label().unwrap();
+        // Text outside of keywords is dropped
+        formatter.write_str(" ").unwrap();
+        formatter.push_label("label").unwrap();
+        formatter.write_str("unlabeled").unwrap();
+        formatter.push_label("other").unwrap();
+        formatter.write_str("\"quoted\"\n").unwrap();
+        formatter.raw().write_all(b"raw").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_label().unwrap();
+        insta::assert_snapshot!(
+            formatter.to_json(),
+            @r###"{"outer":"outer1  outer2","inner":"inner method","other":"\"quoted\"\nraw"}"###);
+    }
 }
@@ -17,7 +17,11 @@ use std::io::Write;
 use std::{cmp, io};
 
 use itertools::Itertools;
-use jj_lib::settings::UserSettings;
+use jj_lib::backend::BackendError;
+use jj_lib::commit::Commit;
+use jj_lib::op_store::WorkspaceId;
+use jj_lib::repo::Repo;
+use jj_lib::settings::{ConfigResultExt as _, UserSettings};
 use renderdag::{Ancestor, GraphRowRenderer, Renderer};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -150,6 +154,89 @@ pub fn get_graphlog<'a, K: Clone + Eq + Hash + 'a>(
     }
 }
 
+/// Symbols used for the graph nodes of commits, as configured by the
+/// `ui.graph.node` table.
+#[derive(Clone, Debug)]
+pub struct NodeSymbols {
+    default: String,
+    working_copy: String,
+    other_working_copy: Option<String>,
+    root: Option<String>,
+    conflict: Option<String>,
+    immutable: Option<String>,
+    merge: Option<String>,
+}
+
+impl NodeSymbols {
+    pub fn from_settings(
+        settings: &UserSettings,
+        default_node_symbol: &str,
+    ) -> Result<Self, config::ConfigError> {
+        let get = |name: &str| {
+            settings
+                .config()
+                .get_string(&format!("ui.graph.node.{name}"))
+                .optional()
+        };
+        Ok(NodeSymbols {
+            default: get("default")?.unwrap_or_else(|| default_node_symbol.to_owned()),
+            working_copy: get("working-copy")?.unwrap_or_else(|| "@".to_owned()),
+            other_working_copy: get("other-working-copy")?,
+            root: get("root")?,
+            conflict: get("conflict")?,
+            immutable: get("immutable")?,
+            merge: get("merge")?,
+        })
+    }
+
+    /// Whether the caller needs to find out which commits are immutable.
+    pub fn needs_immutable(&self) -> bool {
+        self.immutable.is_some()
+    }
+
+    /// Picks the symbol for `commit`. If several kinds of symbols apply, the
+    /// working-copy one wins, followed by other workspaces' working copies,
+    /// the root commit, conflicts, immutable commits, and merges.
+    pub fn for_commit(
+        &self,
+        repo: &dyn Repo,
+        workspace_id: &WorkspaceId,
+        commit: &Commit,
+        is_immutable: bool,
+    ) -> Result<&str, BackendError> {
+        let view = repo.view();
+        if view.get_wc_commit_id(workspace_id) == Some(commit.id()) {
+            return Ok(&self.working_copy);
+        }
+        if let Some(symbol) = &self.other_working_copy {
+            if view.wc_commit_ids().values().any(|id| id == commit.id()) {
+                return Ok(symbol);
+            }
+        }
+        if let Some(symbol) = &self.root {
+            if commit.id() == repo.store().root_commit_id() {
+                return Ok(symbol);
+            }
+        }
+        if let Some(symbol) = &self.conflict {
+            if commit.has_conflict()? {
+                return Ok(symbol);
+            }
+        }
+        if let Some(symbol) = &self.immutable {
+            if is_immutable {
+                return Ok(symbol);
+            }
+        }
+        if let Some(symbol) = &self.merge {
+            if commit.parent_ids().len() > 1 {
+                return Ok(symbol);
+            }
+        }
+        Ok(&self.default)
+    }
+}
+
 pub struct AsciiGraphDrawer<'writer, K> {
     writer: &'writer mut dyn Write,
     edges: Vec<Edge<K>>,
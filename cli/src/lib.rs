@@ -14,6 +14,7 @@
 
 #![deny(unused_must_use)]
 
+pub mod branch_templater;
 pub mod cleanup_guard;
 pub mod cli_util;
 pub mod commands;
@@ -25,9 +26,11 @@ pub mod graphlog;
 pub mod merge_tools;
 pub mod operation_templater;
 mod progress;
+pub mod stats_templater;
 pub mod template_builder;
 pub mod template_parser;
 pub mod templater;
 pub mod text_util;
 pub mod time_util;
+mod timing;
 pub mod ui;
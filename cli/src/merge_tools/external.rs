@@ -16,7 +16,10 @@ use jj_lib::merged_tree::{MergedTree, MergedTreeBuilder};
 use jj_lib::repo_path::RepoPath;
 use jj_lib::settings::UserSettings;
 use jj_lib::store::Store;
-use jj_lib::working_copy::{CheckoutError, SnapshotOptions, TreeState, TreeStateError};
+use jj_lib::working_copy::{
+    CheckoutError, CheckoutOptions, ConflictMaterialization, SnapshotOptions,
+    SymlinkMaterialization, TreeState, TreeStateError,
+};
 use regex::{Captures, Regex};
 use tempfile::TempDir;
 use thiserror::Error;
@@ -191,8 +194,13 @@ fn check_out(
     std::fs::create_dir(&wc_dir).map_err(DiffCheckoutError::SetUpDir)?;
     std::fs::create_dir(&state_dir).map_err(DiffCheckoutError::SetUpDir)?;
     let mut tree_state = TreeState::init(store, wc_dir, state_dir)?;
-    tree_state.set_sparse_patterns(sparse_patterns)?;
-    tree_state.check_out(tree)?;
+    // The diff editor should see conflicts as regular files
+    let options = CheckoutOptions {
+        conflict_materialization: ConflictMaterialization::Markers,
+        symlink_materialization: SymlinkMaterialization::Native,
+    };
+    tree_state.set_sparse_patterns(sparse_patterns, &options)?;
+    tree_state.check_out(tree, &options)?;
     Ok(tree_state)
 }
 
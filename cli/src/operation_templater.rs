@@ -132,6 +132,7 @@ fn build_operation_keyword(
         "description" => {
             language.wrap_string(wrap_metadata_fn(|metadata| metadata.description.clone()))
         }
+        "hostname" => language.wrap_string(wrap_metadata_fn(|metadata| metadata.hostname.clone())),
         "id" => language.wrap_operation_id(wrap_fn(|op| op.id().clone())),
         "tags" => language.wrap_string(wrap_metadata_fn(|metadata| {
             // TODO: introduce map type
@@ -149,6 +150,7 @@ fn build_operation_keyword(
             // TODO: introduce dedicated type and provide accessors?
             format!("{}@{}", metadata.username, metadata.hostname)
         })),
+        "username" => language.wrap_string(wrap_metadata_fn(|metadata| metadata.username.clone())),
         _ => return Err(TemplateParseError::no_such_keyword(name, span)),
     };
     Ok(property)
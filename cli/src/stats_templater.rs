@@ -0,0 +1,230 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use serde::Serialize;
+
+use crate::formatter::Formatter;
+use crate::template_builder::{
+    self, BuildContext, CoreTemplatePropertyKind, IntoTemplateProperty, TemplateLanguage,
+};
+use crate::template_parser::{
+    self, FunctionCallNode, TemplateAliasesMap, TemplateParseError, TemplateParseResult,
+};
+use crate::templater::{
+    self, IntoTemplate, PlainTextFormattedProperty, Template, TemplateFunction, TemplateProperty,
+    TemplatePropertyFn,
+};
+
+/// Statistics about the repository as reported by `jj stats`.
+#[derive(Clone, Debug, Serialize)]
+pub struct RepoStats {
+    pub commits: usize,
+    pub changes: usize,
+    pub merges: usize,
+    pub authors: Vec<AuthorStats>,
+    pub repo_size: u64,
+    pub store_size: u64,
+    /// `None` if the repo doesn't use the default index.
+    pub index: Option<IndexStats>,
+}
+
+/// The number of commits by an author, sorted by count in `RepoStats`.
+#[derive(Clone, Debug, Serialize)]
+pub struct AuthorStats {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct IndexStats {
+    pub commits: u32,
+    pub levels: usize,
+}
+
+struct StatsTemplateLanguage;
+
+impl TemplateLanguage<'static> for StatsTemplateLanguage {
+    type Context = RepoStats;
+    type Property = StatsTemplatePropertyKind;
+
+    template_builder::impl_core_wrap_property_fns!('static, StatsTemplatePropertyKind::Core);
+
+    fn build_keyword(&self, name: &str, span: pest::Span) -> TemplateParseResult<Self::Property> {
+        build_stats_keyword(self, name, span)
+    }
+
+    fn build_method(
+        &self,
+        build_ctx: &BuildContext<Self::Property>,
+        property: Self::Property,
+        function: &FunctionCallNode,
+    ) -> TemplateParseResult<Self::Property> {
+        match property {
+            StatsTemplatePropertyKind::Core(property) => {
+                template_builder::build_core_method(self, build_ctx, property, function)
+            }
+            StatsTemplatePropertyKind::AuthorStats(property) => {
+                build_author_stats_method(self, build_ctx, property, function)
+            }
+            StatsTemplatePropertyKind::AuthorStatsList(property) => {
+                template_builder::build_formattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    |item| self.wrap_author_stats(item),
+                    |property| self.wrap_author_stats_list(property),
+                )
+            }
+        }
+    }
+}
+
+impl StatsTemplateLanguage {
+    fn wrap_author_stats(
+        &self,
+        property: impl TemplateProperty<RepoStats, Output = AuthorStats> + 'static,
+    ) -> StatsTemplatePropertyKind {
+        StatsTemplatePropertyKind::AuthorStats(Box::new(property))
+    }
+
+    fn wrap_author_stats_list(
+        &self,
+        property: impl TemplateProperty<RepoStats, Output = Vec<AuthorStats>> + 'static,
+    ) -> StatsTemplatePropertyKind {
+        StatsTemplatePropertyKind::AuthorStatsList(Box::new(property))
+    }
+}
+
+enum StatsTemplatePropertyKind {
+    Core(CoreTemplatePropertyKind<'static, RepoStats>),
+    AuthorStats(Box<dyn TemplateProperty<RepoStats, Output = AuthorStats>>),
+    AuthorStatsList(Box<dyn TemplateProperty<RepoStats, Output = Vec<AuthorStats>>>),
+}
+
+impl IntoTemplateProperty<'static, RepoStats> for StatsTemplatePropertyKind {
+    fn try_into_boolean(self) -> Option<Box<dyn TemplateProperty<RepoStats, Output = bool>>> {
+        match self {
+            StatsTemplatePropertyKind::Core(property) => property.try_into_boolean(),
+            StatsTemplatePropertyKind::AuthorStatsList(property) => {
+                Some(Box::new(TemplateFunction::new(property, |authors| {
+                    !authors.is_empty()
+                })))
+            }
+            _ => None,
+        }
+    }
+
+    fn try_into_integer(self) -> Option<Box<dyn TemplateProperty<RepoStats, Output = i64>>> {
+        match self {
+            StatsTemplatePropertyKind::Core(property) => property.try_into_integer(),
+            _ => None,
+        }
+    }
+
+    fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<RepoStats, Output = String>>> {
+        match self {
+            StatsTemplatePropertyKind::Core(property) => property.try_into_plain_text(),
+            _ => {
+                let template = self.try_into_template()?;
+                Some(Box::new(PlainTextFormattedProperty::new(template)))
+            }
+        }
+    }
+
+    fn try_into_template(self) -> Option<Box<dyn Template<RepoStats>>> {
+        match self {
+            StatsTemplatePropertyKind::Core(property) => property.try_into_template(),
+            StatsTemplatePropertyKind::AuthorStats(property) => Some(property.into_template()),
+            StatsTemplatePropertyKind::AuthorStatsList(property) => Some(property.into_template()),
+        }
+    }
+}
+
+fn build_stats_keyword(
+    language: &StatsTemplateLanguage,
+    name: &str,
+    span: pest::Span,
+) -> TemplateParseResult<StatsTemplatePropertyKind> {
+    fn wrap_fn<O, F: Fn(&RepoStats) -> O>(f: F) -> TemplatePropertyFn<F> {
+        TemplatePropertyFn(f)
+    }
+
+    let property = match name {
+        "commits" => language.wrap_integer(wrap_fn(|stats| stats.commits as i64)),
+        "changes" => language.wrap_integer(wrap_fn(|stats| stats.changes as i64)),
+        "merges" => language.wrap_integer(wrap_fn(|stats| stats.merges as i64)),
+        "authors" => language.wrap_author_stats_list(wrap_fn(|stats| stats.authors.clone())),
+        "repo_size" => language.wrap_integer(wrap_fn(|stats| stats.repo_size as i64)),
+        "store_size" => language.wrap_integer(wrap_fn(|stats| stats.store_size as i64)),
+        "index_commits" => language.wrap_integer(wrap_fn(|stats| {
+            stats.index.as_ref().map_or(0, |index| index.commits.into())
+        })),
+        "index_levels" => language.wrap_integer(wrap_fn(|stats| {
+            stats.index.as_ref().map_or(0, |index| index.levels as i64)
+        })),
+        _ => return Err(TemplateParseError::no_such_keyword(name, span)),
+    };
+    Ok(property)
+}
+
+impl Template<()> for AuthorStats {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        write!(formatter, "{} <{}>", self.name, self.email)
+    }
+}
+
+impl Template<()> for Vec<AuthorStats> {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        templater::format_joined(&(), formatter, self, ", ")
+    }
+}
+
+fn build_author_stats_method(
+    language: &StatsTemplateLanguage,
+    _build_ctx: &BuildContext<StatsTemplatePropertyKind>,
+    self_property: impl TemplateProperty<RepoStats, Output = AuthorStats> + 'static,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<StatsTemplatePropertyKind> {
+    let property = match function.name {
+        "name" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |author| author.name))
+        }
+        "email" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |author| author.email))
+        }
+        "commits" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_integer(TemplateFunction::new(self_property, |author| {
+                author.commits as i64
+            }))
+        }
+        _ => return Err(TemplateParseError::no_such_method("AuthorStats", function)),
+    };
+    Ok(property)
+}
+
+pub fn parse(
+    template_text: &str,
+    aliases_map: &TemplateAliasesMap,
+) -> TemplateParseResult<Box<dyn Template<RepoStats>>> {
+    let language = StatsTemplateLanguage;
+    let node = template_parser::parse(template_text, aliases_map)?;
+    template_builder::build(&language, &node)
+}
@@ -12,22 +12,65 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::env;
+use std::rc::Rc;
 
 use itertools::Itertools as _;
 use jj_lib::backend::{Signature, Timestamp};
+use regex::Regex;
 
 use crate::template_parser::{
-    self, ExpressionKind, ExpressionNode, FunctionCallNode, MethodCallNode, TemplateParseError,
-    TemplateParseResult,
+    self, BinaryOp, ExpressionKind, ExpressionNode, FunctionCallNode, MethodCallNode,
+    TemplateParseError, TemplateParseResult,
 };
 use crate::templater::{
-    ConcatTemplate, ConditionalTemplate, IntoTemplate, LabelTemplate, ListPropertyTemplate,
-    ListTemplate, Literal, PlainTextFormattedProperty, PropertyPlaceholder, ReformatTemplate,
-    SeparateTemplate, Template, TemplateFunction, TemplateProperty, TimestampRange,
+    ConcatTemplate, ConditionalTemplate, Email, HyperlinkTemplate, IntoTemplate, LabelTemplate,
+    ListPropertyTemplate, ListTemplate, Literal, PlainTextFormattedProperty, PropertyPlaceholder,
+    ReformatTemplate, RenderSpanTemplate, SeparateTemplate, TableRowTemplate, Template,
+    TemplateFunction, TemplateProperty, TemplatePropertyFn, TimestampRange,
 };
 use crate::{text_util, time_util};
 
+/// Width used by `wrap(0, ..)` if the output width is unknown.
+const DEFAULT_WRAP_WIDTH: usize = 80;
+
+/// Width available to the template output, which `wrap(0, ..)` fills lines at.
+///
+/// Some columns can be reserved while rendering, e.g. for the graph drawn next
+/// to the output, so the width can change between renderings.
+#[derive(Clone, Debug)]
+pub struct AvailableWidth {
+    output_width: usize,
+    reserved: Rc<Cell<usize>>,
+}
+
+impl AvailableWidth {
+    /// Creates width from the output width, or the default width if unknown.
+    pub fn new(output_width: Option<usize>) -> Self {
+        AvailableWidth {
+            output_width: output_width.unwrap_or(DEFAULT_WRAP_WIDTH),
+            reserved: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.output_width.saturating_sub(self.reserved.get())
+    }
+
+    /// Excludes the given number of columns from the available width.
+    pub fn set_reserved(&self, columns: usize) {
+        self.reserved.set(columns);
+    }
+}
+
+/// Prefixes of the config keys which `config(name)` can read.
+const ALLOWED_CONFIG_PREFIXES: &[&str] = &["ui.", "user."];
+
+/// Environment variables which `env(name)` can read.
+const ALLOWED_ENV_VARS: &[&str] = &["HOSTNAME", "LOGNAME", "USER", "USERNAME"];
+
 /// Callbacks to build language-specific evaluation objects from AST nodes.
 pub trait TemplateLanguage<'a> {
     type Context: 'a;
@@ -53,6 +96,10 @@ pub trait TemplateLanguage<'a> {
         &self,
         property: impl TemplateProperty<Self::Context, Output = Signature> + 'a,
     ) -> Self::Property;
+    fn wrap_email(
+        &self,
+        property: impl TemplateProperty<Self::Context, Output = Email> + 'a,
+    ) -> Self::Property;
     fn wrap_timestamp(
         &self,
         property: impl TemplateProperty<Self::Context, Output = Timestamp> + 'a,
@@ -68,7 +115,22 @@ pub trait TemplateLanguage<'a> {
         template: Box<dyn ListTemplate<Self::Context> + 'a>,
     ) -> Self::Property;
 
+    /// Width available to the output, which `wrap(0, ..)` fills lines at.
+    fn available_width(&self) -> Option<&AvailableWidth> {
+        None
+    }
+
+    /// Config which `config(name)` reads values from.
+    fn config(&self) -> Option<&config::Config> {
+        None
+    }
+
     fn build_keyword(&self, name: &str, span: pest::Span) -> TemplateParseResult<Self::Property>;
+    /// Builds a property of space-separated labels to add to the keyword
+    /// depending on its value.
+    fn build_keyword_labels(&self, _name: &str) -> Option<Self::Property> {
+        None
+    }
     fn build_method(
         &self,
         build_ctx: &BuildContext<Self::Property>,
@@ -93,6 +155,7 @@ macro_rules! impl_core_wrap_property_fns {
                 wrap_boolean(bool) => Boolean,
                 wrap_integer(i64) => Integer,
                 wrap_signature(jj_lib::backend::Signature) => Signature,
+                wrap_email($crate::templater::Email) => Email,
                 wrap_timestamp(jj_lib::backend::Timestamp) => Timestamp,
                 wrap_timestamp_range($crate::templater::TimestampRange) => TimestampRange,
             }
@@ -146,6 +209,7 @@ pub enum CoreTemplatePropertyKind<'a, I> {
     Boolean(Box<dyn TemplateProperty<I, Output = bool> + 'a>),
     Integer(Box<dyn TemplateProperty<I, Output = i64> + 'a>),
     Signature(Box<dyn TemplateProperty<I, Output = Signature> + 'a>),
+    Email(Box<dyn TemplateProperty<I, Output = Email> + 'a>),
     Timestamp(Box<dyn TemplateProperty<I, Output = Timestamp> + 'a>),
     TimestampRange(Box<dyn TemplateProperty<I, Output = TimestampRange> + 'a>),
 
@@ -156,12 +220,35 @@ pub enum CoreTemplatePropertyKind<'a, I> {
     ListTemplate(Box<dyn ListTemplate<I> + 'a>),
 }
 
+impl<'a, I> CoreTemplatePropertyKind<'a, I> {
+    /// Name of the type as it appears in the documentation.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            CoreTemplatePropertyKind::String(_) => "String",
+            CoreTemplatePropertyKind::StringList(_) => "List<String>",
+            CoreTemplatePropertyKind::Boolean(_) => "Boolean",
+            CoreTemplatePropertyKind::Integer(_) => "Integer",
+            CoreTemplatePropertyKind::Signature(_) => "Signature",
+            CoreTemplatePropertyKind::Email(_) => "Email",
+            CoreTemplatePropertyKind::Timestamp(_) => "Timestamp",
+            CoreTemplatePropertyKind::TimestampRange(_) => "TimestampRange",
+            CoreTemplatePropertyKind::Template(_) => "Template",
+            CoreTemplatePropertyKind::ListTemplate(_) => "ListTemplate",
+        }
+    }
+}
+
 impl<'a, I: 'a> IntoTemplateProperty<'a, I> for CoreTemplatePropertyKind<'a, I> {
     fn try_into_boolean(self) -> Option<Box<dyn TemplateProperty<I, Output = bool> + 'a>> {
         match self {
             CoreTemplatePropertyKind::String(property) => {
                 Some(Box::new(TemplateFunction::new(property, |s| !s.is_empty())))
             }
+            CoreTemplatePropertyKind::Email(property) => {
+                Some(Box::new(TemplateFunction::new(property, |e| {
+                    !e.0.is_empty()
+                })))
+            }
             // TODO: should we allow implicit cast of List type?
             CoreTemplatePropertyKind::Boolean(property) => Some(property),
             _ => None,
@@ -178,6 +265,9 @@ impl<'a, I: 'a> IntoTemplateProperty<'a, I> for CoreTemplatePropertyKind<'a, I>
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<I, Output = String> + 'a>> {
         match self {
             CoreTemplatePropertyKind::String(property) => Some(property),
+            CoreTemplatePropertyKind::Email(property) => {
+                Some(Box::new(TemplateFunction::new(property, |e| e.0)))
+            }
             _ => {
                 let template = self.try_into_template()?;
                 Some(Box::new(PlainTextFormattedProperty::new(template)))
@@ -192,6 +282,7 @@ impl<'a, I: 'a> IntoTemplateProperty<'a, I> for CoreTemplatePropertyKind<'a, I>
             CoreTemplatePropertyKind::Boolean(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Integer(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Signature(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::Email(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Timestamp(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::TimestampRange(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Template(template) => Some(template),
@@ -204,17 +295,27 @@ impl<'a, I: 'a> IntoTemplateProperty<'a, I> for CoreTemplatePropertyKind<'a, I>
 pub struct Expression<P> {
     property: P,
     labels: Vec<String>,
+    /// Space-separated labels which are computed from the context.
+    dynamic_labels: Option<P>,
 }
 
 impl<P> Expression<P> {
     fn unlabeled(property: P) -> Self {
         let labels = vec![];
-        Expression { property, labels }
+        Expression {
+            property,
+            labels,
+            dynamic_labels: None,
+        }
     }
 
     fn with_label(property: P, label: impl Into<String>) -> Self {
         let labels = vec![label.into()];
-        Expression { property, labels }
+        Expression {
+            property,
+            labels,
+            dynamic_labels: None,
+        }
     }
 
     pub fn try_into_boolean<'a, C: 'a>(
@@ -249,7 +350,18 @@ impl<P> Expression<P> {
         P: IntoTemplateProperty<'a, C>,
     {
         let template = self.property.try_into_template()?;
-        if self.labels.is_empty() {
+        if let Some(dynamic_labels) = self.dynamic_labels {
+            let static_labels = self.labels;
+            let labels = TemplateFunction::new(dynamic_labels.try_into_plain_text()?, move |s| {
+                let dynamic_labels = s.split_whitespace().map(ToString::to_string);
+                static_labels
+                    .iter()
+                    .cloned()
+                    .chain(dynamic_labels)
+                    .collect()
+            });
+            Some(Box::new(LabelTemplate::new(template, labels)))
+        } else if self.labels.is_empty() {
             Some(template)
         } else {
             Some(Box::new(LabelTemplate::new(template, Literal(self.labels))))
@@ -284,11 +396,14 @@ pub fn build_core_method<'a, L: TemplateLanguage<'a>>(
         CoreTemplatePropertyKind::String(property) => {
             build_string_method(language, build_ctx, property, function)
         }
-        CoreTemplatePropertyKind::StringList(property) => {
-            build_formattable_list_method(language, build_ctx, property, function, |item| {
-                language.wrap_string(item)
-            })
-        }
+        CoreTemplatePropertyKind::StringList(property) => build_formattable_list_method(
+            language,
+            build_ctx,
+            property,
+            function,
+            |item| language.wrap_string(item),
+            |property| language.wrap_string_list(property),
+        ),
         CoreTemplatePropertyKind::Boolean(property) => {
             build_boolean_method(language, build_ctx, property, function)
         }
@@ -298,6 +413,9 @@ pub fn build_core_method<'a, L: TemplateLanguage<'a>>(
         CoreTemplatePropertyKind::Signature(property) => {
             build_signature_method(language, build_ctx, property, function)
         }
+        CoreTemplatePropertyKind::Email(property) => {
+            build_email_method(language, build_ctx, property, function)
+        }
         CoreTemplatePropertyKind::Timestamp(property) => {
             build_timestamp_method(language, build_ctx, property, function)
         }
@@ -371,6 +489,29 @@ fn build_string_method<'a, L: TemplateLanguage<'a>>(
                 },
             ))
         }
+        "match" => {
+            let [regex_node] = template_parser::expect_exact_arguments(function)?;
+            let regex = expect_regex_literal(regex_node)?;
+            language.wrap_string(TemplateFunction::new(self_property, move |s| {
+                // Extract the first capture group if there is one, so e.g. the
+                // issue number can be extracted from "Fixes #123".
+                regex
+                    .captures(&s)
+                    .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+                    .map(|m| m.as_str().to_owned())
+                    .unwrap_or_default()
+            }))
+        }
+        "replace" => {
+            let [regex_node, replacement_node] = template_parser::expect_exact_arguments(function)?;
+            let regex = expect_regex_literal(regex_node)?;
+            let replacement_property =
+                expect_plain_text_expression(language, build_ctx, replacement_node)?;
+            language.wrap_string(TemplateFunction::new(
+                (self_property, replacement_property),
+                move |(s, replacement)| regex.replace_all(&s, replacement.as_str()).into_owned(),
+            ))
+        }
         "substr" => {
             let [start_idx, end_idx] = template_parser::expect_exact_arguments(function)?;
             let start_idx_property = expect_integer_expression(language, build_ctx, start_idx)?;
@@ -403,6 +544,33 @@ fn build_string_method<'a, L: TemplateLanguage<'a>>(
                 },
             ))
         }
+        "truncate" => {
+            let [width_node] = template_parser::expect_exact_arguments(function)?;
+            let width_property = expect_integer_expression(language, build_ctx, width_node)?;
+            language.wrap_string(TemplateFunction::new(
+                (self_property, width_property),
+                |(s, width)| {
+                    let width = usize::try_from(width).unwrap_or(0);
+                    text_util::truncate_end(&s, width).0.to_owned()
+                },
+            ))
+        }
+        "pad_left" => {
+            let [width_node] = template_parser::expect_exact_arguments(function)?;
+            let width_property = expect_integer_expression(language, build_ctx, width_node)?;
+            language.wrap_string(TemplateFunction::new(
+                (self_property, width_property),
+                |(s, width)| text_util::pad_start(&s, usize::try_from(width).unwrap_or(0)),
+            ))
+        }
+        "pad_right" => {
+            let [width_node] = template_parser::expect_exact_arguments(function)?;
+            let width_property = expect_integer_expression(language, build_ctx, width_node)?;
+            language.wrap_string(TemplateFunction::new(
+                (self_property, width_property),
+                |(s, width)| text_util::pad_end(&s, usize::try_from(width).unwrap_or(0)),
+            ))
+        }
         "first_line" => {
             template_parser::expect_no_arguments(function)?;
             language.wrap_string(TemplateFunction::new(self_property, |s| {
@@ -415,6 +583,12 @@ fn build_string_method<'a, L: TemplateLanguage<'a>>(
                 s.lines().map(|l| l.to_owned()).collect()
             }))
         }
+        "words" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string_list(TemplateFunction::new(self_property, |s| {
+                s.split_whitespace().map(|w| w.to_owned()).collect()
+            }))
+        }
         "upper" => {
             template_parser::expect_no_arguments(function)?;
             language.wrap_string(TemplateFunction::new(self_property, |s| s.to_uppercase()))
@@ -461,8 +635,8 @@ fn build_signature_method<'a, L: TemplateLanguage<'a>>(
         }
         "email" => {
             template_parser::expect_no_arguments(function)?;
-            language.wrap_string(TemplateFunction::new(self_property, |signature| {
-                signature.email
+            language.wrap_email(TemplateFunction::new(self_property, |signature| {
+                Email(signature.email)
             }))
         }
         "username" => {
@@ -483,6 +657,36 @@ fn build_signature_method<'a, L: TemplateLanguage<'a>>(
     Ok(property)
 }
 
+fn build_email_method<'a, L: TemplateLanguage<'a>>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    self_property: impl TemplateProperty<L::Context, Output = Email> + 'a,
+    function: &FunctionCallNode,
+) -> TemplateParseResult<L::Property> {
+    let property = match function.name {
+        "local" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |email| {
+                let (local, _) = text_util::split_email(&email.0);
+                local.to_owned()
+            }))
+        }
+        "domain" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_string(TemplateFunction::new(self_property, |email| {
+                let (_, domain) = text_util::split_email(&email.0);
+                domain.unwrap_or_default().to_owned()
+            }))
+        }
+        // Email can be used as a string.
+        _ => {
+            let self_property = TemplateFunction::new(self_property, |email| email.0);
+            build_string_method(language, build_ctx, self_property, function)?
+        }
+    };
+    Ok(property)
+}
+
 fn build_timestamp_method<'a, L: TemplateLanguage<'a>>(
     language: &L,
     _build_ctx: &BuildContext<L::Property>,
@@ -510,6 +714,12 @@ fn build_timestamp_method<'a, L: TemplateLanguage<'a>>(
                 time_util::format_absolute_timestamp_with(&timestamp, &format)
             }))
         }
+        "local" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_timestamp(TemplateFunction::new(self_property, |timestamp| {
+                time_util::to_local_timestamp(&timestamp)
+            }))
+        }
         "utc" => {
             template_parser::expect_no_arguments(function)?;
             language.wrap_timestamp(TemplateFunction::new(self_property, |mut timestamp| {
@@ -581,8 +791,9 @@ pub fn build_formattable_list_method<'a, L, O>(
     self_property: impl TemplateProperty<L::Context, Output = Vec<O>> + 'a,
     function: &FunctionCallNode,
     // TODO: Generic L: WrapProperty<L::Context, O> trait might be needed to support more
-    // list operations such as first()/slice(). For .map(), a simple callback works.
+    // list operations such as slice(). For .map(), a simple callback works.
     wrap_item: impl Fn(PropertyPlaceholder<O>) -> L::Property,
+    wrap_list: impl Fn(Box<dyn TemplateProperty<L::Context, Output = Vec<O>> + 'a>) -> L::Property,
 ) -> TemplateParseResult<L::Property>
 where
     L: TemplateLanguage<'a>,
@@ -598,8 +809,14 @@ where
                 });
             language.wrap_template(Box::new(template))
         }
-        "map" => build_map_operation(language, build_ctx, self_property, function, wrap_item)?,
-        _ => return Err(TemplateParseError::no_such_method("List", function)),
+        _ => build_unformattable_list_method(
+            language,
+            build_ctx,
+            self_property,
+            function,
+            wrap_item,
+            wrap_list,
+        )?,
     };
     Ok(property)
 }
@@ -610,6 +827,7 @@ pub fn build_unformattable_list_method<'a, L, O>(
     self_property: impl TemplateProperty<L::Context, Output = Vec<O>> + 'a,
     function: &FunctionCallNode,
     wrap_item: impl Fn(PropertyPlaceholder<O>) -> L::Property,
+    wrap_list: impl Fn(Box<dyn TemplateProperty<L::Context, Output = Vec<O>> + 'a>) -> L::Property,
 ) -> TemplateParseResult<L::Property>
 where
     L: TemplateLanguage<'a>,
@@ -617,12 +835,78 @@ where
 {
     let property = match function.name {
         // No "join"
+        "len" => {
+            template_parser::expect_no_arguments(function)?;
+            language.wrap_integer(TemplateFunction::new(self_property, |items| {
+                i64::try_from(items.len()).unwrap()
+            }))
+        }
+        "first" => {
+            let [count_node] = template_parser::expect_exact_arguments(function)?;
+            let count = expect_integer_expression(language, build_ctx, count_node)?;
+            wrap_list(Box::new(TemplatePropertyFn(move |context: &L::Context| {
+                let count = count.extract(context).try_into().unwrap_or(0);
+                let mut items = self_property.extract(context);
+                items.truncate(count);
+                items
+            })))
+        }
+        "filter" => {
+            let item_placeholder = PropertyPlaceholder::new();
+            let [lambda_node] = template_parser::expect_exact_arguments(function)?;
+            let predicate = build_item_lambda(
+                build_ctx,
+                lambda_node,
+                &item_placeholder,
+                wrap_item,
+                |build_ctx, body| expect_boolean_expression(language, build_ctx, body),
+            )?;
+            wrap_list(Box::new(TemplatePropertyFn(move |context: &L::Context| {
+                self_property
+                    .extract(context)
+                    .into_iter()
+                    .filter(|item| {
+                        item_placeholder.with_value(item.clone(), || predicate.extract(context))
+                    })
+                    .collect_vec()
+            })))
+        }
         "map" => build_map_operation(language, build_ctx, self_property, function, wrap_item)?,
         _ => return Err(TemplateParseError::no_such_method("List", function)),
     };
     Ok(property)
 }
 
+/// Builds the body of lambda which takes a list item as the parameter.
+///
+/// The item is bound to `item_placeholder`, which must be set before
+/// evaluating the built expression.
+fn build_item_lambda<'a, P, O, R>(
+    build_ctx: &BuildContext<P>,
+    lambda_node: &ExpressionNode,
+    item_placeholder: &PropertyPlaceholder<O>,
+    wrap_item: impl Fn(PropertyPlaceholder<O>) -> P,
+    build_body: impl FnOnce(&BuildContext<P>, &ExpressionNode) -> TemplateParseResult<R>,
+) -> TemplateParseResult<R>
+where
+    O: Clone + 'a,
+{
+    template_parser::expect_lambda_with(lambda_node, |lambda, _span| {
+        let item_fn = || wrap_item(item_placeholder.clone());
+        let mut local_variables = build_ctx.local_variables.clone();
+        if let [name] = lambda.params.as_slice() {
+            local_variables.insert(name, &item_fn);
+        } else {
+            return Err(TemplateParseError::unexpected_expression(
+                "Expected 1 lambda parameters",
+                lambda.params_span,
+            ));
+        }
+        let build_ctx = BuildContext { local_variables };
+        build_body(&build_ctx, &lambda.body)
+    })
+}
+
 /// Builds expression that extracts iterable property and applies template to
 /// each item.
 ///
@@ -648,20 +932,13 @@ where
     // also invalid to convert &C to &(C, _).
     let [lambda_node] = template_parser::expect_exact_arguments(function)?;
     let item_placeholder = PropertyPlaceholder::new();
-    let item_template = template_parser::expect_lambda_with(lambda_node, |lambda, _span| {
-        let item_fn = || wrap_item(item_placeholder.clone());
-        let mut local_variables = build_ctx.local_variables.clone();
-        if let [name] = lambda.params.as_slice() {
-            local_variables.insert(name, &item_fn);
-        } else {
-            return Err(TemplateParseError::unexpected_expression(
-                "Expected 1 lambda parameters",
-                lambda.params_span,
-            ));
-        }
-        let build_ctx = BuildContext { local_variables };
-        expect_template_expression(language, &build_ctx, &lambda.body)
-    })?;
+    let item_template = build_item_lambda(
+        build_ctx,
+        lambda_node,
+        &item_placeholder,
+        wrap_item,
+        |build_ctx, body| expect_template_expression(language, build_ctx, body),
+    )?;
     let list_template = ListPropertyTemplate::new(
         self_property,
         Literal(" "), // separator
@@ -672,6 +949,16 @@ where
     Ok(language.wrap_list_template(Box::new(list_template)))
 }
 
+/// Compiles the regex once at parse time, which is why it has to be a string
+/// literal.
+fn expect_regex_literal(node: &ExpressionNode) -> TemplateParseResult<Regex> {
+    template_parser::expect_string_literal_with(node, |text, span| {
+        Regex::new(text).map_err(|err| {
+            TemplateParseError::unexpected_expression(format!("Invalid regex: {err}"), span)
+        })
+    })
+}
+
 fn build_global_function<'a, L: TemplateLanguage<'a>>(
     language: &L,
     build_ctx: &BuildContext<L::Property>,
@@ -688,6 +975,57 @@ fn build_global_function<'a, L: TemplateLanguage<'a>>(
             });
             language.wrap_template(Box::new(template))
         }
+        "wrap" => {
+            let [width_node, content_node] = template_parser::expect_exact_arguments(function)?;
+            let width = expect_integer_expression(language, build_ctx, width_node)?;
+            let content = expect_template_expression(language, build_ctx, content_node)?;
+            let available_width = language
+                .available_width()
+                .cloned()
+                .unwrap_or_else(|| AvailableWidth::new(None));
+            let template = ReformatTemplate::new(content, move |context, formatter, recorded| {
+                let width = match width.extract(context) {
+                    0 => available_width.get(),
+                    width => width.try_into().unwrap_or(0),
+                };
+                text_util::write_refilled(formatter, recorded, width)
+            });
+            language.wrap_template(Box::new(template))
+        }
+        "config" => {
+            let [name_node] = template_parser::expect_exact_arguments(function)?;
+            let name = template_parser::expect_string_literal_with(name_node, |name, span| {
+                if ALLOWED_CONFIG_PREFIXES.iter().any(|p| name.starts_with(p)) {
+                    Ok(name)
+                } else {
+                    Err(TemplateParseError::unexpected_expression(
+                        format!(r#"Config "{name}" cannot be read by templates"#),
+                        span,
+                    ))
+                }
+            })?;
+            // Unset or non-scalar values evaluate to an empty string.
+            let value = language
+                .config()
+                .and_then(|config| config.get_string(name).ok())
+                .unwrap_or_default();
+            language.wrap_string(Literal(value))
+        }
+        "env" => {
+            let [name_node] = template_parser::expect_exact_arguments(function)?;
+            let name = template_parser::expect_string_literal_with(name_node, |name, span| {
+                if ALLOWED_ENV_VARS.contains(&name) {
+                    Ok(name)
+                } else {
+                    Err(TemplateParseError::unexpected_expression(
+                        format!(r#"Environment variable "{name}" cannot be read by templates"#),
+                        span,
+                    ))
+                }
+            })?;
+            let value = env::var(name).unwrap_or_default();
+            language.wrap_string(Literal(value))
+        }
         "indent" => {
             let [prefix_node, content_node] = template_parser::expect_exact_arguments(function)?;
             let prefix = expect_template_expression(language, build_ctx, prefix_node)?;
@@ -714,6 +1052,12 @@ fn build_global_function<'a, L: TemplateLanguage<'a>>(
             });
             language.wrap_template(Box::new(LabelTemplate::new(content, labels)))
         }
+        "hyperlink" => {
+            let [url_node, content_node] = template_parser::expect_exact_arguments(function)?;
+            let url = expect_plain_text_expression(language, build_ctx, url_node)?;
+            let content = expect_template_expression(language, build_ctx, content_node)?;
+            language.wrap_template(Box::new(HyperlinkTemplate::new(content, url)))
+        }
         "if" => {
             let ([condition_node, true_node], [false_node]) =
                 template_parser::expect_arguments(function)?;
@@ -743,6 +1087,31 @@ fn build_global_function<'a, L: TemplateLanguage<'a>>(
                 .try_collect()?;
             language.wrap_template(Box::new(SeparateTemplate::new(separator, contents)))
         }
+        "table" => {
+            let ([first_node], other_nodes) = template_parser::expect_some_arguments(function)?;
+            let cells = [first_node]
+                .into_iter()
+                .chain(other_nodes)
+                .map(|node| expect_template_expression(language, build_ctx, node))
+                .try_collect()?;
+            language.wrap_template(Box::new(TableRowTemplate::new(cells)))
+        }
+        "surround" => {
+            let [prefix_node, suffix_node, content_node] =
+                template_parser::expect_exact_arguments(function)?;
+            let prefix = expect_template_expression(language, build_ctx, prefix_node)?;
+            let suffix = expect_template_expression(language, build_ctx, suffix_node)?;
+            let content = expect_template_expression(language, build_ctx, content_node)?;
+            let template = ReformatTemplate::new(content, move |context, formatter, recorded| {
+                if recorded.data().is_empty() {
+                    return Ok(());
+                }
+                prefix.format(context, formatter)?;
+                recorded.replay(formatter)?;
+                suffix.format(context, formatter)
+            });
+            language.wrap_template(Box::new(template))
+        }
         _ => return Err(TemplateParseError::no_such_function(function)),
     };
     Ok(Expression::unlabeled(property))
@@ -761,7 +1130,9 @@ pub fn build_expression<'a, L: TemplateLanguage<'a>>(
                 Ok(Expression::unlabeled(make()))
             } else {
                 let property = language.build_keyword(name, node.span)?;
-                Ok(Expression::with_label(property, *name))
+                let mut expression = Expression::with_label(property, *name);
+                expression.dynamic_labels = language.build_keyword_labels(name);
+                Ok(expression)
             }
         }
         ExpressionKind::Boolean(value) => {
@@ -776,6 +1147,40 @@ pub fn build_expression<'a, L: TemplateLanguage<'a>>(
             let property = language.wrap_string(Literal(value.clone()));
             Ok(Expression::unlabeled(property))
         }
+        ExpressionKind::Binary(op, lhs_node, rhs_node) => {
+            let lhs = expect_integer_expression(language, build_ctx, lhs_node)?;
+            let rhs = expect_integer_expression(language, build_ctx, rhs_node)?;
+            let operands = (lhs, rhs);
+            let property = match op {
+                BinaryOp::Add => language
+                    .wrap_integer(TemplateFunction::new(operands, |(l, r)| {
+                        l.saturating_add(r)
+                    })),
+                BinaryOp::Sub => language
+                    .wrap_integer(TemplateFunction::new(operands, |(l, r)| {
+                        l.saturating_sub(r)
+                    })),
+                BinaryOp::Eq => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l == r))
+                }
+                BinaryOp::Ne => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l != r))
+                }
+                BinaryOp::Ge => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l >= r))
+                }
+                BinaryOp::Gt => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l > r))
+                }
+                BinaryOp::Le => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l <= r))
+                }
+                BinaryOp::Lt => {
+                    language.wrap_boolean(TemplateFunction::new(operands, |(l, r)| l < r))
+                }
+            };
+            Ok(Expression::unlabeled(property))
+        }
         ExpressionKind::Concat(nodes) => {
             let templates = nodes
                 .iter()
@@ -805,7 +1210,8 @@ pub fn build<'a, L: TemplateLanguage<'a>>(
     let build_ctx = BuildContext {
         local_variables: HashMap::new(),
     };
-    expect_template_expression(language, &build_ctx, node)
+    let template = expect_template_expression(language, &build_ctx, node)?;
+    Ok(Box::new(RenderSpanTemplate(template)))
 }
 
 pub fn expect_boolean_expression<'a, L: TemplateLanguage<'a>>(
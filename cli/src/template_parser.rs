@@ -17,7 +17,9 @@ use std::num::ParseIntError;
 use std::{error, fmt};
 
 use itertools::Itertools as _;
+use once_cell::sync::Lazy;
 use pest::iterators::{Pair, Pairs};
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
 use pest_derive::Parser;
 use thiserror::Error;
@@ -26,6 +28,43 @@ use thiserror::Error;
 #[grammar = "template.pest"]
 struct TemplateParser;
 
+impl Rule {
+    fn to_symbol(self) -> Option<&'static str> {
+        match self {
+            Rule::EOI => None,
+            Rule::whitespace => None,
+            Rule::escape => None,
+            Rule::literal_char => None,
+            Rule::raw_literal => None,
+            Rule::literal => None,
+            Rule::integer_literal => None,
+            Rule::identifier => None,
+            Rule::function => None,
+            Rule::function_arguments => None,
+            Rule::lambda => None,
+            Rule::formal_parameters => None,
+            Rule::conditional => None,
+            Rule::primary => None,
+            Rule::term => None,
+            Rule::add_op => Some("+"),
+            Rule::sub_op => Some("-"),
+            Rule::eq_op => Some("=="),
+            Rule::ne_op => Some("!="),
+            Rule::ge_op => Some(">="),
+            Rule::gt_op => Some(">"),
+            Rule::le_op => Some("<="),
+            Rule::lt_op => Some("<"),
+            Rule::infix_op => None,
+            Rule::expression => None,
+            Rule::concat => None,
+            Rule::template => None,
+            Rule::program => None,
+            Rule::function_alias_declaration => None,
+            Rule::alias_declaration => None,
+        }
+    }
+}
+
 pub type TemplateParseResult<T> = Result<T, TemplateParseError>;
 
 #[derive(Clone, Debug)]
@@ -153,12 +192,20 @@ impl From<pest::error::Error<Rule>> for TemplateParseError {
     fn from(err: pest::error::Error<Rule>) -> Self {
         TemplateParseError {
             kind: TemplateParseErrorKind::SyntaxError,
-            pest_error: Box::new(err),
+            pest_error: Box::new(rename_rules_in_pest_error(err)),
             origin: None,
         }
     }
 }
 
+fn rename_rules_in_pest_error(err: pest::error::Error<Rule>) -> pest::error::Error<Rule> {
+    err.renamed_rules(|rule| {
+        rule.to_symbol()
+            .map(|sym| format!("`{sym}`"))
+            .unwrap_or_else(|| format!("{rule:?}"))
+    })
+}
+
 impl fmt::Display for TemplateParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.pest_error.fmt(f)
@@ -198,6 +245,7 @@ pub enum ExpressionKind<'i> {
     Boolean(bool),
     Integer(i64),
     String(String),
+    Binary(BinaryOp, Box<ExpressionNode<'i>>, Box<ExpressionNode<'i>>),
     Concat(Vec<ExpressionNode<'i>>),
     FunctionCall(FunctionCallNode<'i>),
     MethodCall(MethodCallNode<'i>),
@@ -206,6 +254,26 @@ pub enum ExpressionKind<'i> {
     AliasExpanded(TemplateAliasId<'i>, Box<ExpressionNode<'i>>),
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BinaryOp {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `>=`
+    Ge,
+    /// `>`
+    Gt,
+    /// `<=`
+    Le,
+    /// `<`
+    Lt,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionCallNode<'i> {
     pub name: &'i str,
@@ -309,6 +377,51 @@ fn parse_function_call_node(pair: Pair<Rule>) -> TemplateParseResult<FunctionCal
     })
 }
 
+/// Parses `if(..) elif(..) else(..)` chain into nested `if(..)` function calls.
+fn parse_conditional_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode> {
+    assert_eq!(pair.as_rule(), Rule::conditional);
+    let end_pos = pair.as_span().end_pos();
+    let mut clauses: Vec<_> = pair
+        .into_inner()
+        .map(|clause| {
+            let span = clause.as_span();
+            parse_function_call_node(clause).map(|function| (function, span))
+        })
+        .try_collect()?;
+    if clauses.len() == 1 {
+        let (function, span) = clauses.pop().unwrap();
+        return Ok(ExpressionNode::new(
+            ExpressionKind::FunctionCall(function),
+            span,
+        ));
+    }
+
+    let mut else_node = None;
+    if clauses.last().unwrap().0.name == "else" {
+        let (function, _) = clauses.pop().unwrap();
+        let [node] = expect_exact_arguments(&function)?;
+        else_node = Some(node.clone());
+    }
+    for (function, span) in clauses.into_iter().rev() {
+        let [condition_node, true_node] = expect_exact_arguments(&function)?;
+        let args = [condition_node.clone(), true_node.clone()]
+            .into_iter()
+            .chain(else_node.take())
+            .collect();
+        let function = FunctionCallNode {
+            name: "if",
+            name_span: function.name_span,
+            args,
+            args_span: function.args_span,
+        };
+        else_node = Some(ExpressionNode::new(
+            ExpressionKind::FunctionCall(function),
+            span.start_pos().span(&end_pos),
+        ));
+    }
+    Ok(else_node.unwrap())
+}
+
 fn parse_lambda_node(pair: Pair<Rule>) -> TemplateParseResult<LambdaNode> {
     assert_eq!(pair.as_rule(), Rule::lambda);
     let mut inner = pair.into_inner();
@@ -341,6 +454,7 @@ fn parse_term_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode> {
             ExpressionNode::new(ExpressionKind::Integer(value), span)
         }
         Rule::identifier => ExpressionNode::new(parse_identifier_or_literal(expr), span),
+        Rule::conditional => parse_conditional_node(expr)?,
         Rule::function => {
             let function = parse_function_call_node(expr)?;
             ExpressionNode::new(ExpressionKind::FunctionCall(function), span)
@@ -366,11 +480,46 @@ fn parse_term_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode> {
     })
 }
 
+fn parse_expression_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode> {
+    assert_eq!(pair.as_rule(), Rule::expression);
+    static PRATT: Lazy<PrattParser<Rule>> = Lazy::new(|| {
+        PrattParser::new()
+            .op(Op::infix(Rule::eq_op, Assoc::Left)
+                | Op::infix(Rule::ne_op, Assoc::Left)
+                | Op::infix(Rule::ge_op, Assoc::Left)
+                | Op::infix(Rule::gt_op, Assoc::Left)
+                | Op::infix(Rule::le_op, Assoc::Left)
+                | Op::infix(Rule::lt_op, Assoc::Left))
+            .op(Op::infix(Rule::add_op, Assoc::Left) | Op::infix(Rule::sub_op, Assoc::Left))
+    });
+    PRATT
+        .map_primary(parse_term_node)
+        .map_infix(|lhs, op, rhs| {
+            let op_kind = match op.as_rule() {
+                Rule::add_op => BinaryOp::Add,
+                Rule::sub_op => BinaryOp::Sub,
+                Rule::eq_op => BinaryOp::Eq,
+                Rule::ne_op => BinaryOp::Ne,
+                Rule::ge_op => BinaryOp::Ge,
+                Rule::gt_op => BinaryOp::Gt,
+                Rule::le_op => BinaryOp::Le,
+                Rule::lt_op => BinaryOp::Lt,
+                r => panic!("unexpected infix operator rule {r:?}"),
+            };
+            let lhs = Box::new(lhs?);
+            let rhs = Box::new(rhs?);
+            let span = lhs.span.start_pos().span(&rhs.span.end_pos());
+            let expr = ExpressionKind::Binary(op_kind, lhs, rhs);
+            Ok(ExpressionNode::new(expr, span))
+        })
+        .parse(pair.into_inner())
+}
+
 fn parse_template_node(pair: Pair<Rule>) -> TemplateParseResult<ExpressionNode> {
     assert_eq!(pair.as_rule(), Rule::template);
     let span = pair.as_span();
     let inner = pair.into_inner();
-    let mut nodes: Vec<_> = inner.map(parse_term_node).try_collect()?;
+    let mut nodes: Vec<_> = inner.map(parse_expression_node).try_collect()?;
     if nodes.len() == 1 {
         Ok(nodes.pop().unwrap())
     } else {
@@ -570,6 +719,12 @@ pub fn expand_aliases<'i>(
             ExpressionKind::Boolean(_) | ExpressionKind::Integer(_) | ExpressionKind::String(_) => {
                 Ok(node)
             }
+            ExpressionKind::Binary(op, lhs, rhs) => {
+                let lhs = Box::new(expand_node(*lhs, state)?);
+                let rhs = Box::new(expand_node(*rhs, state)?);
+                node.kind = ExpressionKind::Binary(op, lhs, rhs);
+                Ok(node)
+            }
             ExpressionKind::Concat(nodes) => {
                 node.kind = ExpressionKind::Concat(expand_list(nodes, state)?);
                 Ok(node)
@@ -636,6 +791,83 @@ pub fn parse<'i>(
     expand_aliases(node, aliases_map)
 }
 
+/// Collects the keywords referenced by the (alias-expanded) template, in order
+/// of first appearance. Lambda parameters aren't keywords and are excluded.
+pub fn collect_keywords<'i>(node: &ExpressionNode<'i>) -> Vec<(&'i str, pest::Span<'i>)> {
+    fn collect<'i>(
+        node: &ExpressionNode<'i>,
+        locals: &[&'i str],
+        names: &mut Vec<(&'i str, pest::Span<'i>)>,
+    ) {
+        match &node.kind {
+            ExpressionKind::Identifier(name) => {
+                if !locals.contains(name) && !names.iter().any(|(n, _)| n == name) {
+                    names.push((name, node.span));
+                }
+            }
+            ExpressionKind::Boolean(_) | ExpressionKind::Integer(_) | ExpressionKind::String(_) => {
+            }
+            ExpressionKind::Binary(_, lhs, rhs) => {
+                collect(lhs, locals, names);
+                collect(rhs, locals, names);
+            }
+            ExpressionKind::Concat(nodes) => {
+                for node in nodes {
+                    collect(node, locals, names);
+                }
+            }
+            ExpressionKind::FunctionCall(function) => {
+                for arg in &function.args {
+                    collect(arg, locals, names);
+                }
+            }
+            ExpressionKind::MethodCall(method) => {
+                collect(&method.object, locals, names);
+                for arg in &method.function.args {
+                    collect(arg, locals, names);
+                }
+            }
+            ExpressionKind::Lambda(lambda) => {
+                let locals = [locals, &lambda.params].concat();
+                collect(&lambda.body, &locals, names);
+            }
+            ExpressionKind::AliasExpanded(_, node) => collect(node, locals, names),
+        }
+    }
+
+    let mut names = vec![];
+    collect(node, &[], &mut names);
+    names
+}
+
+/// Returns true if the (alias-expanded) template calls the global function
+/// `name`.
+pub fn calls_function(node: &ExpressionNode, name: &str) -> bool {
+    match &node.kind {
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::Integer(_)
+        | ExpressionKind::String(_) => false,
+        ExpressionKind::Binary(_, lhs, rhs) => {
+            calls_function(lhs, name) || calls_function(rhs, name)
+        }
+        ExpressionKind::Concat(nodes) => nodes.iter().any(|node| calls_function(node, name)),
+        ExpressionKind::FunctionCall(function) => {
+            function.name == name || function.args.iter().any(|arg| calls_function(arg, name))
+        }
+        ExpressionKind::MethodCall(method) => {
+            calls_function(&method.object, name)
+                || method
+                    .function
+                    .args
+                    .iter()
+                    .any(|arg| calls_function(arg, name))
+        }
+        ExpressionKind::Lambda(lambda) => calls_function(&lambda.body, name),
+        ExpressionKind::AliasExpanded(_, node) => calls_function(node, name),
+    }
+}
+
 pub fn expect_no_arguments(function: &FunctionCallNode) -> TemplateParseResult<()> {
     if function.args.is_empty() {
         Ok(())
@@ -702,6 +934,7 @@ pub fn expect_string_literal_with<'a, 'i, T>(
         ExpressionKind::Identifier(_)
         | ExpressionKind::Boolean(_)
         | ExpressionKind::Integer(_)
+        | ExpressionKind::Binary(..)
         | ExpressionKind::Concat(_)
         | ExpressionKind::FunctionCall(_)
         | ExpressionKind::MethodCall(_)
@@ -725,6 +958,7 @@ pub fn expect_lambda_with<'a, 'i, T>(
         | ExpressionKind::Identifier(_)
         | ExpressionKind::Boolean(_)
         | ExpressionKind::Integer(_)
+        | ExpressionKind::Binary(..)
         | ExpressionKind::Concat(_)
         | ExpressionKind::FunctionCall(_)
         | ExpressionKind::MethodCall(_) => Err(TemplateParseError::unexpected_expression(
@@ -803,6 +1037,11 @@ mod tests {
             | ExpressionKind::Boolean(_)
             | ExpressionKind::Integer(_)
             | ExpressionKind::String(_) => node.kind,
+            ExpressionKind::Binary(op, lhs, rhs) => {
+                let lhs = Box::new(normalize_tree(*lhs));
+                let rhs = Box::new(normalize_tree(*rhs));
+                ExpressionKind::Binary(op, lhs, rhs)
+            }
             ExpressionKind::Concat(nodes) => ExpressionKind::Concat(normalize_list(nodes)),
             ExpressionKind::FunctionCall(function) => {
                 ExpressionKind::FunctionCall(normalize_function_call(function))
@@ -893,6 +1132,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_conditional_syntax() {
+        // Plain if() is a function call
+        assert_matches!(
+            parse_into_kind("if(a, b, c)"),
+            Ok(ExpressionKind::FunctionCall(FunctionCallNode {
+                name: "if",
+                ..
+            }))
+        );
+
+        // elif/else chains are expanded to nested if() calls
+        assert_eq!(
+            parse_normalized("if(a, b) else(c)").unwrap(),
+            parse_normalized("if(a, b, c)").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("if(a, b)elif(c, d)  elif(e, f)\nelse(g)").unwrap(),
+            parse_normalized("if(a, b, if(c, d, if(e, f, g)))").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("if(a, b) elif(c, d)").unwrap(),
+            parse_normalized("if(a, b, if(c, d))").unwrap(),
+        );
+
+        // Binding
+        assert_eq!(
+            parse_normalized("x ++ if(a, b) else(c) ++ y").unwrap(),
+            parse_normalized("x ++ (if(a, b, c)) ++ y").unwrap(),
+        );
+        assert_eq!(
+            parse_normalized("if(a, b) else(c).upper()").unwrap(),
+            parse_normalized("(if(a, b, c)).upper()").unwrap(),
+        );
+
+        // Clauses must be in order
+        assert!(parse_template("else(a)").is_ok());
+        assert!(parse_template("if(a, b) else(c) elif(d, e)").is_err());
+        assert!(parse_template("if(a, b) else(c) else(d)").is_err());
+        assert!(parse_template("x elif(a, b)").is_err());
+
+        // if()/elif() in a chain take exactly 2 arguments, else() takes 1
+        assert_matches!(
+            parse_into_kind("if(a, b, c) else(d)"),
+            Err(TemplateParseErrorKind::InvalidArguments { .. })
+        );
+        assert_matches!(
+            parse_into_kind("if(a, b) elif(c) else(d)"),
+            Err(TemplateParseErrorKind::InvalidArguments { .. })
+        );
+        assert_matches!(
+            parse_into_kind("if(a, b) else(c, d)"),
+            Err(TemplateParseErrorKind::InvalidArguments { .. })
+        );
+    }
+
     #[test]
     fn test_lambda_syntax() {
         fn unwrap_lambda(node: ExpressionNode<'_>) -> LambdaNode<'_> {
@@ -945,6 +1240,53 @@ mod tests {
         assert!(parse_template("|false| a").is_err());
     }
 
+    #[test]
+    fn test_binary_operator_syntax() {
+        assert_matches!(
+            parse_into_kind("1 + 2"),
+            Ok(ExpressionKind::Binary(BinaryOp::Add, _, _))
+        );
+        assert_matches!(
+            parse_into_kind("x-1"),
+            Ok(ExpressionKind::Binary(BinaryOp::Sub, _, _))
+        );
+        assert_matches!(
+            parse_into_kind("x != y"),
+            Ok(ExpressionKind::Binary(BinaryOp::Ne, _, _))
+        );
+        // "++" is a concatenation, not an addition of a positive number
+        assert_matches!(parse_into_kind("1 ++ 2"), Ok(ExpressionKind::Concat(_)));
+        assert_matches!(
+            parse_into_kind("1 - -2"),
+            Ok(ExpressionKind::Binary(BinaryOp::Sub, _, _))
+        );
+        assert_eq!(
+            parse_into_kind("1 + + 2"),
+            Err(TemplateParseErrorKind::SyntaxError)
+        );
+
+        // Arithmetic operators bind tighter than comparison operators
+        assert_eq!(
+            parse_normalized("x + 1 > y - 1").unwrap(),
+            parse_normalized("(x + 1) > (y - 1)").unwrap(),
+        );
+        // Operators are left-associative
+        assert_eq!(
+            parse_normalized("x - 1 - 2").unwrap(),
+            parse_normalized("(x - 1) - 2").unwrap(),
+        );
+        // Operators bind tighter than concatenation
+        assert_eq!(
+            parse_normalized(r#""a" ++ x + 1 ++ "b""#).unwrap(),
+            parse_normalized(r#""a" ++ (x + 1) ++ "b""#).unwrap(),
+        );
+        // Method call binds tighter than operators
+        assert_eq!(
+            parse_normalized("x.len() >= 2").unwrap(),
+            parse_normalized("(x.len()) >= 2").unwrap(),
+        );
+    }
+
     #[test]
     fn test_keyword_literal() {
         assert_eq!(parse_into_kind("false"), Ok(ExpressionKind::Boolean(false)));
@@ -1214,4 +1556,42 @@ mod tests {
             TemplateParseErrorKind::BadAliasExpansion("F()".to_owned()),
         );
     }
+
+    #[test]
+    fn test_collect_keywords() {
+        fn collect(aliases: &WithTemplateAliasesMap, template_text: &str) -> Vec<String> {
+            let node = aliases.parse(template_text).unwrap();
+            let keywords = collect_keywords(&node);
+            keywords.iter().map(|(name, _)| name.to_string()).collect()
+        }
+        let no_aliases = with_aliases([] as [(&str, &str); 0]);
+        assert_eq!(
+            collect(&no_aliases, r#"label(if(b, "x"), a.f(c) ++ b ++ "d")"#),
+            ["b", "a", "c"]
+        );
+        // Lambda parameters aren't keywords
+        assert_eq!(
+            collect(&no_aliases, "a.map(|x| x.f(b) ++ y)"),
+            ["a", "b", "y"]
+        );
+        // Aliases are expanded
+        assert_eq!(
+            collect(&with_aliases([("F(x)", "x ++ a")]), "F(b)"),
+            ["b", "a"]
+        );
+    }
+
+    #[test]
+    fn test_calls_function() {
+        let calls = |aliases: &WithTemplateAliasesMap, template_text: &str| {
+            let node = aliases.parse(template_text).unwrap();
+            calls_function(&node, "table")
+        };
+        let no_aliases = with_aliases([] as [(&str, &str); 0]);
+        assert!(calls(&no_aliases, r#"label("x", table(a, b))"#));
+        assert!(calls(&no_aliases, "a.map(|x| table(x))"));
+        assert!(!calls(&no_aliases, "a.table() ++ table"));
+        // Aliases are expanded
+        assert!(calls(&with_aliases([("F(x)", "table(x)")]), "F(a)"));
+    }
 }
@@ -70,6 +70,16 @@ impl Template<()> for Signature {
     }
 }
 
+/// Email address part of a signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Email(pub String);
+
+impl Template<()> for Email {
+    fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
+        formatter.write_str(&self.0)
+    }
+}
+
 impl Template<()> for String {
     fn format(&self, _: &(), formatter: &mut dyn Formatter) -> io::Result<()> {
         formatter.write_str(self)
@@ -169,6 +179,38 @@ where
     }
 }
 
+/// Renders the content as a hyperlink to the URL. If the URL is empty, the
+/// content is rendered as is.
+pub struct HyperlinkTemplate<T, U> {
+    content: T,
+    url: U,
+}
+
+impl<T, U> HyperlinkTemplate<T, U> {
+    pub fn new<C>(content: T, url: U) -> Self
+    where
+        T: Template<C>,
+        U: TemplateProperty<C, Output = String>,
+    {
+        HyperlinkTemplate { content, url }
+    }
+}
+
+impl<C, T, U> Template<C> for HyperlinkTemplate<T, U>
+where
+    T: Template<C>,
+    U: TemplateProperty<C, Output = String>,
+{
+    fn format(&self, context: &C, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let url = self.url.extract(context);
+        if url.is_empty() {
+            self.content.format(context, formatter)
+        } else {
+            formatter.with_hyperlink(&url, |formatter| self.content.format(context, formatter))
+        }
+    }
+}
+
 pub struct ConcatTemplate<T>(pub Vec<T>);
 
 impl<C, T: Template<C>> Template<C> for ConcatTemplate<T> {
@@ -208,6 +250,49 @@ where
     }
 }
 
+/// Renders the whole template within a tracing span.
+pub struct RenderSpanTemplate<T>(pub T);
+
+impl<C, T: Template<C>> Template<C> for RenderSpanTemplate<T> {
+    fn format(&self, context: &C, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let _span = tracing::info_span!("render_template").entered();
+        self.0.format(context, formatter)
+    }
+}
+
+/// Renders the contents as a row of cells, which are aligned into columns with
+/// the other rows if the formatter supports it.
+pub struct TableRowTemplate<T> {
+    cells: Vec<T>,
+}
+
+impl<T> TableRowTemplate<T> {
+    pub fn new<C>(cells: Vec<T>) -> Self
+    where
+        T: Template<C>,
+    {
+        TableRowTemplate { cells }
+    }
+}
+
+impl<C, T> Template<C> for TableRowTemplate<T>
+where
+    T: Template<C>,
+{
+    fn format(&self, context: &C, formatter: &mut dyn Formatter) -> io::Result<()> {
+        let cells = self
+            .cells
+            .iter()
+            .map(|template| {
+                let mut recorder = FormatRecorder::new();
+                template.format(context, &mut recorder)?;
+                Ok(recorder)
+            })
+            .collect::<io::Result<_>>()?;
+        formatter.write_table_row(cells)
+    }
+}
+
 /// Like `ConcatTemplate`, but inserts a separator between non-empty templates.
 pub struct SeparateTemplate<S, T> {
     separator: S,
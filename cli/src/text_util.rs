@@ -64,6 +64,36 @@ pub fn elide_start<'a>(
     (Cow::Owned([ellipsis, text].concat()), concat_width)
 }
 
+/// Shortens `text` to `max_width` by removing trailing characters, returning
+/// `(truncated_text, width)`.
+pub fn truncate_end(text: &str, max_width: usize) -> (&str, usize) {
+    let mut acc_width = 0;
+    for (i, c) in text.char_indices() {
+        let new_width = acc_width + c.width().unwrap_or(0);
+        if new_width > max_width {
+            return (&text[..i], acc_width);
+        }
+        acc_width = new_width;
+    }
+    (text, acc_width)
+}
+
+/// Pads `text` with spaces at start so it is at least `width` columns wide.
+pub fn pad_start(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(str_width(text));
+    [&" ".repeat(fill), text].concat()
+}
+
+/// Pads `text` with spaces at end so it is at least `width` columns wide.
+pub fn pad_end(text: &str, width: usize) -> String {
+    let fill = width.saturating_sub(str_width(text));
+    [text, &" ".repeat(fill)].concat()
+}
+
+fn str_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
 /// Shortens `text` to `max_width` by removing leading characters, returning
 /// `(start_index, width)`.
 ///
@@ -230,7 +260,63 @@ pub fn write_wrapped(
     recorded_content: &FormatRecorder,
     width: usize,
 ) -> io::Result<()> {
-    let data = recorded_content.data();
+    write_wrapped_data(formatter, recorded_content, recorded_content.data(), width)
+}
+
+/// Joins the lines of each paragraph, and wraps them at the given width
+/// preserving labels.
+///
+/// Paragraphs are separated by blank lines.
+pub fn write_refilled(
+    formatter: &mut dyn Formatter,
+    recorded_content: &FormatRecorder,
+    width: usize,
+) -> io::Result<()> {
+    let data = join_paragraph_lines(recorded_content.data());
+    write_wrapped_data(formatter, recorded_content, &data, width)
+}
+
+/// Replaces line breaks within paragraphs with spaces. Lines starting with a
+/// list marker are kept separate. The returned data has the same length as
+/// the input so the recorded ranges still apply.
+fn join_paragraph_lines(data: &[u8]) -> Vec<u8> {
+    let mut joined = data.to_vec();
+    for i in 1..data.len().saturating_sub(1) {
+        if data[i] == b'\n'
+            && data[i - 1] != b'\n'
+            && data[i + 1] != b'\n'
+            && !starts_with_list_marker(&data[i + 1..])
+        {
+            joined[i] = b' ';
+        }
+    }
+    joined
+}
+
+/// Returns true if the line starts with a bullet (`-`, `*`, `+`) or a number
+/// (`1.`, `1)`) followed by a space, optionally indented.
+fn starts_with_list_marker(line: &[u8]) -> bool {
+    let line = &line[line.iter().take_while(|&&b| b == b' ').count()..];
+    let marker_len = match line.first() {
+        Some(b'-' | b'*' | b'+') => 1,
+        Some(b'0'..=b'9') => {
+            let digits = line.iter().take_while(|b| b.is_ascii_digit()).count();
+            match line.get(digits) {
+                Some(b'.' | b')') => digits + 1,
+                _ => return false,
+            }
+        }
+        _ => return false,
+    };
+    line.get(marker_len) == Some(&b' ')
+}
+
+fn write_wrapped_data(
+    formatter: &mut dyn Formatter,
+    recorded_content: &FormatRecorder,
+    data: &[u8],
+    width: usize,
+) -> io::Result<()> {
     let mut line_ranges = wrap_bytes(data, width)
         .into_iter()
         .map(|line| {
@@ -285,6 +371,34 @@ mod tests {
         String::from_utf8(output).unwrap()
     }
 
+    #[test]
+    fn test_truncate_end() {
+        assert_eq!(truncate_end("", 1), ("", 0));
+        assert_eq!(truncate_end("abcdef", 6), ("abcdef", 6));
+        assert_eq!(truncate_end("abcdef", 10), ("abcdef", 6));
+        assert_eq!(truncate_end("abcdef", 5), ("abcde", 5));
+        assert_eq!(truncate_end("abcdef", 0), ("", 0));
+
+        // East Asian characters (char.width() == 2)
+        assert_eq!(truncate_end("一二三", 6), ("一二三", 6));
+        assert_eq!(truncate_end("一二三", 5), ("一二", 4));
+        assert_eq!(truncate_end("一二三", 1), ("", 0));
+
+        // Decomposed character at the end is kept with its base character
+        assert_eq!(truncate_end("ab\u{300}c", 2), ("ab\u{300}", 2));
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad_start("", 2), "  ");
+        assert_eq!(pad_start("ab", 4), "  ab");
+        assert_eq!(pad_start("abcdef", 4), "abcdef");
+        assert_eq!(pad_end("ab", 4), "ab  ");
+        assert_eq!(pad_end("abcdef", 4), "abcdef");
+        assert_eq!(pad_end("一二", 5), "一二 ");
+        assert_eq!(pad_start("一二", 5), " 一二");
+    }
+
     #[test]
     fn test_elide_start() {
         // Empty string
@@ -627,4 +741,40 @@ mod tests {
             "foo\n",
         );
     }
+
+    #[test]
+    fn test_write_refilled() {
+        let mut recorder = FormatRecorder::new();
+        recorder.push_label("red").unwrap();
+        recorder.write_str("foo bar\nbaz").unwrap();
+        recorder.pop_label().unwrap();
+        recorder.push_label("cyan").unwrap();
+        recorder.write_str(" qux\n\nquux\n").unwrap();
+        recorder.pop_label().unwrap();
+        insta::assert_snapshot!(
+            format_colored(|formatter| write_refilled(formatter, &recorder, 11)),
+            @r###"
+        [38;5;1mfoo bar baz[39m[38;5;6m[39m
+        [38;5;6mqux[39m
+        [38;5;6m[39m
+        [38;5;6mquux[39m
+        "###
+        );
+        assert_eq!(
+            format_plain_text(|formatter| write_refilled(formatter, &recorder, 20)),
+            "foo bar baz qux\n\nquux\n",
+        );
+    }
+
+    #[test]
+    fn test_write_refilled_list() {
+        let mut recorder = FormatRecorder::new();
+        recorder
+            .write_str("Items:\n- foo\nbar\n* baz\n  + qux\n1. quux\n10) corge\n-grault\n")
+            .unwrap();
+        assert_eq!(
+            format_plain_text(|formatter| write_refilled(formatter, &recorder, 80)),
+            "Items:\n- foo bar\n* baz\n  + qux\n1. quux\n10) corge -grault\n",
+        );
+    }
 }
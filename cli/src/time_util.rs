@@ -1,5 +1,5 @@
 use chrono::format::StrftimeItems;
-use chrono::{DateTime, FixedOffset, LocalResult, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, Local, LocalResult, TimeZone, Utc};
 use jj_lib::backend::Timestamp;
 use once_cell::sync::Lazy;
 
@@ -61,6 +61,20 @@ fn datetime_from_timestamp(context: &Timestamp) -> Option<DateTime<FixedOffset>>
     )
 }
 
+/// Converts the timestamp to the local time zone.
+///
+/// On Unix, the local time zone can be overridden by the `TZ` environment
+/// variable.
+pub fn to_local_timestamp(timestamp: &Timestamp) -> Timestamp {
+    match datetime_from_timestamp(timestamp) {
+        Some(datetime) => Timestamp {
+            timestamp: timestamp.timestamp.clone(),
+            tz_offset: datetime.with_timezone(&Local).offset().local_minus_utc() / 60,
+        },
+        None => timestamp.clone(),
+    }
+}
+
 pub fn format_absolute_timestamp(timestamp: &Timestamp) -> String {
     static DEFAULT_FORMAT: Lazy<FormattingItems> =
         Lazy::new(|| FormattingItems::parse("%Y-%m-%d %H:%M:%S.%3f %:z").unwrap());
@@ -83,5 +97,13 @@ pub fn format_duration(from: &Timestamp, to: &Timestamp, format: &timeago::Forma
 }
 
 pub fn format_timestamp_relative_to_now(timestamp: &Timestamp) -> String {
-    format_duration(timestamp, &Timestamp::now(), &timeago::Formatter::new())
+    let now = Timestamp::now();
+    if timestamp.timestamp > now.timestamp {
+        // The timestamp may be in the future if e.g. the clocks are out of sync.
+        let mut format = timeago::Formatter::new();
+        format.ago("");
+        format!("in {}", format_duration(&now, timestamp, &format))
+    } else {
+        format_duration(timestamp, &now, &timeago::Formatter::new())
+    }
 }
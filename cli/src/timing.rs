@@ -0,0 +1,218 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collects the time spent in a few well-known tracing spans, which is
+//! reported by `--debug-timing`.
+
+use std::io;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::span;
+use tracing::subscriber::Interest;
+use tracing::Metadata;
+use tracing_subscriber::layer::{Context, Filter, Layer};
+
+use crate::ui::Ui;
+
+/// Target (module path) and optional name of a span.
+type SpanSelector = (&'static str, Option<&'static str>);
+
+/// Spans to be accounted, identified by category.
+const CATEGORIES: &[(&str, &[SpanSelector])] = &[
+    (
+        "snapshot",
+        &[("jj_cli::cli_util", Some("snapshot_working_copy"))],
+    ),
+    (
+        "revset evaluation",
+        &[
+            ("jj_cli::cli_util", Some("evaluate_revset")),
+            ("jj_lib::default_revset_engine", Some("iter_revset")),
+        ],
+    ),
+    (
+        "template rendering",
+        &[("jj_cli::templater", Some("render_template"))],
+    ),
+    (
+        "index access",
+        &[("jj_lib::default_index_store", Some("get_index_at_op"))],
+    ),
+    ("backend I/O", &[("jj_lib::store", None)]),
+];
+
+fn category_of(metadata: &Metadata<'_>) -> Option<usize> {
+    if !metadata.is_span() {
+        return None;
+    }
+    CATEGORIES.iter().position(|(_, spans)| {
+        spans.iter().any(|&(target, name)| {
+            metadata.target() == target && name.map_or(true, |name| metadata.name() == name)
+        })
+    })
+}
+
+#[derive(Clone, Debug, Default)]
+struct CategoryTiming {
+    /// Number of entered spans. Only the outermost span is accounted so
+    /// recursive or concurrent spans aren't counted twice.
+    depth: usize,
+    entered_at: Option<Instant>,
+    total: Duration,
+    count: usize,
+}
+
+#[derive(Debug)]
+struct TimingState {
+    enabled: AtomicBool,
+    started_at: Mutex<Option<Instant>>,
+    categories: Mutex<Vec<CategoryTiming>>,
+}
+
+/// Handle to enable timing collection and report the results.
+#[derive(Clone, Debug)]
+pub struct Timing {
+    state: Arc<TimingState>,
+}
+
+impl Timing {
+    pub fn new() -> Self {
+        let state = TimingState {
+            enabled: AtomicBool::new(false),
+            started_at: Mutex::new(None),
+            categories: Mutex::new(vec![CategoryTiming::default(); CATEGORIES.len()]),
+        };
+        Timing {
+            state: Arc::new(state),
+        }
+    }
+
+    /// Creates tracing layer which records the time spent in the known spans
+    /// once enabled.
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        TimingLayer {
+            state: self.state.clone(),
+        }
+        .with_filter(TimingFilter {
+            state: self.state.clone(),
+        })
+    }
+
+    pub fn enable(&self) {
+        *self.state.started_at.lock().unwrap() = Some(Instant::now());
+        self.state.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Writes the collected timing to stderr if enabled.
+    pub fn write_report(&self, ui: &mut Ui) -> io::Result<()> {
+        if !self.state.enabled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let Some(started_at) = *self.state.started_at.lock().unwrap() else {
+            return Ok(());
+        };
+        let elapsed = started_at.elapsed();
+        let categories = self.state.categories.lock().unwrap().clone();
+        let mut output = Vec::new();
+        writeln!(output, "Timing:")?;
+        for ((name, _), timing) in CATEGORIES.iter().zip(&categories) {
+            let calls = if timing.count == 1 { "call" } else { "calls" };
+            writeln!(
+                output,
+                "  {name:<20} {:>12} ({} {calls})",
+                format_duration(timing.total),
+                timing.count
+            )?;
+        }
+        writeln!(output, "  {:<20} {:>12}", "total", format_duration(elapsed))?;
+        ui.write_stderr(&String::from_utf8(output).unwrap())
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+struct TimingFilter {
+    state: Arc<TimingState>,
+}
+
+impl<S> Filter<S> for TimingFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        self.state.enabled.load(Ordering::Relaxed) && category_of(metadata).is_some()
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if category_of(metadata).is_some() {
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+}
+
+struct TimingLayer {
+    state: Arc<TimingState>,
+}
+
+impl TimingLayer {
+    fn with_category<S>(
+        &self,
+        id: &span::Id,
+        ctx: &Context<'_, S>,
+        f: impl FnOnce(&mut CategoryTiming),
+    ) where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        let Some(index) = ctx.metadata(id).and_then(category_of) else {
+            return;
+        };
+        f(&mut self.state.categories.lock().unwrap()[index]);
+    }
+}
+
+impl<S> Layer<S> for TimingLayer
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        self.with_category(id, &ctx, |timing| timing.count += 1);
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.with_category(id, &ctx, |timing| {
+            if timing.depth == 0 {
+                timing.entered_at = Some(Instant::now());
+            }
+            timing.depth += 1;
+        });
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.with_category(id, &ctx, |timing| {
+            timing.depth = timing.depth.saturating_sub(1);
+            if timing.depth == 0 {
+                if let Some(entered_at) = timing.entered_at.take() {
+                    timing.total += entered_at.elapsed();
+                }
+            }
+        });
+    }
+}
@@ -28,8 +28,11 @@ pub struct Ui {
     pager_cmd: CommandNameAndArgs,
     paginate: PaginationChoice,
     progress_indicator: bool,
+    width: Option<u16>,
+    output_format: OutputFormat,
     formatter_factory: FormatterFactory,
     output: UiOutput,
+    discard_stdout: bool,
 }
 
 fn progress_indicator_setting(config: &config::Config) -> bool {
@@ -84,6 +87,44 @@ fn use_color(choice: ColorChoice) -> bool {
     }
 }
 
+/// Format of error messages and of `jj log` output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err("must be one of text or json"),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+        };
+        write!(f, "{s}")
+    }
+}
+
+fn output_format_setting(config: &config::Config) -> OutputFormat {
+    config
+        .get_string("ui.output")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default()
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
 #[serde(rename_all(deserialize = "kebab-case"))]
 pub enum PaginationChoice {
@@ -98,6 +139,25 @@ fn pagination_setting(config: &config::Config) -> Result<PaginationChoice, Comma
         .map_err(|err| CommandError::ConfigError(format!("Invalid `ui.paginate`: {err:?}")))
 }
 
+fn width_setting(config: &config::Config) -> Result<Option<u16>, CommandError> {
+    match config.get::<u16>("ui.width") {
+        Ok(0) => Err(CommandError::ConfigError(
+            "Invalid `ui.width`: must be at least 1".to_string(),
+        )),
+        Ok(width) => Ok(Some(width)),
+        Err(config::ConfigError::NotFound(_)) => Ok(None),
+        Err(err) => Err(CommandError::ConfigError(format!(
+            "Invalid `ui.width`: {err:?}"
+        ))),
+    }
+}
+
+/// Returns the width to render the output at, which is `ui.width` if set, or
+/// the terminal width otherwise.
+pub fn output_width(config: &config::Config) -> Result<Option<u16>, CommandError> {
+    Ok(width_setting(config)?.or_else(term_width))
+}
+
 fn pager_setting(config: &config::Config) -> Result<CommandNameAndArgs, CommandError> {
     config
         .get::<CommandNameAndArgs>("ui.pager")
@@ -118,7 +178,10 @@ impl Ui {
             pager_cmd: pager_setting(config)?,
             paginate: pagination_setting(config)?,
             progress_indicator,
+            width: width_setting(config)?,
+            output_format: output_format_setting(config),
             output: UiOutput::new_terminal(),
+            discard_stdout: false,
         })
     }
 
@@ -127,6 +190,8 @@ impl Ui {
         self.paginate = pagination_setting(config)?;
         self.pager_cmd = pager_setting(config)?;
         self.progress_indicator = progress_indicator_setting(config);
+        self.width = width_setting(config)?;
+        self.output_format = output_format_setting(config);
         let sanitize = io::stdout().is_terminal();
         self.formatter_factory = FormatterFactory::prepare(config, self.color, sanitize)?;
         Ok(())
@@ -164,6 +229,16 @@ impl Ui {
         self.color
     }
 
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// Drops everything written to stdout until re-enabled. Stderr is still
+    /// written, so warnings and errors aren't lost.
+    pub fn set_discard_stdout(&mut self, discard: bool) {
+        self.discard_stdout = discard;
+    }
+
     pub fn new_formatter<'output, W: Write + 'output>(
         &self,
         output: W,
@@ -176,6 +251,9 @@ impl Ui {
     /// Labels added to the returned formatter should be removed by caller.
     /// Otherwise the last color would persist.
     pub fn stdout_formatter<'a>(&'a self) -> Box<dyn Formatter + 'a> {
+        if self.discard_stdout {
+            return self.new_formatter(io::sink());
+        }
         match &self.output {
             UiOutput::Terminal { stdout, .. } => self.new_formatter(stdout.lock()),
             UiOutput::Paged { child_stdin, .. } => self.new_formatter(child_stdin),
@@ -214,6 +292,9 @@ impl Ui {
     }
 
     pub fn write(&mut self, text: &str) -> io::Result<()> {
+        if self.discard_stdout {
+            return Ok(());
+        }
         let data = text.as_bytes();
         match &mut self.output {
             UiOutput::Terminal { stdout, .. } => stdout.write_all(data),
@@ -230,6 +311,9 @@ impl Ui {
     }
 
     pub fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
+        if self.discard_stdout {
+            return Ok(());
+        }
         match &mut self.output {
             UiOutput::Terminal { stdout, .. } => stdout.write_fmt(fmt),
             UiOutput::Paged { child_stdin, .. } => child_stdin.write_fmt(fmt),
@@ -297,8 +381,10 @@ impl Ui {
         rpassword::prompt_password(format!("{prompt}: "))
     }
 
+    /// The width to render the output at. The `ui.width` config overrides the
+    /// terminal width.
     pub fn term_width(&self) -> Option<u16> {
-        term_width()
+        self.width.or_else(term_width)
     }
 }
 
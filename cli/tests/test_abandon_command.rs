@@ -57,7 +57,9 @@ fn test_rebase_branch_with_merge() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["abandon", "d"]);
     insta::assert_snapshot!(stdout, @r###"
     Abandoned commit vruxwmqv b7c62f28 d | d
-    Rebased 1 descendant commits onto parents of abandoned commits
+    Rebased 1 descendant commits onto parents of abandoned commits:
+      znkkpsqq 11a2e10e e | e
+    Moved branch d to royxmykx fe2e8e8b c d | c
     Working copy now at: znkkpsqq 11a2e10e e | e
     Parent commit      : rlvkpnrz 2443ea76 a | a
     Parent commit      : royxmykx fe2e8e8b c d | c
@@ -78,6 +80,7 @@ fn test_rebase_branch_with_merge() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["abandon"] /* abandons `e` */);
     insta::assert_snapshot!(stdout, @r###"
     Abandoned commit znkkpsqq 5557ece3 e | e
+    Moved branch e (now conflicted)
     Working copy now at: nkmrtpmo 6b527513 (empty) (no description set)
     Parent commit      : rlvkpnrz 2443ea76 a e?? | a
     Added 0 files, modified 0 files, removed 3 files
@@ -105,6 +108,9 @@ fn test_rebase_branch_with_merge() {
       znkkpsqq 5557ece3 e | e
       vruxwmqv b7c62f28 d | d
       royxmykx fe2e8e8b c | c
+    Moved branch c to zzzzzzzz 00000000 c d e?? | (empty) (no description set)
+    Moved branch d to zzzzzzzz 00000000 c d e?? | (empty) (no description set)
+    Moved branch e (now conflicted)
     Working copy now at: xtnwkqum e7bb0612 (empty) (no description set)
     Parent commit      : rlvkpnrz 2443ea76 a e?? | a
     Added 0 files, modified 0 files, removed 3 files
@@ -122,6 +128,7 @@ fn test_rebase_branch_with_merge() {
     let stdout = test_env.jj_cmd_success(&repo_path, &["abandon", "b", "b"]);
     insta::assert_snapshot!(stdout, @r###"
     Abandoned commit zsuskuln 1394f625 b | b
+    Moved branch b to rlvkpnrz 2443ea76 a b | a
     "###);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     @    e
@@ -142,6 +149,10 @@ fn test_rebase_branch_with_merge() {
       vruxwmqv b7c62f28 d | d
       zsuskuln 1394f625 b | b
       rlvkpnrz 2443ea76 a | a
+    Moved branch a to zzzzzzzz 00000000 a b e?? | (empty) (no description set)
+    Moved branch b to zzzzzzzz 00000000 a b e?? | (empty) (no description set)
+    Moved branch d to royxmykx fe2e8e8b c d e?? | c
+    Moved branch e (now conflicted)
     Working copy now at: xlzxqlsl af874bff (empty) (no description set)
     Parent commit      : zzzzzzzz 00000000 a b e?? | (empty) (no description set)
     Added 0 files, modified 0 files, removed 4 files
@@ -154,6 +165,49 @@ fn test_rebase_branch_with_merge() {
     "###);
 }
 
+#[test]
+fn test_abandon_delete_branches() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["abandon", "b|c", "--delete-branches"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Abandoned the following commits:
+      royxmykx 7e4fbf4f c | c
+      zsuskuln 1394f625 b | b
+    Rebased 1 descendant commits onto parents of abandoned commits:
+      vruxwmqv f0ac9957 d | d
+    Deleted branch b
+    Deleted branch c
+    Working copy now at: vruxwmqv f0ac9957 d | d
+    Parent commit      : rlvkpnrz 2443ea76 a | a
+    Added 0 files, modified 0 files, removed 2 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  d
+    ◉  a
+    ◉
+    "###);
+
+    // With --summary, rebased commits are not listed
+    test_env.jj_cmd_success(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["abandon", "b", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Abandoned commit zsuskuln 1394f625 b | b
+    Rebased 2 descendant commits onto parents of abandoned commits
+    Moved branch b to rlvkpnrz 2443ea76 a b | a
+    Working copy now at: vruxwmqv 06a7c008 d | d
+    Parent commit      : royxmykx c3c483d2 c | c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     test_env.jj_cmd_success(repo_path, &["log", "-T", "branches"])
 }
@@ -541,6 +541,374 @@ fn test_branch_list_filtered_by_revset() {
     "###);
 }
 
+#[test]
+fn test_branch_list_filtered_by_state() {
+    let test_env = TestEnvironment::default();
+
+    // Initialize remote refs
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "remote", "--git"]);
+    let remote_path = test_env.env_root().join("remote");
+    test_env.jj_cmd_success(&remote_path, &["new", "root()", "-m", "remote"]);
+    test_env.jj_cmd_success(&remote_path, &["branch", "set", "remote"]);
+    test_env.jj_cmd_success(&remote_path, &["git", "export"]);
+
+    // Initialize local refs
+    let mut remote_git_path = remote_path;
+    remote_git_path.extend([".jj", "repo", "store", "git"]);
+    test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["git", "clone", remote_git_path.to_str().unwrap(), "local"],
+    );
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_success(&local_path, &["new", "root()", "-m", "local"]);
+    test_env.jj_cmd_success(&local_path, &["branch", "set", "local"]);
+    // Set a branch to different targets in concurrent operations
+    test_env.jj_cmd_success(&local_path, &["branch", "set", "conflicted", "-rremote"]);
+    test_env.jj_cmd_success(
+        &local_path,
+        &["branch", "set", "conflicted", "-r@", "--at-op=@-"],
+    );
+
+    insta::assert_snapshot!(test_env.jj_cmd_success(&local_path, &["branch", "list"]), @r###"
+    Concurrent modification detected, resolving automatically.
+    conflicted (conflicted):
+      + mulpsrsz ab4eeb11 (empty) remote
+      + royxmykx 840f12d8 (empty) local
+    local: royxmykx 840f12d8 (empty) local
+    remote: mulpsrsz ab4eeb11 (empty) remote
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&local_path, &["branch", "list", "--conflicted"]), @r###"
+    conflicted (conflicted):
+      + mulpsrsz ab4eeb11 (empty) remote
+      + royxmykx 840f12d8 (empty) local
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&local_path, &["branch", "list", "--tracked"]), @r###"
+    remote: mulpsrsz ab4eeb11 (empty) remote
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&local_path, &["branch", "list", "--untracked"]), @r###"
+    conflicted (conflicted):
+      + mulpsrsz ab4eeb11 (empty) remote
+      + royxmykx 840f12d8 (empty) local
+    local: royxmykx 840f12d8 (empty) local
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&local_path, &["branch", "list", "--untracked", "-rlocal"]), @r###"
+    conflicted (conflicted):
+      + mulpsrsz ab4eeb11 (empty) remote
+      + royxmykx 840f12d8 (empty) local
+    local: royxmykx 840f12d8 (empty) local
+    "###);
+
+    let stderr =
+        test_env.jj_cmd_cli_error(&local_path, &["branch", "list", "--tracked", "--untracked"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--tracked' cannot be used with '--untracked'
+
+    Usage: jj branch list --tracked
+
+    For more information, try '--help'.
+    "###);
+}
+
+#[test]
+fn test_branch_list_template() {
+    let test_env = TestEnvironment::default();
+
+    // Initialize remote refs
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "remote", "--git"]);
+    let remote_path = test_env.env_root().join("remote");
+    for branch in ["remote-delete", "remote-rewrite"] {
+        test_env.jj_cmd_success(&remote_path, &["new", "root()", "-m", branch]);
+        test_env.jj_cmd_success(&remote_path, &["branch", "set", branch]);
+    }
+    test_env.jj_cmd_success(&remote_path, &["new"]);
+    test_env.jj_cmd_success(&remote_path, &["git", "export"]);
+
+    // Initialize local refs
+    let mut remote_git_path = remote_path;
+    remote_git_path.extend([".jj", "repo", "store", "git"]);
+    test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["git", "clone", remote_git_path.to_str().unwrap(), "local"],
+    );
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_success(&local_path, &["new", "root()", "-m", "local"]);
+    test_env.jj_cmd_success(&local_path, &["branch", "set", "local"]);
+    test_env.jj_cmd_success(&local_path, &["branch", "delete", "remote-delete"]);
+    test_env.jj_cmd_success(&local_path, &["new", "remote-rewrite", "-m", "rewritten"]);
+    test_env.jj_cmd_success(&local_path, &["branch", "set", "remote-rewrite"]);
+    // Set a branch to different targets in concurrent operations
+    test_env.jj_cmd_success(&local_path, &["branch", "set", "conflicted", "-rlocal"]);
+    test_env.jj_cmd_success(
+        &local_path,
+        &["branch", "set", "conflicted", "-r@", "--at-op=@-"],
+    );
+
+    let template = r#"
+    separate(" ",
+      name,
+      if(present, local_target, "(deleted)"),
+      if(conflict, "(conflicted)"),
+      if(tracked, "(tracked)"),
+    ) ++ "\n" ++
+    remotes.map(|r| "  " ++ separate(" ",
+      r.remote(),
+      r.target(),
+      if(r.synced(), "synced", "ahead " ++ r.ahead_count() ++ " behind " ++ r.behind_count()),
+    ) ++ "\n")
+    "#;
+    let stdout = test_env.jj_cmd_success(&local_path, &["branch", "list", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    Concurrent modification detected, resolving automatically.
+    conflicted edcd60f71dab 35286564ae6a (conflicted)
+    local edcd60f71dab
+    remote-delete (deleted) (tracked)
+      origin f8e5d5ffd88c ahead 0 behind 0
+    remote-rewrite 35286564ae6a (tracked)
+      origin c484a0b19fc0 ahead 0 behind 1
+    "###);
+
+    // Columns are aligned across branches
+    let template = r#"table(name, local_target, remotes.len()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&local_path, &["branch", "list", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    conflicted     edcd60f71dab 35286564ae6a 0
+    local          edcd60f71dab              0
+    remote-delete                            1
+    remote-rewrite 35286564ae6a              1
+    "###);
+
+    let template = r#"
+    name ++ ": -[" ++ local_target.removed_ids().join(",") ++ "] +[" ++
+    local_target.added_ids().join(",") ++ "]\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(
+        &local_path,
+        &["branch", "list", "--conflicted", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    conflicted: -[] +[edcd60f71dab0da408aa4875633df600450246fa,35286564ae6ad5ef192a4d96d6b78bfafda022af]
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &local_path,
+        &["branch", "list", "--tracked", "-T", r#"remotes ++ "\n""#],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    remote-delete@origin
+    remote-rewrite@origin
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&local_path, &["branch", "list", "-T", "commit_id"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | commit_id
+      | ^-------^
+      |
+      = Keyword "commit_id" doesn't exist
+    "###);
+}
+
+#[test]
+fn test_branch_move() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "old1"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "a1", "-r", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "old2"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "a2", "-r", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m", "other"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "b", "-r", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m", "new"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @   6a908b4ccd5f
+    │ ◉  b ad418c092d2b
+    ├─╯
+    │ ◉  a2 12f783dab8e9
+    │ ◉  a1 1473246906ac
+    ├─╯
+    ◉   000000000000
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "branch",
+            "move",
+            "--from",
+            "description(old)",
+            "-B",
+            "--dry-run",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Would move branch a1 from 1473246906ac to 6a908b4ccd5f
+    Would move branch a2 from 12f783dab8e9 to 6a908b4ccd5f
+    Dry-run requested, not committing the operation.
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["branch", "move", "--from", "description(old)"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Refusing to move branch backwards or sideways.
+    Hint: Use --allow-backwards to allow it.
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["branch", "move", "--from", "description(old)", "-B"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  a1 a2 6a908b4ccd5f
+    │ ◉  b ad418c092d2b
+    ├─╯
+    │ ◉   12f783dab8e9
+    │ ◉   1473246906ac
+    ├─╯
+    ◉   000000000000
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["branch", "move", "--from", "description(old)"],
+    );
+    insta::assert_snapshot!(stdout, @"No branches to move.");
+}
+
+#[test]
+fn test_branch_move_to_successors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "b"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m", "c"]);
+    let op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-l1", "-T", "id.short()"],
+    );
+    // Rewrite "a" twice so that its first rewrite is hidden, and rebase "b"
+    // onto the new "a". Branches "a" and "b" are created by concurrent
+    // operations, so they are left on the old commits.
+    test_env.jj_cmd_success(&repo_path, &["describe", "description(a)", "-m", "a2"]);
+    test_env.jj_cmd_success(&repo_path, &["describe", "description(a2)", "-m", "a3"]);
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "--at-op",
+            &op_id,
+            "branch",
+            "create",
+            "a",
+            "-r",
+            "description(a)",
+        ],
+    );
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "--at-op",
+            &op_id,
+            "branch",
+            "create",
+            "b",
+            "-r",
+            "description(b)",
+        ],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    Concurrent modification detected, resolving automatically.
+    ◉   2561a729a31e
+    ◉   e4c80d636c64
+    │ @   3b2085f07d42
+    ├─╯
+    ◉   000000000000
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "branch",
+            "move",
+            "--from",
+            "all()",
+            "--to-successors",
+            "--dry-run",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Would move branch a from 4c5b3042d9e0 to e4c80d636c64
+    Would move branch b from 5f2e17b3b0e3 to 2561a729a31e
+    Dry-run requested, not committing the operation.
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["branch", "move", "--from", "all()", "--to-successors"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  b 2561a729a31e
+    ◉  a e4c80d636c64
+    │ @   3b2085f07d42
+    ├─╯
+    ◉   000000000000
+    "###);
+
+    // Divergent successors can't be followed.
+    let op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-l1", "-T", "id.short()"],
+    );
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "c2"]);
+    test_env.jj_cmd_success(&repo_path, &["--at-op", &op_id, "describe", "-m", "c3"]);
+    test_env.jj_cmd_success(&repo_path, &["--at-op", &op_id, "branch", "create", "c"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    Concurrent modification detected, resolving automatically.
+    ◉   4c9c45175a30
+    │ @   da44f6141a29
+    ├─╯
+    │ ◉  b 2561a729a31e
+    │ ◉  a e4c80d636c64
+    ├─╯
+    ◉   000000000000
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["branch", "move", "--from", "c", "--to-successors"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit 3b2085f07d42 has 2 visible successors: 4c9c45175a30, da44f6141a29
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &[
+            "branch",
+            "move",
+            "--from",
+            "a",
+            "--to",
+            "@",
+            "--to-successors",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--to <TO>' cannot be used with '--to-successors'
+
+    Usage: jj branch move --from <FROM> --to <TO>
+
+    For more information, try '--help'.
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"branches ++ " " ++ commit_id.short()"#;
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
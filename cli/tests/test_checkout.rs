@@ -14,7 +14,7 @@
 
 use std::path::Path;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, TestEnvironment};
 
 pub mod common;
 
@@ -124,8 +124,12 @@ fn test_checkout_conflicting_branches() {
     // Trigger resolution of concurrent operations
     test_env.jj_cmd_success(&repo_path, &["st"]);
 
-    let stderr = test_env.jj_cmd_failure(&repo_path, &["checkout", "foo"]);
-    insta::assert_snapshot!(stderr, @r###"
+    let assert = test_env
+        .jj_cmd(&repo_path, &["checkout", "foo"])
+        .assert()
+        .code(4)
+        .stdout("");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
     Error: Revset "foo" resolved to more than one revision
     Hint: Branch foo resolved to multiple revisions because it's conflicted.
     It resolved to these revisions:
@@ -147,8 +151,12 @@ fn test_checkout_conflicting_change_ids() {
     // Trigger resolution of concurrent operations
     test_env.jj_cmd_success(&repo_path, &["st"]);
 
-    let stderr = test_env.jj_cmd_failure(&repo_path, &["checkout", "qpvuntsm"]);
-    insta::assert_snapshot!(stderr, @r###"
+    let assert = test_env
+        .jj_cmd(&repo_path, &["checkout", "qpvuntsm"])
+        .assert()
+        .code(4)
+        .stdout("");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
     Error: Revset "qpvuntsm" resolved to more than one revision
     Hint: The revset "qpvuntsm" resolved to these revisions:
     qpvuntsm d2ae6806 (empty) two
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
 use common::TestEnvironment;
 use regex::Regex;
 
@@ -51,6 +55,20 @@ fn test_log_parents() {
     ~
     "###);
 
+    let template = r#"
+    parents.len() ++ " " ++
+    parents.filter(|c| c.empty()).first(1).map(|c| c.commit_id().short(4)) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @    2 4db4
+    ├─╮
+    ◉ │  1 230d
+    ├─╯
+    ◉  1 0000
+    ◉  0
+    "###);
+
     // Commit object isn't printable
     let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "parents"]);
     insta::assert_snapshot!(stderr, @r###"
@@ -75,6 +93,34 @@ fn test_log_parents() {
     "###);
 }
 
+#[test]
+fn test_log_children() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "@-"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "@", "@-"]);
+
+    let template =
+        r#"commit_id.short() ++ " C: " ++ children.map(|c| c.commit_id().short()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @    c067170d4ca1 C:
+    ├─╮
+    ◉ │  4db490c88528 C: c067170d4ca1
+    ├─╯
+    ◉  230dd059e1b0 C: c067170d4ca1 4db490c88528
+    ◉  000000000000 C: 230dd059e1b0
+    "###);
+
+    // Children of children
+    let template = r#"children.map(|c| c.children().len()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template, "-rroot()"]);
+    insta::assert_snapshot!(stdout, @"◉  2");
+}
+
 #[test]
 fn test_log_author_timestamp() {
     let test_env = TestEnvironment::default();
@@ -123,6 +169,118 @@ fn test_log_author_timestamp_utc() {
     "###);
 }
 
+// The local time zone can only be overridden by the TZ variable on Unix
+#[cfg(unix)]
+#[test]
+fn test_log_author_timestamp_local() {
+    let mut test_env = TestEnvironment::default();
+    test_env.add_env_var("TZ", "UTC-05:30");
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "author.timestamp().local()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  2001-02-03 02:35:07.000 +05:30
+    ◉  1970-01-01 05:30:00.000 +05:30
+    "###);
+    test_env.add_env_var("TZ", "UTC+10:00");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "author.timestamp().local()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  2001-02-02 11:05:07.000 -10:00
+    ◉  1969-12-31 14:00:00.000 -10:00
+    "###);
+
+    // Custom format in the local time zone, configured by the template alias
+    test_env.add_config(
+        r#"
+    [template-aliases]
+    'format_timestamp(timestamp)' = 'timestamp.local().format("%Y-%m-%d %H:%M")'
+    "#,
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", "format_timestamp(author.timestamp())"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  2001-02-02 11:05
+    ◉  1969-12-31 14:00
+    "###);
+}
+
+#[test]
+fn test_log_description_labels() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+    [description-labels]
+    wip = '^(WIP|wip)\b'
+    fixup = '^fixup!'
+    [colors]
+    "description wip" = "yellow"
+    "description fixup" = "red"
+    "#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "WIP: first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "fixup! first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "third"]);
+
+    // Labels are kept through method calls
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "--color=always", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    third
+    [38;5;1mfixup! first[39m
+    [38;5;3mWIP: first[39m
+
+    "###);
+
+    test_env.add_config(r#"description-labels.bad = '('"#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid regex for description label "bad": regex parse error:
+        (
+        ^
+    error: unclosed group
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
+}
+
+#[test]
+fn test_log_is_committer_same_as_author() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    // Rewrite the first commit as someone else, which only updates the committer
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "@-",
+            "-m",
+            "first (amended)",
+            "--config-toml",
+            r#"user.name="Other User""#,
+        ],
+    );
+
+    let template = r#"separate(" ", description.first_line(), author.name(), committer.name(), is_committer_same_as_author)"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second Test User Other User false
+    ◉  first (amended) Test User Other User false
+    ◉  true
+    "###);
+}
+
 #[test]
 fn test_log_default() {
     let test_env = TestEnvironment::default();
@@ -421,3 +579,489 @@ fn test_log_customize_short_id() {
     ◉  ZZZZZZZZ root() 00000000
     "###);
 }
+
+#[test]
+fn test_log_customize_short_signature() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+
+    let decl = "template-aliases.'format_short_signature(signature)'";
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--config-toml", &format!("{decl}='signature.name()'")],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  qpvuntsm Test User 2001-02-03 04:05:08.000 +07:00 69542c19
+    │  (empty) first
+    ◉  zzzzzzzz root() 00000000
+    "###);
+
+    // The oneline template is customized separately
+    let decl = "template-aliases.'format_short_signature_oneline(signature)'";
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-Tbuiltin_log_oneline",
+            "--config-toml",
+            &format!("{decl}='signature.email().upper()'"),
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  qpvuntsm TEST.USER@EXAMPLE.COM 2001-02-03 04:05:08.000 +07:00 69542c19 (empty) first
+    ◉  zzzzzzzz root() 00000000
+    "###);
+}
+
+#[test]
+fn test_log_contained_in() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "main", "-r@-"]);
+
+    let template =
+        r#"description.first_line() ++ if(self.contained_in("::main"), " [main]") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    first [main]
+     [main]
+    "###);
+
+    // Method of commit list item
+    let template = r#"parents.map(|c| c.contained_in("main")) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    true
+    false
+
+    "###);
+
+    // Bad revset
+    let template = r#"self.contained_in("main)")"#;
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:19
+      |
+    1 | self.contained_in("main)")
+      |                   ^-----^
+      |
+      = Invalid revset:  --> 1:5
+      |
+    1 | main)
+      |     ^---
+      |
+      = expected <EOI>, `@`, `-`, `+`, `::`, `..`, `|`, `&`, or `~`
+    "###);
+
+    // Unknown symbol
+    let template = r#"self.contained_in("unknown")"#;
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:19
+      |
+    1 | self.contained_in("unknown")
+      |                   ^-------^
+      |
+      = Invalid revset: Revision "unknown" doesn't exist
+    "###);
+
+    // Dynamic string is not allowed
+    let template = r#"self.contained_in(description)"#;
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:19
+      |
+    1 | self.contained_in(description)
+      |                   ^---------^
+      |
+      = Expected string literal
+    "###);
+}
+
+#[test]
+fn test_log_diff_stat() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\nbar\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "foo\nbaz\nqux\n").unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file3"), "new\n").unwrap();
+
+    let template = r#"diff.stat() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  3 files changed, 3 insertions(+), 2 deletions(-)
+    ◉  2 files changed, 3 insertions(+), 0 deletions(-)
+    ◉  0 files changed, 0 insertions(+), 0 deletions(-)
+    "###);
+
+    let template =
+        r#"separate(" ", diff.stat().files(), diff.stat().added(), diff.stat().removed()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  3 3 2
+    │
+    ~
+    "###);
+
+    let template = r#"diff.summary()"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  M file1
+    │  R file2
+    ~  A file3
+    "###);
+
+    // The diff itself can't be printed
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "diff"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | diff
+      | ^--^
+      |
+      = Expected expression of type "Template"
+    "###);
+}
+
+#[test]
+fn test_log_tree_stats() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\nbar\n").unwrap();
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir").join("file2"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    std::fs::remove_file(repo_path.join("file1")).unwrap();
+
+    let template = r#"separate(" ", tree.file_count(), tree.total_size()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  1 4
+    ◉  2 12
+    ◉  0 0
+    "###);
+
+    // Unreadable files are reported instead of being counted as empty
+    let git_objects_path = repo_path.join(".jj/repo/store/git/objects");
+    // Blob of "foo\n"
+    std::fs::remove_file(git_objects_path.join("25/7cc5642cb1a054f08cc83f2d943e56fd3ebe99"))
+        .unwrap();
+    let stderr = test_env.jj_cmd_internal_error(
+        &repo_path,
+        &["log", "-T", "tree.total_size()", "--ignore-working-copy"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Internal error: I/O error: Object 257cc5642cb1a054f08cc83f2d943e56fd3ebe99 of type file not found: object not found - cannot read header for (257cc5642cb1a054f08cc83f2d943e56fd3ebe99); class=Odb (9); code=NotFound (-3)
+    "###);
+}
+
+#[test]
+fn test_log_files() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("file1"), "bar\n").unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("dir").join("file3"), "new\n").unwrap();
+
+    let template = r#"files.len() ++ ": " ++ files ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  3: dir/file3 file1 file2
+    ◉  2: file1 file2
+    ◉  0:
+    "###);
+
+    let template = r#"files.map(|f| f.status() ++ " " ++ f.path()).join(", ")"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  added dir/file3, modified file1, removed file2
+    │
+    ~
+    "###);
+
+    let template = r#"files.join(" ")"#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--color=always", "-T", template, "-r@"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  [38;5;2mdir/file3[39m [38;5;6mfile1[39m [38;5;1mfile2[39m
+    │
+    ~
+    "###);
+}
+
+#[test]
+fn test_log_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "subject\n\nBody: text\n\nChange-Id: I1234\nSigned-off-by: Alice\n  <alice@example.com>\nSigned-off-by: Bob <bob@example.com>",
+        ],
+    );
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "Not-a-trailer: subject"]);
+
+    let template = r#"trailers.len() ++ " " ++ trailers.get("signed-off-by").join(", ") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  0
+    ◉  3 Alice <alice@example.com>, Bob <bob@example.com>
+    ◉  0
+    "###);
+
+    let template = r#"trailers.map(|t| t.key()).join(",") ++ "\n" ++ trailers"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template, "-r@-"]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  Change-Id,Signed-off-by,Signed-off-by
+    │  Change-Id: I1234
+    ~  Signed-off-by: Alice <alice@example.com>
+       Signed-off-by: Bob <bob@example.com>
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_log_signature() {
+    fn generate_ssh_key(dir: &Path, name: &str) -> (PathBuf, String) {
+        let key_path = dir.join(name);
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", name, "-f"])
+            .arg(&key_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let public_key = std::fs::read_to_string(key_path.with_extension("pub")).unwrap();
+        (key_path, public_key.trim().to_owned())
+    }
+
+    fn sign(key_path: &Path, data: &[u8]) -> String {
+        let mut child = Command::new("ssh-keygen")
+            .args(["-Y", "sign", "-n", "git", "-f"])
+            .arg(key_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(data).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    let test_env = TestEnvironment::default();
+    let repo_path = test_env.env_root().join("repo");
+    let (alice_key, alice_public_key) = generate_ssh_key(test_env.env_root(), "alice");
+    let (bob_key, _) = generate_ssh_key(test_env.env_root(), "bob");
+
+    let git_repo = git2::Repository::init(&repo_path).unwrap();
+    let git_signature =
+        git2::Signature::new("Test", "test@example.com", &git2::Time::new(0, 0)).unwrap();
+    let tree = git_repo
+        .find_tree(git_repo.treebuilder(None).unwrap().write().unwrap())
+        .unwrap();
+    let mut parents = vec![];
+    for (message, key, tampered) in [
+        ("signed by alice", Some(&alice_key), false),
+        ("signed by bob", Some(&bob_key), false),
+        ("bad signature", Some(&alice_key), true),
+        ("unsigned", None, false),
+    ] {
+        let parent_refs = parents.iter().collect::<Vec<_>>();
+        let buffer = git_repo
+            .commit_create_buffer(&git_signature, &git_signature, message, &tree, &parent_refs)
+            .unwrap();
+        let buffer = buffer.as_str().unwrap();
+        let commit_id = match key {
+            Some(key) => {
+                let signed_data = if tampered {
+                    buffer.replace(message, "something else")
+                } else {
+                    buffer.to_owned()
+                };
+                git_repo
+                    .commit_signed(buffer, &sign(key, signed_data.as_bytes()), None)
+                    .unwrap()
+            }
+            None => git_repo
+                .commit(
+                    None,
+                    &git_signature,
+                    &git_signature,
+                    message,
+                    &tree,
+                    &parent_refs,
+                )
+                .unwrap(),
+        };
+        parents = vec![git_repo.find_commit(commit_id).unwrap()];
+    }
+    git_repo.branch("main", &parents[0], false).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["init", "--git-repo=."]);
+
+    let template = r#"
+    if(signature,
+      signature.status() ++ " " ++ signature.display()
+        ++ " " ++ signature.key().starts_with("SHA256:"),
+      "unsigned",
+    ) ++ " " ++ description.first_line() ++ "\n"
+    "#;
+    // Without allowed signers, valid signatures can't be trusted
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r::main", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  unsigned unsigned
+    ◉  bad  false bad signature
+    ◉  unknown  true signed by bob
+    ◉  unknown  true signed by alice
+    ◉  unsigned
+    "###);
+
+    let allowed_signers_path = test_env.env_root().join("allowed_signers");
+    std::fs::write(
+        &allowed_signers_path,
+        format!("alice@example.com {alice_public_key}\n"),
+    )
+    .unwrap();
+    test_env.add_config(&format!(
+        r#"signing.backends.ssh.allowed-signers = ["{}"]"#,
+        allowed_signers_path.display()
+    ));
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r::main", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  unsigned unsigned
+    ◉  bad alice@example.com false bad signature
+    ◉  unknown  true signed by bob
+    ◉  good alice@example.com true signed by alice
+    ◉  unsigned
+    "###);
+
+    // The status is labeled for coloring
+    let template = r#"
+    separate(" ",
+      signature_status,
+      signature_status.starts_with("un"),
+      description.first_line(),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r::main", "-T", template, "--color=always"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  [38;5;8munsigned[39m true unsigned
+    ◉  [38;5;1mbad[39m false bad signature
+    ◉  [38;5;3munknown[39m true signed by bob
+    ◉  [38;5;2mgood[39m false signed by alice
+    ◉  [38;5;8munsigned[39m true
+    "###);
+
+    // The built-in templates show the status through an alias
+    test_env
+        .add_config("template-aliases.format_signature_status = 'if(signature, signature_status)'");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r::main", "-T", "builtin_log_oneline"],
+    );
+    insta::with_settings!({filters => vec![
+        (r"◉  [k-z]{8} ", "◉  <change id> "),
+        (r"[0-9a-f]{8} ", "<commit id> "),
+    ]}, {
+        insta::assert_snapshot!(stdout, @r###"
+        ◉  <change id> test 1970-01-01 00:00:00.000 +00:00 main <commit id> (empty) unsigned
+        ◉  <change id> test 1970-01-01 00:00:00.000 +00:00 <commit id> bad (empty) bad signature
+        ◉  <change id> test 1970-01-01 00:00:00.000 +00:00 <commit id> unknown (empty) signed by bob
+        ◉  <change id> test 1970-01-01 00:00:00.000 +00:00 <commit id> good (empty) signed by alice
+        ◉  <change id> root() 00000000
+        "###);
+    });
+}
+
+#[test]
+fn test_log_publish_state() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "--git", "origin"]);
+    let origin_path = test_env.env_root().join("origin");
+    let origin_git_repo_path = origin_path
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    test_env.jj_cmd_success(&origin_path, &["describe", "-m=trunk"]);
+    test_env.jj_cmd_success(&origin_path, &["branch", "create", "main"]);
+    test_env.jj_cmd_success(&origin_path, &["new", "-m=feature"]);
+    test_env.jj_cmd_success(&origin_path, &["branch", "create", "feature"]);
+    test_env.jj_cmd_success(&origin_path, &["git", "export"]);
+    test_env.jj_cmd_success(
+        test_env.env_root(),
+        &[
+            "git",
+            "clone",
+            origin_git_repo_path.to_str().unwrap(),
+            "local",
+        ],
+    );
+    let workspace_root = test_env.env_root().join("local");
+    test_env.jj_cmd_success(&workspace_root, &["new", "feature", "-m=local"]);
+
+    let template = r#"publish_state ++ " " ++ description"#;
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  unpushed local
+    ◉  pushed feature
+    ◉  immutable trunk
+    │
+    ~
+    "###);
+
+    // The state follows the set of immutable commits
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "none()""#);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  unpushed local
+    ◉  pushed feature
+    ◉  pushed trunk
+    ◉  immutable
+    "###);
+
+    // Errors in the immutable_heads() alias are reported
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "missing""#);
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &["log", "-r@", "--no-graph", "-T", template],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | publish_state ++ " " ++ description
+      | ^-----------^
+      |
+      = Invalid revset: Revision "missing" doesn't exist
+    "###);
+}
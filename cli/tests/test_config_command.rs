@@ -15,7 +15,7 @@
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, get_stdout_string, TestEnvironment};
 
 pub mod common;
 
@@ -487,6 +487,140 @@ fn test_config_get() {
     insta::assert_snapshot!(stdout, @"bar");
 }
 
+#[test]
+fn test_config_validate() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["config", "validate"]);
+    insta::assert_snapshot!(stdout, @"No problems found in the config");
+
+    test_env.add_config(
+        r#"
+        [ui]
+        colr = "always"
+        graph.style = "curvy"
+
+        [git.push-checks]
+        no-conflict = false
+
+        [colors]
+        commit_id = { fg = "blu" }
+
+        [colours]
+        commit_id = "blue"
+
+        [some-other-tool]
+        setting = true
+        "#,
+    );
+    std::fs::write(
+        repo_path.join(".jj/repo/config.toml"),
+        "ui.default-descripton = \"TODO\"\n",
+    )
+    .unwrap();
+
+    // Other commands warn about likely mistakes
+    let assert = test_env
+        .jj_cmd(&repo_path, &["log", "-r@", "-Tdescription"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(test_env.normalize_output(&get_stderr_string(&assert)), @r###"
+    $TEST_ENV/config/config0002.toml:10: Invalid value for config key "colors.commit_id.fg": expected "default", "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "bright black", "bright red", "bright green", "bright yellow", "bright blue", "bright magenta", "bright cyan", or "bright white", found "blu"
+    $TEST_ENV/config/config0002.toml:12: Unknown config key "colours"
+    Hint: Did you mean "colors"?
+    $TEST_ENV/config/config0002.toml:7: Unknown config key "git.push-checks.no-conflict"
+    Hint: Did you mean "git.push-checks.no-conflicts"?
+    $TEST_ENV/config/config0002.toml:3: Unknown config key "ui.colr"
+    Hint: Did you mean "ui.color"?
+    $TEST_ENV/config/config0002.toml:4: Invalid value for config key "ui.graph.style": expected "legacy", "curved", "square", "ascii", or "ascii-large", found "curvy"
+    $TEST_ENV/repo/.jj/repo/config.toml:1: Unknown config key "ui.default-descripton"
+    Hint: Did you mean "ui.default-command", "ui.default-description", "ui.default-revset"?
+    "###);
+
+    // `jj config validate` also reports unknown tables and fails
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &["config", "validate", "--config-toml=ui.log-word-wrap=1"],
+        )
+        .assert()
+        .failure();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"");
+    insta::assert_snapshot!(test_env.normalize_output(&get_stderr_string(&assert)), @r###"
+    $TEST_ENV/config/config0002.toml:10: Invalid value for config key "colors.commit_id.fg": expected "default", "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white", "bright black", "bright red", "bright green", "bright yellow", "bright blue", "bright magenta", "bright cyan", or "bright white", found "blu"
+    $TEST_ENV/config/config0002.toml:12: Unknown config key "colours"
+    Hint: Did you mean "colors"?
+    $TEST_ENV/config/config0002.toml:7: Unknown config key "git.push-checks.no-conflict"
+    Hint: Did you mean "git.push-checks.no-conflicts"?
+    $TEST_ENV/config/config0002.toml:15: Unknown config key "some-other-tool"
+    $TEST_ENV/config/config0002.toml:3: Unknown config key "ui.colr"
+    Hint: Did you mean "ui.color"?
+    $TEST_ENV/config/config0002.toml:4: Invalid value for config key "ui.graph.style": expected "legacy", "curved", "square", "ascii", or "ascii-large", found "curvy"
+    $TEST_ENV/repo/.jj/repo/config.toml:1: Unknown config key "ui.default-descripton"
+    Hint: Did you mean "ui.default-command", "ui.default-description", "ui.default-revset"?
+    --config-toml: Invalid value for config key "ui.log-word-wrap": expected a boolean, found an integer
+    Error: Found 8 problems in the config
+    "###);
+}
+
+#[test]
+fn test_config_trust_repo_config() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("repo-config.require-trust = true");
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let config_path = repo_path.join(".jj/repo/config.toml");
+
+    // A repo config written by jj is trusted
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["config", "set", "--repo", "test-key", "from-jj"],
+    );
+    let assert = test_env
+        .jj_cmd(&repo_path, &["config", "get", "test-key"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"from-jj");
+    insta::assert_snapshot!(get_stderr_string(&assert), @"");
+
+    // A modified repo config is ignored until trusted
+    std::fs::write(&config_path, "test-key = \"modified\"\n").unwrap();
+    let assert = test_env
+        .jj_cmd(&repo_path, &["config", "list", "test-key"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"");
+    insta::assert_snapshot!(
+        test_env.normalize_output(&get_stderr_string(&assert)), @r###"
+    Ignoring untrusted repo config $TEST_ENV/repo/.jj/repo/config.toml
+    Hint: Review the file and run `jj config trust` to use it.
+    No matching config key for test-key
+    "###);
+
+    let assert = test_env
+        .jj_cmd(&repo_path, &["config", "trust"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(test_env.normalize_output(&get_stdout_string(&assert)), @r###"
+    Trusting $TEST_ENV/repo/.jj/repo/config.toml
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["config", "get", "test-key"]);
+    insta::assert_snapshot!(stdout, @"modified");
+
+    // Revoking trust makes jj ignore the repo config again
+    let stdout = test_env.jj_cmd_success(&repo_path, &["config", "trust", "--revoke"]);
+    insta::assert_snapshot!(test_env.normalize_output(&stdout), @r###"
+    No longer trusting $TEST_ENV/repo/.jj/repo/config.toml
+    "###);
+    let assert = test_env
+        .jj_cmd(&repo_path, &["config", "list", "test-key"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"");
+}
+
 fn find_stdout_lines(keyname_pattern: &str, stdout: &str) -> String {
     let key_line_re = Regex::new(&format!(r"(?m)^{keyname_pattern}=.*$")).unwrap();
     key_line_re
@@ -15,7 +15,7 @@
 use insta::assert_snapshot;
 use regex::Regex;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, get_stdout_string, TestEnvironment};
 
 pub mod common;
 
@@ -56,6 +56,113 @@ fn test_debug_revset() {
     });
 }
 
+#[test]
+fn test_debug_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.add_config(r#"template-aliases.'my_summary' = 'description.first_line()'"#);
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "template",
+            r#"commit_id.short() ++ my_summary ++ branches.map(|b| b.name())"#,
+        ],
+    );
+    insta::with_settings!({filters => vec![
+        (r"(?m)(^    .*\n)+", "    ..\n"),
+    ]}, {
+        assert_snapshot!(stdout, @r###"
+        -- Parsed:
+        ExpressionNode {
+            ..
+        }
+
+        -- Expanded:
+        ExpressionNode {
+            ..
+        }
+
+        -- Keywords:
+        commit_id: CommitOrChangeId
+        description: String
+        branches: List<RefName>
+        "###);
+    });
+
+    // Type errors are reported after the keywords are listed
+    let assert = test_env
+        .jj_cmd(&workspace_path, &["debug", "template", "empty.short()"])
+        .assert()
+        .code(1);
+    let stdout = get_stdout_string(&assert);
+    insta::with_settings!({filters => vec![
+        (r"(?m)(^    .*\n)+", "    ..\n"),
+    ]}, {
+        assert_snapshot!(stdout, @r###"
+        -- Parsed:
+        ExpressionNode {
+            ..
+        }
+
+        -- Expanded:
+        ExpressionNode {
+            ..
+        }
+
+        -- Keywords:
+        empty: Boolean
+        "###);
+    });
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Error: Failed to parse template:  --> 1:7
+      |
+    1 | empty.short()
+      |       ^---^
+      |
+      = Method "short" doesn't exist for type "Boolean"
+    "###);
+}
+
+#[test]
+fn test_debug_common_ancestors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&workspace_path, &["describe", "-m", "base"]);
+    test_env.jj_cmd_success(&workspace_path, &["new", "-m", "left"]);
+    test_env.jj_cmd_success(
+        &workspace_path,
+        &["new", "description(base)", "-m", "right"],
+    );
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "common-ancestors", "description(left)", "@"],
+    );
+    let base_id = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "description(base)",
+            "-T",
+            r#"commit_id ++ "\n""#,
+        ],
+    );
+    assert_eq!(stdout, base_id);
+
+    // No common ancestors with an empty set
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["debug", "common-ancestors", "none()", "@"],
+    );
+    assert_snapshot!(stdout, @"");
+}
+
 #[test]
 fn test_debug_index() {
     let test_env = TestEnvironment::default();
@@ -131,6 +238,133 @@ fn test_debug_operation_id() {
     );
 }
 
+#[test]
+fn test_debug_tree_and_object() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("dir").join("file"), "contents\n").unwrap();
+    std::fs::write(workspace_path.join("other"), "other\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "tree"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Tree: Legacy(TreeId("0598e192386e8827591ce31fca0683cc39e3ad3d"))
+    "dir/file": Merge { removes: [], adds: [Some(File { id: FileId("12f00e90b6ef79117ce6e650416b8cf517099b78"), executable: false })] }
+    "other": Merge { removes: [], adds: [Some(File { id: FileId("e45c9c2666d44e0327c1f9c239a74c508336053e"), executable: false })] }
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "tree", "dir"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Tree: Legacy(TreeId("0598e192386e8827591ce31fca0683cc39e3ad3d"))
+    "dir/file": Merge { removes: [], adds: [Some(File { id: FileId("12f00e90b6ef79117ce6e650416b8cf517099b78"), executable: false })] }
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "object",
+            "file",
+            "--path",
+            "dir/file",
+            "12f00e90b6ef79117ce6e650416b8cf517099b78",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"contents");
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &["log", "--no-graph", "-r", "@", "-T", "commit_id"],
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "object", "commit", &stdout]);
+    insta::assert_snapshot!(stdout, @r###"
+    Commit {
+        parents: [
+            CommitId(
+                "0000000000000000000000000000000000000000",
+            ),
+        ],
+        predecessors: [
+            CommitId(
+                "230dd059e1b059aefc0da06a2e5a7dbf22362f22",
+            ),
+        ],
+        root_tree: Legacy(
+            TreeId(
+                "0598e192386e8827591ce31fca0683cc39e3ad3d",
+            ),
+        ),
+        change_id: ChangeId(
+            "9a45c67d3e96a7e5007c110ede34dec5",
+        ),
+        description: "",
+        author: Signature {
+            name: "Test User",
+            email: "test.user@example.com",
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(
+                    981147907000,
+                ),
+                tz_offset: 420,
+            },
+        },
+        committer: Signature {
+            name: "Test User",
+            email: "test.user@example.com",
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(
+                    981147908000,
+                ),
+                tz_offset: 420,
+            },
+        },
+    }
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&workspace_path, &["debug", "object", "tree", "xyz"]);
+    insta::assert_snapshot!(stderr, @"Error: Invalid object ID: xyz");
+}
+
+#[test]
+fn test_debug_working_copy() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_success(&workspace_path, &["new", "root()"]);
+    std::fs::write(workspace_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_success(&workspace_path, &["new", "@-"]);
+    std::fs::write(workspace_path.join("other"), "other\n").unwrap();
+    test_env.jj_cmd_success(&workspace_path, &["new", "all:visible_heads()"]);
+    test_env.jj_cmd_success(
+        &workspace_path,
+        &["sparse", "set", "--clear", "--add", "file"],
+    );
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "workingcopy", "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sparse_patterns"], serde_json::json!(["file"]));
+    assert_eq!(json["conflicts"], serde_json::json!(["file"]));
+    assert_eq!(json["tree"].as_array().unwrap().len(), 1);
+    let files = json["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["path"], "file");
+    assert_eq!(files[0]["type"], "normal");
+
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "workingcopy"]);
+    let stdout = stdout
+        .lines()
+        .skip(2)
+        .take(4)
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(stdout, @r###"
+    Sparse patterns:
+      "file"
+    Conflicts:
+      "file"
+    "###);
+}
+
 fn filter_index_stats(text: &str) -> String {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     regex.replace_all(text, "    Name: [hash]").to_string()
@@ -310,3 +310,95 @@ fn test_describe_author() {
     ~
     "###);
 }
+
+#[test]
+fn test_describe_description_checks() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [description-checks]
+        subject-max-length = 20
+        body-max-line-length = 10
+        subject-pattern = '^[a-z]+: '
+        "#,
+    );
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "Fix a bug in the frobnicator",
+            "-m",
+            "short\nway too long line",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid description:
+    The first line is 28 characters long (maximum is 20)
+    Line 4 is 17 characters long (maximum is 10)
+    The first line doesn't match the pattern `^[a-z]+: `
+    Hint: Fix the description, or set `description-checks.warn-only = true` to only warn about these problems.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["commit", "-m", "no prefix"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid description:
+    The first line doesn't match the pattern `^[a-z]+: `
+    Hint: Fix the description, or set `description-checks.warn-only = true` to only warn about these problems.
+    "###);
+
+    // Valid and empty descriptions are accepted
+    let stdout = test_env.jj_cmd_success(&repo_path, &["describe", "-m", "cli: fix bug"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: qpvuntsm 12a81bbb (empty) cli: fix bug
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["describe", "-m", ""]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: qpvuntsm 42911b89 (empty) (no description set)
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+
+    // Problems can be reported as warnings instead
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "describe",
+                "-m",
+                "no prefix",
+                "--config-toml=description-checks.warn-only=true",
+            ],
+        )
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stderr_string(&assert), @"The first line doesn't match the pattern `^[a-z]+: `");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_describe_description_check_command() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        description-checks.command = ["sh", "-c", "grep -q '^BUG=' || { echo 'missing BUG= line' >&2; exit 1; }"]
+        "#,
+    );
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-m", "first"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid description:
+    'sh -c grep -q '^BUG=' || { echo 'missing BUG= line' >&2; exit 1; }' rejected the description:
+    missing BUG= line
+    Hint: Fix the description, or set `description-checks.warn-only = true` to only warn about these problems.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first", "-m", "BUG=123"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: qpvuntsm d1f40594 (empty) first
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common::TestEnvironment;
+use common::{get_stderr_string, get_stdout_string, TestEnvironment};
 use itertools::Itertools;
 
 pub mod common;
@@ -55,6 +55,22 @@ fn test_diff_basic() {
     -F file3
     "###);
 
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file1
+    file2
+    file3
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--name-only", "-z"]);
+    insta::assert_snapshot!(stdout.replace('\0', "<NUL>"), @"file1<NUL>file2<NUL>file3<NUL>");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-z"]);
+    insta::assert_snapshot!(stdout.replace('\0', "<NUL>"), @"R file1<NUL>M file2<NUL>A file3<NUL>");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--types", "-z"]);
+    insta::assert_snapshot!(stdout.replace('\0', "<NUL>"), @"F- file1<NUL>FF file2<NUL>-F file3<NUL>");
+
     let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
     insta::assert_snapshot!(stdout, @r###"
     diff --git a/file1 b/file1
@@ -117,6 +133,49 @@ fn test_diff_basic() {
     "###);
 }
 
+#[test]
+fn test_diff_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    std::fs::remove_file(repo_path.join("file1")).unwrap();
+    std::fs::write(repo_path.join("file2"), "foo\nbar\n").unwrap();
+    std::fs::write(repo_path.join("file3"), "foo\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"path":"file1","status":"removed","old_type":"file","new_type":null,"hunks":[{"old_start":1,"old_lines":1,"new_start":1,"new_lines":0,"lines":[{"kind":"removed","content":"foo\n"}]}]}
+    {"path":"file2","status":"modified","old_type":"file","new_type":"file","hunks":[{"old_start":1,"old_lines":1,"new_start":1,"new_lines":2,"lines":[{"kind":"context","content":"foo\n"},{"kind":"added","content":"bar\n"}]}]}
+    {"path":"file3","status":"added","old_type":null,"new_type":"file","hunks":[{"old_start":1,"old_lines":0,"new_start":1,"new_lines":1,"lines":[{"kind":"added","content":"foo\n"}]}]}
+    "###);
+
+    // The content of the hunks can be left out
+    test_env.add_config(
+        r#"
+        ui.diff.format = "json"
+        ui.diff.json.include-content = false
+        "#,
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "file2"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"path":"file2","status":"modified","old_type":"file","new_type":"file","hunks":[{"old_start":1,"old_lines":1,"new_start":1,"new_lines":2}]}
+    "###);
+
+    // Can't be combined with other long formats
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["diff", "--json", "--git"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--json' cannot be used with '--git'
+
+    Usage: jj diff --json [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+}
+
 #[test]
 fn test_diff_empty() {
     let test_env = TestEnvironment::default();
@@ -145,6 +204,104 @@ fn test_diff_empty() {
     "###);
 }
 
+#[test]
+fn test_diff_from_to_multiple_revisions() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "a"]);
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m", "b"]);
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new", "all:root()+"]);
+    std::fs::write(repo_path.join("file3"), "c\n").unwrap();
+
+    // Compare against the merged parents explicitly
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--from", "all:@-"]);
+    insta::assert_snapshot!(stdout, @"A file3");
+    // Without the prefix, multiple revisions are an error
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "-s", "--from", "@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revset "@-" resolved to more than one revision
+    Hint: The revset "@-" resolved to these revisions:
+    kkmpptxz 4a6ed25d b
+    qpvuntsm 6040a6b2 a
+    Prefix the expression with 'all' to allow any number of revisions (i.e. 'all:@-').
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "-s", "--from", "all:none()"]);
+    insta::assert_snapshot!(stderr, @"Error: Empty revision set");
+
+    // Diff between the root and unrelated revisions
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "--from", "root()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file1
+    A file2
+    A file3
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "diff",
+            "-s",
+            "--from",
+            "description(a)",
+            "--to",
+            "description(b)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    R file1
+    A file2
+    "###);
+}
+
+#[test]
+fn test_diff_summary_threshold() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("ui.diff.summary-threshold = 2");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Added regular file file1:
+            1: a
+    Added regular file file2:
+            1: b
+    "###);
+
+    std::fs::write(repo_path.join("file3"), "c\n").unwrap();
+    let assert = test_env.jj_cmd(&repo_path, &["diff"]).assert().success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @r###"
+    A file1
+    A file2
+    A file3
+    "###);
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Hint: Showing only a summary because 3 paths changed (more than ui.diff.summary-threshold). Pass a format like --git, or restrict the diff to some paths, to see the changes.
+    "###);
+
+    // An explicit format or a path restriction shows the full diff
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "file3"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file3 b/file3
+    new file mode 100644
+    index 0000000000..f2ad6c76f0
+    --- /dev/null
+    +++ b/file3
+    @@ -1,0 +1,1 @@
+    +c
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "file3"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Added regular file file3:
+            1: c
+    "###);
+}
+
 #[test]
 fn test_diff_types() {
     let test_env = TestEnvironment::default();
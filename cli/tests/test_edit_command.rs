@@ -63,6 +63,42 @@ fn test_edit() {
     "###);
 }
 
+#[test]
+fn test_edit_abandons_empty_wc_commit() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["commit", "-m", "first"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  b88fb4e51bdd
+    ◉  69542c1984c1 first
+    ◉  000000000000
+    "###);
+
+    // Leaving an empty working-copy commit without description abandons it
+    test_env.jj_cmd_success(&repo_path, &["edit", "@-"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  69542c1984c1 first
+    ◉  000000000000
+    "###);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "description(first)"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  cf5a8546feef
+    ◉  69542c1984c1 first
+    ◉  000000000000
+    "###);
+
+    // A described working-copy commit is kept
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "placeholder"]);
+    test_env.jj_cmd_success(&repo_path, &["edit", "@-"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  4362ff0fef74 placeholder
+    @  69542c1984c1 first
+    ◉  000000000000
+    "###);
+}
+
 #[test]
 // Windows says "Access is denied" when trying to delete the object file.
 #[cfg(unix)]
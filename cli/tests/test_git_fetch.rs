@@ -13,7 +13,7 @@
 // limitations under the License.
 use std::path::Path;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, get_stdout_string, TestEnvironment};
 
 pub mod common;
 
@@ -146,6 +146,206 @@ fn test_git_fetch_single_remote_from_config() {
     "###);
 }
 
+#[test]
+fn test_git_fetch_branch_patterns() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "rem1");
+
+    // The name matches exactly by default
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["git", "fetch", "--remote", "rem1", "--branch", "rem"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @"");
+
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["git", "fetch", "--remote", "rem1", "--branch", "glob:rem*"],
+    );
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    rem1: qxosxrvv 6a211027 message
+    "###);
+
+    // Refspecs don't support the other wildcards
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["git", "fetch", "--remote", "rem1", "--branch", "glob:rem?"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid branch pattern provided. Only `*` is supported as a wildcard, and patterns may not contain the characters `:` or `^`
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["git", "fetch", "--remote", "rem1", "--branch", "unknown:a"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'unknown:a' for '--branch <BRANCH>': Invalid string pattern kind "unknown"
+
+    For more information, try '--help'.
+    "###);
+}
+
+#[test]
+fn test_git_fetch_branches_from_config() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+        [git]
+        fetch = ["rem1", "rem2"]
+        [git.remotes.rem1]
+        fetch-branches = ["main", "rem*"]
+        [git.remotes.rem2]
+        fetch-branches = ["main", "release/*"]
+        "#,
+    );
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "rem1");
+    add_git_remote(&test_env, &repo_path, "rem2");
+    let git_repo = git2::Repository::open(test_env.env_root().join("rem1")).unwrap();
+    let commit = git_repo
+        .revparse_single("rem1")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    git_repo.branch("extra", &commit, false).unwrap();
+
+    // Only the configured branches are fetched
+    test_env.jj_cmd_success(&repo_path, &["git", "fetch"]);
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    rem1: qxosxrvv 6a211027 message
+    "###);
+
+    // --branch overrides the config
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["git", "fetch", "--remote", "rem2", "--branch", "rem2"],
+    );
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    rem1: qxosxrvv 6a211027 message
+    rem2: yszkquru 2497a8a0 message
+    "###);
+
+    // --all-branches ignores the config
+    test_env.jj_cmd_success(&repo_path, &["git", "fetch", "--all-branches"]);
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    extra: qxosxrvv 6a211027 message
+    rem1: qxosxrvv 6a211027 message
+    rem2: yszkquru 2497a8a0 message
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["git", "fetch", "--all-branches", "--branch", "rem1"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    error: the argument '--all-branches' cannot be used with '--branch <BRANCH>'
+
+    Usage: jj git fetch --all-branches
+
+    For more information, try '--help'.
+    "###);
+
+    // Invalid patterns are reported
+    test_env.add_config(r#"git.remotes.rem1.fetch-branches = ["[a"]"#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "fetch", "--remote", "rem1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid pattern in git.remotes.rem1.fetch-branches: [a: Pattern syntax error near position 0: invalid range pattern
+    "###);
+}
+
+#[test]
+fn test_git_fetch_auto_fetch() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+        git.fetch = "rem1"
+        git.auto-fetch-interval = 60
+        "#,
+    );
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "rem1");
+
+    // The first command fetches since there was no fetch yet
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    rem1: qxosxrvv 6a211027 message
+    "###);
+
+    // Nothing is fetched until the interval has passed
+    let git_repo = git2::Repository::open(test_env.env_root().join("rem1")).unwrap();
+    let commit = git_repo
+        .revparse_single("rem1")
+        .unwrap()
+        .peel_to_commit()
+        .unwrap();
+    git_repo.branch("extra", &commit, false).unwrap();
+    insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
+    rem1: qxosxrvv 6a211027 message
+    "###);
+
+    // Commands that don't need remote branches, and commands run without the
+    // working copy, never fetch
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--config-toml", "git.auto-fetch-interval = 0"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "branch",
+            "list",
+            "--ignore-working-copy",
+            "--config-toml",
+            "git.auto-fetch-interval = 0",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    rem1: qxosxrvv 6a211027 message
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "branch",
+            "list",
+            "--config-toml",
+            "git.auto-fetch-interval = 0",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    extra: qxosxrvv 6a211027 message
+    rem1: qxosxrvv 6a211027 message
+    "###);
+
+    // A failed fetch doesn't prevent the command from running
+    std::fs::remove_dir_all(test_env.env_root().join("rem1")).unwrap();
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "branch",
+                "list",
+                "--config-toml",
+                "git.auto-fetch-interval = 0",
+            ],
+        )
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @r###"
+    extra: qxosxrvv 6a211027 message
+    rem1: qxosxrvv 6a211027 message
+    "###);
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Automatic fetch failed: unsupported URL protocol; class=Net (12)
+    "###);
+}
+
 #[test]
 fn test_git_fetch_multiple_remotes() {
     let test_env = TestEnvironment::default();
@@ -307,7 +507,7 @@ fn test_git_fetch_conflicting_branches() {
 
     test_env.jj_cmd_success(
         &repo_path,
-        &["git", "fetch", "--remote", "rem1", "--branch", "*"],
+        &["git", "fetch", "--remote", "rem1", "--branch", "glob:*"],
     );
     // This should result in a CONFLICTED branch
     insta::assert_snapshot!(get_branch_output(&test_env, &repo_path), @r###"
@@ -533,10 +733,12 @@ fn test_git_fetch_some_of_many_branches() {
     "###);
 
     // Test an error message
-    let stderr =
-        test_env.jj_cmd_failure(&target_jj_repo_path, &["git", "fetch", "--branch", "^:a*"]);
+    let stderr = test_env.jj_cmd_failure(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", "glob:^:a*"],
+    );
     insta::assert_snapshot!(stderr, @r###"
-    Error: Invalid glob provided. Globs may not contain the characters `:` or `^`.
+    Error: Invalid branch pattern provided. Only `*` is supported as a wildcard, and patterns may not contain the characters `:` or `^`
     "###);
 
     // Nothing in our repo before the fetch
@@ -559,7 +761,10 @@ fn test_git_fetch_some_of_many_branches() {
     b: vpupmnsl c7d4bdcb descr_for_b
     "###);
     // ...then fetch two others with a glob.
-    let stdout = test_env.jj_cmd_success(&target_jj_repo_path, &["git", "fetch", "--branch", "a*"]);
+    let stdout = test_env.jj_cmd_success(
+        &target_jj_repo_path,
+        &["git", "fetch", "--branch", "glob:a*"],
+    );
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(get_log_output(&test_env, &target_jj_repo_path), @r###"
     ◉  decaa3966c83 descr_for_a2 a2
@@ -655,7 +860,7 @@ fn test_git_fetch_some_of_many_branches() {
     // anything.
     let stdout = test_env.jj_cmd_success(
         &target_jj_repo_path,
-        &["git", "fetch", "--branch", "b", "--branch", "a*"],
+        &["git", "fetch", "--branch", "b", "--branch", "glob:a*"],
     );
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(get_log_output(&test_env, &target_jj_repo_path), @r###"
@@ -198,6 +198,107 @@ fn test_git_push_other_remote_has_branch() {
     insta::assert_snapshot!(stderr, @"");
 }
 
+#[test]
+fn test_git_push_tracked_branch() {
+    let (test_env, workspace_root) = set_up();
+    let other_remote_path = test_env
+        .env_root()
+        .join("origin")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "git",
+            "remote",
+            "add",
+            "other",
+            other_remote_path.to_str().unwrap(),
+        ],
+    );
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m=new"]);
+    test_env.jj_cmd_success(
+        &workspace_root,
+        &["branch", "create", "feature", "--track", "other"],
+    );
+
+    // The branch is pushed to the remote it tracks
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["git", "push", "--branch", "feature", "--dry-run"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to other:
+      Add branch feature to 281d832ca86a
+    Dry-run requested, not pushing.
+    "###);
+
+    // Unless another remote is specified
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--branch",
+            "feature",
+            "--remote",
+            "origin",
+            "--dry-run",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to origin:
+      Add branch feature to 281d832ca86a
+    Dry-run requested, not pushing.
+    "###);
+
+    // An explicitly configured remote takes precedence over the upstream
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            "git",
+            "push",
+            "--branch",
+            "feature",
+            "--config-toml",
+            "git.push = 'origin'",
+            "--dry-run",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to origin:
+      Add branch feature to 281d832ca86a
+    Dry-run requested, not pushing.
+    "###);
+
+    // Deleting the branch removes its upstream
+    let git_config = || {
+        git2::Repository::open(workspace_root.join(".jj/repo/store/git"))
+            .unwrap()
+            .config()
+            .unwrap()
+            .snapshot()
+            .unwrap()
+    };
+    assert_eq!(
+        git_config().get_str("branch.feature.remote").unwrap(),
+        "other"
+    );
+    test_env.jj_cmd_success(&workspace_root, &["branch", "delete", "feature"]);
+    assert!(git_config().get_str("branch.feature.remote").is_err());
+    assert!(git_config().get_str("branch.feature.merge").is_err());
+
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &["branch", "create", "other-feature", "--track", "unknown"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No git remote named 'unknown'
+    "###);
+}
+
 #[test]
 fn test_git_push_not_fast_forward() {
     let (test_env, workspace_root) = set_up();
@@ -351,6 +452,44 @@ fn test_git_push_changes() {
     "###);
 }
 
+#[test]
+fn test_git_push_changes_revset() {
+    let (test_env, workspace_root) = set_up();
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m", "foo"]);
+    std::fs::write(workspace_root.join("file"), "contents").unwrap();
+    test_env.jj_cmd_success(&workspace_root, &["new", "-m", "bar"]);
+    std::fs::write(workspace_root.join("file"), "modified").unwrap();
+
+    // Multiple revisions require the all: prefix
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "-c=@-::@"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revset "@-::@" resolved to more than one revision
+    Hint: The revset "@-::@" resolved to these revisions:
+    yostqsxw 28d7620e bar
+    yqosqzyt fa16a141 foo
+    Prefix the expression with 'all' to allow any number of revisions (i.e. 'all:@-::@').
+    "###);
+
+    // A branch is created for each change in the stack, oldest first
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "push", "-c=all:@-::@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Creating branch push-yqosqzytrlsw for revision yqosqzytrlsw
+    Creating branch push-yostqsxwqrlt for revision yostqsxwqrlt
+    Branch changes to push to origin:
+      Add branch push-yqosqzytrlsw to fa16a14170fb
+      Add branch push-yostqsxwqrlt to 28d7620ea63a
+    "###);
+
+    // Can be combined with other --change arguments
+    std::fs::write(workspace_root.join("file"), "modified2").unwrap();
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "push", "-c=@", "-c=all:@-::@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Branch push-yqosqzytrlsw@origin already matches push-yqosqzytrlsw
+    Branch changes to push to origin:
+      Force branch push-yostqsxwqrlt from 28d7620ea63a to a913a342b1aa
+    "###);
+}
+
 #[test]
 fn test_git_push_revisions() {
     let (test_env, workspace_root) = set_up();
@@ -498,6 +637,106 @@ fn test_git_push_no_description() {
     "###);
 }
 
+#[test]
+fn test_git_push_private() {
+    let (test_env, workspace_root) = set_up();
+    test_env.add_config(r#"git.private-commits = "description(private)""#);
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m", "public"]);
+    test_env.jj_cmd_success(&workspace_root, &["branch", "create", "public-branch"]);
+    test_env.jj_cmd_success(&workspace_root, &["new", "-m", "private work"]);
+    test_env.jj_cmd_success(&workspace_root, &["branch", "create", "private-branch"]);
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--all"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Won't push commit 8d9e6fbcd6fd since it is private
+    Hint: Configure the set of private commits via `git.private-commits`.
+    "###);
+
+    // Ancestors of private commits can be pushed
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["git", "push", "--dry-run", "--branch", "public-branch"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to origin:
+      Add branch public-branch to 3aa77c668de5
+    Dry-run requested, not pushing.
+    "###);
+}
+
+#[test]
+fn test_git_push_checks() {
+    let (test_env, workspace_root) = set_up();
+    test_env.jj_cmd_success(&workspace_root, &["branch", "create", "my-branch"]);
+    std::fs::write(workspace_root.join("file"), "x".repeat(2000)).unwrap();
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m="]);
+    let push_args = ["git", "push", "--dry-run", "--branch", "my-branch"];
+
+    // Checks can be disabled individually
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[
+            &push_args[..],
+            &[
+                "--config-toml",
+                "git.push-checks.no-empty-description = false",
+            ],
+        ]
+        .concat(),
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to origin:
+      Add branch my-branch to f3768466c2d0
+    Dry-run requested, not pushing.
+    "###);
+
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m=wip"]);
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            &push_args[..],
+            &[
+                "--config-toml",
+                r#"git.push-checks.description-regex = "^(feat|fix): "
+                   git.push-checks.max-commit-size = "1KiB""#,
+            ],
+        ]
+        .concat(),
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Won't push commit c8e0a9b383a4 since its description doesn't match `^(feat|fix): ` and its changes are larger than 1.0KiB (2000 bytes)
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &[
+            &push_args[..],
+            &[
+                "--config-toml",
+                r#"git.push-checks.description-regex = "(""#,
+            ],
+        ]
+        .concat(),
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid regex in `git.push-checks.description-regex`: regex parse error:
+        (
+        ^
+    error: unclosed group
+    "###);
+
+    // All checks can be skipped
+    test_env.jj_cmd_success(&workspace_root, &["describe", "-m="]);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &[&push_args[..], &["--ignore-checks"]].concat(),
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Branch changes to push to origin:
+      Add branch my-branch to c7cc6c2af136
+    Dry-run requested, not pushing.
+    "###);
+}
+
 #[test]
 fn test_git_push_missing_author() {
     let (test_env, workspace_root) = set_up();
@@ -620,17 +859,32 @@ fn test_git_push_conflicting_branches() {
     "###);
 
     // --branch should be blocked by conflicting branch
-    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--branch", "branch2"]);
-    insta::assert_snapshot!(stderr, @r###"
+    let assert = test_env
+        .jj_cmd(&workspace_root, &["git", "push", "--branch", "branch2"])
+        .assert()
+        .code(4)
+        .stdout("");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
     Error: Branch branch2 is conflicted
     "###);
+    let assert = test_env
+        .jj_cmd(
+            &workspace_root,
+            &["git", "push", "--branch", "branch2", "--output", "json"],
+        )
+        .assert()
+        .code(4)
+        .stdout("");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    {"code":4,"hint":null,"kind":"conflict","message":"Branch branch2 is conflicted"}
+    "###);
 
     // --all shouldn't be blocked by conflicting branch
     bump_branch1();
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--all"]);
     insta::assert_snapshot!(stdout, @r###"
     Branch changes to push to origin:
-      Move branch branch1 from 45a3aa29e907 to fd1d63e031ea
+      Move branch branch1 from 45a3aa29e907 to 5bf9a5431cff
     "###);
     insta::assert_snapshot!(stderr, @r###"
     Branch branch2 is conflicted
@@ -641,9 +895,76 @@ fn test_git_push_conflicting_branches() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "-rall()"]);
     insta::assert_snapshot!(stdout, @r###"
     Branch changes to push to origin:
-      Move branch branch1 from fd1d63e031ea to 8263cf992d33
+      Move branch branch1 from 5bf9a5431cff to 078b8982b0bf
     "###);
     insta::assert_snapshot!(stderr, @r###"
     Branch branch2 is conflicted
     "###);
 }
+
+#[test]
+fn test_git_push_to_checked_out_branch() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"revset-aliases."immutable_heads()" = "none()""#);
+    // Set up a non-bare remote with "main" checked out
+    let remote_path = test_env.env_root().join("remote");
+    let remote_repo = git2::Repository::init(&remote_path).unwrap();
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    let tree_id = remote_repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = remote_repo.find_tree(tree_id).unwrap();
+    remote_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "initial",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    remote_repo.set_head("refs/heads/main").unwrap();
+    test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["git", "clone", remote_path.to_str().unwrap(), "local"],
+    );
+    let workspace_root = test_env.env_root().join("local");
+    test_env.jj_cmd_success(&workspace_root, &["new", "main", "-m", "local"]);
+    test_env.jj_cmd_success(&workspace_root, &["branch", "set", "main"]);
+    test_env.jj_cmd_success(&workspace_root, &["branch", "create", "feature"]);
+
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--all"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Refusing to push branch main since it is checked out in the working tree of remote origin
+    Hint: Check out another branch in the remote repository, or push to a bare repository instead.
+    "###);
+
+    // A relative remote URL is resolved from the repo, not from the current
+    // directory
+    let git_repo = git2::Repository::open(workspace_root.join(".jj/repo/store/git")).unwrap();
+    git_repo
+        .remote_set_url("origin", "../../../../../remote")
+        .unwrap();
+    std::fs::create_dir(workspace_root.join("subdir")).unwrap();
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root.join("subdir"),
+        &["git", "push", "--all", "--dry-run"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Refusing to push branch main since it is checked out in the working tree of remote origin
+    Hint: Check out another branch in the remote repository, or push to a bare repository instead.
+    "###);
+    git_repo
+        .remote_set_url("origin", remote_path.to_str().unwrap())
+        .unwrap();
+
+    // Pushing other branches to a non-bare repo isn't supported either
+    let assert = test_env
+        .jj_cmd(&workspace_root, &["git", "push", "--branch=feature"])
+        .assert()
+        .code(1);
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Error: Cannot push to remote origin since it is not a bare repository
+    Hint: Push to a bare repository instead, e.g. one created by `git clone --bare`.
+    "###);
+}
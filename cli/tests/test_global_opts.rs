@@ -97,6 +97,45 @@ fn test_no_subcommand() {
     "###);
 }
 
+#[test]
+fn test_default_command_with_args() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+
+    // The default command can include arguments
+    test_env.add_config(r#"ui.default-command = ["log", "-r", "@-", "-T", "description"]"#);
+    let stdout = test_env.jj_cmd_success(&repo_path, &[]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  first
+    │
+    ~
+    "###);
+
+    // Arguments given on the command line are passed to the default command
+    let stdout = test_env.jj_cmd_success(&repo_path, &["--no-graph"]);
+    insta::assert_snapshot!(stdout, @"first");
+
+    // The default command can be an alias with arguments
+    test_env.add_config(
+        r#"
+        aliases.l = ["log", "-T", "description"]
+        ui.default-command = ["l", "--no-graph"]
+        "#,
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &[]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    first
+    "###);
+
+    test_env.add_config(r#"ui.default-command = []"#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &[]);
+    insta::assert_snapshot!(stderr, @"Error: ui.default-command must not be an empty list");
+}
+
 #[test]
 fn test_ignore_working_copy() {
     let test_env = TestEnvironment::default();
@@ -376,6 +415,39 @@ fn test_invalid_config() {
     "###);
 }
 
+#[test]
+fn test_error_output_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "foo", "--output", "json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    {"code":1,"hint":null,"kind":"user","message":"Revision \"foo\" doesn't exist"}
+    "###);
+
+    // Errors detected by clap are printed as text
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["log", "--no-such-flag", "--output=json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: unexpected argument '--no-such-flag' found
+
+      tip: to pass '--no-such-flag' as a value, use '-- --no-such-flag'
+
+    Usage: jj log [OPTIONS] [PATHS]...
+
+    For more information, try '--help'.
+    "###);
+
+    // Can be set in config, and doesn't affect help
+    test_env.add_config("ui.output = 'json'");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["edit", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    {"code":1,"hint":"Configure the set of immutable commits via `revset-aliases.immutable_heads()`.","kind":"user","message":"Commit 000000000000 is immutable"}
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--help"]);
+    assert!(stdout.starts_with("Show commit history"));
+}
+
 #[test]
 fn test_no_user_configured() {
     // Test that the user is reminded if they haven't configured their name or email
@@ -405,6 +477,75 @@ fn test_no_user_configured() {
     "###);
 }
 
+#[test]
+fn test_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m=a"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "a"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m=b"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "b"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m=c"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "c"]);
+    let get_log_output = || {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["log", "-T", r#"commit_id.short() ++ " " ++ branches"#],
+        )
+    };
+    let log_before = get_log_output();
+    insta::assert_snapshot!(log_before, @r###"
+    @  b915bd615902 c
+    │ ◉  ecdd3e25a6e2 b
+    │ ◉  4c5b3042d9e0 a
+    ├─╯
+    ◉  000000000000
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["rebase", "-s=a", "-d=c", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Would rewrite commit 4c5b3042d9e0 as qpvuntsm a97adc9f a | (empty) a
+    Would rewrite commit ecdd3e25a6e2 as zsuskuln e0ba7210 b | (empty) b
+    Would move branch a from 4c5b3042d9e0 to a97adc9f7a22
+    Would move branch b from ecdd3e25a6e2 to e0ba72107b78
+    Dry-run requested, not committing the operation.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["abandon", "a", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Would rewrite commit ecdd3e25a6e2 as zsuskuln 29b177a7 b | (empty) b
+    Would abandon commit qpvuntsm 4c5b3042 (empty) a
+    Would move branch a from 4c5b3042d9e0 to 000000000000
+    Would move branch b from ecdd3e25a6e2 to 29b177a76412
+    Dry-run requested, not committing the operation.
+    "###);
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["squash", "-r=b", "-m=squashed", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Would rewrite commit 4c5b3042d9e0, ecdd3e25a6e2 as qpvuntsm 5ae3ab57 a b | (empty) squashed
+    Would move branch a from 4c5b3042d9e0 to 5ae3ab57f91d
+    Would move branch b from ecdd3e25a6e2 to 5ae3ab57f91d
+    Dry-run requested, not committing the operation.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["branch", "delete", "c", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Would delete branch c (was b915bd615902)
+    Dry-run requested, not committing the operation.
+    "###);
+    // Nothing was changed
+    assert_eq!(get_log_output(), log_before);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-l1", "-T=description"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    create branch c pointing to commit b915bd6159022a4028901fc4c87016672d45f179
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-m=x", "--dry-run"]);
+    insta::assert_snapshot!(stderr, @"Error: --dry-run is not supported by this command");
+}
+
 #[test]
 fn test_help() {
     // Test that global options are separated out in the help output
@@ -428,8 +569,14 @@ fn test_help() {
           --ignore-working-copy          Don't snapshot the working copy, and don't update it
           --at-operation <AT_OPERATION>  Operation to load the repo at [default: @] [aliases: at-op]
       -v, --verbose                      Enable verbose logging
+          --dry-run                      Only show what the command would change, without changing
+                                         anything
+          --debug-timing                 Report the time spent in snapshotting, revset evaluation,
+                                         template rendering, index access, and backend I/O
           --color <WHEN>                 When to colorize output (always, never, auto)
           --no-pager                     Disable the pager
+          --width <COLUMNS>              Width of the output, instead of the terminal width
+          --output <FORMAT>              Format of error messages and of `jj log` output (text, json)
           --config-toml <TOML>           Additional configuration options (can be repeated)
     "###);
 }
@@ -452,3 +599,34 @@ fn test_verbose_logging_enabled() {
     // Luckily, insta will print this in colour when reviewing.
     insta::assert_snapshot!(log_line, @"[32m INFO[0m [2mjj_cli::cli_util[0m[2m:[0m verbose logging enabled");
 }
+
+#[test]
+fn test_debug_timing() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["log", "--debug-timing"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  qpvuntsm test.user@example.com 2001-02-03 04:05:07.000 +07:00 230dd059
+    │  (empty) (no description set)
+    ◉  zzzzzzzz root() 00000000
+    "###);
+    // Durations and numbers of calls depend on the environment
+    let stderr = regex::Regex::new(r" +\d+\.\d+ms( \(\d+ calls?\))?")
+        .unwrap()
+        .replace_all(&stderr, " <time>");
+    insta::assert_snapshot!(stderr, @r###"
+    Timing:
+      snapshot <time>
+      revset evaluation <time>
+      template rendering <time>
+      index access <time>
+      backend I/O <time>
+      total <time>
+    "###);
+
+    // Nothing is reported by default
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["log"]);
+    insta::assert_snapshot!(stderr, @"");
+}
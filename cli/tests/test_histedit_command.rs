@@ -0,0 +1,244 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+pub mod common;
+
+fn create_stack(test_env: &TestEnvironment, repo_path: &Path) {
+    for (i, name) in ["a", "b", "c"].into_iter().enumerate() {
+        if i > 0 {
+            test_env.jj_cmd_success(repo_path, &["new"]);
+        }
+        test_env.jj_cmd_success(repo_path, &["describe", "-m", name]);
+        std::fs::write(repo_path.join(name), name).unwrap();
+    }
+    test_env.jj_cmd_success(repo_path, &["new"]);
+}
+
+fn commit_id(test_env: &TestEnvironment, repo_path: &Path, description: &str) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            &format!("description({description:?})"),
+            "-T",
+            "commit_id.short()",
+        ],
+    )
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"commit_id.short() ++ " " ++ description"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template, "--summary"])
+}
+
+#[test]
+fn test_histedit_plan() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_stack(&test_env, &repo_path);
+
+    // The plan lists the commits oldest first. An empty plan aborts.
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, "dump editor0\0write\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @"Error: Aborted because the plan is empty");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+    pick 44a20c57b5f3 a
+    pick 3feb862fdd0b b
+    pick 78e3d852f929 c
+    JJ: Edit the list of commits above, oldest first. The lines can be reordered.
+    JJ: Actions:
+    JJ:   pick = keep the commit
+    JJ:   reword = keep the commit, but edit its description
+    JJ:   squash = move the changes into the commit on the previous line
+    JJ:   drop = abandon the commit
+    JJ: Removing a line also abandons the commit. An empty list aborts.
+    "###);
+
+    // Leaving the plan unchanged leaves the commits alone
+    std::fs::write(&edit_script, "").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  0430a7aa7d18
+    ◉  78e3d852f929 c
+    │  A c
+    ◉  3feb862fdd0b b
+    │  A b
+    ◉  44a20c57b5f3 a
+    │  A a
+    ◉  000000000000
+    "###);
+}
+
+#[test]
+fn test_histedit_reorder_squash_drop() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_stack(&test_env, &repo_path);
+    let a = commit_id(&test_env, &repo_path, "a");
+    let c = commit_id(&test_env, &repo_path, "c");
+
+    // Move "c" first, squash "a" into it, and drop "b" by removing its line. The
+    // descriptions of the squashed commits are combined in the editor.
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        format!(
+            "write\npick {c} c\nsquash {a} a\n\0next invocation\n\0dump editor1\0write\nc and a"
+        ),
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: yqosqzyt c7f10629 (empty) (no description set)
+    Parent commit      : mzvwutvl 25845295 c and a
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @r###"
+    JJ: Enter a description for the combined commit.
+    JJ: Description from commit 78e3d852f929:
+    c
+    JJ: Description from commit 44a20c57b5f3:
+    a
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  c7f10629849b
+    ◉  258452951d76 c and a
+    │  A a
+    │  A c
+    ◉  000000000000
+    "###);
+
+    // Reword a commit
+    let ca = commit_id(&test_env, &repo_path, "c and a");
+    std::fs::write(
+        &edit_script,
+        format!("write\nreword {ca}\n\0next invocation\n\0write\nreworded"),
+    )
+    .unwrap();
+    test_env.jj_cmd_success(&repo_path, &["histedit", "@-"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  46ed09a6f37f
+    ◉  11f5218a343c reworded
+    │  A a
+    │  A c
+    ◉  000000000000
+    "###);
+}
+
+#[test]
+fn test_histedit_errors() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_stack(&test_env, &repo_path);
+    let a = commit_id(&test_env, &repo_path, "a");
+    let b = commit_id(&test_env, &repo_path, "b");
+
+    // The revisions must form a linear stack
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "@ | @--"]);
+    insta::assert_snapshot!(stderr, @"Error: The revisions must form a linear stack");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit 000000000000 is immutable
+    Hint: Configure the set of immutable commits via `revset-aliases.immutable_heads()`.
+    "###);
+
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(&edit_script, format!("write\nfixup {a}\n")).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Unknown action "fixup" in plan
+    "###);
+
+    std::fs::write(&edit_script, format!("write\nsquash {a}\npick {b}\n")).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot squash commit "44a20c57b5f3" without a previous commit
+    "###);
+
+    std::fs::write(&edit_script, format!("write\npick {a}\npick {a}\n")).unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit "44a20c57b5f3" appears more than once in plan
+    "###);
+
+    std::fs::write(&edit_script, "write\npick 0123\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Commit "0123" in plan is not one of the edited commits
+    "###);
+
+    // Reworded descriptions are checked like in `jj describe`
+    test_env.add_config(r#"description-checks.subject-pattern = '^[a-z]+: '"#);
+    std::fs::write(
+        &edit_script,
+        format!("write\nreword {a}\n\0next invocation\n\0write\nno prefix"),
+    )
+    .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid description:
+    The first line doesn't match the pattern `^[a-z]+: `
+    Hint: Fix the description, or set `description-checks.warn-only = true` to only warn about these problems.
+    "###);
+}
+
+#[test]
+fn test_histedit_reorder_moves_descendants_to_new_top() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_stack(&test_env, &repo_path);
+    let a = commit_id(&test_env, &repo_path, "a");
+    let b = commit_id(&test_env, &repo_path, "b");
+    let c = commit_id(&test_env, &repo_path, "c");
+
+    // Moving the tip "c" first leaves "b" on top of the stack. The working-copy
+    // commit, which was on top of "c", ends up on top of "b".
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        format!("write\npick {c} c\npick {a} a\npick {b} b\n"),
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["histedit", "::@- ~ root()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: yqosqzyt db4499d0 (empty) (no description set)
+    Parent commit      : kkmpptxz 41784f8d b
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  db4499d02f29
+    ◉  41784f8dc8a6 b
+    │  A b
+    ◉  9e4e4e1ef6d2 a
+    │  A a
+    ◉  aef12007b5d4 c
+    │  A c
+    ◉  000000000000
+    "###);
+}
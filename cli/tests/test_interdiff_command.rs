@@ -78,6 +78,38 @@ fn test_interdiff_basic() {
     "###);
 }
 
+#[test]
+fn test_interdiff_rewritten_and_rebased() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file2"), "foo\n").unwrap();
+    let old_commit_id =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r=@", "-T=commit_id"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "change"]);
+
+    // Amend the change and rebase it onto an unrelated commit
+    std::fs::write(repo_path.join("file2"), "foo\nbar\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new", "root()"]);
+    std::fs::write(repo_path.join("file3"), "foo\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["rebase", "-r", "change", "-d", "@"]);
+
+    // The diff between the two versions of the change excludes the changes
+    // from the rebase
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["interdiff", "--from", &old_commit_id, "--to", "change"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Modified regular file file2:
+       1    1: foo
+            2: bar
+    "###);
+}
+
 #[test]
 fn test_interdiff_paths() {
     let test_env = TestEnvironment::default();
@@ -456,6 +456,18 @@ fn test_log_shortest_accessors() {
     qpv[untsmwlqt] ba1[a30916d29]
     zzz[zzzzzzzzz] 00[0000000000]
     "###);
+
+    // Can disambiguate within the displayed revisions
+    test_env.add_config(r#"ui.log-scoped-prefixes = true"#);
+    insta::assert_snapshot!(
+        render("(@----)::", r#"format_id(change_id) ++ " " ++ format_id(commit_id) ++ "\n""#),
+        @r###"
+    w[qnwkozpkust] 03[f51310b83e]
+    km[kuslswpqwq] f[77fb1909080]
+    kp[qxywonksrl] e[715ad5db646]
+    z[nkkpsqqskkl] 3[8622e54e2e5]
+    y[ostqsxwqrlt] 0c[f42f60199c]
+    "###);
 }
 
 #[test]
@@ -861,6 +873,180 @@ fn test_log_filtered_by_path() {
     "###);
 }
 
+#[test]
+fn test_log_output_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first\n\nwith \"quotes\""]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+
+    let template = r#"commit_id.short() ++ " " ++ author.name() ++ " " ++ description"#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", template, "--output=json", "--no-graph"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {"commit_id":"7efab3265779","author":"Test User","description":"second\n"}
+    {"commit_id":"2cc56896afbe","author":"Test User","description":"first\n\nwith \"quotes\"\n"}
+    {"commit_id":"000000000000"}
+    "###);
+
+    // Labels that aren't keywords don't hide the keywords inside them
+    let template = r#"label(if(current_working_copy, "working_copy"), change_id.short())"#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-T", template, "--output=json", "--no-graph"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {"change_id":"kkmpptxzrspx"}
+    {"change_id":"qpvuntsmwlqt"}
+    {"change_id":"zzzzzzzzzzzz"}
+    "###);
+
+    // Revisions are listed in the same order as without the graph
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T",
+            "change_id.short()",
+            "--output=json",
+            "--no-graph",
+            "--reversed",
+            "--limit=2",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    {"change_id":"zzzzzzzzzzzz"}
+    {"change_id":"qpvuntsmwlqt"}
+    "###);
+
+    // The graph and patches can't be rendered as JSON
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--output=json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    {"code":1,"hint":"Use --no-graph to print one JSON object per revision.","kind":"user","message":"The graph can't be shown with JSON output"}
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--output=json", "--no-graph", "-p"]);
+    insta::assert_snapshot!(stderr, @r###"
+    {"code":1,"hint":null,"kind":"user","message":"Patches can't be shown with JSON output"}
+    "###);
+}
+
+#[test]
+fn test_log_template_file() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    std::fs::write(
+        repo_path.join("log.tmpl"),
+        "separate(\" \",\n  change_id.short(),\n  description.first_line(),\n)\n",
+    )
+    .unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r@", "-T", "@log.tmpl"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  qpvuntsmwlqt first
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r@", "--no-graph", "--template-file", "log.tmpl"],
+    );
+    insta::assert_snapshot!(stdout, @"qpvuntsmwlqt first");
+
+    // Read from stdin
+    let assert = test_env
+        .jj_cmd(&repo_path, &["log", "-r@", "--no-graph", "-T", "@-"])
+        .write_stdin("description")
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @r###"
+    first
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "@missing.tmpl"]);
+    assert!(stderr.starts_with("Error: Failed to read template file missing.tmpl: "));
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["log", "-T", "description", "--template-file", "log.tmpl"],
+    );
+    insta::assert_snapshot!(stderr.lines().next().unwrap(), @"error: the argument '--template <TEMPLATE>' cannot be used with '--template-file <PATH>'");
+}
+
+#[test]
+fn test_log_cursor() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    for description in ["a", "b", "c", "d"] {
+        test_env.jj_cmd_success(&repo_path, &["new", "-m", description]);
+    }
+    let template = r#"separate(" ", commit_id, description.first_line()) ++ "\n""#;
+    let page = |cursor: Option<&str>| {
+        let mut args = vec![
+            "log",
+            "--no-graph",
+            "-r",
+            "::@ ~ root()",
+            "--limit=2",
+            "-T",
+            template,
+        ];
+        if let Some(cursor) = cursor {
+            args.extend(["--cursor", cursor]);
+        }
+        test_env.jj_cmd_success(&repo_path, &args)
+    };
+    let last_commit_id = |stdout: &str| {
+        let line = stdout.lines().last().unwrap();
+        line.split(' ').next().unwrap().to_owned()
+    };
+
+    let stdout = page(None);
+    insta::assert_snapshot!(stdout, @r###"
+    92222e93550a25d21a87d7e5b350eb7bb3d9fc5e d
+    cd95d06a5e503e86c67d36c47ffcbfb70939a632 c
+    "###);
+    let stdout = page(Some(&last_commit_id(&stdout)));
+    insta::assert_snapshot!(stdout, @r###"
+    8d63812e63065655735c484c999128d7de54ace7 b
+    f19b941f58d28712c8ecbe19f13943d3b8d44df3 a
+    "###);
+    let stdout = page(Some(&last_commit_id(&stdout)));
+    insta::assert_snapshot!(stdout, @r###"
+    230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    "###);
+
+    // The cursor works with the graph too
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T",
+            "description",
+            "--limit=1",
+            "--cursor",
+            &last_commit_id(&page(None)),
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  b
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--cursor", "xyz"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid cursor: xyz
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "@", "--cursor", "abcd"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cursor abcd doesn't point to any of the revisions
+    "###);
+}
+
 #[test]
 fn test_log_limit() {
     let test_env = TestEnvironment::default();
@@ -1234,6 +1420,129 @@ fn test_graph_styles() {
     "###);
 }
 
+#[test]
+fn test_graph_node_symbols() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["commit", "-m", "initial"]);
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "left"]);
+    std::fs::write(repo_path.join("file1"), "left\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new", "description(initial)", "-m", "right"]);
+    std::fs::write(repo_path.join("file2"), "right\n").unwrap();
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "new",
+            "description(left)",
+            "description(right)",
+            "-m",
+            "merge",
+        ],
+    );
+    test_env.jj_cmd_success(&repo_path, &["new", "description(right)", "-m", "other"]);
+    std::fs::write(repo_path.join("file1"), "other\n").unwrap();
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "new",
+            "description(merge)",
+            "description(other)",
+            "-m",
+            "conflict",
+        ],
+    );
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "top"]);
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+
+    // Only the working-copy symbol differs by default
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T=description", "-r=all()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉
+    │ @  top
+    ├─╯
+    ◉    conflict
+    ├─╮
+    │ ◉  other
+    ◉ │  merge
+    ├─╮
+    │ ◉  right
+    ◉ │  left
+    ├─╯
+    ◉  initial
+    ◉
+    "###);
+
+    // Unset kinds fall back to the default symbol
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T=description",
+            "-r=all()",
+            "--config-toml",
+            r#"ui.graph.node = { merge = "M" }"#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉
+    │ @  top
+    ├─╯
+    M    conflict
+    ├─╮
+    │ ◉  other
+    M │  merge
+    ├─╮
+    │ ◉  right
+    ◉ │  left
+    ├─╯
+    ◉  initial
+    ◉
+    "###);
+
+    test_env.add_config(
+        r#"
+        revset-aliases."immutable_heads()" = "description(initial)"
+        [ui.graph.node]
+        working-copy = "W"
+        other-working-copy = "O"
+        conflict = "X"
+        immutable = "I"
+        merge = "M"
+        root = "R"
+        default = "*"
+        "#,
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T=description", "-r=all()"]);
+    insta::assert_snapshot!(stdout, @r###"
+    O
+    │ W  top
+    ├─╯
+    X    conflict
+    ├─╮
+    │ *  other
+    M │  merge
+    ├─╮
+    │ *  right
+    * │  left
+    ├─╯
+    I  initial
+    R
+    "###);
+
+    // The obslog uses the same symbols
+    let stdout = test_env.jj_cmd_success(&repo_path, &["obslog", "-T=description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    W  top
+    X
+    "###);
+}
+
 #[test]
 fn test_log_word_wrap() {
     let test_env = TestEnvironment::default();
@@ -1354,4 +1663,33 @@ fn test_log_word_wrap() {
        (empty)
        merge
     "###);
+
+    // --width and ui.width override $COLUMNS
+    insta::assert_snapshot!(render(&["log", "-r@", "--width=40"], 100, true), @r###"
+    @  mzvwutvl test.user@example.com
+    │  2001-02-03 04:05:11.000 +07:00
+    ~  68518a7e
+       (empty) merge
+    "###);
+    insta::assert_snapshot!(
+        render(&["log", "-r@", "--config-toml=ui.width=40"], 100, true),
+        @r###"
+    @  mzvwutvl test.user@example.com
+    │  2001-02-03 04:05:11.000 +07:00
+    ~  68518a7e
+       (empty) merge
+    "###);
+
+    // A width of 0 would leave no room for any content
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["log", "-r@", "--width=0"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value '0' for '--width <COLUMNS>': 0 is not in 1..=65535
+
+    For more information, try '--help'.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r@", "--config-toml=ui.width=0"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid `ui.width`: must be at least 1
+    For help, see https://github.com/martinvonz/jj/blob/main/docs/config.md.
+    "###);
 }
@@ -159,6 +159,27 @@ fn test_move() {
     insta::assert_snapshot!(stdout, @r###"
     e
     "###);
+
+    // Can move between non-adjacent commits, and new conflicts are reported
+    test_env.jj_cmd_success(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["move", "--from", "f", "--to", "d"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: qwyusntz 52dd0614 (empty) (no description set)
+    Parent commit      : znkkpsqq 1c535e3d e f | (no description set)
+    New conflicts appeared in these commits:
+      vruxwmqv 527eacb5 d | (conflict) (no description set)
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  52dd0614ba1c
+    ◉  1c535e3db632 e f
+    ◉  527eacb5c7de d
+    │ ◉  caa4d0b23201 c
+    │ ◉  55171e33db26 b
+    ├─╯
+    ◉  3db0a2f5b535 a
+    ◉  000000000000
+    "###);
 }
 
 #[test]
@@ -0,0 +1,68 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+pub mod common;
+
+#[test]
+fn test_note() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    let template = r#"description.first_line() ++ " " ++ commit_id.short() ++ " " ++ note"#;
+
+    // Adding a note doesn't rewrite the commit
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "add", "-m", "needs review"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    first 69542c1984c1 needs review
+    "###);
+
+    // Notes can be added to
+    test_env.jj_cmd_success(&repo_path, &["note", "add", "-m", "reviewed"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "show"]);
+    insta::assert_snapshot!(stdout, @r###"
+    needs review
+    reviewed
+    "###);
+
+    // The note stays with the change when it's rewritten
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "second"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "show", "-r", "@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    needs review
+    reviewed
+    "###);
+
+    // Notes can be removed, and the removal undone
+    test_env.jj_cmd_success(&repo_path, &["note", "remove"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "show"]);
+    insta::assert_snapshot!(stdout, @"");
+    test_env.jj_cmd_success(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "show"]);
+    insta::assert_snapshot!(stdout, @r###"
+    needs review
+    reviewed
+    "###);
+
+    // Other commits have no note
+    let stdout = test_env.jj_cmd_success(&repo_path, &["note", "show", "-r", "root()"]);
+    insta::assert_snapshot!(stdout, @"");
+}
@@ -235,3 +235,75 @@ fn test_obslog_squash() {
          (empty) second
     "###);
 }
+
+#[test]
+fn test_obslog_predecessors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "second"]);
+
+    let template = r#"commit_id.short() ++ " " ++ description.first_line()
+        ++ " (rewritten from [" ++ predecessors.map(|c| c.commit_id().short()) ++ "])\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["obslog", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    f42395110c15 second (rewritten from [69542c1984c1])
+    69542c1984c1 first (rewritten from [230dd059e1b0])
+    230dd059e1b0  (rewritten from [])
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "predecessors(@)", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @"69542c1984c1 first (rewritten from [230dd059e1b0])");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "predecessors(root())",
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_obslog_cursor() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "second"]);
+
+    let template = r#"separate(" ", commit_id, description.first_line()) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["obslog", "--no-graph", "--limit=1", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    f42395110c1533120b8bbfffa91980f0af200861 second
+    "###);
+    let cursor = stdout.split(' ').next().unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "obslog",
+            "--no-graph",
+            "--limit=1",
+            "--cursor",
+            cursor,
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    69542c1984c1f9d91f7c6c9c9e6941782c944bd9 first
+    "###);
+}
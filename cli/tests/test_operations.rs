@@ -83,7 +83,10 @@ fn test_op_log() {
 
     // We get a reasonable message if an invalid operation ID is specified
     insta::assert_snapshot!(test_env.jj_cmd_failure(&repo_path, &["log", "--at-op", "foo"]), @r###"
-    Error: Operation ID "foo" is not a valid hexadecimal prefix
+    Error: No operation tag named "foo"
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_failure(&repo_path, &["log", "--at-op", "foo/bar"]), @r###"
+    Error: Operation ID "foo/bar" is not a valid hexadecimal prefix
     "###);
     // Odd length
     insta::assert_snapshot!(test_env.jj_cmd_failure(&repo_path, &["log", "--at-op", "123456789"]), @r###"
@@ -192,6 +195,12 @@ fn test_op_log_template() {
     ◉  |
     "###);
 
+    insta::assert_snapshot!(
+        render(r#"separate(" ", username, hostname, description) ++ "\n""#), @r###"
+    @  test-username host.example.com add workspace 'default'
+    ◉  test-username host.example.com initialize repo
+    "###);
+
     // Test the default template, i.e. with relative start time and duration. We
     // don't generally use that template because it depends on the current time,
     // so we need to reset the time range format here.
@@ -303,9 +312,303 @@ fn test_op_log_configurable() {
     assert!(stdout.contains("my-username@my-hostname"));
 }
 
+#[test]
+fn test_read_commands_at_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "old\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["op", "tag", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    std::fs::write(repo_path.join("file"), "new\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "new\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["st"]);
+
+    // Revsets, file contents, and diffs are evaluated against the view at the
+    // operation
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T",
+            "description",
+            "--at-op",
+            "first",
+            "-r",
+            "@ | @-",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  first
+    ◉
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["files", "--at-op", "first"]);
+    insta::assert_snapshot!(stdout, @"file");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["cat", "file", "--at-op", "first"]);
+    insta::assert_snapshot!(stdout, @"old");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git", "--at-op", "first"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    new file mode 100644
+    index 0000000000..3367afdbbf
+    --- /dev/null
+    +++ b/file
+    @@ -1,0 +1,1 @@
+    +old
+    "###);
+
+    // The workspace had no working-copy commit before it was added
+    let init_op_id = test_env
+        .jj_cmd_success(
+            &repo_path,
+            &["op", "log", "--no-graph", "-T", r#"id ++ "\n""#],
+        )
+        .lines()
+        .last()
+        .unwrap()
+        .to_owned();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--at-op", &init_op_id]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Workspace "default" doesn't have a working-copy commit at operation f1c462c494be
+    Hint: Specify revisions other than `@`, e.g. with `-r`.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["diff", "--at-op", &init_op_id]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Workspace "default" doesn't have a working-copy commit at operation f1c462c494be
+    Hint: Specify revisions other than `@`, e.g. with `-r`.
+    "###);
+    // Other revisions can still be read
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "-r", "all()", "--at-op", &init_op_id]);
+    insta::assert_snapshot!(stdout, @"◉  zzzzzzzz root() 00000000");
+}
+
+#[test]
+fn test_op_tag() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "tag", "before-new"]);
+    insta::assert_snapshot!(stdout, @"Tagged operation 3dd2b143413e as before-new");
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_success(&repo_path, &["op", "tag", "after-new"]);
+
+    // Tags can be used instead of operation IDs
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", "description", "--at-op", "before-new"]), @r###"
+    @  first
+    ◉
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", "description", "--at-op", "after-new-"]), @r###"
+    @  first
+    ◉
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "tag"]);
+    insta::assert_snapshot!(stdout, @r###"
+    after-new: 04a831addc15 new empty commit
+    before-new: 3dd2b143413e describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    "###);
+
+    // Tags can't be moved or confused with operation IDs
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "tag", "before-new"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Operation tag "before-new" already exists and points to operation 3dd2b143413e
+    Hint: Use `jj op tag --delete before-new` to delete it first.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "tag", "abc"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Invalid operation tag name "abc"
+    Hint: Names may contain letters, digits, `-`, `_`, and `.`. They must not end with `-` or consist only of hexadecimal digits.
+    "###);
+
+    test_env.jj_cmd_success(&repo_path, &["op", "restore", "before-new"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]), @r###"
+    @  first
+    ◉
+    "###);
+
+    test_env.jj_cmd_success(&repo_path, &["op", "tag", "--delete", "after-new"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "tag"]);
+    insta::assert_snapshot!(stdout, @r###"
+    before-new: 3dd2b143413e describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "tag", "--delete", "after-new"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No operation tag named "after-new"
+    "###);
+}
+
+#[test]
+fn test_op_restore_partial() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let log_template = r#"separate(" ", description.first_line(), branches)"#;
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "a"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "b"]);
+    test_env.jj_cmd_success(&repo_path, &["op", "tag", "saved"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "set", "a", "-r", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "delete", "b"]);
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "c"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    @  second a c
+    ◉  first
+    ◉
+    "###);
+
+    // Restore only the given branch
+    test_env.jj_cmd_success(&repo_path, &["op", "restore", "saved", "--branch", "a"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    @  second c
+    ◉  first a
+    ◉
+    "###);
+
+    // Restore all branches, including deleting ones that didn't exist
+    test_env.jj_cmd_success(&repo_path, &["op", "restore", "saved", "--branches"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    @  second
+    ◉  first a b
+    ◉
+    "###);
+
+    // Restore only the working-copy commit. The second commit is kept.
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "d", "-r", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["op", "restore", "saved", "--working-copies"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    ◉  second d
+    @  first a b
+    ◉
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "restore", "saved", "--branch", "x"]);
+    insta::assert_snapshot!(stderr, @"Error: No such branch: x");
+}
+
+#[test]
+fn test_op_commits_added_by_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "root()", "-m", "third"]);
+
+    let template = r#"description.first_line() ++ " <- " ++ operation_id ++ " "
+        ++ operation_description ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    third <- 7ad53e111213 new empty commit
+    second <- cc9a55dbeca6 new empty commit
+    first <- 3dd2b143413e describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
+     <- f1c462c494be initialize repo
+    "###);
+
+    // The operation that created the "second" commit
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-T", r#"id.short() ++ "\n""#],
+    );
+    let second_op_id = stdout.lines().nth(1).unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            &format!("op({second_op_id})"),
+            "-T",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"second <- cc9a55dbeca6 new empty commit");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", r#"op("@")"#, "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @"third <- 7ad53e111213 new empty commit");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", "op(fffffffff)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to resolve operation "fffffffff": No operation ID matching "fffffffff"
+    "###);
+
+    // A commit that was added again is attributed to the latest operation
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(second)"]);
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "description(second)", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @"second <- 8ceca3f1d4e4 undo operation 6295bc5da6898801b74fff2a5ca206dd3f15c1cd5c905f6d7018bf8b828aac5fcabb10ba50c0e41154ab924c3de66753a5ecd966de9d9b77871437ddea5da149");
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path, op_id: &str) -> String {
     test_env.jj_cmd_success(
         repo_path,
         &["log", "-T", "commit_id", "--at-op", op_id, "-r", "all()"],
     )
 }
+
+#[test]
+fn test_op_recover() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let log_template = r#"separate(" ", change_id.short(), description.first_line())"#;
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    let change_id =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", "change_id"]);
+    let commit_id =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", "commit_id"]);
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "@"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "third"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    @  yqosqzytrlsw third
+    ◉  royxmykxtrkr
+    ◉  qpvuntsmwlqt first
+    ◉  zzzzzzzzzzzz
+    "###);
+
+    // The abandoned commit is brought back, and the later work is kept
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "recover", &change_id[..12]]);
+    insta::assert_snapshot!(stdout, @r###"
+    Recovered commit kkmpptxz bc28fa9d (empty) second
+    "###);
+    insta::assert_snapshot!(stderr, @"");
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]), @r###"
+    @  yqosqzytrlsw third
+    ◉  royxmykxtrkr
+    │ ◉  kkmpptxzrspx second
+    ├─╯
+    ◉  qpvuntsmwlqt first
+    ◉  zzzzzzzzzzzz
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "recover", &commit_id]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revision "bc28fa9df1e531145bb9b91f6afc7c5ddf7f5861" is already visible
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "recover", "mmmmmmmm"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revision "mmmmmmmm" doesn't exist in any operation
+    "###);
+}
@@ -171,6 +171,74 @@ fn test_rebase_branch() {
     "###);
 }
 
+#[test]
+fn test_rebase_multiple_stacks_single_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["a"]);
+    create_commit(&test_env, &repo_path, "e", &["d"]);
+    create_commit(&test_env, &repo_path, "f", &["a"]);
+    // Test the setup
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  f
+    │ ◉  e
+    │ ◉  d
+    ├─╯
+    │ ◉  c
+    │ ◉  b
+    ├─╯
+    ◉  a
+    ◉
+    "###);
+
+    // Independent stacks are rebased onto the new trunk by a single operation
+    let stdout = test_env.jj_cmd_success(&repo_path, &["rebase", "-b=c", "-b=e", "-d=f"]);
+    insta::assert_snapshot!(stdout, @"Rebased 4 commits");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ◉  e
+    ◉  d
+    │ ◉  c
+    │ ◉  b
+    ├─╯
+    @  f
+    ◉  a
+    ◉
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "-l=2",
+            "-T=description ++ \"\\n\"",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    rebase 2 commits and their descendants
+    create branch f pointing to commit 0c3e469ca8d498fbdc16af5d6a309d26fa659fc0
+    "###);
+
+    // ... which can be undone at once
+    test_env.jj_cmd_success(&repo_path, &["undo"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  f
+    │ ◉  e
+    │ ◉  d
+    ├─╯
+    │ ◉  c
+    │ ◉  b
+    ├─╯
+    ◉  a
+    ◉
+    "###);
+}
+
 #[test]
 fn test_rebase_branch_with_merge() {
     let test_env = TestEnvironment::default();
@@ -0,0 +1,180 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+pub mod common;
+
+fn create_commit(
+    test_env: &TestEnvironment,
+    repo_path: &Path,
+    name: &str,
+    parents: &[&str],
+    files: &[(&str, &str)],
+) {
+    if parents.is_empty() {
+        test_env.jj_cmd_success(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_success(repo_path, &args);
+    }
+    for (name, content) in files {
+        std::fs::write(repo_path.join(name), content).unwrap();
+    }
+    test_env.jj_cmd_success(repo_path, &["branch", "create", name]);
+}
+
+fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &[
+            "log",
+            "-T",
+            r#"separate(" ", branches, if(conflict, "conflict"))"#,
+        ],
+    )
+}
+
+#[test]
+fn test_resolution_replayed_on_rebase() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(r#"conflicts.remember-resolutions = true"#);
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("file", "1\n2\n3\n4\n5\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file", "1\na\n3\n4\n5\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file", "1\nb\n3\n4\n5\n")],
+    );
+    create_commit(&test_env, &repo_path, "merge", &["a", "b"], &[]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @    merge conflict
+    ├─╮
+    │ ◉  b
+    ◉ │  a
+    ├─╯
+    ◉  base
+    ◉
+    "###);
+
+    // Resolve the conflict in the working copy
+    std::fs::write(repo_path.join("file"), "1\nab\n3\n4\n5\n").unwrap();
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolution", "list"]), @"995e76c351bc file");
+
+    // The same conflict appears when rebasing b onto a. The non-conflicting
+    // parts of the file may differ.
+    test_env.jj_cmd_success(&repo_path, &["branch", "delete", "merge"]);
+    test_env.jj_cmd_success(&repo_path, &["abandon", "@"]);
+    test_env.jj_cmd_success(&repo_path, &["edit", "b"]);
+    std::fs::write(repo_path.join("file"), "1\nb\n3\n4\nfive\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: royxmykx a69f9f3d b | b
+    Parent commit      : zsuskuln 997bf4e9 a | a
+    Added 0 files, modified 1 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  b
+    ◉  a
+    ◉  base
+    ◉
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(repo_path.join("file")).unwrap(), @r###"
+    1
+    ab
+    3
+    4
+    five
+    "###);
+
+    // Once forgotten, the conflict is no longer resolved automatically
+    test_env.jj_cmd_success(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolution", "forget", "--all"]);
+    insta::assert_snapshot!(stdout, @"Forgot 1 recorded resolutions");
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolution", "list"]), @"");
+    test_env.jj_cmd_success(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  b conflict
+    ◉  a
+    ◉  base
+    ◉
+    "###);
+}
+
+#[test]
+fn test_resolution_recorded_by_resolve() {
+    let mut test_env = TestEnvironment::default();
+    test_env.add_config(r#"conflicts.remember-resolutions = true"#);
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    let editor_script = test_env.set_up_fake_editor();
+    std::fs::write(&editor_script, "write\nresolved\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["resolve"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolution", "list"]), @"80d70f26c6b1 file");
+
+    // Forget by key prefix
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolution", "list"]);
+    let key = stdout.split_whitespace().next().unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["resolution", "forget", "zzz"]);
+    insta::assert_snapshot!(stderr, @"Error: No such recorded resolution: zzz");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolution", "forget", &key[..4]]);
+    insta::assert_snapshot!(stdout, @"Forgot 1 recorded resolutions");
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolution", "list"]), @"");
+}
+
+#[test]
+fn test_resolution_disabled() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+    std::fs::write(repo_path.join("file"), "resolved\n").unwrap();
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolution", "list"]), @"");
+}
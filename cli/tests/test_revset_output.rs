@@ -128,7 +128,7 @@ fn test_bad_function_call() {
     1 | file(a, not:a-string)
       |         ^----------^
       |
-      = Invalid arguments to revset function "file": Expected function argument of type string
+      = Invalid arguments to revset function "file": Invalid file pattern kind "not:"
     "###);
 
     let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-r", r#"file(a, "../out")"#]);
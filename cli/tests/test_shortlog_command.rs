@@ -0,0 +1,83 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+pub mod common;
+
+#[test]
+fn test_shortlog() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first\n\nbody"]);
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "--config-toml",
+            r#"user.email="other@example.com""#,
+            "new",
+            "-m",
+            "second",
+        ],
+    );
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "--config-toml",
+            r#"user.name="Other User""#,
+            "new",
+            "-m",
+            "third",
+        ],
+    );
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["shortlog"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Test User (3):
+          first
+          second
+          (no description set)
+
+    Other User (1):
+          third
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["shortlog", "--summary", "--email"]);
+    insta::assert_snapshot!(stdout, @r###"
+    2  Test User <test.user@example.com>
+    1  Other User <test.user@example.com>
+    1  Test User <other@example.com>
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["shortlog", "-r", "..@-", "-T", "commit_id.short()"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Test User (2):
+          e0bb864c3dfd
+          d4e1ffd97890
+
+    Other User (1):
+          7a1f01426826
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["shortlog", "-r", "@--", "--json"]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"name":"Test User","count":1,"commits":[{"commit_id":"d4e1ffd978902833228b1ca0abc2246a1707e2cc","change_id":"ffdaa62087a280bddc5e3d3ff933b8ae","summary":"second"}]}
+    "###);
+}
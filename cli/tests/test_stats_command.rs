@@ -0,0 +1,156 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use regex::Regex;
+
+use crate::common::TestEnvironment;
+
+pub mod common;
+
+// The sizes depend on the exact on-disk encoding, so don't check them
+fn redact_sizes(output: &str) -> String {
+    let output = Regex::new(r"size: [0-9.]+[KMGT]?i?B")
+        .unwrap()
+        .replace_all(output, "size: <size>");
+    Regex::new(r#""(repo|store)_size": \d+"#)
+        .unwrap()
+        .replace_all(&output, r#""${1}_size": <size>"#)
+        .into_owned()
+}
+
+#[test]
+fn test_stats() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_success(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_success(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "--config-toml",
+            r#"user.name="Other User""#,
+            "new",
+            "root()",
+            "-m",
+            "other",
+        ],
+    );
+    test_env.jj_cmd_success(
+        &repo_path,
+        &["new", "@", "description(second)", "-m", "merge"],
+    );
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stats"]);
+    insta::assert_snapshot!(redact_sizes(&stdout), @r###"
+    Commits: 4
+    Changes: 4
+    Merges: 1
+    Authors:
+         3  Test User <test.user@example.com>
+         1  Other User <test.user@example.com>
+    Repo size: <size>
+    Store size: <size>
+    Index: 6 commits in 2 levels
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stats", "-r", "root()..@-"]);
+    insta::assert_snapshot!(redact_sizes(&stdout), @r###"
+    Commits: 3
+    Changes: 3
+    Merges: 0
+    Authors:
+         2  Test User <test.user@example.com>
+         1  Other User <test.user@example.com>
+    Repo size: <size>
+    Store size: <size>
+    Index: 6 commits in 2 levels
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stats", "--json", "-r", "@-"]);
+    insta::assert_snapshot!(redact_sizes(&stdout), @r###"
+    {
+      "commits": 2,
+      "changes": 2,
+      "merges": 0,
+      "authors": [
+        {
+          "name": "Other User",
+          "email": "test.user@example.com",
+          "commits": 1
+        },
+        {
+          "name": "Test User",
+          "email": "test.user@example.com",
+          "commits": 1
+        }
+      ],
+      "repo_size": <size>,
+      "store_size": <size>,
+      "index": {
+        "commits": 6,
+        "levels": 2
+      }
+    }
+    "###);
+
+    let template = r#"commits ++ " commits by " ++ authors.map(|a| a.name() ++ ": " ++ a.commits()).join(", ") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stats", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    4 commits by Test User: 3, Other User: 1
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "stats",
+            "-T",
+            r#"authors ++ " (" ++ merges ++ " merges)\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Test User <test.user@example.com>, Other User <test.user@example.com> (1 merges)
+    "###);
+}
+
+#[test]
+fn test_stats_sizes_are_disjoint() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["commit", "-m", "first"]);
+
+    // The Git repo is inside the store, so the two sizes add up to the size of
+    // .jj/repo
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stats", "-T", "repo_size + store_size"]);
+    let total: u64 = stdout.parse().unwrap();
+    assert_eq!(total, dir_size(&repo_path.join(".jj").join("repo")));
+}
+
+fn dir_size(path: &std::path::Path) -> u64 {
+    std::fs::read_dir(path)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let metadata = entry.metadata().unwrap();
+            if metadata.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                metadata.len()
+            }
+        })
+        .sum()
+}
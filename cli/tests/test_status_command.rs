@@ -40,6 +40,45 @@ fn test_status_merge() {
     "###);
 }
 
+#[test]
+fn test_status_merge_conflict_origins() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "base").unwrap();
+    std::fs::write(repo_path.join("file2"), "base").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=side1"]);
+    std::fs::write(repo_path.join("file1"), "side1").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "side1"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "base", "-m=side2"]);
+    std::fs::write(repo_path.join("file1"), "side2").unwrap();
+    // The left parent has a conflict in file1, and modifies file2
+    test_env.jj_cmd_ok(&repo_path, &["new", "side1", "@", "-m=left"]);
+    std::fs::write(repo_path.join("file2"), "left").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "left"]);
+    // The right parent modifies file2 differently
+    test_env.jj_cmd_ok(&repo_path, &["new", "base", "-m=right"]);
+    std::fs::write(repo_path.join("file2"), "right").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "left", "@"]);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    The working copy is clean
+    There are unresolved conflicts at these paths:
+    file1    2-sided conflict
+    file2    2-sided conflict
+    Conflicts inherited from parent royxmykx bbaf427a left | (conflict) left:
+      file1
+    Conflicts introduced by merging the parents:
+      file2
+    Working copy : yostqsxw 4c6935a5 (conflict) (empty) (no description set)
+    Parent commit: royxmykx bbaf427a left | (conflict) left
+    Parent commit: vruxwmqv e8003b74 right
+    "###);
+}
+
 // See https://github.com/martinvonz/jj/issues/2051.
 #[test]
 fn test_status_ignored_gitignore() {
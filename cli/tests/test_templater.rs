@@ -77,6 +77,39 @@ fn test_templater_branches() {
     ├─╯
     ◉  000000000000
     "###);
+
+    let template = r#"
+    commit_id.short() ++ " "
+      ++ branches.filter(|b| b.remote()).map(|b| b.remote() ++ "/" ++ b.name()).join(",")
+      ++ " " ++ branches.len()
+      ++ if(branches.contains("origin"), " remote")
+    "#;
+    let output = test_env.jj_cmd_success(&workspace_root, &["log", "-T", template]);
+    insta::assert_snapshot!(output, @r###"
+    ◉  fed794e2ba44 origin/branch3 2 remote
+    │ ◉  b1bb3766d584  1
+    ├─╯
+    │ ◉  21c33875443e  1
+    ├─╯
+    │ @  a5b4d15489cc  2
+    │ ◉  8476341eb395 origin/branch2 1 remote
+    ├─╯
+    ◉  000000000000  0
+    "###);
+
+    let template = r#"commit_id.short() ++ " " ++ branches.with_distance()"#;
+    let output = test_env.jj_cmd_success(&workspace_root, &["log", "-T", template]);
+    insta::assert_snapshot!(output, @r###"
+    ◉  fed794e2ba44 branch3?? branch3@origin
+    │ ◉  b1bb3766d584 branch3??
+    ├─╯
+    │ ◉  21c33875443e branch1*
+    ├─╯
+    │ @  a5b4d15489cc branch2* new-branch
+    │ ◉  8476341eb395 branch2@origin+0-1
+    ├─╯
+    ◉  000000000000
+    "###);
 }
 
 #[test]
@@ -121,7 +154,7 @@ fn test_templater_parse_error() {
     1 | description ()
       |             ^---
       |
-      = expected EOI
+      = expected EOI, `+`, `-`, `==`, `!=`, `>=`, `>`, `<=`, or `<`
     "###);
 
     insta::assert_snapshot!(render_err(r#"foo"#), @r###"
@@ -158,6 +191,25 @@ fn test_templater_parse_error() {
       |
       = Method "foo" doesn't exist for type "String"
     "###);
+    insta::assert_snapshot!(render_err(r#"description.match("(")"#), @r###"
+    Error: Failed to parse template:  --> 1:19
+      |
+    1 | description.match("(")
+      |                   ^-^
+      |
+      = Invalid regex: regex parse error:
+        (
+        ^
+    error: unclosed group
+    "###);
+    insta::assert_snapshot!(render_err(r#"description.match(description)"#), @r###"
+    Error: Failed to parse template:  --> 1:19
+      |
+    1 | description.match(description)
+      |                   ^---------^
+      |
+      = Expected string literal
+    "###);
 
     insta::assert_snapshot!(render_err(r#"10000000000000000000"#), @r###"
     Error: Failed to parse template:  --> 1:1
@@ -245,6 +297,15 @@ fn test_templater_parse_error() {
       = Function "if": Expected 2 to 3 arguments
     "###);
 
+    insta::assert_snapshot!(render_err(r#"surround("(", "a")"#), @r###"
+    Error: Failed to parse template:  --> 1:10
+      |
+    1 | surround("(", "a")
+      |          ^------^
+      |
+      = Function "surround": Expected 3 arguments
+    "###);
+
     insta::assert_snapshot!(render_err(r#"if(label("foo", "bar"), "baz")"#), @r###"
     Error: Failed to parse template:  --> 1:4
       |
@@ -308,6 +369,19 @@ fn test_templater_list_method() {
     // Lambda expression in alias
     insta::assert_snapshot!(render(r#""a\nb\nc".lines().map(identity)"#), @"a b c");
 
+    insta::assert_snapshot!(render(r#""".lines().len()"#), @"0");
+    insta::assert_snapshot!(render(r#""a\nb\nc".lines().len()"#), @"3");
+    insta::assert_snapshot!(render(r#""a\nb\nc".lines().first(2)"#), @"a b");
+    insta::assert_snapshot!(render(r#""a\nb\nc".lines().first(5).join(",")"#), @"a,b,c");
+    insta::assert_snapshot!(render(r#""a\nb\nc".lines().first(-1).len()"#), @"0");
+    insta::assert_snapshot!(
+        render(r#""foo\nbar\nbaz".lines().filter(|s| s.starts_with("b"))"#), @"bar baz");
+    insta::assert_snapshot!(
+        render(r#"separate(" ", "a\nb\nc".lines().first(1), "of", "a\nb\nc".lines().len())"#),
+        @"a of 3");
+    insta::assert_snapshot!(
+        render(r#""a\nb\nc".lines().filter(|s| s.contains("b")).map(|s| s ++ s)"#), @"bb");
+
     // Not a lambda expression
     insta::assert_snapshot!(render_err(r#""a".lines().map(empty)"#), @r###"
     Error: Failed to parse template:  --> 1:17
@@ -334,6 +408,14 @@ fn test_templater_list_method() {
       |
       = Expected 1 lambda parameters
     "###);
+    insta::assert_snapshot!(render_err(r#""a".lines().filter(|s| s.lines())"#), @r###"
+    Error: Failed to parse template:  --> 1:24
+      |
+    1 | "a".lines().filter(|s| s.lines())
+      |                        ^-------^
+      |
+      = Expected expression of type "Boolean"
+    "###);
     // Error in lambda expression
     insta::assert_snapshot!(render_err(r#""a".lines().map(|s| s.unknown())"#), @r###"
     Error: Failed to parse template:  --> 1:23
@@ -411,6 +493,29 @@ fn test_templater_string_method() {
     // ranges with end > start are empty
     insta::assert_snapshot!(render(r#""abcdef".substr(4, 2)"#), @"");
     insta::assert_snapshot!(render(r#""abcdef".substr(-2, -4)"#), @"");
+
+    insta::assert_snapshot!(render(r#""abcdef".truncate(3)"#), @"abc");
+    insta::assert_snapshot!(render(r#""abcdef".truncate(99)"#), @"abcdef");
+    insta::assert_snapshot!(render(r#""abcdef".truncate(-1)"#), @"");
+    insta::assert_snapshot!(render(r#""一二三".truncate(5)"#), @"一二");
+
+    insta::assert_snapshot!(render(r#""[" ++ "ab".pad_left(4) ++ "]""#), @"[  ab]");
+    insta::assert_snapshot!(render(r#""[" ++ "ab".pad_right(4) ++ "]""#), @"[ab  ]");
+    insta::assert_snapshot!(render(r#""[" ++ "abcdef".pad_right(4) ++ "]""#), @"[abcdef]");
+    insta::assert_snapshot!(render(r#""[" ++ "一二".pad_left(5) ++ "]""#), @"[ 一二]");
+    insta::assert_snapshot!(
+        render(r#""[" ++ description.first_line().truncate(8).pad_right(8) ++ "]""#),
+        @"[descript]");
+
+    insta::assert_snapshot!(render(r##""Fixes #123 and #45".match("#[0-9]+")"##), @"#123");
+    insta::assert_snapshot!(render(r##""Fixes #123 and #45".match("#([0-9]+)")"##), @"123");
+    insta::assert_snapshot!(render(r##""no issue".match("#([0-9]+)")"##), @"");
+    insta::assert_snapshot!(render(r#""a1b22c".replace("[0-9]+", "-")"#), @"a-b-c");
+    insta::assert_snapshot!(
+        render(r#""key: value".replace("^(\\w+): (\\w+)$", "$2=$1")"#), @"value=key");
+    insta::assert_snapshot!(
+        render(r#""title\n\nSigned-off-by: x\n".replace("(?m)^Signed-off-by: .*\n", "")"#),
+        @"title");
 }
 
 #[test]
@@ -426,6 +531,11 @@ fn test_templater_signature() {
     insta::assert_snapshot!(render(r#"author.name()"#), @"Test User");
     insta::assert_snapshot!(render(r#"author.email()"#), @"test.user@example.com");
     insta::assert_snapshot!(render(r#"author.username()"#), @"test.user");
+    insta::assert_snapshot!(render(r#"author.email().local()"#), @"test.user");
+    insta::assert_snapshot!(render(r#"author.email().domain()"#), @"example.com");
+    // Email can be used as a string
+    insta::assert_snapshot!(render(r#"author.email().upper()"#), @"TEST.USER@EXAMPLE.COM");
+    insta::assert_snapshot!(render(r#"if(author.email(), "yes", "no")"#), @"yes");
 
     test_env.jj_cmd_success(
         &repo_path,
@@ -434,6 +544,7 @@ fn test_templater_signature() {
 
     insta::assert_snapshot!(render(r#"author"#), @"Another Test User <test.user@example.com>");
     insta::assert_snapshot!(render(r#"author.name()"#), @"Another Test User");
+    insta::assert_snapshot!(render(r#"author.name().words().first(1)"#), @"Another");
     insta::assert_snapshot!(render(r#"author.email()"#), @"test.user@example.com");
     insta::assert_snapshot!(render(r#"author.username()"#), @"test.user");
 
@@ -455,6 +566,7 @@ fn test_templater_signature() {
     insta::assert_snapshot!(render(r#"author"#), @"Test User <test.user>");
     insta::assert_snapshot!(render(r#"author.email()"#), @"test.user");
     insta::assert_snapshot!(render(r#"author.username()"#), @"test.user");
+    insta::assert_snapshot!(render(r#"author.email().domain()"#), @"");
 
     test_env.jj_cmd_success(
         &repo_path,
@@ -487,6 +599,7 @@ fn test_templater_signature() {
     insta::assert_snapshot!(render(r#"author.name()"#), @"Test User");
     insta::assert_snapshot!(render(r#"author.email()"#), @"");
     insta::assert_snapshot!(render(r#"author.username()"#), @"");
+    insta::assert_snapshot!(render(r#"if(author.email(), "yes", "no")"#), @"no");
 
     test_env.jj_cmd_ok(
         &repo_path,
@@ -522,6 +635,24 @@ fn test_templater_timestamp_method() {
     insta::assert_snapshot!(
         render(r#"author.timestamp().format("%Y%m%d %H:%M:%S")"#), @"19700101 00:00:00");
 
+    // Relative to the current time
+    let stdout = render(r#"author.timestamp().ago()"#);
+    assert!(stdout.ends_with(" years ago"), "{stdout:?}");
+    test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "describe",
+            "--config-toml=debug.commit-timestamp='2200-01-01T00:00:00Z'",
+            "-m",
+            "future",
+        ],
+    );
+    let stdout = get_template_output(&test_env, &repo_path, "@", "committer.timestamp().ago()");
+    assert!(
+        stdout.starts_with("in ") && stdout.ends_with(" years"),
+        "{stdout:?}"
+    );
+
     // Invalid format string
     insta::assert_snapshot!(render_err(r#"author.timestamp().format("%_")"#), @r###"
     Error: Failed to parse template:  --> 1:27
@@ -570,6 +701,32 @@ fn test_templater_timestamp_method() {
     "###);
 }
 
+#[test]
+fn test_templater_repo_keywords() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |template| get_template_output(&test_env, &repo_path, "@", template);
+
+    insta::assert_snapshot!(render(r#"current_workspace"#), @"default");
+    insta::assert_snapshot!(
+        render(r#"parents.map(|c| current_workspace)"#), @"default");
+
+    let op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "-T",
+            "if(current_operation, id.short())",
+        ],
+    );
+    assert_eq!(render(r#"current_operation_id"#), op_id);
+
+    insta::assert_snapshot!(render(r#"workspace_root"#), @"$TEST_ENV/repo");
+}
+
 #[test]
 fn test_templater_fill_function() {
     let test_env = TestEnvironment::default();
@@ -669,6 +826,118 @@ fn test_templater_fill_function() {
     "###);
 }
 
+#[test]
+fn test_templater_wrap_function() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |template| get_colored_template_output(&test_env, &repo_path, "@-", template);
+
+    // Lines within a paragraph are joined before wrapping
+    insta::assert_snapshot!(
+        render(r#"wrap(20, "The quick fox\njumps over the\n" ++
+                       label("error", "lazy") ++ " dog\n\nSecond paragraph\n")"#),
+        @r###"
+    The quick fox jumps
+    over the [38;5;1mlazy[39m dog
+
+    Second paragraph
+    "###);
+
+    // Width 0 means the output width
+    let render_with_width = |width: &str, template| {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &[
+                "log",
+                "--color=always",
+                "--no-graph",
+                "-r@-",
+                "--width",
+                width,
+                "-T",
+                template,
+            ],
+        )
+    };
+    insta::assert_snapshot!(
+        render_with_width("12", r#"wrap(0, "The quick fox jumps\nover the lazy dog\n")"#),
+        @r###"
+    The quick
+    fox jumps
+    over the
+    lazy dog
+    "###);
+
+    // The graph width is excluded from the output width
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-r@-",
+            "--width",
+            "15",
+            "-T",
+            r#"wrap(0, "The quick fox jumps\nover the lazy dog\n")"#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  The quick
+       fox jumps
+       over the
+       lazy dog
+    "###);
+}
+
+#[test]
+fn test_templater_config_env_functions() {
+    let mut test_env = TestEnvironment::default();
+    test_env.add_env_var("USER", "test-user");
+    test_env.add_env_var("SECRET", "hunter2");
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |template| get_template_output(&test_env, &repo_path, "@-", template);
+
+    insta::assert_snapshot!(render(r#"config("user.name")"#), @"Test User");
+    insta::assert_snapshot!(render(r#"config("ui.username") ++ "|""#), @"|");
+    insta::assert_snapshot!(render(r#"env("USER")"#), @"test-user");
+    insta::assert_snapshot!(render(r#"env("HOSTNAME") ++ "|""#), @"|");
+
+    insta::assert_snapshot!(test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "--no-graph", "-r@-", "-T", r#"config("git.push")"#],
+    ), @r###"
+    Error: Failed to parse template:  --> 1:8
+      |
+    1 | config("git.push")
+      |        ^--------^
+      |
+      = Config "git.push" cannot be read by templates
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "--no-graph", "-r@-", "-T", r#"env("SECRET")"#],
+    ), @r###"
+    Error: Failed to parse template:  --> 1:5
+      |
+    1 | env("SECRET")
+      |     ^------^
+      |
+      = Environment variable "SECRET" cannot be read by templates
+    "###);
+    insta::assert_snapshot!(test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "--no-graph", "-r@-", "-T", r#"env("US" ++ "ER")"#],
+    ), @r###"
+    Error: Failed to parse template:  --> 1:5
+      |
+    1 | env("US" ++ "ER")
+      |     ^----------^
+      |
+      = Expected string literal
+    "###);
+}
+
 #[test]
 fn test_templater_indent_function() {
     let test_env = TestEnvironment::default();
@@ -736,6 +1005,128 @@ fn test_templater_label_function() {
         render(r#"label(if(empty, "error", "warning"), "text")"#), @"[38;5;1mtext[39m");
 }
 
+#[test]
+fn test_templater_hyperlink_function() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_success(&repo_path, &["branch", "create", "main"]);
+    let render = |template| get_colored_template_output(&test_env, &repo_path, "@", template);
+
+    insta::assert_snapshot!(
+        render(r#"hyperlink("https://example.com/", label("error", "text"))"#),
+        @r###"
+    ]8;;https://example.com/\[38;5;1mtext[39m]8;;\
+    "###);
+
+    // An empty URL doesn't create a link
+    insta::assert_snapshot!(render(r#"hyperlink("", "text")"#), @"text");
+
+    // URLs aren't configured by default
+    insta::assert_snapshot!(render(r#"commit_id.url() ++ "|" ++ branches"#), @"|[38;5;5mmain[39m");
+
+    test_env.add_config(
+        r#"
+        hyperlinks.commit-id = "https://example.com/commit/{commit_id}"
+        hyperlinks.branch = "https://example.com/tree/{branch}"
+        "#,
+    );
+    insta::assert_snapshot!(render(r#"commit_id.url()"#), @r###"
+    [38;5;4mhttps://example.com/commit/230dd059e1b059aefc0da06a2e5a7dbf22362f22[39m
+    "###);
+    insta::assert_snapshot!(render(r#"change_id.url()"#), @"");
+    insta::assert_snapshot!(render(r#"format_short_commit_id(commit_id)"#), @r###"
+    ]8;;https://example.com/commit/230dd059e1b059aefc0da06a2e5a7dbf22362f22\[1m[38;5;4m2[0m[38;5;8m30dd059[39m]8;;\
+    "###);
+    insta::assert_snapshot!(render(r#"branches"#), @r###"
+    ]8;;https://example.com/tree/main\[38;5;5mmain]8;;\[39m
+    "###);
+
+    // The branch names still behave like a String
+    insta::assert_snapshot!(render(r#"if(branches, branches.upper())"#), @"[38;5;5mMAIN[39m");
+}
+
+#[test]
+fn test_templater_conditional() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |rev, template| get_template_output(&test_env, &repo_path, rev, template);
+    let template = r#"
+    if(root, "root")
+    elif(current_working_copy, "working copy")
+    elif(empty, "empty")
+    else("other")
+    "#;
+
+    insta::assert_snapshot!(render("root()", template), @"root");
+    insta::assert_snapshot!(render("@", template), @"working copy");
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    insta::assert_snapshot!(render("@-", template), @"empty");
+    std::fs::write(repo_path.join("file"), "").unwrap();
+    test_env.jj_cmd_success(&repo_path, &["new"]);
+    insta::assert_snapshot!(render("@-", template), @"other");
+
+    // Without else(), nothing is rendered if no condition matches
+    insta::assert_snapshot!(
+        render("@-", r#"if(root, "root") elif(empty, "empty")"#), @"");
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["log", "-T", r#"if(empty, "a") else("b", "c")"#],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:21
+      |
+    1 | if(empty, "a") else("b", "c")
+      |                     ^------^
+      |
+      = Function "else": Expected 1 arguments
+    "###);
+}
+
+#[test]
+fn test_templater_integer_operators() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |template| get_template_output(&test_env, &repo_path, "@-", template);
+    let render_err = |template| test_env.jj_cmd_failure(&repo_path, &["log", "-T", template]);
+
+    insta::assert_snapshot!(render(r#"1 + 2"#), @"3");
+    insta::assert_snapshot!(render(r#"1 - 2 - 3"#), @"-4");
+    insta::assert_snapshot!(render(r#"parents.len() + 1"#), @"1");
+    insta::assert_snapshot!(render(r#""a" ++ 2 + 3 ++ "b""#), @"a5b");
+
+    insta::assert_snapshot!(render(r#"1 == 1"#), @"true");
+    insta::assert_snapshot!(render(r#"1 != 1"#), @"false");
+    insta::assert_snapshot!(render(r#"2 > 1"#), @"true");
+    insta::assert_snapshot!(render(r#"1 >= 2"#), @"false");
+    insta::assert_snapshot!(render(r#"1 < 2"#), @"true");
+    insta::assert_snapshot!(render(r#"2 <= 2"#), @"true");
+    insta::assert_snapshot!(
+        render(r#"if(parents.len() > 3, "many", "few")"#), @"few");
+    insta::assert_snapshot!(
+        render(r#"if(parents.len() - 1 > 0, "merge", "not merge")"#), @"not merge");
+
+    insta::assert_snapshot!(render_err(r#"description + 1"#), @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | description + 1
+      | ^---------^
+      |
+      = Expected expression of type "Integer"
+    "###);
+    insta::assert_snapshot!(render_err(r#"1 > 2 > 3"#), @r###"
+    Error: Failed to parse template:  --> 1:1
+      |
+    1 | 1 > 2 > 3
+      | ^---^
+      |
+      = Expected expression of type "Integer"
+    "###);
+}
+
 #[test]
 fn test_templater_concat_function() {
     let test_env = TestEnvironment::default();
@@ -801,6 +1192,102 @@ fn test_templater_separate_function() {
         render(r#"separate(hidden, "X", "Y", "Z")"#), @"XfalseYfalseZ");
 }
 
+#[test]
+fn test_templater_surround_function() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    let render = |template| get_colored_template_output(&test_env, &repo_path, "@-", template);
+
+    insta::assert_snapshot!(render(r#"surround("(", ")", "")"#), @"");
+    insta::assert_snapshot!(render(r#"surround("(", ")", "a")"#), @"(a)");
+    insta::assert_snapshot!(render(r#"surround("", "", "a")"#), @"a");
+    insta::assert_snapshot!(render(r#"surround("(", ")", "a" ++ "b")"#), @"(ab)");
+
+    // Labeled content is preserved, empty labeled content is omitted
+    insta::assert_snapshot!(
+        render(r#"surround("[", "]", label("error", "a"))"#), @"[[38;5;1ma[39m]");
+    insta::assert_snapshot!(
+        render(r#"surround("[", "]", label("error", ""))"#), @"");
+
+    // Labeled prefix and suffix
+    insta::assert_snapshot!(
+        render(r#"surround(label("warning", "<"), label("warning", ">"), "a")"#), @"[38;5;3m<[39ma[38;5;3m>[39m");
+
+    // Conditional and keyword content
+    insta::assert_snapshot!(render(r#"surround("(", ")", if(false, "t"))"#), @"");
+    insta::assert_snapshot!(render(r#"surround("(", ")", branches)"#), @"");
+    insta::assert_snapshot!(render(r#"surround("(", ")", commit_id.short(4))"#), @"([38;5;4m0000[39m)");
+
+    // Nested
+    insta::assert_snapshot!(
+        render(r#"surround("(", ")", separate(" ", surround("<", ">", ""), "a"))"#), @"(a)");
+}
+
+#[test]
+fn test_templater_table_function() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "repo", "--git"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "-m", "a much longer second description"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "long-branch-name"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "third"]);
+    test_env.jj_cmd_ok(&repo_path, &["branch", "create", "b"]);
+
+    let template = r#"table(description.first_line(), branches, commit_id.short(4)) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    third                            b                c1be
+    a much longer second description long-branch-name 3463
+    first                                             6954
+                                                      0000
+    "###);
+
+    // Cells aren't aligned in the graph log
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["log", "-r@-", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◉  a much longer second description long-branch-name 3463
+    │
+    ~
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    warning: The cells of table() are only aligned into columns with --no-graph and ui.log-word-wrap disabled.
+    "###);
+
+    // Nor when the content is wrapped
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r@-",
+            "-T",
+            template,
+            "--config-toml=ui.log-word-wrap=true",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    a much longer second description long-branch-name 3463
+    "###);
+    insta::assert_snapshot!(stderr, @r###"
+    warning: The cells of table() are only aligned into columns with --no-graph and ui.log-word-wrap disabled.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "table()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:7
+      |
+    1 | table()
+      |       ^
+      |
+      = Function "table": Expected at least 1 arguments
+    "###);
+}
+
 #[test]
 fn test_templater_upper_lower() {
     let test_env = TestEnvironment::default();
@@ -14,7 +14,7 @@
 
 use std::path::Path;
 
-use crate::common::TestEnvironment;
+use crate::common::{get_stderr_string, get_stdout_string, TestEnvironment};
 
 pub mod common;
 
@@ -70,6 +70,145 @@ fn test_workspaces_add_second_workspace() {
     "###);
 }
 
+/// Test the workspace objects returned by the working_copies keyword
+#[test]
+fn test_workspaces_working_copies_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    // No workspace is listed in a single-workspace repo
+    let template = r#"working_copies.len() ++ " " ++ if(working_copies, "yes", "no")"#;
+    let stdout = test_env.jj_cmd_success(&main_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  0 no
+    │
+    ~
+    "###);
+
+    test_env.jj_cmd_ok(
+        &main_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+    test_env.jj_cmd_ok(&secondary_path, &["edit", "default@"]);
+
+    let template = r#"
+    separate(" ",
+      working_copies,
+      working_copies.len(),
+      working_copies.map(|w| if(w.current(), "[" ++ w.name() ++ "]", w.name())),
+      working_copies.contains("second@"),
+    )
+    "#;
+    let stdout = test_env.jj_cmd_success(&main_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  default@ second@ 2 [default] second true
+    │
+    ~
+    "###);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["log", "-T", template, "-r@"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  default@ second@ 2 default [second] true
+    │
+    ~
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["log", "-T", "working_copies.map(|w| w.foo())", "-r@"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template:  --> 1:26
+      |
+    1 | working_copies.map(|w| w.foo())
+      |                          ^-^
+      |
+      = Method "foo" doesn't exist for type "WorkspaceRef"
+    "###);
+}
+
+/// Test diffing the working copies of two workspaces, including changes that
+/// haven't been snapshotted yet
+#[test]
+fn test_workspaces_diff_working_copies() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_success(&main_path, &["commit", "-m", "initial"]);
+    test_env.jj_cmd_success(
+        &main_path,
+        &["workspace", "add", "--name", "second", "../secondary"],
+    );
+
+    // Neither of these changes is snapshotted before running `jj diff`
+    std::fs::write(main_path.join("file"), "main\n").unwrap();
+    std::fs::write(secondary_path.join("file"), "secondary\n").unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &main_path,
+        &["diff", "--git", "--from", "default@", "--to", "second@"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index ba2906d066...344306889e 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -main
+    +secondary
+    "###);
+
+    // Also works from the other side
+    std::fs::write(main_path.join("file"), "main 2\n").unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &secondary_path,
+        &["diff", "--git", "--from", "second@", "--to", "default@"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 344306889e...7ba95f89a9 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -secondary
+    +main 2
+    "###);
+
+    // If the other workspace is gone, its last snapshot is used
+    std::fs::rename(&secondary_path, test_env.env_root().join("moved")).unwrap();
+    let assert = test_env
+        .jj_cmd(&main_path, &["diff", "--summary", "--to", "second@"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"M file");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Could not snapshot the working copy of workspace second; using its last snapshot instead
+    "###);
+}
+
+/// Test that the location of a workspace is recorded by commands that modify
+/// the repo, but not by read-only ones
+#[test]
+fn test_workspaces_record_workspace_root() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let roots_dir = main_path.join(".jj").join("repo").join("workspace_roots");
+    assert!(roots_dir.is_dir());
+
+    // Like a workspace created by an older version
+    std::fs::remove_dir_all(&roots_dir).unwrap();
+    test_env.jj_cmd_success(&main_path, &["diff", "--from", "default@"]);
+    test_env.jj_cmd_success(&main_path, &["log"]);
+    assert!(!roots_dir.exists());
+
+    test_env.jj_cmd_ok(&main_path, &["describe", "-m", "first"]);
+    assert!(roots_dir.is_dir());
+}
+
 /// Test making changes to the working copy in a workspace as it gets rewritten
 /// from another workspace
 #[test]
@@ -282,6 +421,76 @@ fn test_workspaces_update_stale_snapshot() {
 }
 
 /// Test forgetting workspaces
+#[test]
+fn test_workspaces_copy_changes() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_success(test_env.env_root(), &["init", "--git", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file1"), "contents\n").unwrap();
+    std::fs::write(main_path.join("file2"), "contents\n").unwrap();
+    test_env.jj_cmd_success(&main_path, &["new"]);
+    test_env.jj_cmd_success(&main_path, &["workspace", "add", "../secondary"]);
+
+    std::fs::write(main_path.join("file1"), "changed in main\n").unwrap();
+    std::fs::write(main_path.join("file2"), "changed in main\n").unwrap();
+    std::fs::write(secondary_path.join("file2"), "changed in secondary\n").unwrap();
+    test_env.jj_cmd_success(&secondary_path, &["st"]);
+
+    // Copy only some of the changes
+    let assert = test_env
+        .jj_cmd(
+            &main_path,
+            &["workspace", "copy-changes", "secondary", "file1"],
+        )
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @"");
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Hint: Run `jj workspace update-stale` in workspace secondary to update its working copy.
+    "###);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["workspace", "update-stale"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy now at: pmmvwywv b1b65f27 (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    "###);
+    let stdout = test_env.jj_cmd_success(&secondary_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M file1
+    M file2
+    "###);
+    // The changes are still in the current workspace
+    let stdout = test_env.jj_cmd_success(&main_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M file1
+    M file2
+    "###);
+
+    // Conflicting changes are reported
+    let assert = test_env
+        .jj_cmd(&main_path, &["workspace", "copy-changes", "secondary"])
+        .assert()
+        .success();
+    insta::assert_snapshot!(get_stdout_string(&assert), @r###"
+    The changes conflict with the working-copy commit of workspace secondary at these paths:
+    file2    2-sided conflict
+    "###);
+    insta::assert_snapshot!(get_stderr_string(&assert), @r###"
+    Hint: Run `jj workspace update-stale` in workspace secondary to update its working copy.
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&main_path, &["workspace", "copy-changes", "default"]);
+    insta::assert_snapshot!(stderr, @"Error: Cannot copy changes to the current workspace");
+    let stderr = test_env.jj_cmd_failure(&main_path, &["workspace", "copy-changes", "unknown"]);
+    insta::assert_snapshot!(stderr, @"Error: No such workspace");
+    let stderr = test_env.jj_cmd_failure(
+        &main_path,
+        &["workspace", "copy-changes", "secondary", "nonexistent"],
+    );
+    insta::assert_snapshot!(stderr, @"Error: No changes to copy");
+}
+
 #[test]
 fn test_workspaces_forget() {
     let test_env = TestEnvironment::default();
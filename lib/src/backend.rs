@@ -17,6 +17,7 @@
 use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
+use std::io;
 use std::io::Read;
 use std::result::Result;
 use std::vec::Vec;
@@ -486,6 +487,17 @@ pub trait Backend: Send + Sync + Debug {
 
     fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>>;
 
+    /// Returns the size of the file contents in bytes. Backends that can look
+    /// up the size without reading the contents should override this.
+    fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let mut reader = self.read_file(path, id)?;
+        io::copy(&mut reader, &mut io::sink()).map_err(|err| BackendError::ReadObject {
+            object_type: id.object_type(),
+            hash: id.hex(),
+            source: Box::new(err),
+        })
+    }
+
     fn write_file(&self, path: &RepoPath, contents: &mut dyn Read) -> BackendResult<FileId>;
 
     fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> BackendResult<String>;
@@ -37,8 +37,10 @@ use thiserror::Error;
 use crate::backend::{ChangeId, CommitId, ObjectId};
 use crate::commit::{Commit, CommitByCommitterTimestamp};
 use crate::file_util::persist_content_addressed_temp_file;
+use crate::id_prefix::{IdIndex, IdIndexSource, IdIndexSourceEntry};
 use crate::index::{
-    HexPrefix, Index, IndexStore, IndexWriteError, MutableIndex, PrefixResolution, ReadonlyIndex,
+    ChangeIdIndex, HexPrefix, Index, IndexStore, IndexWriteError, MutableIndex, PrefixResolution,
+    ReadonlyIndex,
 };
 use crate::op_store::{OpStoreError, OperationId};
 use crate::operation::Operation;
@@ -210,6 +212,7 @@ impl IndexStore for DefaultIndexStore {
         "default"
     }
 
+    #[tracing::instrument(skip_all)]
     fn get_index_at_op(&self, op: &Operation, store: &Arc<Store>) -> Box<dyn ReadonlyIndex> {
         let op_id_hex = op.id().hex();
         let op_id_file = self.dir.join("operations").join(op_id_hex);
@@ -677,6 +680,13 @@ impl Index for MutableIndexImpl {
     ) -> Result<Box<dyn Revset<'index> + 'index>, RevsetEvaluationError> {
         CompositeIndex(self).evaluate_revset(expression, store)
     }
+
+    fn change_id_index(
+        &self,
+        heads: &mut dyn Iterator<Item = &CommitId>,
+    ) -> Box<dyn ChangeIdIndex + '_> {
+        CompositeIndex(self).change_id_index(heads)
+    }
 }
 
 impl MutableIndex for MutableIndexImpl {
@@ -899,7 +909,7 @@ impl<'a> CompositeIndex<'a> {
         false
     }
 
-    fn common_ancestors_pos(
+    pub fn common_ancestors_pos(
         &self,
         set1: &[IndexPosition],
         set2: &[IndexPosition],
@@ -1001,6 +1011,24 @@ impl<'a> CompositeIndex<'a> {
         let revset_impl = default_revset_engine::evaluate(expression, store, *self)?;
         Ok(Box::new(revset_impl))
     }
+
+    fn change_id_index(
+        &self,
+        heads: &mut dyn Iterator<Item = &CommitId>,
+    ) -> Box<dyn ChangeIdIndex + 'a> {
+        // TODO: Create a persistent lookup from change id to commit ids.
+        let head_positions = heads
+            .map(|id| self.commit_id_to_pos(id).unwrap())
+            .collect_vec();
+        let mut pos_by_change = IdIndex::builder();
+        for entry in self.walk_revs(&head_positions, &[]) {
+            pos_by_change.insert(&entry.change_id(), entry.position());
+        }
+        Box::new(ChangeIdIndexImpl {
+            index: *self,
+            pos_by_change: pos_by_change.build(),
+        })
+    }
 }
 
 impl Index for CompositeIndex<'_> {
@@ -1080,6 +1108,45 @@ impl Index for CompositeIndex<'_> {
     ) -> Result<Box<dyn Revset<'index> + 'index>, RevsetEvaluationError> {
         CompositeIndex::evaluate_revset(self, expression, store)
     }
+
+    fn change_id_index(
+        &self,
+        heads: &mut dyn Iterator<Item = &CommitId>,
+    ) -> Box<dyn ChangeIdIndex + '_> {
+        CompositeIndex::change_id_index(self, heads)
+    }
+}
+
+struct ChangeIdIndexImpl<'index> {
+    index: CompositeIndex<'index>,
+    pos_by_change: IdIndex<ChangeId, IndexPosition, 4>,
+}
+
+impl ChangeIdIndex for ChangeIdIndexImpl<'_> {
+    fn resolve_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>> {
+        self.pos_by_change
+            .resolve_prefix_with(self.index, prefix, |entry| entry.commit_id())
+            .map(|(_, commit_ids)| commit_ids)
+    }
+
+    fn shortest_unique_prefix_len(&self, change_id: &ChangeId) -> usize {
+        self.pos_by_change
+            .shortest_unique_prefix_len(self.index, change_id)
+    }
+}
+
+impl<'index> IdIndexSource<IndexPosition> for CompositeIndex<'index> {
+    type Entry = IndexEntry<'index>;
+
+    fn entry_at(&self, pointer: &IndexPosition) -> Self::Entry {
+        self.entry_by_pos(*pointer)
+    }
+}
+
+impl IdIndexSourceEntry<ChangeId> for IndexEntry<'_> {
+    fn to_key(&self) -> ChangeId {
+        self.change_id()
+    }
 }
 
 pub struct IndexLevelStats {
@@ -1341,7 +1408,10 @@ pub struct RevWalk<'a>(RevWalkImpl<'a, CompositeIndex<'a>>);
 impl<'a> RevWalk<'a> {
     fn new(index: CompositeIndex<'a>) -> Self {
         let queue = RevWalkQueue::new(index);
-        RevWalk(RevWalkImpl { queue })
+        RevWalk(RevWalkImpl {
+            queue,
+            min_generation: 0,
+        })
     }
 
     fn add_wanted(&mut self, pos: IndexPosition) {
@@ -1368,13 +1438,19 @@ impl<'a> RevWalk<'a> {
     /// Use this if you are only interested in descendants of the given roots.
     /// The caller still needs to filter out unwanted entries.
     pub fn take_until_roots(
-        self,
+        mut self,
         root_positions: &[IndexPosition],
     ) -> impl Iterator<Item = IndexEntry<'a>> + Clone + 'a {
-        // We can also make it stop visiting based on the generation number. Maybe
-        // it will perform better for unbalanced branchy history.
-        // https://github.com/martinvonz/jj/pull/1492#discussion_r1160678325
+        // Entries older than the oldest root by position or by generation number
+        // can't be descendants of the roots. The generation number prunes side
+        // branches which would otherwise be walked down to the bottom position.
+        let index = self.0.queue.index;
         let bottom_position = *root_positions.iter().min().unwrap_or(&IndexPosition::MAX);
+        self.0.min_generation = root_positions
+            .iter()
+            .map(|&pos| index.entry_by_pos(pos).generation_number())
+            .min()
+            .unwrap_or(u32::MAX);
         self.take_while(move |entry| entry.position() >= bottom_position)
     }
 
@@ -1413,13 +1489,18 @@ impl<'a> Iterator for RevWalk<'a> {
 #[derive(Clone)]
 struct RevWalkImpl<'a, I: RevWalkIndex<'a>> {
     queue: RevWalkQueue<'a, I, ()>,
+    /// Wanted entries of lower generation are skipped along with their
+    /// ancestors.
+    min_generation: u32,
 }
 
 impl<'a, I: RevWalkIndex<'a>> RevWalkImpl<'a, I> {
     fn next(&mut self) -> Option<IndexEntry<'a>> {
         while let Some(item) = self.queue.pop() {
             self.queue.skip_while_eq(&item.entry);
-            if item.is_wanted() {
+            if item.is_wanted() && item.entry.generation_number() < self.min_generation {
+                continue;
+            } else if item.is_wanted() {
                 self.queue.push_wanted_adjacents(&item.entry, ());
                 return Some(item.entry);
             } else if self.queue.items.len() == self.queue.unwanted_count {
@@ -1979,6 +2060,13 @@ impl Index for ReadonlyIndexImpl {
     ) -> Result<Box<dyn Revset<'index> + 'index>, RevsetEvaluationError> {
         CompositeIndex(self).evaluate_revset(expression, store)
     }
+
+    fn change_id_index(
+        &self,
+        heads: &mut dyn Iterator<Item = &CommitId>,
+    ) -> Box<dyn ChangeIdIndex + '_> {
+        CompositeIndex(self).change_id_index(heads)
+    }
 }
 
 #[cfg(test)]
@@ -2911,6 +2999,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_walk_revs_take_until_roots() {
+        let mut new_change_id = change_id_generator();
+        let mut index = MutableIndexImpl::full(3, 16);
+        // 6
+        // |
+        // 3
+        // |
+        // 2 5
+        // | |
+        // 1 4
+        // |/
+        // 0
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        let id_4 = CommitId::from_hex("444444");
+        let id_5 = CommitId::from_hex("555555");
+        let id_6 = CommitId::from_hex("666666");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_1.clone()]);
+        index.add_commit_data(id_3.clone(), new_change_id(), &[id_2.clone()]);
+        index.add_commit_data(id_4.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_5.clone(), new_change_id(), &[id_4.clone()]);
+        index.add_commit_data(id_6.clone(), new_change_id(), &[id_3.clone()]);
+
+        let visible_heads = [&id_6, &id_5].map(Clone::clone);
+        let walk_commit_ids = |roots: &[CommitId]| {
+            let index = index.as_composite();
+            let root_positions = to_positions_vec(index, roots);
+            let head_positions = to_positions_vec(index, &visible_heads);
+            index
+                .walk_revs(&head_positions, &[])
+                .take_until_roots(&root_positions)
+                .map(|entry| entry.commit_id())
+                .collect_vec()
+        };
+
+        assert_eq!(
+            walk_commit_ids(&[&id_0].map(Clone::clone)),
+            [&id_6, &id_5, &id_4, &id_3, &id_2, &id_1, &id_0].map(Clone::clone)
+        );
+        // The side branch 4-5 has higher positions than the root 3, but it can be
+        // skipped by generation number.
+        assert_eq!(
+            walk_commit_ids(&[&id_3].map(Clone::clone)),
+            [&id_6, &id_3].map(Clone::clone)
+        );
+        assert_eq!(
+            walk_commit_ids(&[&id_2, &id_5].map(Clone::clone)),
+            [&id_6, &id_5, &id_3, &id_2].map(Clone::clone)
+        );
+        assert_eq!(walk_commit_ids(&[]), [] as [CommitId; 0]);
+    }
+
     #[test]
     fn test_walk_revs_descendants_filtered_by_generation() {
         let mut new_change_id = change_id_generator();
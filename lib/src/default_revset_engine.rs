@@ -28,12 +28,10 @@ use crate::default_index_store::{
     CompositeIndex, IndexEntry, IndexEntryByPosition, IndexPosition, RevWalk,
 };
 use crate::default_revset_graph_iterator::RevsetGraphIterator;
-use crate::id_prefix::{IdIndex, IdIndexSource, IdIndexSourceEntry};
-use crate::index::{HexPrefix, PrefixResolution};
-use crate::matchers::{EverythingMatcher, Matcher, PrefixMatcher, Visit};
+use crate::matchers::{EverythingMatcher, GlobsMatcher, Matcher, PrefixMatcher, Visit};
 use crate::repo_path::RepoPath;
 use crate::revset::{
-    ChangeIdIndex, ResolvedExpression, ResolvedPredicateExpression, Revset, RevsetEvaluationError,
+    ResolvedExpression, ResolvedPredicateExpression, Revset, RevsetEvaluationError,
     RevsetFilterPredicate, GENERATION_RANGE_FULL,
 };
 use crate::revset_graph::RevsetGraphEdge;
@@ -93,31 +91,19 @@ impl fmt::Debug for RevsetImpl<'_> {
 
 impl<'index> Revset<'index> for RevsetImpl<'index> {
     fn iter(&self) -> Box<dyn Iterator<Item = CommitId> + '_> {
-        Box::new(self.inner.iter().map(|index_entry| index_entry.commit_id()))
+        Box::new(TracedIterator::new(
+            self.inner.iter().map(|index_entry| index_entry.commit_id()),
+        ))
     }
 
     fn commit_change_ids(&self) -> Box<dyn Iterator<Item = (CommitId, ChangeId)> + '_> {
-        Box::new(
-            self.inner
-                .iter()
-                .map(|index_entry| (index_entry.commit_id(), index_entry.change_id())),
-        )
+        Box::new(TracedIterator::new(self.inner.iter().map(|index_entry| {
+            (index_entry.commit_id(), index_entry.change_id())
+        })))
     }
 
     fn iter_graph(&self) -> Box<dyn Iterator<Item = (CommitId, Vec<RevsetGraphEdge>)> + '_> {
-        Box::new(self.iter_graph_impl())
-    }
-
-    fn change_id_index(&self) -> Box<dyn ChangeIdIndex + 'index> {
-        // TODO: Create a persistent lookup from change id to commit ids.
-        let mut pos_by_change = IdIndex::builder();
-        for entry in self.inner.iter() {
-            pos_by_change.insert(&entry.change_id(), entry.position());
-        }
-        Box::new(ChangeIdIndexImpl {
-            index: self.index,
-            pos_by_change: pos_by_change.build(),
-        })
+        Box::new(TracedIterator::new(self.iter_graph_impl()))
     }
 
     fn is_empty(&self) -> bool {
@@ -125,39 +111,32 @@ impl<'index> Revset<'index> for RevsetImpl<'index> {
     }
 
     fn count(&self) -> usize {
-        self.inner.iter().count()
+        TracedIterator::new(self.inner.iter()).count()
     }
 }
 
-struct ChangeIdIndexImpl<'index> {
-    index: CompositeIndex<'index>,
-    pos_by_change: IdIndex<ChangeId, IndexPosition, 4>,
+/// Iterates within the "iter_revset" span. Since filters are applied lazily,
+/// this is where most of the time of revset evaluation is spent.
+struct TracedIterator<I> {
+    span: tracing::Span,
+    iter: I,
 }
 
-impl ChangeIdIndex for ChangeIdIndexImpl<'_> {
-    fn resolve_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>> {
-        self.pos_by_change
-            .resolve_prefix_with(self.index, prefix, |entry| entry.commit_id())
-            .map(|(_, commit_ids)| commit_ids)
-    }
-
-    fn shortest_unique_prefix_len(&self, change_id: &ChangeId) -> usize {
-        self.pos_by_change
-            .shortest_unique_prefix_len(self.index, change_id)
+impl<I> TracedIterator<I> {
+    fn new(iter: I) -> Self {
+        TracedIterator {
+            span: tracing::info_span!("iter_revset"),
+            iter,
+        }
     }
 }
 
-impl<'index> IdIndexSource<IndexPosition> for CompositeIndex<'index> {
-    type Entry = IndexEntry<'index>;
+impl<I: Iterator> Iterator for TracedIterator<I> {
+    type Item = I::Item;
 
-    fn entry_at(&self, pointer: &IndexPosition) -> Self::Entry {
-        self.entry_by_pos(*pointer)
-    }
-}
-
-impl IdIndexSourceEntry<ChangeId> for IndexEntry<'_> {
-    fn to_key(&self) -> ChangeId {
-        self.change_id()
+    fn next(&mut self) -> Option<Self::Item> {
+        let _entered = self.span.enter();
+        self.iter.next()
     }
 }
 
@@ -621,6 +600,45 @@ impl<'index> EvaluationContext<'index> {
                 }
                 Ok(Box::new(EagerRevset { index_entries }))
             }
+            ResolvedExpression::Predecessors(candidates) => {
+                let candidate_set = self.evaluate(candidates)?;
+                let mut index_entries = vec![];
+                for entry in candidate_set.iter() {
+                    let commit = self
+                        .store
+                        .get_commit(&entry.commit_id())
+                        .map_err(RevsetEvaluationError::StoreError)?;
+                    // Predecessors should have been indexed when they were written, but
+                    // don't crash if the index doesn't know about some of them.
+                    index_entries.extend(
+                        commit
+                            .predecessor_ids()
+                            .iter()
+                            .filter_map(|id| self.index.entry_by_id(id)),
+                    );
+                }
+                index_entries.sort_unstable_by_key(|entry| Reverse(entry.position()));
+                index_entries.dedup();
+                Ok(Box::new(EagerRevset { index_entries }))
+            }
+            ResolvedExpression::ForkPoint(candidates) => {
+                let candidate_set = self.evaluate(candidates)?;
+                let mut positions = candidate_set.iter().map(|entry| entry.position());
+                let fork_point_positions = if let Some(first) = positions.next() {
+                    positions.fold(BTreeSet::from([first]), |acc, pos| {
+                        let acc = acc.into_iter().collect_vec();
+                        self.index.common_ancestors_pos(&acc, &[pos])
+                    })
+                } else {
+                    BTreeSet::new()
+                };
+                let index_entries = fork_point_positions
+                    .into_iter()
+                    .rev()
+                    .map(|pos| self.index.entry_by_pos(pos))
+                    .collect();
+                Ok(Box::new(EagerRevset { index_entries }))
+            }
             ResolvedExpression::Latest { candidates, count } => {
                 let candidate_set = self.evaluate(candidates)?;
                 Ok(Box::new(
@@ -867,6 +885,10 @@ fn build_predicate_fn<'index>(
                 has_diff_from_parent(&store, index, entry, matcher.as_ref())
             })
         }
+        RevsetFilterPredicate::FileGlob(patterns) => {
+            let matcher = GlobsMatcher::new(patterns);
+            pure_predicate_fn(move |entry| has_diff_from_parent(&store, index, entry, &matcher))
+        }
         RevsetFilterPredicate::HasConflict => pure_predicate_fn(move |entry| {
             let commit = store.get_commit(&entry.commit_id()).unwrap();
             commit.has_conflict().unwrap()
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 #![allow(missing_docs)]
+use std::borrow::Cow;
 
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::default::Default;
@@ -29,7 +30,7 @@ use crate::backend::{BackendError, CommitId, ObjectId};
 use crate::git_backend::GitBackend;
 use crate::op_store::{BranchTarget, RefTarget, RefTargetOptionExt};
 use crate::repo::{MutableRepo, Repo};
-use crate::revset;
+use crate::revset::{self, StringPattern};
 use crate::settings::GitSettings;
 use crate::view::{RefName, View};
 
@@ -269,7 +270,7 @@ pub fn import_some_refs(
             mut_repo.set_remote_branch_target(branch, remote, new_git_target.clone());
             // If a git remote-tracking branch changed, apply the change to the local branch
             // as well.
-            if git_settings.auto_local_branch {
+            if git_settings.auto_local_branch_for(branch) {
                 let local_ref_name = RefName::LocalBranch(branch.clone());
                 mut_repo.merge_single_ref(&local_ref_name, old_git_target, new_git_target);
             }
@@ -788,8 +789,11 @@ pub fn rename_remote(
 pub enum GitFetchError {
     #[error("No git remote named '{0}'")]
     NoSuchRemote(String),
-    #[error("Invalid glob provided. Globs may not contain the characters `:` or `^`.")]
-    InvalidGlob,
+    #[error(
+        "Invalid branch pattern provided. Only `*` is supported as a wildcard, and patterns \
+         may not contain the characters `:` or `^`"
+    )]
+    InvalidBranchPattern,
     #[error("Failed to import Git refs: {0}")]
     GitImportError(#[from] GitImportError),
     // TODO: I'm sure there are other errors possible, such as transport-level errors.
@@ -797,29 +801,23 @@ pub enum GitFetchError {
     InternalGitError(#[from] git2::Error),
 }
 
+// Refspecs only support `*` as a wildcard.
+// TODO: Escape special characters instead of rejecting them.
+const INVALID_REFSPEC_CHARS: [char; 5] = [':', '^', '?', '[', ']'];
+
 #[tracing::instrument(skip(mut_repo, git_repo, callbacks))]
 pub fn fetch(
     mut_repo: &mut MutableRepo,
     git_repo: &git2::Repository,
     remote_name: &str,
-    branch_name_globs: Option<&[&str]>,
+    branch_names: Option<&[StringPattern]>,
     callbacks: RemoteCallbacks<'_>,
     git_settings: &GitSettings,
 ) -> Result<Option<String>, GitFetchError> {
-    let branch_name_filter = {
-        let regex = if let Some(globs) = branch_name_globs {
-            let result = regex::RegexSet::new(
-                globs
-                    .iter()
-                    .map(|glob| format!("^{}$", glob.replace('*', ".*"))),
-            )
-            .map_err(|_| GitFetchError::InvalidGlob)?;
-            tracing::debug!(?globs, ?result, "globs as regex");
-            Some(result)
-        } else {
-            None
-        };
-        move |branch: &str| regex.as_ref().map(|r| r.is_match(branch)).unwrap_or(true)
+    let branch_name_filter = |branch: &str| {
+        branch_names.map_or(true, |patterns| {
+            patterns.iter().any(|pattern| pattern.matches(branch))
+        })
     };
 
     // In non-colocated repositories, it's possible that `jj branch forget` was run
@@ -875,10 +873,23 @@ pub fn fetch(
     let callbacks = callbacks.into_git();
     fetch_options.remote_callbacks(callbacks);
     let refspecs = {
-        // If no globs have been given, import all branches
-        let globs = branch_name_globs.unwrap_or(&["*"]);
-        if globs.iter().any(|g| g.contains(|c| ":^".contains(c))) {
-            return Err(GitFetchError::InvalidGlob);
+        // If no patterns have been given, import all branches
+        let globs = if let Some(patterns) = branch_names {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    // Branch names can't contain glob metacharacters, so exact
+                    // names don't have to be escaped.
+                    pattern
+                        .as_exact()
+                        .map_or_else(|| pattern.to_glob(), Cow::Borrowed)
+                })
+                .collect_vec()
+        } else {
+            vec!["*".into()]
+        };
+        if globs.iter().any(|g| g.contains(INVALID_REFSPEC_CHARS)) {
+            return Err(GitFetchError::InvalidBranchPattern);
         }
         // At this point, we are only updating Git's remote tracking branches, not the
         // local branches.
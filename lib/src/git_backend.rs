@@ -36,6 +36,7 @@ use crate::file_util::{IoResultExt as _, PathError};
 use crate::lock::FileLock;
 use crate::merge::{Merge, MergeBuilder};
 use crate::repo_path::{RepoPath, RepoPathComponent};
+use crate::signing::SecureSig;
 use crate::stacked_table::{
     MutableTable, ReadonlyTable, TableSegment, TableStore, TableStoreError,
 };
@@ -163,6 +164,26 @@ impl GitBackend {
         git2::Repository::open(path).unwrap()
     }
 
+    /// Returns the signature of the commit and the data it signs, or `None`
+    /// if the commit isn't signed.
+    pub fn read_commit_signature(&self, id: &CommitId) -> BackendResult<Option<SecureSig>> {
+        if *id == self.root_commit_id {
+            return Ok(None);
+        }
+        let git_commit_id = validate_git_object_id(id)?;
+        let locked_repo = self.repo.lock().unwrap();
+        match locked_repo.extract_signature(&git_commit_id, None) {
+            Ok((sig, data)) => Ok(Some(SecureSig {
+                data: data.to_vec(),
+                sig: sig.to_vec(),
+            })),
+            // libgit2 doesn't distinguish a missing commit from a missing
+            // signature header.
+            Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(err) => Err(map_not_found_err(err, id)),
+        }
+    }
+
     fn cached_extra_metadata_table(&self) -> BackendResult<Arc<ReadonlyTable>> {
         let mut locked_head = self.cached_extra_metadata.lock().unwrap();
         match locked_head.as_ref() {
@@ -502,6 +523,17 @@ impl Backend for GitBackend {
         Ok(Box::new(Cursor::new(content)))
     }
 
+    fn file_size(&self, _path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        let git_blob_id = validate_git_object_id(id)?;
+        let locked_repo = self.repo.lock().unwrap();
+        // Only the object header is read, not the contents
+        let (size, _) = locked_repo
+            .odb()
+            .and_then(|odb| odb.read_header(git_blob_id))
+            .map_err(|err| map_not_found_err(err, id))?;
+        Ok(size as u64)
+    }
+
     fn write_file(&self, _path: &RepoPath, contents: &mut dyn Read) -> BackendResult<FileId> {
         let mut bytes = Vec::new();
         contents.read_to_end(&mut bytes).unwrap();
@@ -1175,6 +1207,53 @@ mod tests {
         );
     }
 
+    /// Test that author and committer are written and read back separately
+    #[test]
+    fn git_commit_signatures() {
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        let git_repo_path = temp_dir.path().join("git");
+        let git_repo = git2::Repository::init(&git_repo_path).unwrap();
+
+        let backend = GitBackend::init_external(store_path, &git_repo_path).unwrap();
+        let commit = Commit {
+            parents: vec![backend.root_commit_id().clone()],
+            predecessors: vec![],
+            root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+            change_id: ChangeId::from_hex("abc123"),
+            description: "".to_string(),
+            author: Signature {
+                name: "Author".to_string(),
+                email: "author@example.com".to_string(),
+                timestamp: Timestamp {
+                    timestamp: MillisSinceEpoch(1000 * 1000 + 123),
+                    tz_offset: 60,
+                },
+            },
+            committer: Signature {
+                name: "Committer".to_string(),
+                email: "committer@example.com".to_string(),
+                timestamp: Timestamp {
+                    timestamp: MillisSinceEpoch(2000 * 1000 + 456),
+                    tz_offset: -480,
+                },
+            },
+        };
+        let (commit_id, written_commit) = backend.write_commit(commit.clone()).unwrap();
+        // Git stores timestamps with second precision
+        let mut expected_commit = commit;
+        expected_commit.author.timestamp.timestamp = MillisSinceEpoch(1000 * 1000);
+        expected_commit.committer.timestamp.timestamp = MillisSinceEpoch(2000 * 1000);
+        assert_eq!(written_commit, expected_commit);
+        assert_eq!(backend.read_commit(&commit_id).unwrap(), expected_commit);
+
+        let git_commit = git_repo.find_commit(git_id(&commit_id)).unwrap();
+        assert_eq!(git_commit.author().name(), Some("Author"));
+        assert_eq!(git_commit.author().when(), git2::Time::new(1000, 60));
+        assert_eq!(git_commit.committer().name(), Some("Committer"));
+        assert_eq!(git_commit.committer().when(), git2::Time::new(2000, -480));
+    }
+
     #[test]
     fn write_tree_conflicts() {
         let temp_dir = testutils::new_temp_dir();
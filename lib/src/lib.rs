@@ -47,21 +47,25 @@ pub mod merge;
 pub mod merged_tree;
 pub mod op_heads_store;
 pub mod op_store;
+pub mod op_tag_store;
 pub mod operation;
 #[allow(missing_docs)]
 pub mod protos;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod resolution_store;
 pub mod revset;
 pub mod revset_graph;
 pub mod rewrite;
 pub mod settings;
+pub mod signing;
 pub mod simple_op_heads_store;
 pub mod simple_op_store;
 pub mod stacked_table;
 pub mod store;
 pub mod submodule_store;
+pub mod trailer;
 pub mod transaction;
 pub mod tree;
 pub mod tree_builder;
@@ -160,6 +160,68 @@ impl Matcher for PrefixMatcher {
     }
 }
 
+/// Matches file paths against glob patterns, in which `*` doesn't match `/`
+/// but `**` does.
+#[derive(Debug)]
+pub struct GlobsMatcher {
+    patterns: Vec<glob::Pattern>,
+    /// Directories containing everything each pattern can match, i.e. the
+    /// leading components of the pattern without special characters.
+    base_dirs: Vec<RepoPath>,
+}
+
+impl GlobsMatcher {
+    pub fn new(patterns: &[glob::Pattern]) -> Self {
+        let base_dirs = patterns
+            .iter()
+            .map(|pattern| {
+                let mut components: Vec<_> = pattern.as_str().split('/').collect();
+                components.pop(); // the last component is a file name
+                let literal_components = components
+                    .into_iter()
+                    .take_while(|component| glob::Pattern::escape(component) == *component)
+                    .map(RepoPathComponent::from)
+                    .collect();
+                RepoPath::from_components(literal_components)
+            })
+            .collect();
+        GlobsMatcher {
+            patterns: patterns.to_vec(),
+            base_dirs,
+        }
+    }
+}
+
+impl Matcher for GlobsMatcher {
+    fn matches(&self, file: &RepoPath) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        let file = file.to_internal_file_string();
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_with(&file, options))
+    }
+
+    fn visit(&self, dir: &RepoPath) -> Visit {
+        // Directories outside of the base directories can be skipped. Pruning
+        // more precisely would require matching each pattern component-wise.
+        let may_match = self
+            .base_dirs
+            .iter()
+            .any(|base_dir| base_dir.contains(dir) || dir.contains(base_dir));
+        if may_match {
+            Visit::Specific {
+                dirs: VisitDirs::All,
+                files: VisitFiles::All,
+            }
+        } else {
+            Visit::Nothing
+        }
+    }
+}
+
 /// Matches paths that are matched by the first input matcher but not by the
 /// second.
 pub struct DifferenceMatcher<'input> {
@@ -549,6 +611,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_globsmatcher() {
+        let m = GlobsMatcher::new(&[
+            glob::Pattern::new("dir1/*.rs").unwrap(),
+            glob::Pattern::new("dir2/**/file").unwrap(),
+        ]);
+        assert!(m.matches(&RepoPath::from_internal_string("dir1/file.rs")));
+        assert!(!m.matches(&RepoPath::from_internal_string("dir1/sub/file.rs")));
+        assert!(!m.matches(&RepoPath::from_internal_string("file.rs")));
+        assert!(m.matches(&RepoPath::from_internal_string("dir2/file")));
+        assert!(m.matches(&RepoPath::from_internal_string("dir2/a/b/file")));
+        assert!(!m.matches(&RepoPath::from_internal_string("dir2/a/b/file2")));
+
+        // Directories outside of the literal prefixes aren't visited
+        let visit_all = Visit::Specific {
+            dirs: VisitDirs::All,
+            files: VisitFiles::All,
+        };
+        assert_eq!(m.visit(&RepoPath::root()), visit_all);
+        assert_eq!(m.visit(&RepoPath::from_internal_string("dir1")), visit_all);
+        assert_eq!(
+            m.visit(&RepoPath::from_internal_string("dir2/a/b")),
+            visit_all
+        );
+        assert_eq!(
+            m.visit(&RepoPath::from_internal_string("dir3")),
+            Visit::Nothing
+        );
+
+        // Patterns starting with a wildcard may match anywhere
+        let m = GlobsMatcher::new(&[glob::Pattern::new("**/*.rs").unwrap()]);
+        assert!(m.matches(&RepoPath::from_internal_string("dir/file.rs")));
+        assert_eq!(m.visit(&RepoPath::from_internal_string("dir")), visit_all);
+    }
+
     #[test]
     fn test_differencematcher_remove_subdir() {
         let m1 = PrefixMatcher::new(&[
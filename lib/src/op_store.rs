@@ -20,7 +20,8 @@ use std::fmt::{Debug, Error, Formatter};
 use once_cell::sync::Lazy;
 use thiserror::Error;
 
-use crate::backend::{id_type, CommitId, ObjectId, Timestamp};
+use crate::backend::{id_type, ChangeId, CommitId, ObjectId, Timestamp};
+use crate::content_hash::ContentHash;
 use crate::merge::Merge;
 
 content_hash! {
@@ -169,26 +170,44 @@ content_hash! {
     }
 }
 
-content_hash! {
-    /// Represents the way the repo looks at a given time, just like how a Tree
-    /// object represents how the file system looks at a given time.
-    #[derive(PartialEq, Eq, Clone, Debug, Default)]
-    pub struct View {
-        /// All head commits
-        pub head_ids: HashSet<CommitId>,
-        /// Heads of the set of public commits.
-        pub public_head_ids: HashSet<CommitId>,
-        pub branches: BTreeMap<String, BranchTarget>,
-        pub tags: BTreeMap<String, RefTarget>,
-        pub git_refs: BTreeMap<String, RefTarget>,
-        /// The commit the Git HEAD points to.
-        // TODO: Support multiple Git worktrees?
-        // TODO: Do we want to store the current branch name too?
-        pub git_head: RefTarget,
-        // The commit that *should be* checked out in the workspace. Note that the working copy
-        // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
-        // precise: the commit to which we most recently completed an update to).
-        pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+/// Represents the way the repo looks at a given time, just like how a Tree
+/// object represents how the file system looks at a given time.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct View {
+    /// All head commits
+    pub head_ids: HashSet<CommitId>,
+    /// Heads of the set of public commits.
+    pub public_head_ids: HashSet<CommitId>,
+    pub branches: BTreeMap<String, BranchTarget>,
+    pub tags: BTreeMap<String, RefTarget>,
+    pub git_refs: BTreeMap<String, RefTarget>,
+    /// The commit the Git HEAD points to.
+    // TODO: Support multiple Git worktrees?
+    // TODO: Do we want to store the current branch name too?
+    pub git_head: RefTarget,
+    // The commit that *should be* checked out in the workspace. Note that the working copy
+    // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
+    // precise: the commit to which we most recently completed an update to).
+    pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Notes attached to changes. They don't affect the content of the commits,
+    /// so they can be edited without rewriting them.
+    pub notes: BTreeMap<ChangeId, String>,
+}
+
+impl ContentHash for View {
+    fn hash(&self, state: &mut impl digest::Update) {
+        self.head_ids.hash(state);
+        self.public_head_ids.hash(state);
+        self.branches.hash(state);
+        self.tags.hash(state);
+        self.git_refs.hash(state);
+        self.git_head.hash(state);
+        self.wc_commit_ids.hash(state);
+        // Notes were added later. Don't hash them if there are none so that the
+        // ids of existing views don't change.
+        if !self.notes.is_empty() {
+            self.notes.hash(state);
+        }
     }
 }
 
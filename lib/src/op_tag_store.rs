@@ -0,0 +1,175 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Names of operations ("savepoints") that can be used instead of operation
+//! IDs, e.g. to restore the repo to a state recorded before a risky change.
+
+#![allow(missing_docs)]
+
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::ObjectId;
+use crate::op_store::OperationId;
+
+#[derive(Debug, Error)]
+pub enum OpTagStoreError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Invalid operation ID {id:?} recorded for tag {name:?}")]
+    InvalidOperationId { name: String, id: String },
+}
+
+/// Checks if the name can be used as an operation tag. Names must not be
+/// confused with operation IDs or the operation expression syntax, and must be
+/// usable as file names.
+pub fn is_valid_tag_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.ends_with('-')
+        && !name.bytes().all(|b| b.is_ascii_hexdigit())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+}
+
+/// File-based storage of operation tags, one file per tag containing the ID of
+/// the tagged operation.
+#[derive(Debug)]
+pub struct OpTagStore {
+    dir: PathBuf,
+}
+
+impl OpTagStore {
+    /// Loads the store in the given repo directory. The underlying directory is
+    /// created lazily when the first tag is set.
+    pub fn load(repo_path: &Path) -> Self {
+        OpTagStore {
+            dir: repo_path.join("op_tags"),
+        }
+    }
+
+    /// Looks up the operation tagged with the given name.
+    pub fn get(&self, name: &str) -> Result<Option<OperationId>, OpTagStoreError> {
+        if !is_valid_tag_name(name) {
+            return Ok(None);
+        }
+        let data = match fs::read_to_string(self.dir.join(name)) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let id = data.trim();
+        match hex::decode(id) {
+            Ok(binary_id) => Ok(Some(OperationId::new(binary_id))),
+            Err(_) => Err(OpTagStoreError::InvalidOperationId {
+                name: name.to_owned(),
+                id: id.to_owned(),
+            }),
+        }
+    }
+
+    /// Returns all tags sorted by name.
+    pub fn tags(&self) -> Result<Vec<(String, OperationId)>, OpTagStoreError> {
+        let dir_entries = match fs::read_dir(&self.dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut tags = vec![];
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry?;
+            let Some(name) = dir_entry.file_name().to_str().map(|s| s.to_owned()) else {
+                continue;
+            };
+            if let Some(id) = self.get(&name)? {
+                tags.push((name, id));
+            }
+        }
+        tags.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(tags)
+    }
+
+    /// Tags the operation with the name, replacing the existing tag of the
+    /// same name.
+    pub fn set(&self, name: &str, id: &OperationId) -> Result<(), OpTagStoreError> {
+        assert!(is_valid_tag_name(name), "invalid tag name {name:?}");
+        fs::create_dir_all(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir)?;
+        temp_file.write_all(id.hex().as_bytes())?;
+        temp_file
+            .persist(self.dir.join(name))
+            .map_err(|err| err.error)?;
+        Ok(())
+    }
+
+    /// Removes the tag of the given name. Returns false if no such tag
+    /// existed.
+    pub fn remove(&self, name: &str) -> Result<bool, OpTagStoreError> {
+        if !is_valid_tag_name(name) {
+            return Ok(false);
+        }
+        match fs::remove_file(self.dir.join(name)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_tag_name() {
+        assert!(is_valid_tag_name("before-rebase"));
+        assert!(is_valid_tag_name("v1.0_pre"));
+        assert!(!is_valid_tag_name(""));
+        assert!(!is_valid_tag_name("abc123"));
+        assert!(!is_valid_tag_name("before-"));
+        assert!(!is_valid_tag_name(".hidden"));
+        assert!(!is_valid_tag_name("a/b"));
+        assert!(!is_valid_tag_name("@"));
+    }
+
+    #[test]
+    fn test_set_get_remove() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = OpTagStore::load(temp_dir.path());
+        assert_eq!(store.tags().unwrap(), vec![]);
+        assert_eq!(store.get("save").unwrap(), None);
+
+        let id1 = OperationId::from_hex("0123");
+        let id2 = OperationId::from_hex("4567");
+        store.set("save", &id1).unwrap();
+        store.set("other", &id2).unwrap();
+        assert_eq!(store.get("save").unwrap(), Some(id1.clone()));
+        assert_eq!(
+            store.tags().unwrap(),
+            vec![("other".to_owned(), id2.clone()), ("save".to_owned(), id1)]
+        );
+
+        store.set("save", &id2).unwrap();
+        assert_eq!(store.get("save").unwrap(), Some(id2.clone()));
+
+        assert!(store.remove("save").unwrap());
+        assert!(!store.remove("save").unwrap());
+        assert_eq!(store.tags().unwrap(), vec![("other".to_owned(), id2)]);
+    }
+}
@@ -11,6 +11,9 @@ pub struct FileState {
     #[deprecated]
     #[prost(bytes = "vec", tag = "4")]
     pub conflict_id: ::prost::alloc::vec::Vec<u8>,
+    /// 0 if unknown
+    #[prost(uint64, tag = "5")]
+    pub inode: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -39,13 +39,15 @@ use crate::default_index_store::DefaultIndexStore;
 use crate::default_submodule_store::DefaultSubmoduleStore;
 use crate::file_util::{IoResultExt as _, PathError};
 use crate::git_backend::GitBackend;
-use crate::index::{HexPrefix, Index, IndexStore, MutableIndex, PrefixResolution, ReadonlyIndex};
+use crate::index::{
+    ChangeIdIndex, HexPrefix, Index, IndexStore, MutableIndex, PrefixResolution, ReadonlyIndex,
+};
 use crate::local_backend::LocalBackend;
 use crate::op_heads_store::{self, OpHeadResolutionError, OpHeadsStore};
 use crate::op_store::{BranchTarget, OpStore, OpStoreError, OperationId, RefTarget, WorkspaceId};
 use crate::operation::Operation;
 use crate::refs::merge_ref_targets;
-use crate::revset::{self, ChangeIdIndex, Revset, RevsetExpression};
+use crate::revset;
 use crate::rewrite::DescendantRebaser;
 use crate::settings::{RepoSettings, UserSettings};
 use crate::simple_op_heads_store::SimpleOpHeadsStore;
@@ -256,23 +258,20 @@ impl ReadonlyRepo {
             .deref()
     }
 
-    fn change_id_index<'a>(&'a self) -> &'a (dyn ChangeIdIndex + 'a) {
-        let change_id_index: &'a (dyn ChangeIdIndex + 'a) = self
-            .change_id_index
+    fn change_id_index(&self) -> &(dyn ChangeIdIndex + '_) {
+        self.change_id_index
             .get_or_init(|| {
-                let revset: Box<dyn Revset<'a>> = RevsetExpression::all()
-                    .resolve(self)
-                    .unwrap()
-                    .evaluate(self)
-                    .unwrap();
-                let change_id_index: Box<dyn ChangeIdIndex + 'a> = revset.change_id_index();
-                // evaluate() above only borrows the index, not the whole repo
+                let change_id_index: Box<dyn ChangeIdIndex + '_> = self
+                    .readonly_index()
+                    .as_index()
+                    .change_id_index(&mut self.view().heads().iter());
+                // The change id index only borrows the index, which is pinned and outlives
+                // it (see the declaration order of the fields)
                 let change_id_index: Box<dyn ChangeIdIndex> =
                     unsafe { std::mem::transmute(change_id_index) };
                 change_id_index
             })
-            .as_ref();
-        change_id_index
+            .as_ref()
     }
 
     pub fn op_heads_store(&self) -> &Arc<dyn OpHeadsStore> {
@@ -1021,6 +1020,15 @@ impl MutableRepo {
         self.view_mut().set_tag_target(name, target);
     }
 
+    pub fn get_note(&self, change_id: &ChangeId) -> Option<String> {
+        self.view
+            .with_ref(|v| v.get_note(change_id).map(ToOwned::to_owned))
+    }
+
+    pub fn set_note(&mut self, change_id: &ChangeId, note: String) {
+        self.view_mut().set_note(change_id, note);
+    }
+
     pub fn get_git_ref(&self, name: &str) -> RefTarget {
         self.view.with_ref(|v| v.get_git_ref(name).clone())
     }
@@ -1084,6 +1092,19 @@ impl MutableRepo {
             }
         }
 
+        let changed_note_ids: HashSet<_> = itertools::chain(base.notes(), other.notes())
+            .filter(|(change_id, _)| base.get_note(change_id) != other.get_note(change_id))
+            .map(|(change_id, _)| change_id.clone())
+            .collect();
+        for change_id in changed_note_ids {
+            // Like the working-copy commits, the other side's note is only taken if
+            // this side didn't change it.
+            if self.view().get_note(&change_id) == base.get_note(&change_id) {
+                let note = other.get_note(&change_id).unwrap_or_default().to_owned();
+                self.view_mut().set_note(&change_id, note);
+            }
+        }
+
         for removed_head in base.public_heads().difference(other.public_heads()) {
             self.view_mut().remove_public_head(removed_head);
         }
@@ -1253,22 +1274,16 @@ impl Repo for MutableRepo {
     }
 
     fn resolve_change_id_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>> {
-        let revset = RevsetExpression::all()
-            .resolve(self)
-            .unwrap()
-            .evaluate(self)
-            .unwrap();
-        let change_id_index = revset.change_id_index();
+        let change_id_index = self
+            .index()
+            .change_id_index(&mut self.view().heads().iter());
         change_id_index.resolve_prefix(prefix)
     }
 
     fn shortest_unique_change_id_prefix_len(&self, target_id: &ChangeId) -> usize {
-        let revset = RevsetExpression::all()
-            .resolve(self)
-            .unwrap()
-            .evaluate(self)
-            .unwrap();
-        let change_id_index = revset.change_id_index();
+        let change_id_index = self
+            .index()
+            .change_id_index(&mut self.view().heads().iter());
         change_id_index.shortest_unique_prefix_len(target_id)
     }
 }
@@ -0,0 +1,456 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Remembers how conflicts were resolved so that the same resolution can be
+//! replayed when an identical conflict reappears (similar to `git rerere`).
+//!
+//! Resolutions are recorded per conflicted region (hunk), keyed by the content
+//! hash of the conflicting terms. Changes outside of the conflicted regions
+//! don't affect the lookup.
+
+#![allow(missing_docs)]
+
+use std::fs;
+use std::io::{self, Read as _, Write as _};
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::{BackendError, BackendResult, FileId, MergedTreeId, TreeValue};
+use crate::conflicts;
+use crate::content_hash::blake2b_hash;
+use crate::files::{self, ContentHunk, MergeResult};
+use crate::merge::Merge;
+use crate::merged_tree::{MergedTree, MergedTreeBuilder};
+use crate::repo_path::RepoPath;
+
+#[derive(Debug, Error)]
+pub enum ResolutionStoreError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Recorded resolution of a single conflicted region.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ResolutionEntry {
+    /// Path of the file the resolution was recorded from. This is informative
+    /// only. The resolution is applied to any file with the same conflict.
+    pub path: String,
+    pub removes: Vec<String>,
+    pub adds: Vec<String>,
+    pub resolution: String,
+}
+
+impl ResolutionEntry {
+    fn from_hunk(path: &RepoPath, hunk: &Merge<ContentHunk>, resolution: &[u8]) -> Option<Self> {
+        let to_string = |hunk: &ContentHunk| String::from_utf8(hunk.0.clone()).ok();
+        Some(ResolutionEntry {
+            path: path.to_internal_file_string(),
+            removes: hunk
+                .removes()
+                .iter()
+                .map(to_string)
+                .collect::<Option<_>>()?,
+            adds: hunk.adds().iter().map(to_string).collect::<Option<_>>()?,
+            resolution: String::from_utf8(resolution.to_vec()).ok()?,
+        })
+    }
+
+    /// Content hash of the conflicted region this entry resolves.
+    pub fn key(&self) -> String {
+        let hunk = Merge::new(
+            self.removes.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            self.adds.iter().map(|s| s.as_bytes().to_vec()).collect(),
+        );
+        hunk_key(&hunk)
+    }
+}
+
+fn hunk_key(hunk: &Merge<Vec<u8>>) -> String {
+    hex::encode(blake2b_hash(hunk))
+}
+
+fn content_hunk_key(hunk: &Merge<ContentHunk>) -> String {
+    hunk_key(&hunk.map(|content| content.0.clone()))
+}
+
+/// File-based storage of conflict resolutions, one file per conflicted region.
+#[derive(Debug)]
+pub struct ResolutionStore {
+    dir: PathBuf,
+}
+
+impl ResolutionStore {
+    /// Loads the store in the given repo directory. The underlying directory is
+    /// created lazily when the first resolution is recorded.
+    pub fn load(repo_path: &Path) -> Self {
+        ResolutionStore {
+            dir: repo_path.join("conflict_resolutions"),
+        }
+    }
+
+    /// Looks up the resolution for the given key. Unreadable entries are
+    /// treated as missing.
+    pub fn get(&self, key: &str) -> Option<ResolutionEntry> {
+        let data = fs::read(self.dir.join(key)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Returns all recorded entries sorted by key.
+    pub fn entries(&self) -> Result<Vec<(String, ResolutionEntry)>, ResolutionStoreError> {
+        let dir_entries = match fs::read_dir(&self.dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut entries = vec![];
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry?;
+            let Some(key) = dir_entry.file_name().to_str().map(|s| s.to_owned()) else {
+                continue;
+            };
+            let data = fs::read(dir_entry.path())?;
+            entries.push((key, serde_json::from_slice(&data)?));
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries)
+    }
+
+    /// Records the entry, replacing the existing resolution of the same
+    /// conflict. Returns the key of the entry.
+    pub fn insert(&self, entry: &ResolutionEntry) -> Result<String, ResolutionStoreError> {
+        let key = entry.key();
+        fs::create_dir_all(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir)?;
+        temp_file.write_all(&serde_json::to_vec(entry)?)?;
+        temp_file
+            .persist(self.dir.join(&key))
+            .map_err(|err| err.error)?;
+        Ok(key)
+    }
+
+    /// Removes the entry of the given key. Returns false if no such entry
+    /// existed.
+    pub fn remove(&self, key: &str) -> Result<bool, ResolutionStoreError> {
+        match fs::remove_file(self.dir.join(key)) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Records how the conflicted regions of `conflict` were resolved in the
+    /// `resolved` content. Returns the number of recorded regions.
+    ///
+    /// If the resolved content can't be split into the conflicted regions
+    /// (e.g. because the non-conflicting parts were also edited), nothing is
+    /// recorded.
+    pub fn record_file_resolution(
+        &self,
+        path: &RepoPath,
+        conflict: &Merge<ContentHunk>,
+        resolved: &[u8],
+    ) -> Result<usize, ResolutionStoreError> {
+        let slices = conflict.map(|content| content.0.as_slice());
+        let MergeResult::Conflict(hunks) = files::merge(slices) else {
+            return Ok(0);
+        };
+        let Some(resolutions) = split_resolved_content(&hunks, resolved) else {
+            return Ok(0);
+        };
+        let mut count = 0;
+        for (hunk, resolution) in resolutions {
+            if let Some(entry) = ResolutionEntry::from_hunk(path, hunk, resolution) {
+                self.insert(&entry)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Resolves the conflict by using the recorded resolutions. Returns `None`
+    /// unless all conflicted regions have been resolved before.
+    pub fn resolve_file_conflict(&self, conflict: &Merge<ContentHunk>) -> Option<Vec<u8>> {
+        let slices = conflict.map(|content| content.0.as_slice());
+        let hunks = match files::merge(slices) {
+            MergeResult::Resolved(content) => return Some(content.0),
+            MergeResult::Conflict(hunks) => hunks,
+        };
+        let mut content = vec![];
+        for hunk in &hunks {
+            if let Some(resolved) = hunk.as_resolved() {
+                content.extend_from_slice(&resolved.0);
+            } else {
+                let entry = self.get(&content_hunk_key(hunk))?;
+                content.extend_from_slice(entry.resolution.as_bytes());
+            }
+        }
+        Some(content)
+    }
+
+    /// Records resolutions of the files that were conflicted in `old_tree`, but
+    /// are resolved in `new_tree`. Returns the number of recorded regions.
+    pub fn record_tree_resolutions(
+        &self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+    ) -> Result<usize, ResolutionStoreError> {
+        let store = old_tree.store();
+        let mut count = 0;
+        for (path, conflict) in old_tree.conflicts() {
+            let Some((file_merge, _)) = to_file_merge_with_executable(&conflict) else {
+                continue;
+            };
+            let Ok(Some(TreeValue::File { id, .. })) = new_tree.path_value(&path).into_resolved()
+            else {
+                continue;
+            };
+            let mut resolved = vec![];
+            store.read_file(&path, &id)?.read_to_end(&mut resolved)?;
+            let content = conflicts::extract_as_single_hunk(&file_merge, store, &path);
+            count += self.record_file_resolution(&path, &content, &resolved)?;
+        }
+        Ok(count)
+    }
+
+    /// Resolves conflicted files in the `tree` by using the recorded
+    /// resolutions. Returns the new tree id if any file was resolved.
+    pub fn resolve_tree_conflicts(&self, tree: &MergedTree) -> BackendResult<Option<MergedTreeId>> {
+        let store = tree.store();
+        let mut tree_builder = MergedTreeBuilder::new(tree.id());
+        let mut resolved_any = false;
+        for (path, conflict) in tree.conflicts() {
+            let Some((file_merge, executable)) = to_file_merge_with_executable(&conflict) else {
+                continue;
+            };
+            let content = conflicts::extract_as_single_hunk(&file_merge, store, &path);
+            let Some(resolved) = self.resolve_file_conflict(&content) else {
+                continue;
+            };
+            let id = store.write_file(&path, &mut resolved.as_slice())?;
+            let value = TreeValue::File { id, executable };
+            tree_builder.set_or_remove(path, Merge::normal(value));
+            resolved_any = true;
+        }
+        if resolved_any {
+            Ok(Some(tree_builder.write_tree(store)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Like `Merge::to_file_merge()`, but also accepts executable files. Returns
+/// the merge of the file ids along with the executable bit of the result. The
+/// executable bit must merge cleanly, otherwise `None` is returned.
+fn to_file_merge_with_executable(
+    conflict: &Merge<Option<TreeValue>>,
+) -> Option<(Merge<Option<FileId>>, bool)> {
+    let file_merge = conflict.maybe_map(|term| match term {
+        None => Some(None),
+        Some(TreeValue::File { id, .. }) => Some(Some(id.clone())),
+        _ => None,
+    })?;
+    let executable_merge = conflict.map(|term| match term {
+        Some(TreeValue::File { executable, .. }) => Some(*executable),
+        _ => None,
+    });
+    let executable = (*executable_merge.resolve_trivial()?)?;
+    Some((file_merge, executable))
+}
+
+/// Splits the `resolved` content into resolutions of the conflicted `hunks`.
+///
+/// The resolved (non-conflicting) hunks must appear in the `resolved` content
+/// in order. The content between them is the resolution of the conflicted
+/// hunk.
+fn split_resolved_content<'a, 'b>(
+    hunks: &'a [Merge<ContentHunk>],
+    resolved: &'b [u8],
+) -> Option<Vec<(&'a Merge<ContentHunk>, &'b [u8])>> {
+    let mut resolutions = vec![];
+    let mut pos = 0;
+    let mut pending_conflict = None;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let Some(ContentHunk(text)) = hunk.as_resolved() else {
+            pending_conflict = Some(hunk);
+            continue;
+        };
+        let start = match pending_conflict.take() {
+            None => resolved[pos..].starts_with(text).then_some(pos)?,
+            Some(conflict) => {
+                let start = if i + 1 == hunks.len() {
+                    // The last hunk must be anchored at the end.
+                    resolved
+                        .ends_with(text)
+                        .then(|| resolved.len() - text.len())
+                        .filter(|&start| start >= pos)?
+                } else {
+                    pos + find_subslice(&resolved[pos..], text)?
+                };
+                resolutions.push((conflict, &resolved[pos..start]));
+                start
+            }
+        };
+        pos = start + text.len();
+    }
+    if let Some(conflict) = pending_conflict {
+        resolutions.push((conflict, &resolved[pos..]));
+    } else if pos != resolved.len() {
+        return None;
+    }
+    Some(resolutions)
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .find_position(|window| *window == needle)
+        .map(|(pos, _)| pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ObjectId;
+
+    fn hunk(removes: &[&str], adds: &[&str]) -> Merge<ContentHunk> {
+        Merge::new(
+            removes
+                .iter()
+                .map(|s| ContentHunk(s.as_bytes().to_vec()))
+                .collect(),
+            adds.iter()
+                .map(|s| ContentHunk(s.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+
+    fn resolved(s: &str) -> Merge<ContentHunk> {
+        Merge::resolved(ContentHunk(s.as_bytes().to_vec()))
+    }
+
+    #[test]
+    fn test_split_resolved_content() {
+        let hunks = vec![
+            resolved("a\n"),
+            hunk(&["b\n"], &["c\n", "d\n"]),
+            resolved("e\n"),
+            hunk(&["f\n"], &["g\n", "h\n"]),
+        ];
+        let split = split_resolved_content(&hunks, b"a\nc\nd\ne\nX\n").unwrap();
+        assert_eq!(
+            split.iter().map(|(_, s)| *s).collect_vec(),
+            vec![&b"c\nd\n"[..], &b"X\n"[..]]
+        );
+
+        // Edited outside of the conflicted regions
+        assert!(split_resolved_content(&hunks, b"A\nc\ne\nX\n").is_none());
+
+        // Trailing resolved hunk must be anchored at the end
+        let hunks = vec![hunk(&["b\n"], &["c\n", "d\n"]), resolved("e\n")];
+        let split = split_resolved_content(&hunks, b"e\ne\n").unwrap();
+        assert_eq!(split[0].1, b"e\n");
+        assert!(split_resolved_content(&hunks, b"c\n").is_none());
+    }
+
+    #[test]
+    fn test_find_subslice() {
+        assert_eq!(find_subslice(b"abc", b"bc"), Some(1));
+        assert_eq!(find_subslice(b"abc", b"x"), None);
+        assert_eq!(find_subslice(b"abc", b""), Some(0));
+        assert_eq!(find_subslice(b"", b""), Some(0));
+    }
+
+    #[test]
+    fn test_to_file_merge_with_executable() {
+        let file = |id: &str, executable: bool| {
+            Some(TreeValue::File {
+                id: FileId::from_hex(id),
+                executable,
+            })
+        };
+        // The executable bit is carried over from the sides
+        let conflict = Merge::new(
+            vec![file("00", true)],
+            vec![file("01", true), file("02", true)],
+        );
+        let (file_merge, executable) = to_file_merge_with_executable(&conflict).unwrap();
+        assert_eq!(
+            file_merge,
+            conflict.map(|term| match term {
+                Some(TreeValue::File { id, .. }) => Some(id.clone()),
+                _ => None,
+            })
+        );
+        assert!(executable);
+
+        // One side made the file executable
+        let conflict = Merge::new(
+            vec![file("00", false)],
+            vec![file("01", true), file("02", false)],
+        );
+        assert!(to_file_merge_with_executable(&conflict).unwrap().1);
+
+        // Both sides changed the executable bit differently from an absent base
+        let conflict = Merge::new(vec![None], vec![file("01", true), file("02", false)]);
+        assert_eq!(to_file_merge_with_executable(&conflict), None);
+    }
+
+    #[test]
+    fn test_record_and_resolve() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = ResolutionStore::load(temp_dir.path());
+        assert!(store.entries().unwrap().is_empty());
+
+        let path = RepoPath::from_internal_string("file");
+        let conflict = hunk(&["a\nb\nc\n"], &["a\nB1\nc\n", "a\nB2\nc\n"]);
+        assert_eq!(store.resolve_file_conflict(&conflict), None);
+        assert_eq!(
+            store
+                .record_file_resolution(&path, &conflict, b"a\nB3\nc\n")
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            store.resolve_file_conflict(&conflict),
+            Some(b"a\nB3\nc\n".to_vec())
+        );
+
+        // Same conflicted region surrounded by different content
+        let conflict = hunk(&["x\nb\nc\n"], &["x\nB1\nc\n", "x\nB2\nc\n"]);
+        assert_eq!(
+            store.resolve_file_conflict(&conflict),
+            Some(b"x\nB3\nc\n".to_vec())
+        );
+
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        let (key, entry) = &entries[0];
+        assert_eq!(entry.resolution, "B3\n");
+        assert!(store.remove(key).unwrap());
+        assert!(!store.remove(key).unwrap());
+        assert_eq!(store.resolve_file_conflict(&conflict), None);
+    }
+}
@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::Infallible;
 use std::ops::Range;
@@ -59,6 +60,8 @@ pub enum RevsetResolutionError {
     AmbiguousCommitIdPrefix(String),
     #[error("Change ID prefix \"{0}\" is ambiguous")]
     AmbiguousChangeIdPrefix(String),
+    #[error("Failed to resolve operation \"{name}\": {message}")]
+    InvalidOperation { name: String, message: String },
     #[error("Unexpected error from store: {0}")]
     StoreError(#[source] BackendError),
 }
@@ -305,12 +308,25 @@ fn rename_rules_in_pest_error(mut err: pest::error::Error<Rule>) -> pest::error:
 pub const GENERATION_RANGE_FULL: Range<u64> = 0..u64::MAX;
 pub const GENERATION_RANGE_EMPTY: Range<u64> = 0..0;
 
+/// Error occurred during parsing string pattern.
+#[derive(Debug, Error)]
+pub enum StringPatternParseError {
+    /// Unknown pattern kind is specified.
+    #[error(r#"Invalid string pattern kind "{0}""#)]
+    InvalidKind(String),
+    /// Failed to parse glob pattern.
+    #[error(transparent)]
+    GlobPattern(glob::PatternError),
+}
+
 /// Pattern to be tested against string property like commit description or
 /// branch name.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum StringPattern {
     /// Matches strings exactly equal to `string`.
     Exact(String),
+    /// Unix-style shell wildcard pattern.
+    Glob(glob::Pattern),
     /// Matches strings that contain `substring`.
     Substring(String),
 }
@@ -321,10 +337,52 @@ impl StringPattern {
         StringPattern::Substring(String::new())
     }
 
+    /// Parses the given string as a `StringPattern`. Everything before the
+    /// first ":" is considered the string's prefix. If the prefix is "exact:",
+    /// "glob:", or "substring:", a pattern of the specified kind is returned.
+    /// Returns an error if the string has an unrecognized prefix. Otherwise, a
+    /// `StringPattern::Exact` is returned.
+    pub fn parse(src: &str) -> Result<StringPattern, StringPatternParseError> {
+        if let Some((kind, pat)) = src.split_once(':') {
+            StringPattern::from_str_kind(pat, kind)
+        } else {
+            Ok(StringPattern::Exact(src.to_owned()))
+        }
+    }
+
+    /// Creates pattern that matches by Unix-style shell wildcard pattern.
+    pub fn glob(src: &str) -> Result<Self, StringPatternParseError> {
+        let pattern = glob::Pattern::new(src).map_err(StringPatternParseError::GlobPattern)?;
+        Ok(StringPattern::Glob(pattern))
+    }
+
+    /// Converts this pattern to a glob string that matches the same strings.
+    pub fn to_glob(&self) -> Cow<'_, str> {
+        match self {
+            StringPattern::Exact(literal) => glob::Pattern::escape(literal).into(),
+            StringPattern::Glob(pattern) => pattern.as_str().into(),
+            StringPattern::Substring(needle) if needle.is_empty() => "*".into(),
+            StringPattern::Substring(needle) => {
+                format!("*{}*", glob::Pattern::escape(needle)).into()
+            }
+        }
+    }
+
+    /// Parses the given string as pattern of the specified `kind`.
+    pub fn from_str_kind(src: &str, kind: &str) -> Result<Self, StringPatternParseError> {
+        match kind {
+            "exact" => Ok(StringPattern::Exact(src.to_owned())),
+            "glob" => StringPattern::glob(src),
+            "substring" => Ok(StringPattern::Substring(src.to_owned())),
+            _ => Err(StringPatternParseError::InvalidKind(kind.to_owned())),
+        }
+    }
+
     /// Returns true if this pattern matches the `haystack`.
     pub fn matches(&self, haystack: &str) -> bool {
         match self {
             StringPattern::Exact(literal) => haystack == literal,
+            StringPattern::Glob(pattern) => pattern.matches(haystack),
             StringPattern::Substring(needle) => haystack.contains(needle),
         }
     }
@@ -335,7 +393,7 @@ impl StringPattern {
     pub fn as_exact(&self) -> Option<&str> {
         match self {
             StringPattern::Exact(literal) => Some(literal),
-            StringPattern::Substring(_) => None,
+            StringPattern::Glob(_) | StringPattern::Substring(_) => None,
         }
     }
 }
@@ -359,6 +417,8 @@ pub enum RevsetCommitRef {
     Tags,
     GitRefs,
     GitHead,
+    /// Commits added by the operation.
+    Operation(String),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -373,6 +433,9 @@ pub enum RevsetFilterPredicate {
     Committer(StringPattern),
     /// Commits modifying the paths specified by the pattern.
     File(Option<Vec<RepoPath>>), // TODO: embed matcher expression?
+    /// Commits modifying the paths matching any of the glob patterns. The
+    /// patterns are relative to the workspace root.
+    FileGlob(Vec<glob::Pattern>),
     /// Commits with conflicts
     HasConflict,
 }
@@ -408,6 +471,8 @@ pub enum RevsetExpression {
     },
     Heads(Rc<RevsetExpression>),
     Roots(Rc<RevsetExpression>),
+    Predecessors(Rc<RevsetExpression>),
+    ForkPoint(Rc<RevsetExpression>),
     Latest {
         candidates: Rc<RevsetExpression>,
         count: usize,
@@ -492,6 +557,12 @@ impl RevsetExpression {
         Rc::new(RevsetExpression::CommitRef(RevsetCommitRef::GitHead))
     }
 
+    pub fn operation(op_str: String) -> Rc<RevsetExpression> {
+        Rc::new(RevsetExpression::CommitRef(RevsetCommitRef::Operation(
+            op_str,
+        )))
+    }
+
     pub fn latest(self: &Rc<RevsetExpression>, count: usize) -> Rc<RevsetExpression> {
         Rc::new(RevsetExpression::Latest {
             candidates: self.clone(),
@@ -513,6 +584,16 @@ impl RevsetExpression {
         Rc::new(RevsetExpression::Roots(self.clone()))
     }
 
+    /// Commits that `self` were rewritten from.
+    pub fn predecessors(self: &Rc<RevsetExpression>) -> Rc<RevsetExpression> {
+        Rc::new(RevsetExpression::Predecessors(self.clone()))
+    }
+
+    /// Heads of the common ancestors of all commits in `self`.
+    pub fn fork_point(self: &Rc<RevsetExpression>) -> Rc<RevsetExpression> {
+        Rc::new(RevsetExpression::ForkPoint(self.clone()))
+    }
+
     /// Parents of `self`.
     pub fn parents(self: &Rc<RevsetExpression>) -> Rc<RevsetExpression> {
         Rc::new(RevsetExpression::Ancestors {
@@ -728,6 +809,8 @@ pub enum ResolvedExpression {
     },
     Heads(Box<ResolvedExpression>),
     Roots(Box<ResolvedExpression>),
+    Predecessors(Box<ResolvedExpression>),
+    ForkPoint(Box<ResolvedExpression>),
     Latest {
         candidates: Box<ResolvedExpression>,
         count: usize,
@@ -1203,6 +1286,16 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         let candidates = parse_expression_rule(arg.into_inner(), state)?;
         Ok(candidates.roots())
     });
+    map.insert("predecessors", |name, arguments_pair, state| {
+        let arg = expect_one_argument(name, arguments_pair)?;
+        let expression = parse_expression_rule(arg.into_inner(), state)?;
+        Ok(expression.predecessors())
+    });
+    map.insert("fork_point", |name, arguments_pair, state| {
+        let arg = expect_one_argument(name, arguments_pair)?;
+        let expression = parse_expression_rule(arg.into_inner(), state)?;
+        Ok(expression.fork_point())
+    });
     map.insert("visible_heads", |name, arguments_pair, _state| {
         expect_no_arguments(name, arguments_pair)?;
         Ok(RevsetExpression::visible_heads())
@@ -1250,6 +1343,11 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         expect_no_arguments(name, arguments_pair)?;
         Ok(RevsetExpression::git_head())
     });
+    map.insert("op", |name, arguments_pair, state| {
+        let arg = expect_one_argument(name, arguments_pair)?;
+        let op_str = parse_function_argument_to_string(name, arg, state)?;
+        Ok(RevsetExpression::operation(op_str))
+    });
     map.insert("latest", |name, arguments_pair, state| {
         let ([candidates_arg], [count_opt_arg]) = expect_arguments(name, arguments_pair)?;
         let candidates = parse_expression_rule(candidates_arg.into_inner(), state)?;
@@ -1300,34 +1398,54 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
     map.insert("file", |name, arguments_pair, state| {
         if let Some(ctx) = state.workspace_ctx {
             let arguments_span = arguments_pair.as_span();
-            let paths: Vec<_> = arguments_pair
-                .into_inner()
-                .map(|arg| -> Result<_, RevsetParseError> {
-                    let span = arg.as_span();
-                    let needle = parse_function_argument_to_string(name, arg, state)?;
-                    let path = RepoPath::parse_fs_path(ctx.cwd, ctx.workspace_root, needle)
-                        .map_err(|e| {
-                            RevsetParseError::with_span(
-                                RevsetParseErrorKind::FsPathParseError(e),
-                                span,
-                            )
-                        })?;
-                    Ok(path)
-                })
-                .try_collect()?;
-            if paths.is_empty() {
-                Err(RevsetParseError::with_span(
-                    RevsetParseErrorKind::InvalidFunctionArguments {
-                        name: name.to_owned(),
-                        message: "Expected at least 1 argument".to_string(),
-                    },
-                    arguments_span,
-                ))
-            } else {
-                Ok(RevsetExpression::filter(RevsetFilterPredicate::File(Some(
-                    paths,
-                ))))
+            let mut paths = vec![];
+            let mut globs = vec![];
+            for arg in arguments_pair.into_inner() {
+                let span = arg.as_span();
+                let parse_fs_path = |needle: &str| {
+                    RepoPath::parse_fs_path(ctx.cwd, ctx.workspace_root, needle).map_err(|e| {
+                        RevsetParseError::with_span(RevsetParseErrorKind::FsPathParseError(e), span)
+                    })
+                };
+                match parse_function_argument_to_file_pattern(name, arg, state)? {
+                    FilePatternArg::Path(needle) => paths.push(parse_fs_path(&needle)?),
+                    FilePatternArg::Glob(pattern) => {
+                        // Make the pattern relative to the workspace root
+                        let cwd = parse_fs_path(".")?;
+                        let prefix = glob::Pattern::escape(&cwd.to_internal_dir_string());
+                        let pattern =
+                            glob::Pattern::new(&format!("{prefix}{pattern}")).map_err(|err| {
+                                RevsetParseError::with_span(
+                                    RevsetParseErrorKind::InvalidFunctionArguments {
+                                        name: name.to_owned(),
+                                        message: format!("Invalid glob pattern: {err}"),
+                                    },
+                                    span,
+                                )
+                            })?;
+                        globs.push(pattern);
+                    }
+                }
             }
+            let expression = match (paths.is_empty(), globs.is_empty()) {
+                (true, true) => {
+                    return Err(RevsetParseError::with_span(
+                        RevsetParseErrorKind::InvalidFunctionArguments {
+                            name: name.to_owned(),
+                            message: "Expected at least 1 argument".to_string(),
+                        },
+                        arguments_span,
+                    ));
+                }
+                (false, true) => RevsetExpression::filter(RevsetFilterPredicate::File(Some(paths))),
+                (true, false) => RevsetExpression::filter(RevsetFilterPredicate::FileGlob(globs)),
+                (false, false) => {
+                    RevsetExpression::filter(RevsetFilterPredicate::File(Some(paths))).union(
+                        &RevsetExpression::filter(RevsetFilterPredicate::FileGlob(globs)),
+                    )
+                }
+            };
+            Ok(expression)
         } else {
             Err(RevsetParseError::new(
                 RevsetParseErrorKind::FsPathWithoutWorkspace,
@@ -1512,22 +1630,63 @@ fn parse_function_argument_to_string_pattern(
             else {
                 return Err(make_type_error());
             };
-            match kind.as_ref() {
-                "exact" => StringPattern::Exact(needle.clone()),
-                "substring" => StringPattern::Substring(needle.clone()),
-                _ => {
-                    // TODO: error span can be narrowed to the lhs node
-                    return Err(make_error(format!(
-                        r#"Invalid string pattern kind "{kind}""#
-                    )));
-                }
-            }
+            // TODO: error span can be narrowed to the lhs node
+            StringPattern::from_str_kind(needle, kind).map_err(|err| make_error(err.to_string()))?
         }
         _ => return Err(make_type_error()),
     };
     Ok(pattern)
 }
 
+/// Argument of `file()`, which is a path or a `glob:` pattern.
+enum FilePatternArg {
+    Path(String),
+    Glob(String),
+}
+
+fn parse_function_argument_to_file_pattern(
+    name: &str,
+    pair: Pair<Rule>,
+    state: ParseState,
+) -> Result<FilePatternArg, RevsetParseError> {
+    let span = pair.as_span();
+    let make_error = |message| {
+        RevsetParseError::with_span(
+            RevsetParseErrorKind::InvalidFunctionArguments {
+                name: name.to_string(),
+                message,
+            },
+            span,
+        )
+    };
+    let make_type_error = || make_error("Expected function argument of file pattern".to_owned());
+    let expression = parse_expression_rule(pair.into_inner(), state)?;
+    match expression.as_ref() {
+        RevsetExpression::CommitRef(RevsetCommitRef::Symbol(symbol)) => {
+            Ok(FilePatternArg::Path(symbol.to_owned()))
+        }
+        // TODO: Add proper parsed node if we drop support for legacy x:y range
+        RevsetExpression::DagRange {
+            roots,
+            heads,
+            is_legacy: true,
+        } => {
+            let RevsetExpression::CommitRef(RevsetCommitRef::Symbol(kind)) = roots.as_ref() else {
+                return Err(make_type_error());
+            };
+            let RevsetExpression::CommitRef(RevsetCommitRef::Symbol(needle)) = heads.as_ref()
+            else {
+                return Err(make_type_error());
+            };
+            match kind.as_str() {
+                "glob" => Ok(FilePatternArg::Glob(needle.to_owned())),
+                _ => Err(make_error(format!("Invalid file pattern kind \"{kind}:\""))),
+            }
+        }
+        _ => Err(make_type_error()),
+    }
+}
+
 fn parse_function_argument_as_literal<T: FromStr>(
     type_name: &str,
     name: &str,
@@ -1646,6 +1805,12 @@ fn try_transform_expression<E>(
             RevsetExpression::Roots(candidates) => {
                 transform_rec(candidates, pre, post)?.map(RevsetExpression::Roots)
             }
+            RevsetExpression::Predecessors(candidates) => {
+                transform_rec(candidates, pre, post)?.map(RevsetExpression::Predecessors)
+            }
+            RevsetExpression::ForkPoint(candidates) => {
+                transform_rec(candidates, pre, post)?.map(RevsetExpression::ForkPoint)
+            }
             RevsetExpression::Latest { candidates, count } => transform_rec(candidates, pre, post)?
                 .map(|candidates| RevsetExpression::Latest {
                     candidates,
@@ -2078,6 +2243,14 @@ fn resolve_full_commit_id(
 
 pub trait SymbolResolver {
     fn resolve_symbol(&self, symbol: &str) -> Result<Vec<CommitId>, RevsetResolutionError>;
+
+    /// Resolves `op(op_str)` to the commits added by the operation.
+    fn resolve_operation(&self, op_str: &str) -> Result<Vec<CommitId>, RevsetResolutionError> {
+        Err(RevsetResolutionError::InvalidOperation {
+            name: op_str.to_owned(),
+            message: "Operations can't be resolved in this context".to_owned(),
+        })
+    }
 }
 
 /// Fails on any attempt to resolve a symbol.
@@ -2097,12 +2270,16 @@ impl SymbolResolver for FailingSymbolResolver {
 
 pub type PrefixResolver<'a, T> = Box<dyn Fn(&dyn Repo, &HexPrefix) -> PrefixResolution<T> + 'a>;
 
+pub type OperationResolver<'a> =
+    Box<dyn Fn(&str) -> Result<Vec<CommitId>, RevsetResolutionError> + 'a>;
+
 /// Resolves branches, remote branches, tags, git refs, and full and abbreviated
 /// commit and change ids.
 pub struct DefaultSymbolResolver<'a> {
     repo: &'a dyn Repo,
     commit_id_resolver: PrefixResolver<'a, CommitId>,
     change_id_resolver: PrefixResolver<'a, Vec<CommitId>>,
+    operation_resolver: Option<OperationResolver<'a>>,
 }
 
 impl<'a> DefaultSymbolResolver<'a> {
@@ -2111,6 +2288,7 @@ impl<'a> DefaultSymbolResolver<'a> {
             repo,
             commit_id_resolver: Box::new(|repo, prefix| repo.index().resolve_prefix(prefix)),
             change_id_resolver: Box::new(|repo, prefix| repo.resolve_change_id_prefix(prefix)),
+            operation_resolver: None,
         }
     }
 
@@ -2129,6 +2307,13 @@ impl<'a> DefaultSymbolResolver<'a> {
         self.change_id_resolver = change_id_resolver;
         self
     }
+
+    /// Sets the function that resolves `op(op_str)`. The operation log isn't
+    /// accessible through `Repo`, so `op()` fails to resolve without it.
+    pub fn with_operation_resolver(mut self, operation_resolver: OperationResolver<'a>) -> Self {
+        self.operation_resolver = Some(operation_resolver);
+        self
+    }
 }
 
 impl SymbolResolver for DefaultSymbolResolver<'_> {
@@ -2194,6 +2379,16 @@ impl SymbolResolver for DefaultSymbolResolver<'_> {
 
         Err(make_no_such_symbol_error(self.repo, symbol))
     }
+
+    fn resolve_operation(&self, op_str: &str) -> Result<Vec<CommitId>, RevsetResolutionError> {
+        match &self.operation_resolver {
+            Some(operation_resolver) => operation_resolver(op_str),
+            None => Err(RevsetResolutionError::InvalidOperation {
+                name: op_str.to_owned(),
+                message: "Operations can't be resolved in this context".to_owned(),
+            }),
+        }
+    }
 }
 
 fn resolve_commit_ref(
@@ -2253,6 +2448,7 @@ fn resolve_commit_ref(
             Ok(commit_ids)
         }
         RevsetCommitRef::GitHead => Ok(repo.view().git_head().added_ids().cloned().collect()),
+        RevsetCommitRef::Operation(op_str) => symbol_resolver.resolve_operation(op_str),
     }
 }
 
@@ -2275,6 +2471,7 @@ fn resolve_symbols(
                         | RevsetResolutionError::EmptyString
                         | RevsetResolutionError::AmbiguousCommitIdPrefix(_)
                         | RevsetResolutionError::AmbiguousChangeIdPrefix(_)
+                        | RevsetResolutionError::InvalidOperation { .. }
                         | RevsetResolutionError::StoreError(_) => Err(err),
                     })
                     .map(Some) // Always rewrite subtree
@@ -2362,6 +2559,12 @@ impl VisibilityResolutionContext<'_> {
             RevsetExpression::Roots(candidates) => {
                 ResolvedExpression::Roots(self.resolve(candidates).into())
             }
+            RevsetExpression::Predecessors(candidates) => {
+                ResolvedExpression::Predecessors(self.resolve(candidates).into())
+            }
+            RevsetExpression::ForkPoint(candidates) => {
+                ResolvedExpression::ForkPoint(self.resolve(candidates).into())
+            }
             RevsetExpression::Latest { candidates, count } => ResolvedExpression::Latest {
                 candidates: self.resolve(candidates).into(),
                 count: *count,
@@ -2442,6 +2645,8 @@ impl VisibilityResolutionContext<'_> {
             | RevsetExpression::DagRange { .. }
             | RevsetExpression::Heads(_)
             | RevsetExpression::Roots(_)
+            | RevsetExpression::Predecessors(_)
+            | RevsetExpression::ForkPoint(_)
             | RevsetExpression::Latest { .. } => {
                 ResolvedPredicateExpression::Set(self.resolve(expression).into())
             }
@@ -2478,34 +2683,11 @@ pub trait Revset<'index>: fmt::Debug {
 
     fn iter_graph(&self) -> Box<dyn Iterator<Item = (CommitId, Vec<RevsetGraphEdge>)> + '_>;
 
-    fn change_id_index(&self) -> Box<dyn ChangeIdIndex + 'index>;
-
     fn is_empty(&self) -> bool;
 
     fn count(&self) -> usize;
 }
 
-pub trait ChangeIdIndex: Send + Sync {
-    /// Resolve an unambiguous change ID prefix to the commit IDs in the revset.
-    fn resolve_prefix(&self, prefix: &HexPrefix) -> PrefixResolution<Vec<CommitId>>;
-
-    /// This function returns the shortest length of a prefix of `key` that
-    /// disambiguates it from every other key in the index.
-    ///
-    /// The length to be returned is a number of hexadecimal digits.
-    ///
-    /// This has some properties that we do not currently make much use of:
-    ///
-    /// - The algorithm works even if `key` itself is not in the index.
-    ///
-    /// - In the special case when there are keys in the trie for which our
-    ///   `key` is an exact prefix, returns `key.len() + 1`. Conceptually, in
-    ///   order to disambiguate, you need every letter of the key *and* the
-    ///   additional fact that it's the entire key). This case is extremely
-    ///   unlikely for hashes with 12+ hexadecimal characters.
-    fn shortest_unique_prefix_len(&self, change_id: &ChangeId) -> usize;
-}
-
 pub trait RevsetIteratorExt<'index, I> {
     fn commits(self, store: &Arc<Store>) -> RevsetCommitIterator<I>;
     fn reversed(self) -> ReverseRevsetIterator;
@@ -3146,6 +3328,11 @@ mod tests {
             Ok(Rc::new(RevsetExpression::CommitRef(RevsetCommitRef::Root)))
         );
         assert!(parse("root(a)").is_err());
+        assert_eq!(
+            parse(r#"op("@-")"#),
+            Ok(RevsetExpression::operation("@-".to_string()))
+        );
+        assert!(parse("op()").is_err());
         assert_eq!(
             parse(r#"description("")"#),
             Ok(RevsetExpression::filter(
@@ -3196,6 +3383,28 @@ mod tests {
                 vec![RepoPath::from_internal_string("foo")]
             ))))
         );
+        assert_eq!(
+            parse_with_workspace(r#"file(glob:"src/**/*.rs")"#, &WorkspaceId::default()),
+            Ok(RevsetExpression::filter(RevsetFilterPredicate::FileGlob(
+                vec![glob::Pattern::new("src/**/*.rs").unwrap()]
+            )))
+        );
+        assert_eq!(
+            parse_with_workspace(r#"file(exact:"foo")"#, &WorkspaceId::default()),
+            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+                name: "file".to_string(),
+                message: r#"Invalid file pattern kind "exact:""#.to_string()
+            })
+        );
+        assert_eq!(
+            parse_with_workspace(r#"file(glob:"[")"#, &WorkspaceId::default()),
+            Err(RevsetParseErrorKind::InvalidFunctionArguments {
+                name: "file".to_string(),
+                message: "Invalid glob pattern: Pattern syntax error near position 0: invalid \
+                          range pattern"
+                    .to_string()
+            })
+        );
         assert_eq!(
             parse_with_workspace("file(foo, bar, baz)", &WorkspaceId::default()),
             Ok(RevsetExpression::filter(RevsetFilterPredicate::File(Some(
@@ -27,6 +27,7 @@ use crate::index::Index;
 use crate::merged_tree::MergedTree;
 use crate::op_store::RefTarget;
 use crate::repo::{MutableRepo, Repo};
+use crate::resolution_store::ResolutionStore;
 use crate::revset::{RevsetExpression, RevsetIteratorExt};
 use crate::settings::UserSettings;
 use crate::store::Store;
@@ -92,7 +93,14 @@ pub fn rebase_commit(
         let new_base_tree = merge_commit_trees(mut_repo, new_parents)?;
         let old_tree = old_commit.tree()?;
         let merged_tree = new_base_tree.merge(&old_base_tree, &old_tree)?;
-        merged_tree.id()
+        if settings.remember_conflict_resolutions() && merged_tree.has_conflict() {
+            let store = ResolutionStore::load(mut_repo.base_repo().repo_path());
+            store
+                .resolve_tree_conflicts(&merged_tree)?
+                .unwrap_or_else(|| merged_tree.id())
+        } else {
+            merged_tree.id()
+        }
     };
     let new_parent_ids = new_parents
         .iter()
@@ -24,6 +24,7 @@ use rand_chacha::ChaCha20Rng;
 use crate::backend::{ChangeId, ObjectId, Signature, Timestamp};
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorKind;
+use crate::working_copy::{ConflictMaterialization, SymlinkMaterialization};
 
 #[derive(Debug, Clone)]
 pub struct UserSettings {
@@ -37,23 +38,62 @@ pub struct RepoSettings {
     _config: config::Config,
 }
 
-#[derive(Debug, Clone)]
+/// For which remote branches `jj git fetch` and `jj git import` create or
+/// update local branches, as set by `git.auto-local-branch`.
+#[derive(Debug, Clone, Default)]
+pub enum AutoLocalBranch {
+    /// All remote branches, which is the default or `true`.
+    #[default]
+    All,
+    /// No remote branches, which is `false`.
+    None,
+    /// The remote branches matching any of the glob patterns. An empty list
+    /// matches no branches.
+    Patterns(Vec<glob::Pattern>),
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct GitSettings {
-    pub auto_local_branch: bool,
+    pub auto_local_branch: AutoLocalBranch,
 }
 
 impl GitSettings {
-    pub fn from_config(config: &config::Config) -> Self {
-        GitSettings {
-            auto_local_branch: config.get_bool("git.auto-local-branch").unwrap_or(true),
-        }
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        // `git.auto-local-branch` is either a boolean or a list of glob patterns.
+        let auto_local_branch = match config.get::<Vec<String>>("git.auto-local-branch") {
+            Ok(patterns) => AutoLocalBranch::Patterns(
+                patterns
+                    .iter()
+                    .map(|pattern| {
+                        glob::Pattern::new(pattern).map_err(|err| {
+                            config::ConfigError::Message(format!(
+                                "Invalid pattern {pattern:?} in `git.auto-local-branch`: {err}"
+                            ))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Err(config::ConfigError::NotFound(_)) => AutoLocalBranch::All,
+            Err(_) => {
+                if config.get_bool("git.auto-local-branch")? {
+                    AutoLocalBranch::All
+                } else {
+                    AutoLocalBranch::None
+                }
+            }
+        };
+        Ok(GitSettings { auto_local_branch })
     }
-}
 
-impl Default for GitSettings {
-    fn default() -> Self {
-        GitSettings {
-            auto_local_branch: true,
+    /// Whether a local branch should be created or updated when the remote
+    /// branch of the given name is imported.
+    pub fn auto_local_branch_for(&self, branch: &str) -> bool {
+        match &self.auto_local_branch {
+            AutoLocalBranch::All => true,
+            AutoLocalBranch::None => false,
+            AutoLocalBranch::Patterns(patterns) => {
+                patterns.iter().any(|pattern| pattern.matches(branch))
+            }
         }
     }
 }
@@ -103,6 +143,28 @@ impl UserSettings {
             .unwrap_or(false)
     }
 
+    pub fn conflict_materialization(&self) -> Result<ConflictMaterialization, config::ConfigError> {
+        match self.config.get_string("conflicts.materialization") {
+            Ok(materialization) => materialization.parse(),
+            Err(config::ConfigError::NotFound(_)) => Ok(ConflictMaterialization::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn symlink_materialization(&self) -> Result<SymlinkMaterialization, config::ConfigError> {
+        match self.config.get_string("symlinks.materialization") {
+            Ok(materialization) => materialization.parse(),
+            Err(config::ConfigError::NotFound(_)) => Ok(SymlinkMaterialization::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn remember_conflict_resolutions(&self) -> bool {
+        self.config
+            .get_bool("conflicts.remember-resolutions")
+            .unwrap_or(false)
+    }
+
     pub fn user_name(&self) -> String {
         self.config.get_string("user.name").unwrap_or_default()
     }
@@ -188,7 +250,7 @@ impl UserSettings {
         &self.config
     }
 
-    pub fn git_settings(&self) -> GitSettings {
+    pub fn git_settings(&self) -> Result<GitSettings, config::ConfigError> {
         GitSettings::from_config(&self.config)
     }
 
@@ -354,4 +416,35 @@ mod tests {
         );
         assert_eq!(parse_human_byte_size(""), Err("must start with a number"));
     }
+
+    #[test]
+    fn git_settings_auto_local_branch() {
+        let from_toml = |text: &str| {
+            let config = config::Config::builder()
+                .add_source(config::File::from_str(text, config::FileFormat::Toml))
+                .build()
+                .unwrap();
+            GitSettings::from_config(&config)
+        };
+
+        let settings = from_toml("").unwrap();
+        assert!(settings.auto_local_branch_for("main"));
+
+        let settings = from_toml("git.auto-local-branch = false").unwrap();
+        assert!(!settings.auto_local_branch_for("main"));
+
+        let settings = from_toml(r#"git.auto-local-branch = ["main", "release/*"]"#).unwrap();
+        assert!(settings.auto_local_branch_for("main"));
+        assert!(settings.auto_local_branch_for("release/1.0"));
+        assert!(!settings.auto_local_branch_for("feature"));
+        assert!(!settings.auto_local_branch_for("main2"));
+
+        // An empty list matches no branches
+        let settings = from_toml("git.auto-local-branch = []").unwrap();
+        assert!(!settings.auto_local_branch_for("main"));
+
+        let err = from_toml(r#"git.auto-local-branch = ["[main"]"#).unwrap_err();
+        assert!(err.to_string().contains(r#"Invalid pattern "[main""#));
+        assert!(from_toml(r#"git.auto-local-branch = "main""#).is_err());
+    }
 }
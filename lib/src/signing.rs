@@ -0,0 +1,355 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of cryptographic commit signatures.
+
+#![allow(missing_docs)]
+
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use thiserror::Error;
+
+/// The signature of a commit along with the data it signs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SecureSig {
+    /// The signed commit object, without the signature header.
+    pub data: Vec<u8>,
+    /// The armored signature.
+    pub sig: Vec<u8>,
+}
+
+/// The outcome of verifying a signature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SigStatus {
+    /// The signature is valid and made by a known signer.
+    Good,
+    /// The signature is invalid.
+    Bad,
+    /// The signature is valid, but the signer isn't trusted, or the signature
+    /// couldn't be checked.
+    Unknown,
+}
+
+impl SigStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigStatus::Good => "good",
+            SigStatus::Bad => "bad",
+            SigStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// Information about a verified signature.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Verification {
+    pub status: SigStatus,
+    /// The fingerprint of the signing key, if known.
+    pub key: Option<String>,
+    /// The signer, e.g. the principal in the allowed signers file.
+    pub display: Option<String>,
+}
+
+impl Verification {
+    fn unknown() -> Self {
+        Verification {
+            status: SigStatus::Unknown,
+            key: None,
+            display: None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SignatureVerifyError {
+    #[error("Failed to run {program}: {source}")]
+    Command {
+        program: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Failed to read allowed signers file {path}: {source}")]
+    AllowedSigners {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+const SSH_SIGNATURE_PREFIX: &[u8] = b"-----BEGIN SSH SIGNATURE-----";
+
+/// Verifies SSH signatures by using `ssh-keygen -Y`.
+#[derive(Clone, Debug)]
+pub struct SshVerifier {
+    program: String,
+    /// Files in the `allowed_signers` format of `ssh-keygen`. Keys published
+    /// by a forge can be listed in a separate file.
+    allowed_signers: Vec<PathBuf>,
+}
+
+impl Default for SshVerifier {
+    fn default() -> Self {
+        SshVerifier {
+            program: "ssh-keygen".to_owned(),
+            allowed_signers: vec![],
+        }
+    }
+}
+
+impl SshVerifier {
+    pub fn from_config(config: &config::Config) -> Result<Self, config::ConfigError> {
+        let mut verifier = SshVerifier::default();
+        match config.get_string("signing.backends.ssh.program") {
+            Ok(program) => verifier.program = program,
+            Err(config::ConfigError::NotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+        // `signing.backends.ssh.allowed-signers` is either a path or a list of
+        // paths.
+        let key = "signing.backends.ssh.allowed-signers";
+        let paths = match config.get::<config::Value>(key) {
+            Ok(value) => match value.kind {
+                config::ValueKind::String(path) => vec![path],
+                config::ValueKind::Array(_) => config.get::<Vec<String>>(key)?,
+                _ => {
+                    return Err(config::ConfigError::Message(format!(
+                        "Invalid type for {key}: expected a string or a list of strings"
+                    )))
+                }
+            },
+            Err(config::ConfigError::NotFound(_)) => vec![],
+            Err(err) => return Err(err),
+        };
+        verifier.allowed_signers = paths.into_iter().map(expand_home_path).collect();
+        Ok(verifier)
+    }
+
+    /// Whether the signature looks like an SSH signature.
+    pub fn can_verify(&self, sig: &[u8]) -> bool {
+        sig.starts_with(SSH_SIGNATURE_PREFIX)
+    }
+
+    /// Verifies the signature against the allowed signers.
+    ///
+    /// Signatures made by keys that aren't in any of the allowed signers files
+    /// are reported as `Unknown` if they are otherwise valid.
+    pub fn verify(&self, secure_sig: &SecureSig) -> Result<Verification, SignatureVerifyError> {
+        if !self.can_verify(&secure_sig.sig) {
+            return Ok(Verification::unknown());
+        }
+        let temp_dir = tempfile::tempdir()?;
+        let sig_path = temp_dir.path().join("signature");
+        File::create(&sig_path)?.write_all(&secure_sig.sig)?;
+
+        if !self.allowed_signers.is_empty() {
+            let allowed_signers_path = temp_dir.path().join("allowed_signers");
+            let mut allowed_signers_file = File::create(&allowed_signers_path)?;
+            for path in &self.allowed_signers {
+                let content =
+                    std::fs::read(path).map_err(|source| SignatureVerifyError::AllowedSigners {
+                        path: path.clone(),
+                        source,
+                    })?;
+                allowed_signers_file.write_all(&content)?;
+                allowed_signers_file.write_all(b"\n")?;
+            }
+            drop(allowed_signers_file);
+
+            let output = self.run(
+                &[
+                    "-Y".as_ref(),
+                    "find-principals".as_ref(),
+                    "-f".as_ref(),
+                    allowed_signers_path.as_os_str(),
+                    "-s".as_ref(),
+                    sig_path.as_os_str(),
+                ],
+                &[],
+            )?;
+            let principals = String::from_utf8_lossy(&output.stdout).into_owned();
+            if let Some(principal) = principals.lines().find(|line| !line.is_empty()) {
+                let output = self.run(
+                    &[
+                        "-Y".as_ref(),
+                        "verify".as_ref(),
+                        "-f".as_ref(),
+                        allowed_signers_path.as_os_str(),
+                        "-I".as_ref(),
+                        principal.as_ref(),
+                        "-n".as_ref(),
+                        "git".as_ref(),
+                        "-s".as_ref(),
+                        sig_path.as_os_str(),
+                    ],
+                    &secure_sig.data,
+                )?;
+                let status = if output.status.success() {
+                    SigStatus::Good
+                } else {
+                    SigStatus::Bad
+                };
+                return Ok(Verification {
+                    status,
+                    key: parse_key_fingerprint(&output.stdout),
+                    display: Some(principal.to_owned()),
+                });
+            }
+        }
+
+        // The signer isn't known, but the signature itself can still be
+        // checked.
+        let output = self.run(
+            &[
+                "-Y".as_ref(),
+                "check-novalidate".as_ref(),
+                "-n".as_ref(),
+                "git".as_ref(),
+                "-s".as_ref(),
+                sig_path.as_os_str(),
+            ],
+            &secure_sig.data,
+        )?;
+        let status = if output.status.success() {
+            SigStatus::Unknown
+        } else {
+            SigStatus::Bad
+        };
+        Ok(Verification {
+            status,
+            key: parse_key_fingerprint(&output.stdout),
+            display: None,
+        })
+    }
+
+    fn run(
+        &self,
+        args: &[&std::ffi::OsStr],
+        stdin: &[u8],
+    ) -> Result<std::process::Output, SignatureVerifyError> {
+        let map_err = |source| SignatureVerifyError::Command {
+            program: self.program.clone(),
+            source,
+        };
+        let mut child = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(map_err)?;
+        // ssh-keygen may exit without reading the input, e.g. if the signature
+        // is malformed.
+        let mut child_stdin = child.stdin.take().unwrap();
+        match child_stdin.write_all(stdin) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(map_err(err)),
+            _ => {}
+        }
+        drop(child_stdin);
+        child.wait_with_output().map_err(map_err)
+    }
+}
+
+/// Expands "~/" to "$HOME/" like the paths in Git config.
+fn expand_home_path(path: String) -> PathBuf {
+    if let Some(remainder) = path.strip_prefix("~/") {
+        if let Ok(home_dir) = std::env::var("HOME") {
+            return PathBuf::from(home_dir).join(remainder);
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// Extracts the key fingerprint from the output of `ssh-keygen -Y verify`,
+/// e.g. `Good "git" signature for alice with ED25519 key SHA256:...`.
+fn parse_key_fingerprint(stdout: &[u8]) -> Option<String> {
+    let stdout = String::from_utf8_lossy(stdout);
+    let line = stdout.lines().next()?;
+    let (_, key) = line.rsplit_once(" key ")?;
+    Some(key.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from_toml(text: &str) -> config::Config {
+        config::Config::builder()
+            .add_source(config::File::from_str(text, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ssh_verifier_from_config() {
+        let verifier = SshVerifier::from_config(&config_from_toml("")).unwrap();
+        assert_eq!(verifier.program, "ssh-keygen");
+        assert!(verifier.allowed_signers.is_empty());
+
+        let verifier = SshVerifier::from_config(&config_from_toml(
+            r#"
+            signing.backends.ssh.program = "my-ssh-keygen"
+            signing.backends.ssh.allowed-signers = "/signers"
+            "#,
+        ))
+        .unwrap();
+        assert_eq!(verifier.program, "my-ssh-keygen");
+        assert_eq!(verifier.allowed_signers, vec![PathBuf::from("/signers")]);
+
+        let verifier = SshVerifier::from_config(&config_from_toml(
+            r#"signing.backends.ssh.allowed-signers = ["/a", "/b"]"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            verifier.allowed_signers,
+            vec![PathBuf::from("/a"), PathBuf::from("/b")]
+        );
+
+        assert!(SshVerifier::from_config(&config_from_toml(
+            r#"signing.backends.ssh.allowed-signers = 1"#
+        ))
+        .is_err());
+        assert!(SshVerifier::from_config(&config_from_toml(
+            r#"signing.backends.ssh.allowed-signers = ["/a", { b = 1 }]"#
+        ))
+        .is_err());
+        assert!(SshVerifier::from_config(&config_from_toml(
+            r#"signing.backends.ssh.program = ["ssh-keygen"]"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_key_fingerprint() {
+        assert_eq!(
+            parse_key_fingerprint(
+                b"Good \"git\" signature for alice@example.com with ED25519 key SHA256:abc\n"
+            ),
+            Some("SHA256:abc".to_owned())
+        );
+        assert_eq!(
+            parse_key_fingerprint(b"Good \"git\" signature with RSA key SHA256:def\n"),
+            Some("SHA256:def".to_owned())
+        );
+        assert_eq!(parse_key_fingerprint(b""), None);
+        assert_eq!(
+            parse_key_fingerprint(b"Could not verify signature.\n"),
+            None
+        );
+    }
+}
@@ -24,7 +24,7 @@ use prost::Message;
 use tempfile::{NamedTempFile, PersistError};
 use thiserror::Error;
 
-use crate::backend::{CommitId, MillisSinceEpoch, ObjectId, Timestamp};
+use crate::backend::{ChangeId, CommitId, MillisSinceEpoch, ObjectId, Timestamp};
 use crate::content_hash::blake2b_hash;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::merge::Merge;
@@ -289,6 +289,13 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (change_id, text) in &view.notes {
+        proto.notes.push(crate::protos::op_store::Note {
+            change_id: change_id.to_bytes(),
+            text: text.clone(),
+        });
+    }
+
     proto
 }
 
@@ -354,6 +361,11 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    for note_proto in proto.notes {
+        view.notes
+            .insert(ChangeId::new(note_proto.change_id), note_proto.text);
+    }
+
     view
 }
 
@@ -437,7 +449,7 @@ mod tests {
     use maplit::{btreemap, hashmap, hashset};
 
     use super::*;
-    use crate::backend::{CommitId, MillisSinceEpoch, ObjectId, Timestamp};
+    use crate::backend::{ChangeId, CommitId, MillisSinceEpoch, ObjectId, Timestamp};
     use crate::content_hash::blake2b_hash;
     use crate::op_store::{BranchTarget, OperationMetadata, RefTarget, WorkspaceId};
 
@@ -486,6 +498,7 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            notes: btreemap! {},
         }
     }
 
@@ -544,6 +557,20 @@ mod tests {
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_notes() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleOpStore::init(temp_dir.path());
+        let mut view = create_view();
+        view.notes
+            .insert(ChangeId::from_hex("eee111"), "reviewed".to_string());
+        let view_id = store.write_view(&view).unwrap();
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+        // Views with notes get a different id
+        assert_ne!(view_id, store.write_view(&create_view()).unwrap());
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = testutils::new_temp_dir();
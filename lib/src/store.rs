@@ -17,9 +17,12 @@
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::io;
 use std::io::Read;
 use std::sync::{Arc, RwLock};
 
+use tracing::trace_span;
+
 use crate::backend;
 use crate::backend::{
     Backend, BackendResult, ChangeId, CommitId, ConflictId, FileId, MergedTreeId, SymlinkId,
@@ -38,6 +41,7 @@ pub struct Store {
     backend: Box<dyn Backend>,
     commit_cache: RwLock<HashMap<CommitId, Arc<backend::Commit>>>,
     tree_cache: RwLock<HashMap<(RepoPath, TreeId), Arc<backend::Tree>>>,
+    file_size_cache: RwLock<HashMap<FileId, u64>>,
     use_tree_conflict_format: bool,
 }
 
@@ -55,6 +59,7 @@ impl Store {
             backend,
             commit_cache: Default::default(),
             tree_cache: Default::default(),
+            file_size_cache: Default::default(),
             use_tree_conflict_format,
         })
     }
@@ -108,7 +113,7 @@ impl Store {
                 return Ok(data);
             }
         }
-        let commit = self.backend.read_commit(id)?;
+        let commit = trace_span!("read_commit").in_scope(|| self.backend.read_commit(id))?;
         let data = Arc::new(commit);
         let mut write_locked_cache = self.commit_cache.write().unwrap();
         write_locked_cache.insert(id.clone(), data.clone());
@@ -117,7 +122,8 @@ impl Store {
 
     pub fn write_commit(self: &Arc<Self>, commit: backend::Commit) -> BackendResult<Commit> {
         assert!(!commit.parents.is_empty());
-        let (commit_id, commit) = self.backend.write_commit(commit)?;
+        let (commit_id, commit) =
+            trace_span!("write_commit").in_scope(|| self.backend.write_commit(commit))?;
         let data = Arc::new(commit);
         {
             let mut write_locked_cache = self.commit_cache.write().unwrap();
@@ -140,7 +146,8 @@ impl Store {
                 return Ok(data);
             }
         }
-        let data = Arc::new(self.backend.read_tree(dir, id)?);
+        let data = trace_span!("read_tree").in_scope(|| self.backend.read_tree(dir, id))?;
+        let data = Arc::new(data);
         let mut write_locked_cache = self.tree_cache.write().unwrap();
         write_locked_cache.insert(key, data.clone());
         Ok(data)
@@ -164,7 +171,8 @@ impl Store {
         path: &RepoPath,
         tree: backend::Tree,
     ) -> BackendResult<Tree> {
-        let tree_id = self.backend.write_tree(path, &tree)?;
+        let tree_id =
+            trace_span!("write_tree").in_scope(|| self.backend.write_tree(path, &tree))?;
         let data = Arc::new(tree);
         {
             let mut write_locked_cache = self.tree_cache.write().unwrap();
@@ -175,19 +183,35 @@ impl Store {
     }
 
     pub fn read_file(&self, path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
-        self.backend.read_file(path, id)
+        let span = trace_span!("read_file");
+        let reader = span.in_scope(|| self.backend.read_file(path, id))?;
+        Ok(Box::new(TracedReader { span, reader }))
+    }
+
+    /// Returns the size of the file contents in bytes without reading them if
+    /// the backend supports it.
+    pub fn file_size(&self, path: &RepoPath, id: &FileId) -> BackendResult<u64> {
+        if let Some(size) = self.file_size_cache.read().unwrap().get(id) {
+            return Ok(*size);
+        }
+        let size = trace_span!("file_size").in_scope(|| self.backend.file_size(path, id))?;
+        self.file_size_cache
+            .write()
+            .unwrap()
+            .insert(id.clone(), size);
+        Ok(size)
     }
 
     pub fn write_file(&self, path: &RepoPath, contents: &mut dyn Read) -> BackendResult<FileId> {
-        self.backend.write_file(path, contents)
+        trace_span!("write_file").in_scope(|| self.backend.write_file(path, contents))
     }
 
     pub fn read_symlink(&self, path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
-        self.backend.read_symlink(path, id)
+        trace_span!("read_symlink").in_scope(|| self.backend.read_symlink(path, id))
     }
 
     pub fn write_symlink(&self, path: &RepoPath, contents: &str) -> BackendResult<SymlinkId> {
-        self.backend.write_symlink(path, contents)
+        trace_span!("write_symlink").in_scope(|| self.backend.write_symlink(path, contents))
     }
 
     pub fn read_conflict(
@@ -195,7 +219,8 @@ impl Store {
         path: &RepoPath,
         id: &ConflictId,
     ) -> BackendResult<Merge<Option<TreeValue>>> {
-        let backend_conflict = self.backend.read_conflict(path, id)?;
+        let backend_conflict =
+            trace_span!("read_conflict").in_scope(|| self.backend.read_conflict(path, id))?;
         Ok(Merge::from_backend_conflict(backend_conflict))
     }
 
@@ -204,11 +229,27 @@ impl Store {
         path: &RepoPath,
         contents: &Merge<Option<TreeValue>>,
     ) -> BackendResult<ConflictId> {
-        self.backend
-            .write_conflict(path, &contents.clone().into_backend_conflict())
+        trace_span!("write_conflict").in_scope(|| {
+            self.backend
+                .write_conflict(path, &contents.clone().into_backend_conflict())
+        })
     }
 
     pub fn tree_builder(self: &Arc<Self>, base_tree_id: TreeId) -> TreeBuilder {
         TreeBuilder::new(self.clone(), base_tree_id)
     }
 }
+
+/// Reads the file contents within the span, so the time spent in reading is
+/// accounted in addition to the time spent in opening the file.
+struct TracedReader {
+    span: tracing::Span,
+    reader: Box<dyn Read>,
+}
+
+impl Read for TracedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let _entered = self.span.enter();
+        self.reader.read(buf)
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of Git-style trailers, e.g. `Signed-off-by: ...`, at the end of
+//! commit descriptions.
+
+/// A `key: value` line in the trailer block of a description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trailer {
+    /// The trailer key, e.g. `Signed-off-by`.
+    pub key: String,
+    /// The value with continuation lines joined by spaces.
+    pub value: String,
+}
+
+/// Parses the trailers in the last paragraph of the description.
+///
+/// The paragraph is considered a trailer block only if every line in it is a
+/// trailer or a continuation of one, i.e. starts with whitespace. The first
+/// paragraph (the subject) is never a trailer block.
+pub fn parse_description_trailers(description: &str) -> Vec<Trailer> {
+    let paragraphs = split_paragraphs(description);
+    let [_, .., last] = paragraphs.as_slice() else {
+        return vec![];
+    };
+    let mut trailers: Vec<Trailer> = vec![];
+    for line in last {
+        if line.starts_with(char::is_whitespace) {
+            let Some(trailer) = trailers.last_mut() else {
+                return vec![];
+            };
+            trailer.value.push(' ');
+            trailer.value.push_str(line.trim());
+        } else if let Some(trailer) = parse_trailer_line(line) {
+            trailers.push(trailer);
+        } else {
+            return vec![];
+        }
+    }
+    trailers
+}
+
+fn split_paragraphs(text: &str) -> Vec<Vec<&str>> {
+    let mut paragraphs = vec![];
+    let mut current = vec![];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line.trim_end());
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs
+}
+
+fn parse_trailer_line(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim_end();
+    let is_valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    is_valid_key.then(|| Trailer {
+        key: key.to_owned(),
+        value: value.trim().to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trailer(key: &str, value: &str) -> Trailer {
+        Trailer {
+            key: key.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_parse_description_trailers() {
+        assert_eq!(parse_description_trailers(""), vec![]);
+        // The subject is never a trailer
+        assert_eq!(parse_description_trailers("fix: typo\n"), vec![]);
+        assert_eq!(
+            parse_description_trailers(
+                "subject\n\nbody: not a trailer\n\nSigned-off-by: Alice <a@example.com>\n\
+                 Change-Id: I1234\n"
+            ),
+            vec![
+                trailer("Signed-off-by", "Alice <a@example.com>"),
+                trailer("Change-Id", "I1234"),
+            ]
+        );
+        // Continuation lines
+        assert_eq!(
+            parse_description_trailers("subject\n\nReviewed-by: Bob\n  <b@example.com>\n"),
+            vec![trailer("Reviewed-by", "Bob <b@example.com>")]
+        );
+        // A line that isn't a trailer makes the whole paragraph body text
+        assert_eq!(
+            parse_description_trailers("subject\n\nChange-Id: I1234\nmore text\n"),
+            vec![]
+        );
+        assert_eq!(
+            parse_description_trailers("subject\n\nsee http://example.com: here\n"),
+            vec![]
+        );
+    }
+}
@@ -19,7 +19,7 @@ use std::fmt;
 
 use itertools::Itertools;
 
-use crate::backend::CommitId;
+use crate::backend::{ChangeId, CommitId};
 use crate::index::Index;
 use crate::op_store;
 use crate::op_store::{BranchTarget, RefTarget, RefTargetOptionExt as _, WorkspaceId};
@@ -273,6 +273,24 @@ impl View {
         }
     }
 
+    pub fn notes(&self) -> &BTreeMap<ChangeId, String> {
+        &self.data.notes
+    }
+
+    pub fn get_note(&self, change_id: &ChangeId) -> Option<&str> {
+        self.data.notes.get(change_id).map(String::as_str)
+    }
+
+    /// Sets the note attached to the change. If the note is empty, it will be
+    /// removed.
+    pub fn set_note(&mut self, change_id: &ChangeId, note: String) {
+        if note.is_empty() {
+            self.data.notes.remove(change_id);
+        } else {
+            self.data.notes.insert(change_id.clone(), note);
+        }
+    }
+
     pub fn get_git_ref(&self, name: &str) -> &RefTarget {
         self.data.git_refs.get(name).flatten()
     }
@@ -24,8 +24,9 @@ use std::ops::Bound;
 #[cfg(unix)]
 use std::os::unix::fs::symlink;
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{MetadataExt as _, PermissionsExt};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
 use std::time::UNIX_EPOCH;
@@ -49,7 +50,7 @@ use crate::fsmonitor::FsmonitorKind;
 use crate::gitignore::GitIgnoreFile;
 use crate::lock::FileLock;
 use crate::matchers::{
-    DifferenceMatcher, EverythingMatcher, IntersectionMatcher, Matcher, PrefixMatcher,
+    DifferenceMatcher, EverythingMatcher, IntersectionMatcher, Matcher, PrefixMatcher, Visit,
 };
 use crate::merge::{Merge, MergeBuilder};
 use crate::merged_tree::{MergedTree, MergedTreeBuilder};
@@ -76,6 +77,9 @@ pub struct FileState {
     pub file_type: FileType,
     pub mtime: MillisSinceEpoch,
     pub size: u64,
+    /// The inode number, which detects files replaced by another file of the
+    /// same size and mtime. Always 0 on platforms without inodes.
+    pub inode: u64,
     /* TODO: What else do we need here? Git stores a lot of fields.
      * TODO: Could possibly handle case-insensitive file systems keeping an
      *       Option<PathBuf> with the actual path here. */
@@ -93,6 +97,7 @@ impl FileState {
             file_type: FileType::Normal { executable },
             mtime: mtime_from_metadata(metadata),
             size,
+            inode: inode_from_metadata(metadata),
         }
     }
 
@@ -104,14 +109,27 @@ impl FileState {
             file_type: FileType::Symlink,
             mtime: mtime_from_metadata(metadata),
             size: metadata.len(),
+            inode: inode_from_metadata(metadata),
         }
     }
 
+    /// Whether the file described by `new_state` is unchanged from `self`, so
+    /// its contents don't need to be hashed. An inode of 0 is unknown and
+    /// matches any inode, since states written before inodes were recorded
+    /// would otherwise make every file look modified.
+    fn is_clean(&self, new_state: &FileState) -> bool {
+        self.file_type == new_state.file_type
+            && self.mtime == new_state.mtime
+            && self.size == new_state.size
+            && (self.inode == 0 || self.inode == new_state.inode)
+    }
+
     fn for_gitsubmodule() -> Self {
         FileState {
             file_type: FileType::GitSubmodule,
             mtime: MillisSinceEpoch(0),
             size: 0,
+            inode: 0,
         }
     }
 }
@@ -130,6 +148,9 @@ pub struct TreeState {
     /// the repo is configured to use the Watchman filesystem monitor and
     /// Watchman has been queried at least once.
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
+    /// Whether the working copy is on a case-insensitive file system. Probed on
+    /// first checkout.
+    case_insensitive: once_cell::sync::OnceCell<bool>,
 }
 
 fn file_state_from_proto(proto: crate::protos::working_copy::FileState) -> FileState {
@@ -152,6 +173,7 @@ fn file_state_from_proto(proto: crate::protos::working_copy::FileState) -> FileS
         file_type,
         mtime: MillisSinceEpoch(proto.mtime_millis_since_epoch),
         size: proto.size,
+        inode: proto.inode,
     }
 }
 
@@ -170,6 +192,7 @@ fn file_state_to_proto(file_state: &FileState) -> crate::protos::working_copy::F
     proto.file_type = file_type as i32;
     proto.mtime_millis_since_epoch = file_state.mtime.0;
     proto.size = file_state.size;
+    proto.inode = file_state.inode;
     proto
 }
 
@@ -250,6 +273,16 @@ fn mtime_from_metadata(metadata: &Metadata) -> MillisSinceEpoch {
     )
 }
 
+#[cfg(unix)]
+fn inode_from_metadata(metadata: &Metadata) -> u64 {
+    metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn inode_from_metadata(_metadata: &Metadata) -> u64 {
+    0
+}
+
 fn file_state(metadata: &Metadata) -> Option<FileState> {
     let metadata_file_type = metadata.file_type();
     let file_type = if metadata_file_type.is_dir() {
@@ -271,14 +304,108 @@ fn file_state(metadata: &Metadata) -> Option<FileState> {
     file_type.map(|file_type| {
         let mtime = mtime_from_metadata(metadata);
         let size = metadata.len();
+        let inode = inode_from_metadata(metadata);
         FileState {
             file_type,
             mtime,
             size,
+            inode,
         }
     })
 }
 
+/// How conflicted files are written to the working copy.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConflictMaterialization {
+    /// Write the conflict to the file with conflict markers.
+    #[default]
+    Markers,
+    /// Leave the file absent and write the base and the two sides of a 2-sided
+    /// file conflict to `<file>.jjconflict-base`, `<file>.jjconflict-left`,
+    /// and `<file>.jjconflict-right`. The conflict is resolved by writing the
+    /// file itself. Other conflicts are written with conflict markers.
+    SiblingFiles,
+}
+
+impl FromStr for ConflictMaterialization {
+    type Err = config::ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "markers" => Ok(Self::Markers),
+            "sibling-files" => Ok(Self::SiblingFiles),
+            other => Err(config::ConfigError::Message(format!(
+                "unknown conflict materialization: {other}"
+            ))),
+        }
+    }
+}
+
+/// How symlinks are written to the working copy.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SymlinkMaterialization {
+    /// Create a symlink. On Windows, if symlinks cannot be created (e.g.
+    /// because developer mode is disabled), fall back to a placeholder file.
+    #[default]
+    Native,
+    /// Write a regular file containing the symlink target. Changes to the file
+    /// are recorded as changes to the target, so the path remains a symlink in
+    /// the tree.
+    PlaceholderFiles,
+}
+
+impl FromStr for SymlinkMaterialization {
+    type Err = config::ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "native" => Ok(Self::Native),
+            "placeholder-files" => Ok(Self::PlaceholderFiles),
+            other => Err(config::ConfigError::Message(format!(
+                "unknown symlink materialization: {other}"
+            ))),
+        }
+    }
+}
+
+const CONFLICT_SIBLING_SUFFIXES: [&str; 3] =
+    [".jjconflict-base", ".jjconflict-left", ".jjconflict-right"];
+
+fn conflict_sibling_path(disk_path: &Path, suffix: &str) -> PathBuf {
+    let mut sibling_path = disk_path.as_os_str().to_owned();
+    sibling_path.push(suffix);
+    PathBuf::from(sibling_path)
+}
+
+fn has_conflict_siblings(disk_path: &Path) -> bool {
+    CONFLICT_SIBLING_SUFFIXES.iter().any(|suffix| {
+        conflict_sibling_path(disk_path, suffix)
+            .symlink_metadata()
+            .is_ok()
+    })
+}
+
+fn remove_conflict_siblings(disk_path: &Path) {
+    for suffix in CONFLICT_SIBLING_SUFFIXES {
+        fs::remove_file(conflict_sibling_path(disk_path, suffix)).ok();
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CheckoutOptions {
+    pub conflict_materialization: ConflictMaterialization,
+    pub symlink_materialization: SymlinkMaterialization,
+}
+
+impl CheckoutOptions {
+    pub fn empty_for_test() -> Self {
+        CheckoutOptions {
+            conflict_materialization: ConflictMaterialization::Markers,
+            symlink_materialization: SymlinkMaterialization::Native,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct CheckoutStats {
     pub updated_files: u32,
@@ -312,6 +439,68 @@ pub enum SnapshotError {
     },
 }
 
+/// Returns two paths in `new_tree`, or their parent directories, that differ
+/// only in case. At least one of them is added by the diff from `old_tree`
+/// within `diff_matcher`; the added path is returned first. Only the siblings
+/// of the added paths are checked, so the cost depends on the size of the
+/// change rather than on the size of the tree. Siblings count only if they're
+/// in the working copy according to `wc_matcher`.
+///
+/// Names are compared after `str::to_lowercase()`. That's close to, but not
+/// exactly, the case folding of case-insensitive file systems (APFS and NTFS
+/// use their own tables), so some collisions may go undetected.
+fn find_case_collision(
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+    diff_matcher: &dyn Matcher,
+    wc_matcher: &dyn Matcher,
+) -> Option<(RepoPath, RepoPath)> {
+    let mut checked_paths = HashSet::new();
+    for (path, before, after) in old_tree.diff(new_tree, diff_matcher) {
+        if before.is_present() || !after.is_present() {
+            continue;
+        }
+        let mut dir = RepoPath::root();
+        let mut dir_tree = new_tree.clone();
+        for component in path.components() {
+            let child = dir.join(component);
+            if checked_paths.insert(child.clone()) {
+                let folded = component.as_str().to_lowercase();
+                for name in dir_tree.names() {
+                    if name == component || name.as_str().to_lowercase() != folded {
+                        continue;
+                    }
+                    let sibling = dir.join(name);
+                    let in_working_copy = if dir_tree.sub_tree(name).is_some() {
+                        wc_matcher.visit(&sibling) != Visit::Nothing
+                    } else {
+                        wc_matcher.matches(&sibling)
+                    };
+                    if in_working_copy {
+                        return Some((child, sibling));
+                    }
+                }
+            }
+            match dir_tree.sub_tree(component) {
+                Some(sub_tree) => dir_tree = sub_tree,
+                None => break,
+            }
+            dir = child;
+        }
+    }
+    None
+}
+
+/// Checks if the file system the directory is on is case-insensitive by
+/// creating a temporary file and looking it up by its upper-cased name.
+fn is_case_insensitive_dir(dir: &Path) -> std::io::Result<bool> {
+    let probe = tempfile::Builder::new()
+        .prefix(".case-probe-")
+        .tempfile_in(dir)?;
+    let name = probe.path().file_name().unwrap().to_string_lossy();
+    Ok(dir.join(name.to_uppercase()).exists())
+}
+
 #[derive(Debug, Error)]
 pub enum CheckoutError {
     // The current working-copy commit was deleted, maybe by an overly aggressive GC that happened
@@ -330,6 +519,14 @@ pub enum CheckoutError {
         #[source]
         err: std::io::Error,
     },
+    // The file system is case-insensitive, so the two paths would be written to
+    // the same file.
+    #[error(
+        r#"Paths "{}" and "{}" differ only in case, but the file system is case-insensitive"#,
+        .path1.to_internal_file_string(),
+        .path2.to_internal_file_string()
+    )]
+    CaseCollision { path1: RepoPath, path2: RepoPath },
     #[error("Internal error: {0}")]
     InternalBackendError(#[from] BackendError),
     #[error(transparent)]
@@ -467,6 +664,7 @@ impl TreeState {
             sparse_patterns: vec![RepoPath::root()],
             own_mtime: MillisSinceEpoch(0),
             watchman_clock: None,
+            case_insensitive: once_cell::sync::OnceCell::new(),
         }
     }
 
@@ -618,6 +816,23 @@ impl TreeState {
         Ok(self.store.write_symlink(path, str_target)?)
     }
 
+    fn write_symlink_placeholder_to_store(
+        &self,
+        path: &RepoPath,
+        disk_path: &Path,
+    ) -> Result<SymlinkId, SnapshotError> {
+        let content = fs::read(disk_path).map_err(|err| SnapshotError::IoError {
+            message: format!("Failed to open file {}", disk_path.display()),
+            err,
+        })?;
+        let target =
+            String::from_utf8(content).map_err(|err| SnapshotError::InvalidUtf8SymlinkTarget {
+                path: disk_path.to_path_buf(),
+                target: PathBuf::from(String::from_utf8_lossy(err.as_bytes()).as_ref()),
+            })?;
+        Ok(self.store.write_symlink(path, &target)?)
+    }
+
     fn reset_watchman(&mut self) {
         self.watchman_clock.take();
     }
@@ -715,6 +930,13 @@ impl TreeState {
                 deleted_files.remove(&path);
             }
         });
+        // A conflict materialized as sibling files is absent on disk until it's
+        // resolved.
+        let current_tree = self.current_tree()?;
+        deleted_files.retain(|path| {
+            current_tree.path_value(path).is_resolved()
+                || !has_conflict_siblings(&path.to_fs_path(&self.working_copy_path))
+        });
         trace_span!("process deleted files").in_scope(|| {
             for file in &deleted_files {
                 is_dirty = true;
@@ -872,6 +1094,11 @@ impl TreeState {
                         // If it wasn't already tracked and it matches
                         // the ignored paths, then
                         // ignore it.
+                    } else if maybe_current_file_state.is_none()
+                        && self.is_conflict_sibling(&dir, name, current_tree)
+                    {
+                        // Sibling files of a conflict are written by us, and are
+                        // removed when the conflict is resolved.
                     } else {
                         let metadata = entry.metadata().map_err(|err| SnapshotError::IoError {
                             message: format!("Failed to stat file {}", entry.path().display()),
@@ -897,7 +1124,12 @@ impl TreeState {
                             if let Some(tree_value) = update {
                                 tree_entries_tx.send((path.clone(), tree_value)).ok();
                             }
-                            if Some(&new_file_state) != maybe_current_file_state {
+                            // Racily clean files are also recorded so the state file is
+                            // rewritten with a newer mtime, and they aren't hashed again by
+                            // the next snapshot.
+                            if Some(&new_file_state) != maybe_current_file_state
+                                || self.is_racy(&new_file_state)
+                            {
                                 file_states_tx.send((path, new_file_state)).ok();
                             }
                         }
@@ -909,6 +1141,21 @@ impl TreeState {
         Ok(())
     }
 
+    fn is_conflict_sibling(&self, dir: &RepoPath, name: &str, current_tree: &MergedTree) -> bool {
+        let Some(conflict_name) = CONFLICT_SIBLING_SUFFIXES
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+        else {
+            return false;
+        };
+        if conflict_name.is_empty() {
+            return false;
+        }
+        let conflict_path = dir.join(&RepoPathComponent::from(conflict_name));
+        self.file_states.contains_key(&conflict_path)
+            && !current_tree.path_value(&conflict_path).is_resolved()
+    }
+
     #[instrument(skip_all)]
     fn make_fsmonitor_matcher(
         &mut self,
@@ -962,6 +1209,13 @@ impl TreeState {
         })
     }
 
+    /// If the file's mtime was set at the same time as this state file's own
+    /// mtime, then we don't know if the file was modified before or after this
+    /// state file.
+    fn is_racy(&self, file_state: &FileState) -> bool {
+        file_state.mtime >= self.own_mtime
+    }
+
     fn get_updated_tree_value(
         &self,
         repo_path: &RepoPath,
@@ -976,9 +1230,7 @@ impl TreeState {
                 false
             }
             Some(current_file_state) => {
-                // If the file's mtime was set at the same time as this state file's own mtime,
-                // then we don't know if the file was modified before or after this state file.
-                current_file_state == new_file_state && current_file_state.mtime < self.own_mtime
+                current_file_state.is_clean(new_file_state) && !self.is_racy(current_file_state)
             }
         };
         if clean {
@@ -986,12 +1238,31 @@ impl TreeState {
         } else {
             let new_file_type = new_file_state.file_type.clone();
             let current_tree_values = current_tree.path_value(repo_path);
-            let new_tree_values = self.write_path_to_store(
-                repo_path,
-                &disk_path,
-                &current_tree_values,
-                new_file_type,
-            )?;
+            // A regular file recorded for a symlink in the tree is a placeholder
+            // written by a checkout that couldn't create the symlink.
+            let is_symlink_placeholder = matches!(new_file_type, FileType::Normal { .. })
+                && matches!(
+                    maybe_current_file_state,
+                    Some(FileState {
+                        file_type: FileType::Normal { .. },
+                        ..
+                    })
+                )
+                && matches!(
+                    current_tree_values.as_resolved(),
+                    Some(Some(TreeValue::Symlink(_)))
+                );
+            let new_tree_values = if is_symlink_placeholder {
+                let id = self.write_symlink_placeholder_to_store(repo_path, &disk_path)?;
+                Merge::normal(TreeValue::Symlink(id))
+            } else {
+                self.write_path_to_store(
+                    repo_path,
+                    &disk_path,
+                    &current_tree_values,
+                    new_file_type,
+                )?
+            };
             if new_tree_values != current_tree_values {
                 Ok(Some(new_tree_values))
             } else {
@@ -1046,6 +1317,7 @@ impl TreeState {
             )?;
             match new_file_ids.into_resolved() {
                 Ok(file_id) => {
+                    remove_conflict_siblings(disk_path);
                     #[cfg(windows)]
                     let executable = {
                         let () = executable; // use the variable
@@ -1102,18 +1374,34 @@ impl TreeState {
         Ok(FileState::for_file(executable, size, &metadata))
     }
 
-    #[cfg_attr(windows, allow(unused_variables))]
     fn write_symlink(
         &self,
         disk_path: &Path,
         path: &RepoPath,
         id: &SymlinkId,
+        materialization: SymlinkMaterialization,
     ) -> Result<FileState, CheckoutError> {
         create_parent_dirs(&self.working_copy_path, path)?;
         let target = self.store.read_symlink(path, id)?;
+        if materialization == SymlinkMaterialization::PlaceholderFiles {
+            return self.write_symlink_placeholder(disk_path, &target);
+        }
         #[cfg(windows)]
         {
-            println!("ignoring symlink at {:?}", path);
+            let target_path = PathBuf::from(&target);
+            let is_dir = disk_path
+                .parent()
+                .map_or(false, |dir| dir.join(&target_path).is_dir());
+            let result = if is_dir {
+                std::os::windows::fs::symlink_dir(&target_path, disk_path)
+            } else {
+                std::os::windows::fs::symlink_file(&target_path, disk_path)
+            };
+            if result.is_err() {
+                // Creating symlinks requires developer mode or elevated
+                // privileges.
+                return self.write_symlink_placeholder(disk_path, &target);
+            }
         }
         #[cfg(unix)]
         {
@@ -1133,6 +1421,33 @@ impl TreeState {
         Ok(FileState::for_symlink(&metadata))
     }
 
+    /// Writes a regular file containing the symlink target in place of the
+    /// symlink. The file is recorded as a regular file so snapshotting can tell
+    /// it apart from a symlink that was replaced by a file.
+    fn write_symlink_placeholder(
+        &self,
+        disk_path: &Path,
+        target: &str,
+    ) -> Result<FileState, CheckoutError> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true) // Don't overwrite un-ignored file. Don't follow symlink.
+            .open(disk_path)
+            .map_err(|err| CheckoutError::IoError {
+                message: format!("Failed to open file {} for writing", disk_path.display()),
+                err,
+            })?;
+        file.write_all(target.as_bytes())
+            .map_err(|err| CheckoutError::IoError {
+                message: format!("Failed to write file {}", disk_path.display()),
+                err,
+            })?;
+        let metadata = file
+            .metadata()
+            .map_err(|err| CheckoutError::for_stat_error(err, disk_path))?;
+        Ok(FileState::for_file(false, target.len() as u64, &metadata))
+    }
+
     fn write_conflict(
         &self,
         disk_path: &Path,
@@ -1165,6 +1480,39 @@ impl TreeState {
         Ok(FileState::for_file(false, size, &metadata))
     }
 
+    /// Writes the sides of a 2-sided file conflict to sibling files. Returns
+    /// `None` if the conflict can't be written that way.
+    fn write_conflict_siblings(
+        &self,
+        disk_path: &Path,
+        path: &RepoPath,
+        conflict: &Merge<Option<TreeValue>>,
+    ) -> Result<Option<FileState>, CheckoutError> {
+        let ([base], [left, right]) = (conflict.removes(), conflict.adds()) else {
+            return Ok(None);
+        };
+        let is_file = |value: &Option<TreeValue>| matches!(value, Some(TreeValue::File { .. }));
+        if !is_file(left) || !is_file(right) || !(base.is_none() || is_file(base)) {
+            return Ok(None);
+        }
+        for (suffix, value) in CONFLICT_SIBLING_SUFFIXES.iter().zip([base, left, right]) {
+            if let Some(TreeValue::File { id, executable }) = value {
+                let sibling_path = conflict_sibling_path(disk_path, suffix);
+                self.write_file(&sibling_path, path, id, *executable)?;
+            }
+        }
+        // The conflicted file itself doesn't exist on disk, so any file written
+        // there will be considered modified.
+        Ok(Some(FileState {
+            file_type: FileType::Normal {
+                executable: FileExecutableFlag::default(),
+            },
+            mtime: MillisSinceEpoch(0),
+            size: 0,
+            inode: 0,
+        }))
+    }
+
     #[cfg_attr(windows, allow(unused_variables))]
     fn set_executable(&self, disk_path: &Path, executable: bool) -> Result<(), CheckoutError> {
         #[cfg(unix)]
@@ -1176,14 +1524,25 @@ impl TreeState {
         Ok(())
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
                 source: Box::new(err),
             },
             other => CheckoutError::InternalBackendError(other),
         })?;
-        let stats = self.update(&old_tree, new_tree, self.sparse_matcher().as_ref(), Err)?;
+        let sparse_matcher = self.sparse_matcher();
+        self.check_case_collisions(
+            &old_tree,
+            new_tree,
+            sparse_matcher.as_ref(),
+            sparse_matcher.as_ref(),
+        )?;
+        let stats = self.update(&old_tree, new_tree, sparse_matcher.as_ref(), options, Err)?;
         self.tree_id = new_tree.id();
         Ok(stats)
     }
@@ -1191,6 +1550,7 @@ impl TreeState {
     pub fn set_sparse_patterns(
         &mut self,
         sparse_patterns: Vec<RepoPath>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1203,13 +1563,15 @@ impl TreeState {
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::null(self.store.clone(), RepoPath::root()));
+        self.check_case_collisions(&empty_tree, &tree, &added_matcher, &new_matcher)?;
         let added_stats = self.update(
             &empty_tree,
             &tree,
             &added_matcher,
+            options,
             suppress_file_exists_error, // Keep un-ignored file and mark it as modified
         )?;
-        let removed_stats = self.update(&tree, &empty_tree, &removed_matcher, Err)?;
+        let removed_stats = self.update(&tree, &empty_tree, &removed_matcher, options, Err)?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
         assert_eq!(added_stats.removed_files, 0);
@@ -1222,11 +1584,40 @@ impl TreeState {
         })
     }
 
+    /// Fails if the diff adds paths that differ only in case from other paths
+    /// in the working copy and the working copy is on a case-insensitive file
+    /// system, since one of the files would silently overwrite the other.
+    fn check_case_collisions(
+        &self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+        diff_matcher: &dyn Matcher,
+        wc_matcher: &dyn Matcher,
+    ) -> Result<(), CheckoutError> {
+        let case_insensitive = self.case_insensitive.get_or_try_init(|| {
+            is_case_insensitive_dir(&self.working_copy_path).map_err(|err| CheckoutError::IoError {
+                message: format!(
+                    "Failed to check case sensitivity of {}",
+                    self.working_copy_path.display()
+                ),
+                err,
+            })
+        })?;
+        if !case_insensitive {
+            return Ok(());
+        }
+        match find_case_collision(old_tree, new_tree, diff_matcher, wc_matcher) {
+            Some((path1, path2)) => Err(CheckoutError::CaseCollision { path1, path2 }),
+            None => Ok(()),
+        }
+    }
+
     fn update(
         &mut self,
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
+        options: &CheckoutOptions,
         mut handle_error: impl FnMut(CheckoutError) -> Result<(), CheckoutError>,
     ) -> Result<CheckoutStats, CheckoutError> {
         let mut apply_diff = |path: RepoPath,
@@ -1238,6 +1629,9 @@ impl TreeState {
             if before.is_present() {
                 fs::remove_file(&disk_path).ok();
             }
+            if !before.is_resolved() {
+                remove_conflict_siblings(&disk_path);
+            }
             // TODO: Check that the file has not changed before overwriting/removing it.
             match after.into_resolved() {
                 Ok(None) => {
@@ -1255,7 +1649,12 @@ impl TreeState {
                         TreeValue::File { id, executable } => {
                             self.write_file(&disk_path, &path, &id, executable)?
                         }
-                        TreeValue::Symlink(id) => self.write_symlink(&disk_path, &path, &id)?,
+                        TreeValue::Symlink(id) => self.write_symlink(
+                            &disk_path,
+                            &path,
+                            &id,
+                            options.symlink_materialization,
+                        )?,
                         TreeValue::Conflict(_) => {
                             panic!("unexpected conflict entry in diff at {path:?}");
                         }
@@ -1270,7 +1669,16 @@ impl TreeState {
                     self.file_states.insert(path, file_state);
                 }
                 Err(after_conflict) => {
-                    let file_state = self.write_conflict(&disk_path, &path, &after_conflict)?;
+                    let sibling_file_state = match options.conflict_materialization {
+                        ConflictMaterialization::Markers => None,
+                        ConflictMaterialization::SiblingFiles => {
+                            self.write_conflict_siblings(&disk_path, &path, &after_conflict)?
+                        }
+                    };
+                    let file_state = match sibling_file_state {
+                        Some(file_state) => file_state,
+                        None => self.write_conflict(&disk_path, &path, &after_conflict)?,
+                    };
                     self.file_states.insert(path, file_state);
                 }
             }
@@ -1336,6 +1744,7 @@ impl TreeState {
                     file_type,
                     mtime: MillisSinceEpoch(0),
                     size: 0,
+                    inode: 0,
                 };
                 self.file_states.insert(path.clone(), file_state);
             }
@@ -1516,6 +1925,7 @@ impl WorkingCopy {
         operation_id: OperationId,
         old_tree_id: Option<&MergedTreeId>,
         new_tree: &MergedTree,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let mut locked_wc = self.start_mutation()?;
         // Check if the current working-copy commit has changed on disk compared to what
@@ -1527,7 +1937,7 @@ impl WorkingCopy {
                 return Err(CheckoutError::ConcurrentCheckout);
             }
         }
-        let stats = locked_wc.check_out(new_tree)?;
+        let stats = locked_wc.check_out(new_tree, options)?;
         locked_wc.finish(operation_id)?;
         Ok(stats)
     }
@@ -1578,10 +1988,14 @@ impl LockedWorkingCopy<'_> {
         Ok(tree_state.current_tree_id().clone())
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
-        let stats = self.wc.tree_state_mut()?.check_out(new_tree)?;
+        let stats = self.wc.tree_state_mut()?.check_out(new_tree, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -1599,13 +2013,14 @@ impl LockedWorkingCopy<'_> {
     pub fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPath>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
         let stats = self
             .wc
             .tree_state_mut()?
-            .set_sparse_patterns(new_sparse_patterns)?;
+            .set_sparse_patterns(new_sparse_patterns, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -200,6 +200,7 @@ impl Workspace {
                 init_working_copy(user_settings, &repo, workspace_root, &jj_dir, workspace_id)?;
             let repo_loader = repo.loader();
             let workspace = Workspace::new(workspace_root, working_copy, repo_loader)?;
+            workspace.record_workspace_root()?;
             Ok((workspace, repo))
         })()
         .map_err(|err| {
@@ -248,6 +249,7 @@ impl Workspace {
         let (working_copy, repo) =
             init_working_copy(user_settings, repo, workspace_root, &jj_dir, workspace_id)?;
         let workspace = Workspace::new(workspace_root, working_copy, repo.loader())?;
+        workspace.record_workspace_root()?;
         Ok((workspace, repo))
     }
 
@@ -284,6 +286,48 @@ impl Workspace {
     pub fn working_copy_mut(&mut self) -> &mut WorkingCopy {
         &mut self.working_copy
     }
+
+    /// Records the root of this workspace in the repo so commands run in other
+    /// workspaces can find its working copy. Does nothing if the recorded path
+    /// is already up to date.
+    pub fn record_workspace_root(&self) -> Result<(), PathError> {
+        let record_path = workspace_root_record_path(self.repo_path(), self.workspace_id());
+        let root_bytes = self.workspace_root.to_string_lossy();
+        if fs::read(&record_path).ok().as_deref() == Some(root_bytes.as_bytes()) {
+            return Ok(());
+        }
+        let dir = record_path.parent().unwrap();
+        fs::create_dir_all(dir).context(dir)?;
+        fs::write(&record_path, root_bytes.as_bytes()).context(&record_path)
+    }
+}
+
+const WORKSPACE_ROOTS_DIR: &str = "workspace_roots";
+
+fn workspace_root_record_path(repo_path: &Path, workspace_id: &WorkspaceId) -> PathBuf {
+    // Workspace names may contain characters that aren't valid in file names
+    repo_path
+        .join(WORKSPACE_ROOTS_DIR)
+        .join(hex::encode(workspace_id.as_str()))
+}
+
+/// Returns the root of the given workspace if it has been recorded in the repo
+/// at `repo_path`.
+pub fn get_workspace_root(repo_path: &Path, workspace_id: &WorkspaceId) -> Option<PathBuf> {
+    let content = fs::read_to_string(workspace_root_record_path(repo_path, workspace_id)).ok()?;
+    Some(PathBuf::from(content))
+}
+
+/// Removes the recorded root of the given workspace, if any.
+pub fn forget_workspace_root(
+    repo_path: &Path,
+    workspace_id: &WorkspaceId,
+) -> Result<(), PathError> {
+    let record_path = workspace_root_record_path(repo_path, workspace_id);
+    match fs::remove_file(&record_path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result.context(&record_path),
+    }
 }
 
 #[derive(Clone, Debug)]
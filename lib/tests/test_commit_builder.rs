@@ -135,6 +135,8 @@ fn test_rewrite(backend: TestRepoBackend) {
         .unwrap()
         .set_override("user.email", "rewrite.user@example.com")
         .unwrap()
+        .set_override("debug.commit-timestamp", "2001-02-03T04:05:06+07:00")
+        .unwrap()
         .build()
         .unwrap();
     let rewrite_settings = UserSettings::from_config(config);
@@ -162,6 +164,15 @@ fn test_rewrite(backend: TestRepoBackend) {
         rewritten_commit.committer().email,
         rewrite_settings.user_email()
     );
+    // The author timestamp is preserved, but the committer timestamp is updated
+    assert_eq!(
+        rewritten_commit.author().timestamp,
+        initial_commit.author().timestamp
+    );
+    assert_eq!(
+        rewritten_commit.committer().timestamp,
+        rewrite_settings.signature().timestamp
+    );
     assert_eq!(
         store
             .root_commit()
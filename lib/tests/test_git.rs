@@ -33,7 +33,7 @@ use jj_lib::git::{
 use jj_lib::git_backend::GitBackend;
 use jj_lib::op_store::{BranchTarget, RefTarget};
 use jj_lib::repo::{MutableRepo, ReadonlyRepo, Repo};
-use jj_lib::settings::{GitSettings, UserSettings};
+use jj_lib::settings::{AutoLocalBranch, GitSettings, UserSettings};
 use jj_lib::view::RefName;
 use maplit::{btreemap, hashset};
 use tempfile::TempDir;
@@ -1259,7 +1259,7 @@ fn test_import_export_no_auto_local_branch() {
     // branch.
     let test_data = GitRepoData::create();
     let git_settings = GitSettings {
-        auto_local_branch: false,
+        auto_local_branch: AutoLocalBranch::None,
     };
     let git_repo = test_data.git_repo;
     let git_commit = empty_git_commit(&git_repo, "refs/remotes/origin/main", &[]);
@@ -1291,6 +1291,37 @@ fn test_import_export_no_auto_local_branch() {
     assert_eq!(mut_repo.get_git_ref("refs/heads/main"), RefTarget::absent());
 }
 
+#[test]
+fn test_import_auto_local_branch_patterns() {
+    // Only the remote branches matching the patterns get local branches.
+    let test_data = GitRepoData::create();
+    let git_settings = GitSettings {
+        auto_local_branch: AutoLocalBranch::Patterns(vec![glob::Pattern::new("ma*").unwrap()]),
+    };
+    let git_repo = test_data.git_repo;
+    let commit_main = empty_git_commit(&git_repo, "refs/remotes/origin/main", &[]);
+    let commit_feature = empty_git_commit(&git_repo, "refs/remotes/origin/feature", &[]);
+
+    let mut tx = test_data
+        .repo
+        .start_transaction(&test_data.settings, "test");
+    let mut_repo = tx.mut_repo();
+    git::import_refs(mut_repo, &git_repo, &git_settings).unwrap();
+
+    assert_eq!(
+        mut_repo.view().get_local_branch("main"),
+        &RefTarget::normal(jj_id(&commit_main))
+    );
+    assert_eq!(
+        mut_repo.view().get_local_branch("feature"),
+        RefTarget::absent_ref()
+    );
+    assert_eq!(
+        mut_repo.view().get_remote_branch("feature", "origin"),
+        &RefTarget::normal(jj_id(&commit_feature))
+    );
+}
+
 #[test]
 fn test_export_conflicts() {
     // We skip export of conflicted branches
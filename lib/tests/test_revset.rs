@@ -908,6 +908,90 @@ fn test_evaluate_expression_heads() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_fork_point() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let root_commit = repo.store().root_commit();
+    let mut tx = repo.start_transaction(&settings, "test");
+    let mut_repo = tx.mut_repo();
+    let mut graph_builder = CommitGraphBuilder::new(&settings, mut_repo);
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit3 = graph_builder.commit_with_parents(&[&commit2]);
+    let commit4 = graph_builder.commit_with_parents(&[&commit1]);
+    let commit5 = graph_builder.commit_with_parents(&[&commit3, &commit4]);
+    let commit6 = graph_builder.commit_with_parents(&[&commit4, &commit3]);
+
+    // Fork point of an empty set is an empty set
+    assert_eq!(resolve_commit_ids(mut_repo, "fork_point(none())"), vec![]);
+
+    // Fork point of a single commit is that commit
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("fork_point({})", commit3.id().hex())),
+        vec![commit3.id().clone()]
+    );
+
+    // Fork point of an ancestor and a descendant is the ancestor
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {})",
+                commit2.id().hex(),
+                commit3.id().hex()
+            )
+        ),
+        vec![commit2.id().clone()]
+    );
+
+    // Fork point of siblings is their common ancestor
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {})",
+                commit3.id().hex(),
+                commit4.id().hex()
+            )
+        ),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {} | {})",
+                commit2.id().hex(),
+                commit3.id().hex(),
+                commit4.id().hex()
+            )
+        ),
+        vec![commit1.id().clone()]
+    );
+
+    // Criss-cross merges can have more than one fork point
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "fork_point({} | {})",
+                commit5.id().hex(),
+                commit6.id().hex()
+            )
+        ),
+        vec![commit4.id().clone(), commit3.id().clone()]
+    );
+
+    // Fork point of all commits is the root
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "fork_point(all())"),
+        vec![root_commit.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_roots() {
     let settings = testutils::user_settings();
@@ -963,6 +1047,53 @@ fn test_evaluate_expression_roots() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_predecessors() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings, "test");
+    let mut_repo = tx.mut_repo();
+    let mut graph_builder = CommitGraphBuilder::new(&settings, mut_repo);
+    let commit1 = graph_builder.initial_commit();
+    let commit2 = graph_builder.initial_commit();
+    let commit1b = mut_repo
+        .rewrite_commit(&settings, &commit1)
+        .set_description("rewritten")
+        .write()
+        .unwrap();
+    let commit1c = mut_repo
+        .rewrite_commit(&settings, &commit1b)
+        .set_description("rewritten again")
+        .write()
+        .unwrap();
+
+    // The root commit and new commits have no predecessors
+    assert_eq!(resolve_commit_ids(mut_repo, "predecessors(root())"), vec![]);
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("predecessors({})", commit2.id().hex())),
+        vec![]
+    );
+
+    // Only the immediate predecessors are included, even if they're hidden
+    assert_eq!(
+        resolve_commit_ids(mut_repo, &format!("predecessors({})", commit1c.id().hex())),
+        vec![commit1b.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!(
+                "predecessors({} | {})",
+                commit1b.id().hex(),
+                commit1c.id().hex()
+            )
+        ),
+        vec![commit1b.id().clone(), commit1.id().clone()]
+    );
+}
+
 #[test]
 fn test_evaluate_expression_parents() {
     let settings = testutils::user_settings();
@@ -1756,6 +1887,10 @@ fn test_evaluate_expression_branches() {
         resolve_commit_ids(mut_repo, "branches(exact:branch1)"),
         vec![commit1.id().clone()]
     );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, r#"branches(glob:"bra*1")"#),
+        vec![commit1.id().clone()]
+    );
     // Can silently resolve to an empty set if there's no matches
     assert_eq!(resolve_commit_ids(mut_repo, "branches(branch3)"), vec![]);
     assert_eq!(
@@ -2136,6 +2271,19 @@ fn test_evaluate_expression_author() {
         resolve_commit_ids(mut_repo, "author(\"name3\")"),
         vec![commit3.id().clone()]
     );
+    // Can match by string pattern
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author(exact:\"name\")"),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author(exact:\"email1\")"),
+        vec![commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "author(glob:\"email[12]\")"),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
     // Searches only among candidates if specified
     assert_eq!(
         resolve_commit_ids(mut_repo, "visible_heads() & author(\"name2\")"),
@@ -2277,6 +2425,19 @@ fn test_evaluate_expression_committer() {
         resolve_commit_ids(mut_repo, "committer(\"name3\")"),
         vec![commit3.id().clone()]
     );
+    // Can match by string pattern
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "committer(exact:\"name\")"),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "committer(exact:\"email3\")"),
+        vec![commit3.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "committer(glob:\"name[23]\")"),
+        vec![commit3.id().clone(), commit2.id().clone()]
+    );
     // Searches only among candidates if specified
     assert_eq!(
         resolve_commit_ids(mut_repo, "visible_heads() & committer(\"name2\")"),
@@ -2647,6 +2808,53 @@ fn test_evaluate_expression_file() {
         vec![commit2.id().clone()]
     );
 
+    // file(glob:..) revset, in which "*" doesn't match "/"
+    assert_eq!(
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            r#"file(glob:"added_*_removed")"#,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        ),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            r#"file(glob:"*_clean")"#,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        ),
+        vec![commit2.id().clone(), commit1.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            r#"file(glob:"*/*_clean")"#,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        ),
+        vec![]
+    );
+    // Paths and globs can be mixed
+    assert_eq!(
+        resolve_commit_ids_in_workspace(
+            mut_repo,
+            r#"file(added_clean_clean, glob:"*_removed")"#,
+            &test_workspace.workspace,
+            Some(test_workspace.workspace.workspace_root()),
+        ),
+        vec![
+            commit3.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone()
+        ]
+    );
+
     // empty() revset, which is identical to ~file(".")
     assert_eq!(
         resolve_commit_ids(mut_repo, &format!("{}: & empty()", commit1.id().hex())),
@@ -2783,18 +2991,17 @@ fn test_change_id_index() {
     let commit_4 = commit_with_change_id("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
     let commit_5 = commit_with_change_id("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
 
-    let revset = revset_for_commits(
-        tx.repo(),
-        &[
-            &root_commit,
-            &commit_1,
-            &commit_2,
-            &commit_3,
-            &commit_4,
-            &commit_5,
-        ],
+    let index = tx.repo().index();
+    let change_id_index = index.change_id_index(
+        &mut [
+            commit_1.id(),
+            commit_2.id(),
+            commit_3.id(),
+            commit_4.id(),
+            commit_5.id(),
+        ]
+        .into_iter(),
     );
-    let change_id_index = revset.change_id_index();
     let prefix_len =
         |commit: &Commit| change_id_index.shortest_unique_prefix_len(commit.change_id());
     assert_eq!(prefix_len(&root_commit), 1);
@@ -2837,10 +3044,9 @@ fn test_change_id_index() {
     // No match
     assert_eq!(resolve_prefix("ba"), PrefixResolution::NoMatch);
 
-    // Test with a revset containing only some of the commits. We should get shorter
-    // prefixes and be able to resolve shorter prefixes.
-    let revset = revset_for_commits(tx.repo(), &[&commit_2, &commit_3]);
-    let change_id_index = revset.change_id_index();
+    // Test with only some of the commits as heads. We should get shorter prefixes
+    // and be able to resolve shorter prefixes. Ancestors of the heads are included.
+    let change_id_index = index.change_id_index(&mut [commit_2.id(), commit_3.id()].into_iter());
     let prefix_len =
         |commit: &Commit| change_id_index.shortest_unique_prefix_len(commit.change_id());
     assert_eq!(prefix_len(&commit_1), 6);
@@ -2848,7 +3054,10 @@ fn test_change_id_index() {
     assert_eq!(prefix_len(&commit_3), 2);
     let resolve_prefix =
         |prefix: &str| change_id_index.resolve_prefix(&HexPrefix::new(prefix).unwrap());
-    assert_eq!(resolve_prefix("0"), PrefixResolution::NoMatch);
+    assert_eq!(
+        resolve_prefix("0"),
+        PrefixResolution::SingleMatch(vec![root_commit.id().clone()])
+    );
     assert_eq!(
         resolve_prefix("aa"),
         PrefixResolution::SingleMatch(vec![commit_2.id().clone()])
@@ -357,6 +357,46 @@ fn test_merge_views_tags() {
     );
 }
 
+#[test]
+fn test_merge_views_notes() {
+    // Tests merging of notes (by performing concurrent operations).
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings, "test");
+    let mut_repo = tx.mut_repo();
+    let commit1 = write_random_commit(mut_repo, &settings);
+    let commit2 = write_random_commit(mut_repo, &settings);
+    let commit3 = write_random_commit(mut_repo, &settings);
+    let commit4 = write_random_commit(mut_repo, &settings);
+    mut_repo.set_note(commit1.change_id(), "note1".to_string());
+    mut_repo.set_note(commit2.change_id(), "note2".to_string());
+    mut_repo.set_note(commit3.change_id(), "note3".to_string());
+    let repo = tx.commit();
+
+    let mut tx1 = repo.start_transaction(&settings, "test");
+    tx1.mut_repo()
+        .set_note(commit1.change_id(), "note1 tx1".to_string());
+    tx1.mut_repo().set_note(commit2.change_id(), "".to_string());
+
+    let mut tx2 = repo.start_transaction(&settings, "test");
+    tx2.mut_repo()
+        .set_note(commit3.change_id(), "note3 tx2".to_string());
+    tx2.mut_repo()
+        .set_note(commit4.change_id(), "note4 tx2".to_string());
+
+    let repo = commit_transactions(&settings, vec![tx1, tx2]);
+    assert_eq!(
+        repo.view().notes(),
+        &btreemap! {
+            commit1.change_id().clone() => "note1 tx1".to_string(),
+            commit3.change_id().clone() => "note3 tx2".to_string(),
+            commit4.change_id().clone() => "note4 tx2".to_string(),
+        }
+    );
+}
+
 #[test]
 fn test_merge_views_git_refs() {
     // Tests merging of git refs (by performing concurrent operations). See
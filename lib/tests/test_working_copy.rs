@@ -24,6 +24,7 @@ use std::os::unix::fs::PermissionsExt;
 use std::os::unix::net::UnixListener;
 use std::sync::Arc;
 
+use assert_matches::assert_matches;
 use itertools::Itertools;
 use jj_lib::backend::{MergedTreeId, ObjectId, TreeId, TreeValue};
 use jj_lib::fsmonitor::FsmonitorKind;
@@ -33,7 +34,11 @@ use jj_lib::op_store::{OperationId, WorkspaceId};
 use jj_lib::repo::{ReadonlyRepo, Repo};
 use jj_lib::repo_path::{RepoPath, RepoPathComponent, RepoPathJoin};
 use jj_lib::settings::UserSettings;
-use jj_lib::working_copy::{LockedWorkingCopy, SnapshotError, SnapshotOptions, WorkingCopy};
+use jj_lib::working_copy::{
+    CheckoutError, CheckoutOptions, ConflictMaterialization, LockedWorkingCopy, SnapshotError,
+    SnapshotOptions, SymlinkMaterialization, WorkingCopy,
+};
+use prost::Message as _;
 use test_case::test_case;
 use testutils::{create_tree, write_random_commit, TestRepoBackend, TestWorkspace};
 
@@ -189,10 +194,20 @@ fn test_checkout_file_transitions(backend: TestRepoBackend) {
     let right_tree = store.get_root_tree(&right_tree_id).unwrap();
 
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &left_tree)
-        .unwrap();
-    wc.check_out(repo.op_id().clone(), None, &right_tree)
-        .unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &left_tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &right_tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Check that the working copy is clean.
     let new_tree = test_workspace.snapshot().unwrap();
@@ -273,9 +288,173 @@ fn test_conflict_subdirectory() {
     let merged_tree = tree1.merge(&empty_tree, &tree2).unwrap();
     let repo = &test_workspace.repo;
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree1).unwrap();
-    wc.check_out(repo.op_id().clone(), None, &merged_tree)
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &merged_tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_conflict_sibling_files() {
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    let path = RepoPath::from_internal_string("dir/file");
+    let other_path = RepoPath::from_internal_string("other");
+    let base_tree = create_tree(repo, &[(&path, "base\n")]);
+    let left_tree = create_tree(repo, &[(&path, "left\n")]);
+    let right_tree = create_tree(repo, &[(&path, "right\n"), (&other_path, "other\n")]);
+    let merged_tree = left_tree.merge(&base_tree, &right_tree).unwrap();
+    let options = CheckoutOptions {
+        conflict_materialization: ConflictMaterialization::SiblingFiles,
+        ..CheckoutOptions::default()
+    };
+    let wc = test_workspace.workspace.working_copy_mut();
+    wc.check_out(repo.op_id().clone(), None, &merged_tree, &options)
+        .unwrap();
+
+    // The conflicted file is absent, and the sides are written next to it
+    let disk_path = path.to_fs_path(&workspace_root);
+    let read_sibling = |suffix: &str| {
+        std::fs::read_to_string(workspace_root.join(format!("dir/file.jjconflict-{suffix}")))
+            .unwrap()
+    };
+    assert!(!disk_path.exists());
+    assert_eq!(read_sibling("base"), "base\n");
+    assert_eq!(read_sibling("left"), "left\n");
+    assert_eq!(read_sibling("right"), "right\n");
+
+    // Snapshotting neither resolves the conflict nor adds the sibling files
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), merged_tree.id());
+
+    // Writing the file resolves the conflict and removes the sibling files
+    std::fs::write(&disk_path, "resolved\n").unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(repo, &[(&path, "resolved\n"), (&other_path, "other\n")]);
+    assert_eq!(new_tree.id(), expected_tree.id());
+    assert!(!workspace_root.join("dir/file.jjconflict-left").exists());
+
+    // Checking out the conflict again and removing all sibling files deletes
+    // the file
+    let wc = test_workspace.workspace.working_copy_mut();
+    wc.check_out(repo.op_id().clone(), None, &merged_tree, &options)
+        .unwrap();
+    assert_eq!(read_sibling("left"), "left\n");
+    for suffix in ["base", "left", "right"] {
+        std::fs::remove_file(workspace_root.join(format!("dir/file.jjconflict-{suffix}"))).unwrap();
+    }
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(repo, &[(&other_path, "other\n")]);
+    assert_eq!(new_tree.id(), expected_tree.id());
+}
+
+#[test]
+fn test_symlink_placeholder_files() {
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo.clone();
+    let store = repo.store().clone();
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    let link_path = RepoPath::from_internal_string("link");
+    let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+    testutils::write_symlink(&mut tree_builder, &link_path, "target");
+    let tree_id = tree_builder.write_tree();
+    let tree = MergedTree::legacy(store.get_tree(&RepoPath::root(), &tree_id).unwrap());
+    let options = CheckoutOptions {
+        symlink_materialization: SymlinkMaterialization::PlaceholderFiles,
+        ..CheckoutOptions::default()
+    };
+    let wc = test_workspace.workspace.working_copy_mut();
+    wc.check_out(repo.op_id().clone(), None, &tree, &options)
         .unwrap();
+
+    // The symlink is written as a regular file containing the target
+    let disk_path = link_path.to_fs_path(&workspace_root);
+    assert!(disk_path.symlink_metadata().unwrap().is_file());
+    assert_eq!(std::fs::read_to_string(&disk_path).unwrap(), "target");
+
+    // Snapshotting keeps the symlink
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    // Editing the file changes the target
+    std::fs::write(&disk_path, "other-target").unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+    testutils::write_symlink(&mut tree_builder, &link_path, "other-target");
+    let expected_tree_id = tree_builder.write_tree();
+    assert_eq!(new_tree.id(), MergedTreeId::Legacy(expected_tree_id));
+}
+
+#[test]
+fn test_checkout_case_collision() {
+    // Paths that differ only in case can't be checked out on a case-insensitive
+    // file system
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let repo = &test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    let lower_path = RepoPath::from_internal_string("dir/file");
+    let upper_path = RepoPath::from_internal_string("DIR/file");
+    let tree1 = create_tree(repo, &[(&lower_path, "lower\n")]);
+    let tree2 = create_tree(repo, &[(&lower_path, "lower\n"), (&upper_path, "upper\n")]);
+
+    std::fs::write(workspace_root.join("probe"), "").unwrap();
+    let case_insensitive = workspace_root.join("PROBE").exists();
+    std::fs::remove_file(workspace_root.join("probe")).unwrap();
+
+    // The added path collides with a path that's already in the working copy
+    let wc = test_workspace.workspace.working_copy_mut();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    let result = wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree2,
+        &CheckoutOptions::empty_for_test(),
+    );
+    if case_insensitive {
+        assert_matches!(
+            result,
+            Err(CheckoutError::CaseCollision { path1, path2 })
+                if path1 == RepoPath::from_internal_string("DIR")
+                    && path2 == RepoPath::from_internal_string("dir")
+        );
+        assert_eq!(
+            std::fs::read_to_string(lower_path.to_fs_path(&workspace_root)).unwrap(),
+            "lower\n"
+        );
+    } else {
+        result.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(lower_path.to_fs_path(&workspace_root)).unwrap(),
+            "lower\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(upper_path.to_fs_path(&workspace_root)).unwrap(),
+            "upper\n"
+        );
+    }
 }
 
 #[test]
@@ -289,8 +468,13 @@ fn test_tree_builder_file_directory_transition() {
     let mut check_out_tree = |tree_id: &TreeId| {
         let tree = repo.store().get_tree(&RepoPath::root(), tree_id).unwrap();
         let wc = workspace.working_copy_mut();
-        wc.check_out(repo.op_id().clone(), None, &MergedTree::legacy(tree))
-            .unwrap();
+        wc.check_out(
+            repo.op_id().clone(),
+            None,
+            &MergedTree::legacy(tree),
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
     };
 
     let parent_path = RepoPath::from_internal_string("foo/bar");
@@ -341,8 +525,13 @@ fn test_reset() {
     );
 
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree_with_file)
-        .unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree_with_file,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Test the setup: the file should exist on disk and in the tree state.
     assert!(ignored_path.to_fs_path(&workspace_root).is_file());
@@ -390,7 +579,13 @@ fn test_checkout_discard() {
 
     let wc = test_workspace.workspace.working_copy_mut();
     let state_path = wc.state_path().to_path_buf();
-    wc.check_out(repo.op_id().clone(), None, &tree1).unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Test the setup: the file should exist on disk and in the tree state.
     assert!(file1_path.to_fs_path(&workspace_root).is_file());
@@ -398,7 +593,9 @@ fn test_checkout_discard() {
 
     // Start a checkout
     let mut locked_wc = wc.start_mutation().unwrap();
-    locked_wc.check_out(&tree2).unwrap();
+    locked_wc
+        .check_out(&tree2, &CheckoutOptions::empty_for_test())
+        .unwrap();
     // The change should be reflected in the working copy but not saved
     assert!(!file1_path.to_fs_path(&workspace_root).is_file());
     assert!(file2_path.to_fs_path(&workspace_root).is_file());
@@ -449,6 +646,101 @@ fn test_snapshot_racy_timestamps() {
     }
 }
 
+#[cfg(unix)]
+#[test]
+fn test_snapshot_replaced_file_same_size_and_mtime() {
+    // Tests that a file replaced by another file of the same size and mtime is
+    // detected by its inode number.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+
+    let write_file_with_old_mtime = |path: &std::path::Path, contents: &str| {
+        std::fs::write(path, contents).unwrap();
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        let time = rustix::fs::Timespec {
+            tv_sec: 1_000_000_000,
+            tv_nsec: 0,
+        };
+        let timestamps = rustix::fs::Timestamps {
+            last_access: time,
+            last_modification: time,
+        };
+        rustix::fs::futimens(&file, &timestamps).unwrap();
+    };
+    let file_path = workspace_root.join("file");
+    write_file_with_old_mtime(&file_path, "contents 1");
+    let tree1 = test_workspace.snapshot().unwrap();
+
+    let temp_path = workspace_root.join("temp");
+    write_file_with_old_mtime(&temp_path, "contents 2");
+    std::fs::rename(&temp_path, &file_path).unwrap();
+    let tree2 = test_workspace.snapshot().unwrap();
+    assert_ne!(tree2.id(), tree1.id());
+    let expected_tree = create_tree(
+        &test_workspace.repo,
+        &[(&RepoPath::from_internal_string("file"), "contents 2")],
+    );
+    assert_eq!(tree2.id(), expected_tree.id());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_snapshot_file_states_without_inode() {
+    // Tests that file states written before inodes were recorded don't make
+    // every file look modified. Since an unchanged file isn't read, a file whose
+    // contents change in place without changing size or mtime isn't noticed
+    // either.
+    let settings = testutils::user_settings();
+    let mut test_workspace = TestWorkspace::init(&settings);
+    let workspace_root = test_workspace.workspace.workspace_root().clone();
+    let state_path = test_workspace
+        .workspace
+        .working_copy()
+        .state_path()
+        .to_owned();
+
+    let write_file_with_old_mtime = |path: &std::path::Path, contents: &str| {
+        std::fs::write(path, contents).unwrap();
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        let time = rustix::fs::Timespec {
+            tv_sec: 1_000_000_000,
+            tv_nsec: 0,
+        };
+        let timestamps = rustix::fs::Timestamps {
+            last_access: time,
+            last_modification: time,
+        };
+        rustix::fs::futimens(&file, &timestamps).unwrap();
+    };
+    let file_path = workspace_root.join("file");
+    write_file_with_old_mtime(&file_path, "contents 1");
+    let tree1 = test_workspace.snapshot().unwrap();
+
+    // Rewrite the state as an older version would have written it
+    let tree_state_path = state_path.join("tree_state");
+    let mut proto = jj_lib::protos::working_copy::TreeState::decode(
+        std::fs::read(&tree_state_path).unwrap().as_slice(),
+    )
+    .unwrap();
+    for file_state in proto.file_states.values_mut() {
+        assert_ne!(file_state.inode, 0);
+        file_state.inode = 0;
+    }
+    std::fs::write(&tree_state_path, proto.encode_to_vec()).unwrap();
+
+    write_file_with_old_mtime(&file_path, "contents 2");
+    let tree2 = test_workspace.snapshot().unwrap();
+    assert_eq!(tree2.id(), tree1.id());
+
+    // The inode is recorded again by the snapshot
+    let proto = jj_lib::protos::working_copy::TreeState::decode(
+        std::fs::read(&tree_state_path).unwrap().as_slice(),
+    )
+    .unwrap();
+    assert!(proto.file_states.values().all(|state| state.inode != 0));
+}
+
 #[cfg(unix)]
 #[test]
 fn test_snapshot_special_file() {
@@ -580,7 +872,13 @@ fn test_gitignores_in_ignored_dir() {
 
     let tree1 = create_tree(&test_workspace.repo, &[(&gitignore_path, "ignored\n")]);
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(op_id.clone(), None, &tree1).unwrap();
+    wc.check_out(
+        op_id.clone(),
+        None,
+        &tree1,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     testutils::write_working_copy_file(&workspace_root, &nested_gitignore_path, "!file\n");
     testutils::write_working_copy_file(&workspace_root, &ignored_path, "contents");
@@ -634,7 +932,14 @@ fn test_gitignores_checkout_never_overwrites_ignored() {
     // "contents". The exiting contents ("garbage") shouldn't be replaced in the
     // working copy.
     let wc = test_workspace.workspace.working_copy_mut();
-    assert!(wc.check_out(repo.op_id().clone(), None, &tree).is_err());
+    assert!(wc
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &tree,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_err());
 
     // Check that the old contents are in the working copy
     let path = workspace_root.join("modified");
@@ -668,7 +973,13 @@ fn test_gitignores_ignored_directory_already_tracked() {
 
     // Check out the tree with the files in `ignored/`
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree).unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Make some changes inside the ignored directory and check that they are
     // detected when we snapshot. The files that are still there should not be
@@ -760,7 +1071,13 @@ fn test_gitsubmodule() {
     let tree_id = MergedTreeId::Legacy(tree_builder.write_tree());
     let tree = store.get_root_tree(&tree_id).unwrap();
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree).unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     std::fs::create_dir(submodule_path.to_fs_path(&workspace_root)).unwrap();
 
@@ -799,7 +1116,14 @@ fn test_existing_directory_symlink() {
 
     // Checkout should fail because "parent" already exists and is a symlink.
     let wc = test_workspace.workspace.working_copy_mut();
-    assert!(wc.check_out(repo.op_id().clone(), None, &tree).is_err());
+    assert!(wc
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &tree,
+            &CheckoutOptions::empty_for_test()
+        )
+        .is_err());
 
     // Therefore, "../escaped" shouldn't be created.
     assert!(!workspace_root.parent().unwrap().join("escaped").exists());
@@ -16,7 +16,7 @@ use itertools::Itertools;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
-use jj_lib::working_copy::{CheckoutStats, WorkingCopy};
+use jj_lib::working_copy::{CheckoutOptions, CheckoutStats, WorkingCopy};
 use testutils::{create_tree, TestWorkspace};
 
 #[test]
@@ -49,13 +49,19 @@ fn test_sparse_checkout() {
     );
 
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree).unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Set sparse patterns to only dir1/
     let mut locked_wc = wc.start_mutation().unwrap();
     let sparse_patterns = vec![dir1_path];
     let stats = locked_wc
-        .set_sparse_patterns(sparse_patterns.clone())
+        .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
         .unwrap();
     assert_eq!(
         stats,
@@ -99,7 +105,7 @@ fn test_sparse_checkout() {
     let mut locked_wc = wc.start_mutation().unwrap();
     let sparse_patterns = vec![root_file1_path.clone(), dir1_subdir1_path, dir2_path];
     let stats = locked_wc
-        .set_sparse_patterns(sparse_patterns.clone())
+        .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
         .unwrap();
     assert_eq!(
         stats,
@@ -150,12 +156,20 @@ fn test_sparse_commit() {
     );
 
     let wc = test_workspace.workspace.working_copy_mut();
-    wc.check_out(repo.op_id().clone(), None, &tree).unwrap();
+    wc.check_out(
+        repo.op_id().clone(),
+        None,
+        &tree,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
 
     // Set sparse patterns to only dir1/
     let mut locked_wc = wc.start_mutation().unwrap();
     let sparse_patterns = vec![dir1_path.clone()];
-    locked_wc.set_sparse_patterns(sparse_patterns).unwrap();
+    locked_wc
+        .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
+        .unwrap();
     locked_wc.finish(repo.op_id().clone()).unwrap();
 
     // Write modified version of all files, including files that are not in the
@@ -176,7 +190,9 @@ fn test_sparse_commit() {
     let wc = test_workspace.workspace.working_copy_mut();
     let mut locked_wc = wc.start_mutation().unwrap();
     let sparse_patterns = vec![dir1_path, dir2_path];
-    locked_wc.set_sparse_patterns(sparse_patterns).unwrap();
+    locked_wc
+        .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
+        .unwrap();
     locked_wc.finish(op_id).unwrap();
 
     // Create a tree from the working copy. Only dir1/file1 and dir2/file1 should be
@@ -205,7 +221,9 @@ fn test_sparse_commit_gitignore() {
     // Set sparse patterns to only dir1/
     let mut locked_wc = wc.start_mutation().unwrap();
     let sparse_patterns = vec![dir1_path.clone()];
-    locked_wc.set_sparse_patterns(sparse_patterns).unwrap();
+    locked_wc
+        .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
+        .unwrap();
     locked_wc.finish(repo.op_id().clone()).unwrap();
 
     // Write dir1/file1 and dir1/file2 and a .gitignore saying to ignore dir1/file1